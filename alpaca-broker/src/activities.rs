@@ -0,0 +1,185 @@
+use crate::request::Api;
+use apca::api::v2::account_activities::{
+    Activity, ActivityReq, ActivityType, Get, NonTradeActivity, TradeActivity,
+};
+use model::{ActivityCategory, BrokerActivity, Currency};
+use num_decimal::Num;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Largest page of activities that Alpaca returns
+const PAGE_SIZE: usize = 100;
+
+/// Lists all the activities of the account, newest first.
+/// Alpaca returns them in pages, and the ID of the last activity of a page is
+/// the token of the next one.
+pub async fn activities(client: &Api) -> Result<Vec<BrokerActivity>, Box<dyn Error + Send + Sync>> {
+    let mut activities = Vec::new();
+    let mut page_token = None;
+    loop {
+        let request = ActivityReq {
+            page_size: Some(PAGE_SIZE),
+            page_token,
+            ..Default::default()
+        };
+        let page = client.issue::<Get>(&request).await?;
+        page_token = page.last().map(|activity| activity.id().to_string());
+        let is_last = page.len() < PAGE_SIZE;
+        for activity in page {
+            activities.push(map(activity)?);
+        }
+        if is_last || page_token.is_none() {
+            return Ok(activities);
+        }
+    }
+}
+
+fn map(activity: Activity) -> Result<BrokerActivity, Box<dyn Error + Send + Sync>> {
+    match activity {
+        Activity::Trade(fill) => map_fill(fill),
+        Activity::NonTrade(cash) => map_cash(cash),
+    }
+}
+
+fn map_fill(fill: TradeActivity) -> Result<BrokerActivity, Box<dyn Error + Send + Sync>> {
+    Ok(BrokerActivity {
+        id: fill.id,
+        date: fill.transaction_time.naive_utc(),
+        category: ActivityCategory::Fill,
+        description: "FILL".to_string(),
+        symbol: Some(fill.symbol),
+        amount: decimal(&fill.price)?,
+        currency: Currency::USD,
+        order_id: Some(fill.order_id.0),
+        quantity: decimal(&fill.quantity)?,
+    })
+}
+
+fn map_cash(cash: NonTradeActivity) -> Result<BrokerActivity, Box<dyn Error + Send + Sync>> {
+    let code = serde_json::to_string(&cash.type_)?;
+    let code = code.trim_matches('"');
+    Ok(BrokerActivity {
+        id: cash.id,
+        date: cash.date.naive_utc(),
+        category: category(cash.type_),
+        description: match cash.description {
+            Some(description) => format!("{code} ({description})"),
+            None => code.to_string(),
+        },
+        symbol: cash.symbol,
+        amount: decimal(&cash.net_amount)?,
+        currency: Currency::USD,
+        order_id: None,
+        quantity: cash
+            .quantity
+            .as_ref()
+            .map(decimal)
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn category(type_: ActivityType) -> ActivityCategory {
+    match type_ {
+        ActivityType::CashDeposit => ActivityCategory::Deposit,
+        ActivityType::CashWithdrawal => ActivityCategory::Withdrawal,
+        ActivityType::Dividend
+        | ActivityType::CapitalGainLongTerm
+        | ActivityType::CapitalGainShortTerm
+        | ActivityType::DividendAdjusted
+        | ActivityType::DividendAdjustedNraWithheld
+        | ActivityType::DividendAdjustedTefraWithheld
+        | ActivityType::DividendReturnOfCapital
+        | ActivityType::DividendTaxExtempt => ActivityCategory::Dividend,
+        ActivityType::Interest
+        | ActivityType::InterestAdjustedNraWithheld
+        | ActivityType::InterestAdjustedTefraWithheld => ActivityCategory::Interest,
+        ActivityType::Fee | ActivityType::DividendFee | ActivityType::PassThruCharge => {
+            ActivityCategory::Fee
+        }
+        ActivityType::Fill => ActivityCategory::Fill,
+        _ => ActivityCategory::Other,
+    }
+}
+
+fn decimal(number: &Num) -> Result<Decimal, Box<dyn Error + Send + Sync>> {
+    Decimal::from_str(&number.to_string())
+        .map_err(|e| format!("Failed to parse the amount {number}: {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_map_activities() {
+        let data = r#"[
+            {
+                "activity_type": "FILL",
+                "cum_qty": "10",
+                "id": "20190524113406977::8efc7b9a-8b2b-4000-9955-d36e7db0df74",
+                "leaves_qty": "0",
+                "price": "1.63",
+                "qty": "10",
+                "side": "buy",
+                "symbol": "LPCN",
+                "transaction_time": "2019-05-24T15:34:06.977Z",
+                "order_id": "904837e3-3b76-47ec-b432-046db621571b",
+                "type": "fill"
+            },
+            {
+                "activity_type": "CSW",
+                "id": "20190801011955195::5f596936-6f23-4cef-bdf1-3806aae57dbf",
+                "date": "2019-08-01",
+                "net_amount": "-500",
+                "description": "Cash Disbursement"
+            },
+            {
+                "activity_type": "DIV",
+                "id": "20190801011955195::5f596936-6f23-4cef-bdf1-3806aae57dbe",
+                "date": "2019-08-01",
+                "net_amount": "1.02",
+                "symbol": "T",
+                "qty": "2",
+                "per_share_amount": "0.51"
+            }
+        ]"#;
+        let activities: Vec<Activity> = serde_json::from_str(data).unwrap();
+
+        let mapped: Vec<BrokerActivity> = activities.into_iter().map(|a| map(a).unwrap()).collect();
+
+        let fill = mapped.first().unwrap();
+        assert_eq!(fill.category, ActivityCategory::Fill);
+        assert_eq!(fill.amount, dec!(1.63));
+        assert_eq!(fill.quantity, dec!(10));
+        assert_eq!(
+            fill.order_id,
+            Some(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap())
+        );
+        let withdrawal = mapped.get(1).unwrap();
+        assert_eq!(withdrawal.category, ActivityCategory::Withdrawal);
+        assert_eq!(withdrawal.amount, dec!(-500));
+        assert_eq!(withdrawal.description, "CSW (Cash Disbursement)");
+        let dividend = mapped.last().unwrap();
+        assert_eq!(dividend.category, ActivityCategory::Dividend);
+        assert_eq!(dividend.symbol, Some("T".to_string()));
+        assert_eq!(dividend.amount, dec!(1.02));
+        assert_eq!(dividend.quantity, dec!(2));
+    }
+
+    #[test]
+    fn test_category() {
+        assert_eq!(
+            category(ActivityType::CashDeposit),
+            ActivityCategory::Deposit
+        );
+        assert_eq!(
+            category(ActivityType::PassThruCharge),
+            ActivityCategory::Fee
+        );
+        assert_eq!(category(ActivityType::StockSplit), ActivityCategory::Other);
+    }
+}
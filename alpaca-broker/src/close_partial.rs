@@ -0,0 +1,316 @@
+use crate::close_trade::side;
+use crate::request::Api;
+use crate::sync_trade::get_order;
+use apca::api::v2::order::{
+    Amount, Change, ChangeReq, Class, CreateReq, CreateReqInit, Delete, Id, Order as AlpacaOrder,
+    TimeInForce, Type,
+};
+use model::{Account, BrokerError, BrokerLog, PartialClose, Trade};
+use num_decimal::Num;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Times that the market order is read until it is filled
+const FILL_ATTEMPTS: u32 = 10;
+
+/// Time between two reads of the market order
+const FILL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Closes part of a trade with a market order.
+///
+/// Alpaca holds the quantity of the stop and the target for them, so they are
+/// reduced to the quantity that stays open before the market order is submitted.
+/// A market order that is not filled in time is canceled, and the exits get back
+/// the quantity that it did not close.
+pub async fn close(
+    client: &Api,
+    trade: &Trade,
+    account: &Account,
+    quantity: u64,
+) -> Result<(PartialClose, BrokerLog), Box<dyn Error + Send + Sync>> {
+    assert!(trade.account_id == account.id); // Verify that the trade is for the account
+
+    let open = trade
+        .safety_stop
+        .quantity
+        .checked_sub(quantity)
+        .filter(|open| *open > 0)
+        .ok_or_else(|| format!("Trade {} has less than {quantity} open", trade.id))?;
+
+    let mut requests = Vec::new();
+    let mut responses = Vec::new();
+    let sent = send(client, trade, quantity, open, &mut requests, &mut responses).await;
+    let log = BrokerLog {
+        trade_id: trade.id,
+        request: requests.join("\n"),
+        response: responses.join("\n"),
+        ..Default::default()
+    };
+    match sent {
+        Ok(closed) => Ok((closed, log)),
+        Err(error) => Err(Box::new(BrokerError { log, error })),
+    }
+}
+
+/// Sends the requests of a partial close, keeping what was sent and answered for the log.
+///
+/// The exits get back the quantity of the trade when the close fails before the market
+/// order fills. Alpaca replaces an order when it is changed, so the sync finds the
+/// restored exits by following the replacements of the IDs that Trust has.
+async fn send(
+    client: &Api,
+    trade: &Trade,
+    quantity: u64,
+    open: u64,
+    requests: &mut Vec<String>,
+    responses: &mut Vec<String>,
+) -> Result<PartialClose, Box<dyn Error + Send + Sync>> {
+    let held = trade.safety_stop.quantity;
+
+    // 1. Resize the stop and the target to the quantity that stays open.
+    let stop = resize(
+        client,
+        trade.safety_stop.broker_order_id,
+        open,
+        requests,
+        responses,
+    )
+    .await?;
+    let target = match resize(
+        client,
+        trade.target.broker_order_id,
+        open,
+        requests,
+        responses,
+    )
+    .await
+    {
+        Ok(target) => target,
+        Err(error) => return Err(restore(client, &[stop], held, error, requests, responses).await),
+    };
+
+    // 2. Submit a market order for the quantity to close.
+    let request = request(trade, quantity);
+    requests.push(format!(
+        "POST /v2/orders {}",
+        serde_json::to_string(&request)?
+    ));
+    let market = match client.create_order(&request).await {
+        Ok(market) => market,
+        Err(error) => {
+            let error = Box::new(error);
+            return Err(restore(client, &[stop, target], held, error, requests, responses).await);
+        }
+    };
+
+    // 3. Wait until the market order is filled, or cancel it.
+    let market = settle(client, market, requests).await?;
+    responses.push(serde_json::to_string(&market)?);
+    let closed = filled_quantity(&market)?;
+    if closed == 0 {
+        let error = format!(
+            "The market order {} is {:?} without fills",
+            market.id.0, market.status
+        );
+        return Err(restore(
+            client,
+            &[stop, target],
+            held,
+            error.into(),
+            requests,
+            responses,
+        )
+        .await);
+    }
+
+    // 4. The exits keep the quantity that the market order did not close.
+    let (stop, target) = if closed < quantity {
+        let open = held
+            .checked_sub(closed)
+            .ok_or_else(|| format!("Trade {} has less than {closed} open", trade.id))?;
+        resize_both(client, stop, target, open, requests, responses)
+            .await
+            .map_err(|e| {
+                format!(
+                    "The market order {} closed {closed} of {quantity}, but the exits could not be resized to {open}: {e}",
+                    market.id.0
+                )
+            })?
+    } else {
+        (stop, target)
+    };
+
+    Ok(PartialClose {
+        quantity: closed,
+        price: fill_price(&market)?,
+        stop,
+        target,
+    })
+}
+
+/// Changes the quantity of an exit and returns the ID of the order that replaced it.
+/// The exits of a crypto trade that are not placed yet have no broker ID, and they
+/// are placed with the quantity of Trust.
+async fn resize(
+    client: &Api,
+    broker_order_id: Option<Uuid>,
+    quantity: u64,
+    requests: &mut Vec<String>,
+    responses: &mut Vec<String>,
+) -> Result<Option<Uuid>, Box<dyn Error + Send + Sync>> {
+    let Some(broker_order_id) = broker_order_id else {
+        return Ok(None);
+    };
+
+    let change = ChangeReq {
+        quantity: Some(Num::from(quantity)),
+        ..Default::default()
+    };
+    requests.push(format!(
+        "PATCH /v2/orders/{broker_order_id} {}",
+        serde_json::to_string(&change)?
+    ));
+    let alpaca_order = client
+        .issue::<Change>(&(Id(broker_order_id), change))
+        .await?;
+    responses.push(serde_json::to_string(&alpaca_order)?);
+    Ok(Some(alpaca_order.id.0))
+}
+
+/// Changes the quantity of the stop and then of the target
+async fn resize_both(
+    client: &Api,
+    stop: Option<Uuid>,
+    target: Option<Uuid>,
+    quantity: u64,
+    requests: &mut Vec<String>,
+    responses: &mut Vec<String>,
+) -> Result<(Option<Uuid>, Option<Uuid>), Box<dyn Error + Send + Sync>> {
+    let stop = resize(client, stop, quantity, requests, responses).await?;
+    let target = resize(client, target, quantity, requests, responses).await?;
+    Ok((stop, target))
+}
+
+/// Gives the resized exits back the quantity held by the trade, and returns the error
+/// that stopped the partial close.
+async fn restore(
+    client: &Api,
+    exits: &[Option<Uuid>],
+    held: u64,
+    error: Box<dyn Error + Send + Sync>,
+    requests: &mut Vec<String>,
+    responses: &mut Vec<String>,
+) -> Box<dyn Error + Send + Sync> {
+    for exit in exits {
+        if let Err(e) = resize(client, *exit, held, requests, responses).await {
+            return format!("{error}. The exits could not be resized back to {held}: {e}").into();
+        }
+    }
+    error
+}
+
+/// Reads the market order until it is filled. Market orders are filled right away
+/// while the market is open, so an order that is still waiting is canceled, and it
+/// is read until Alpaca cancels it or it fills in the meantime.
+async fn settle(
+    client: &Api,
+    order: AlpacaOrder,
+    requests: &mut Vec<String>,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    let order = wait(client, order).await?;
+    if order.status.is_terminal() {
+        return Ok(order);
+    }
+
+    let broker_order_id = order.id.0;
+    requests.push(format!("DELETE /v2/orders/{broker_order_id}"));
+    let canceled = client.issue::<Delete>(&Id(broker_order_id)).await;
+    let order = wait(client, get_order(client, broker_order_id).await?).await?;
+    if order.status.is_terminal() {
+        return Ok(order);
+    }
+    Err(match canceled {
+        Ok(()) => format!(
+            "The market order {broker_order_id} is {:?} and not canceled yet, sync the trade once it is",
+            order.status
+        ),
+        Err(e) => format!(
+            "The market order {broker_order_id} is {:?} and could not be canceled: {e}",
+            order.status
+        ),
+    }
+    .into())
+}
+
+/// Reads an order until it reaches a terminal status, or the attempts run out
+async fn wait(
+    client: &Api,
+    mut order: AlpacaOrder,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    for _ in 0..FILL_ATTEMPTS {
+        if order.status.is_terminal() {
+            break;
+        }
+        tokio::time::sleep(FILL_INTERVAL).await;
+        order = get_order(client, order.id.0).await?;
+    }
+    Ok(order)
+}
+
+fn filled_quantity(order: &AlpacaOrder) -> Result<u64, Box<dyn Error + Send + Sync>> {
+    order.filled_quantity.to_u64().ok_or_else(|| {
+        format!(
+            "Failed to parse the filled quantity {}",
+            order.filled_quantity
+        )
+        .into()
+    })
+}
+
+fn fill_price(order: &AlpacaOrder) -> Result<Decimal, Box<dyn Error + Send + Sync>> {
+    let price = order
+        .average_fill_price
+        .as_ref()
+        .ok_or("The market order has no average fill price")?;
+    Decimal::from_str(&price.to_string())
+        .map_err(|e| format!("Failed to parse the fill price {price}: {e}").into())
+}
+
+fn request(trade: &Trade, quantity: u64) -> CreateReq {
+    CreateReqInit {
+        class: Class::Simple,
+        type_: Type::Market,
+        time_in_force: TimeInForce::UntilCanceled,
+        extended_hours: trade.target.extended_hours,
+        ..Default::default()
+    }
+    .init(
+        trade.trading_vehicle.symbol.to_uppercase(),
+        side(trade),
+        Amount::quantity(quantity),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apca::api::v2::order::Side;
+    use model::TradeCategory;
+
+    #[test]
+    fn test_request() {
+        let trade = Trade {
+            category: TradeCategory::Short,
+            ..Default::default()
+        };
+
+        let request = request(&trade, 25);
+
+        assert_eq!(request.type_, Type::Market);
+        assert_eq!(request.side, Side::Buy);
+        assert_eq!(request.amount, Amount::quantity(25));
+    }
+}
@@ -1,40 +1,55 @@
-use crate::keys;
+use crate::crypto;
+use crate::request::Api;
 use apca::api::v2::order::{
-    Amount, Class, Create, CreateReq, CreateReqInit, Delete, Id, Order as AlpacaOrder, Side,
-    TimeInForce, Type,
+    Amount, Class, CreateReq, CreateReqInit, Delete, Id, Order as AlpacaOrder, Side, TimeInForce,
+    Type,
 };
-use apca::Client;
-use model::{Account, BrokerLog, Order, Trade, TradeCategory};
+use model::{Account, BrokerError, BrokerLog, Order, Trade, TradeCategory};
 use std::error::Error;
-use tokio::runtime::Runtime;
 use uuid::Uuid;
 
-pub fn close(trade: &Trade, account: &Account) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+pub async fn close(
+    client: &Api,
+    trade: &Trade,
+    account: &Account,
+) -> Result<(Order, BrokerLog), Box<dyn Error + Send + Sync>> {
     assert!(trade.account_id == account.id); // Verify that the trade is for the account
 
-    let api_info = keys::read_api_key(&account.environment, account)?;
-    let client = Client::new(api_info);
-
-    // 1. Cancel the target order.
-    let target_order_id = trade
-        .target
-        .broker_order_id
-        .ok_or("Target order ID is missing")?;
+    // 1. Cancel the target order, or the stop of a crypto trade, which is the only
+    // exit that rests at Alpaca.
+    let order_id = if crypto::is_crypto(trade) {
+        trade.safety_stop.broker_order_id
+    } else {
+        let target_order_id = trade
+            .target
+            .broker_order_id
+            .ok_or("Target order ID is missing")?;
+        Some(target_order_id)
+    };
 
-    Runtime::new()
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?
-        .block_on(cancel_target(&client, target_order_id))?;
+    let mut requests = Vec::new();
+    if let Some(order_id) = order_id {
+        requests.push(format!("DELETE /v2/orders/{order_id}"));
+        cancel_target(client, order_id)
+            .await
+            .map_err(|e| BrokerError::sent(trade.id, &requests.join("\n"), e))?;
+    }
 
     // 2. Submit a market order to close the trade.
     let request = new_request(trade);
-    let alpaca_order = Runtime::new()
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?
-        .block_on(submit_market_order(client, request))?;
+    requests.push(format!(
+        "POST /v2/orders {}",
+        serde_json::to_string(&request)?
+    ));
+    let alpaca_order = submit_market_order(client, request)
+        .await
+        .map_err(|e| BrokerError::sent(trade.id, &requests.join("\n"), e))?;
 
     // 3. Log the Alpaca order.
     let log = BrokerLog {
         trade_id: trade.id,
-        log: serde_json::to_string(&alpaca_order)?,
+        request: requests.join("\n"),
+        response: serde_json::to_string(&alpaca_order)?,
         ..Default::default()
     };
 
@@ -44,32 +59,19 @@ pub fn close(trade: &Trade, account: &Account) -> Result<(Order, BrokerLog), Box
     Ok((order, log))
 }
 
-async fn cancel_target(client: &Client, order_id: Uuid) -> Result<(), Box<dyn Error>> {
-    let result = client.issue::<Delete>(&Id(order_id)).await;
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            eprintln!("Error cancel target: {e:?}");
-            Err(Box::new(e))
-        }
-    }
+async fn cancel_target(client: &Api, order_id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>> {
+    client.issue::<Delete>(&Id(order_id)).await?;
+    Ok(())
 }
 
 async fn submit_market_order(
-    client: Client,
+    client: &Api,
     request: CreateReq,
-) -> Result<AlpacaOrder, Box<dyn Error>> {
-    let result = client.issue::<Create>(&request).await;
-
-    match result {
-        Ok(order) => Ok(order),
-        Err(e) => {
-            eprintln!("Error posting cancel trade: {e:?}");
-            Err(Box::new(e))
-        }
-    }
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    Ok(client.create_order(&request).await?)
 }
 
+/// The stop has the quantity that is still open, after fills and partial closes.
 fn new_request(trade: &Trade) -> CreateReq {
     CreateReqInit {
         class: Class::Simple,
@@ -81,7 +83,7 @@ fn new_request(trade: &Trade) -> CreateReq {
     .init(
         trade.trading_vehicle.symbol.to_uppercase(),
         side(trade),
-        Amount::quantity(trade.entry.quantity),
+        Amount::quantity(trade.safety_stop.quantity),
     )
 }
 
@@ -113,7 +115,10 @@ mod tests {
             trade.trading_vehicle.symbol.to_uppercase()
         );
         assert_eq!(order_req.side, Side::Sell);
-        assert_eq!(order_req.amount, Amount::quantity(trade.entry.quantity));
+        assert_eq!(
+            order_req.amount,
+            Amount::quantity(trade.safety_stop.quantity)
+        );
         assert_eq!(order_req.time_in_force, TimeInForce::UntilCanceled);
         assert_eq!(order_req.extended_hours, trade.entry.extended_hours);
     }
@@ -0,0 +1,401 @@
+use crate::latest_trade;
+use crate::request::{Api, NOT_FOUND};
+use crate::submit_trade::side;
+use crate::sync_trade::{self, Replacement};
+use apca::api::v2::order::{
+    Amount, Class, CreateReq, CreateReqInit, Delete, GetByClientId, Order as AlpacaOrder,
+    Status as AlpacaStatus, TimeInForce, Type,
+};
+use chrono::Utc;
+use model::{
+    BrokerError, BrokerLog, Order, OrderIds, Trade, TradeCategory, TradingVehicleCategory,
+};
+use num_decimal::Num;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Alpaca only accepts GTC and IOC orders for crypto, whatever the time in force of the trade
+const TIME_IN_FORCE: TimeInForce = TimeInForce::UntilCanceled;
+
+/// How far below the stop price the limit of a crypto stop sits, as a fraction of the stop.
+/// A price that gaps through the stop still fills down to the limit, while a limit at the
+/// stop price would leave the position open.
+const STOP_SLIPPAGE: Decimal = dec!(0.01);
+
+pub fn is_crypto(trade: &Trade) -> bool {
+    trade.trading_vehicle.category == TradingVehicleCategory::Crypto
+}
+
+/// Submits the entry of a crypto trade as a simple limit order.
+///
+/// Alpaca does not accept bracket orders for crypto, so the stop and the target
+/// have no broker ID until the sync places them. An entry that Alpaca already
+/// accepted is found by its client order ID, so the trade can be submitted again.
+pub async fn submit_trade(
+    client: &Api,
+    trade: &Trade,
+) -> Result<(BrokerLog, OrderIds), Box<dyn Error + Send + Sync>> {
+    if trade.category == TradeCategory::Short {
+        return Err("Alpaca does not support short selling crypto".into());
+    }
+
+    let request = entry_request(trade)?;
+    let sent = format!("POST /v2/orders {}", serde_json::to_string(&request)?);
+    let order = match client.create_order(&request).await {
+        Ok(order) => order,
+        Err(error) => match client
+            .issue::<GetByClientId>(&trade.entry.id.to_string())
+            .await
+        {
+            Ok(order) => order,
+            Err(_) => return Err(BrokerError::sent(trade.id, &sent, error).into()),
+        },
+    };
+
+    let log = BrokerLog {
+        trade_id: trade.id,
+        request: sent,
+        response: serde_json::to_string(&order)?,
+        ..Default::default()
+    };
+    let ids = OrderIds {
+        entry: order.id.0,
+        target: None,
+        stop: None,
+    };
+    Ok((log, ids))
+}
+
+/// Get the entry of a crypto trade with the stop and the target as its legs.
+///
+/// Alpaca holds the quantity of a resting sell order, so only one exit can rest
+/// for the position. Once the entry is filled the stop is placed, and the target
+/// is watched here: when the last trade reaches its price, the stop is canceled
+/// and the target is sent as a market order.
+pub async fn get_trade_orders(
+    client: &Api,
+    trade: &Trade,
+) -> Result<(AlpacaOrder, Vec<Replacement>), Box<dyn Error + Send + Sync>> {
+    let entry = sync_trade::get_entry(client, trade).await?;
+    let filled = filled_quantity(&entry);
+    let mut orders = vec![entry];
+    let mut replacements = Vec::new();
+
+    match trade.safety_stop.broker_order_id {
+        Some(broker_order_id) => {
+            orders.push(sync_trade::get_order(client, broker_order_id).await?);
+        }
+        None => {
+            if let Some(stop) = place_stop(client, trade, filled).await? {
+                replacements.push((trade.safety_stop.id, stop.clone()));
+                orders.push(stop);
+            }
+        }
+    }
+    replacements.extend(sync_trade::follow_replacements(client, &mut orders).await?);
+
+    match (trade.target.broker_order_id, orders.get_mut(1)) {
+        (Some(broker_order_id), _) => {
+            orders.push(sync_trade::get_order(client, broker_order_id).await?);
+        }
+        (None, Some(stop)) => {
+            if let Some(target) = take_target(client, trade, stop).await? {
+                replacements.push((trade.target.id, target.clone()));
+                orders.push(target);
+            }
+        }
+        (None, None) => {}
+    }
+    Ok((sync_trade::into_entry(orders)?, replacements))
+}
+
+/// The quantity of an entry that is done filling: all of it, or the part that
+/// was filled before the rest was canceled or expired.
+fn filled_quantity(entry: &AlpacaOrder) -> Option<Num> {
+    match entry.status {
+        AlpacaStatus::Filled => Some(entry.filled_quantity.clone()),
+        AlpacaStatus::Canceled | AlpacaStatus::Expired if !entry.filled_quantity.is_zero() => {
+            Some(entry.filled_quantity.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Place the stop for the filled quantity once the entry is done filling.
+/// Its client order ID is its own ID, so a stop placed by an earlier sync that
+/// was not saved is found instead.
+async fn place_stop(
+    client: &Api,
+    trade: &Trade,
+    filled: Option<Num>,
+) -> Result<Option<AlpacaOrder>, Box<dyn Error + Send + Sync>> {
+    match find(client, &trade.safety_stop).await? {
+        Some(stop) => Ok(Some(stop)),
+        None => match filled {
+            Some(quantity) => {
+                let request = stop_request(trade, quantity)?;
+                Ok(Some(client.create_order(&request).await?))
+            }
+            None => Ok(None),
+        },
+    }
+}
+
+/// Send the target at the market once the last trade reaches its price.
+///
+/// The stop is canceled first, as it holds the quantity that the target sells.
+/// A stop that is canceled may have been canceled by an earlier sync that did
+/// not save the target, so the target is looked up before it is sent again.
+async fn take_target(
+    client: &Api,
+    trade: &Trade,
+    stop: &mut AlpacaOrder,
+) -> Result<Option<AlpacaOrder>, Box<dyn Error + Send + Sync>> {
+    if stop.status == AlpacaStatus::Filled {
+        return Ok(None);
+    }
+    if !is_open(stop) {
+        if let Some(target) = find(client, &trade.target).await? {
+            return Ok(Some(target));
+        }
+    }
+
+    let price = latest_trade::latest_price(client, &trade.trading_vehicle.symbol).await?;
+    if !is_target_reached(trade, &price)? {
+        return Ok(None);
+    }
+
+    let quantity = open_quantity(stop)?;
+    if is_open(stop) {
+        client.issue::<Delete>(&stop.id).await?;
+        stop.status = AlpacaStatus::Canceled;
+        stop.canceled_at = Some(Utc::now());
+    }
+    let request = target_request(trade, quantity);
+    Ok(Some(client.create_order(&request).await?))
+}
+
+/// Read an exit by its client order ID, which is its own ID
+async fn find(
+    client: &Api,
+    exit: &Order,
+) -> Result<Option<AlpacaOrder>, Box<dyn Error + Send + Sync>> {
+    match client.issue::<GetByClientId>(&exit.id.to_string()).await {
+        Ok(order) => Ok(Some(order)),
+        Err(e) if e.status() == Some(NOT_FOUND) => Ok(None),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Crypto trades are long, so the target is reached at its price or above
+fn is_target_reached(trade: &Trade, price: &Num) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    Ok(*price >= num(trade.target.unit_price)?)
+}
+
+/// The quantity of the stop that is not filled, which the target sells
+fn open_quantity(stop: &AlpacaOrder) -> Result<Num, Box<dyn Error + Send + Sync>> {
+    match &stop.amount {
+        Amount::Quantity { quantity } => sync_trade::parse_price(quantity)?
+            .checked_sub(sync_trade::parse_price(&stop.filled_quantity)?)
+            .ok_or_else(|| format!("Stop {} has an invalid quantity", stop.id.0).into())
+            .and_then(num),
+        _ => Err(format!("Stop {} has no quantity", stop.id.0).into()),
+    }
+}
+
+fn is_open(order: &AlpacaOrder) -> bool {
+    matches!(
+        order.status,
+        AlpacaStatus::New
+            | AlpacaStatus::PartiallyFilled
+            | AlpacaStatus::Accepted
+            | AlpacaStatus::PendingNew
+            | AlpacaStatus::Held
+    )
+}
+
+fn entry_request(trade: &Trade) -> Result<CreateReq, Box<dyn Error + Send + Sync>> {
+    Ok(CreateReqInit {
+        class: Class::Simple,
+        type_: Type::Limit,
+        limit_price: Some(num(trade.entry.unit_price)?),
+        time_in_force: TIME_IN_FORCE,
+        client_order_id: Some(trade.entry.id.to_string()),
+        ..Default::default()
+    }
+    .init(
+        trade.trading_vehicle.symbol.to_uppercase(),
+        side(trade),
+        Amount::quantity(trade.entry.quantity),
+    ))
+}
+
+/// Alpaca does not accept plain stop orders for crypto, so the stop is a stop
+/// limit order with its limit below the stop price.
+fn stop_request(trade: &Trade, quantity: Num) -> Result<CreateReq, Box<dyn Error + Send + Sync>> {
+    Ok(CreateReqInit {
+        class: Class::Simple,
+        type_: Type::StopLimit,
+        limit_price: Some(num(stop_limit_price(trade.safety_stop.unit_price)?)?),
+        stop_price: Some(num(trade.safety_stop.unit_price)?),
+        time_in_force: TIME_IN_FORCE,
+        client_order_id: Some(trade.safety_stop.id.to_string()),
+        ..Default::default()
+    }
+    .init(
+        trade.trading_vehicle.symbol.to_uppercase(),
+        !side(trade),
+        Amount::quantity(quantity),
+    ))
+}
+
+fn target_request(trade: &Trade, quantity: Num) -> CreateReq {
+    CreateReqInit {
+        class: Class::Simple,
+        type_: Type::Market,
+        time_in_force: TIME_IN_FORCE,
+        client_order_id: Some(trade.target.id.to_string()),
+        ..Default::default()
+    }
+    .init(
+        trade.trading_vehicle.symbol.to_uppercase(),
+        !side(trade),
+        Amount::quantity(quantity),
+    )
+}
+
+/// The limit price of a crypto stop: the stop price less the slippage that the stop accepts.
+/// Crypto can not be sold short at Alpaca, so the stop always sells.
+pub fn stop_limit_price(stop: Decimal) -> Result<Decimal, Box<dyn Error + Send + Sync>> {
+    stop.checked_mul(STOP_SLIPPAGE)
+        .and_then(|slippage| stop.checked_sub(slippage))
+        .map(|limit| limit.round_dp(stop.scale().max(2)))
+        .ok_or_else(|| format!("Arithmetic overflow in the limit of the stop {stop}").into())
+}
+
+fn num(price: Decimal) -> Result<Num, Box<dyn Error + Send + Sync>> {
+    Num::from_str(&price.to_string())
+        .map_err(|e| format!("Failed to parse price {price}: {e:?}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apca::api::v2::order::Side;
+    use model::TradingVehicle;
+    use rust_decimal_macros::dec;
+
+    fn trade() -> Trade {
+        Trade {
+            trading_vehicle: TradingVehicle {
+                symbol: "btc/usd".to_string(),
+                category: TradingVehicleCategory::Crypto,
+                ..Default::default()
+            },
+            entry: Order {
+                unit_price: dec!(60000),
+                quantity: 2,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(58000),
+                quantity: 2,
+                ..Default::default()
+            },
+            target: Order {
+                unit_price: dec!(65000),
+                quantity: 2,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_entry_request() {
+        let trade = trade();
+
+        let request = entry_request(&trade).unwrap();
+
+        assert!(is_crypto(&trade));
+        assert_eq!(request.class, Class::Simple);
+        assert_eq!(request.type_, Type::Limit);
+        assert_eq!(request.symbol.to_string(), "BTC/USD");
+        assert_eq!(request.side, Side::Buy);
+        assert_eq!(request.time_in_force, TimeInForce::UntilCanceled);
+        assert_eq!(request.limit_price, Some(Num::from(60000)));
+        assert_eq!(request.client_order_id, Some(trade.entry.id.to_string()));
+    }
+
+    #[test]
+    fn test_exit_requests() {
+        let trade = trade();
+
+        let stop = stop_request(&trade, Num::from(2)).unwrap();
+        let target = target_request(&trade, Num::from(1));
+
+        assert_eq!(stop.type_, Type::StopLimit);
+        assert_eq!(stop.side, Side::Sell);
+        assert_eq!(stop.stop_price, Some(Num::from(58000)));
+        assert_eq!(stop.limit_price, Some(Num::from(57420)));
+        assert_eq!(stop.amount, Amount::quantity(2));
+        assert_eq!(stop.client_order_id, Some(trade.safety_stop.id.to_string()));
+        assert_eq!(target.type_, Type::Market);
+        assert_eq!(target.side, Side::Sell);
+        assert_eq!(target.limit_price, None);
+        assert_eq!(target.amount, Amount::quantity(1));
+        assert_eq!(target.client_order_id, Some(trade.target.id.to_string()));
+        assert_eq!(target.time_in_force, TimeInForce::UntilCanceled);
+    }
+
+    #[test]
+    fn test_stop_limit_price() {
+        assert_eq!(stop_limit_price(dec!(58000)).unwrap(), dec!(57420));
+        assert_eq!(stop_limit_price(dec!(0.1234)).unwrap(), dec!(0.1222));
+    }
+
+    #[test]
+    fn test_is_target_reached() {
+        let trade = trade();
+
+        assert!(!is_target_reached(&trade, &Num::from(64999)).unwrap());
+        assert!(is_target_reached(&trade, &Num::from(65000)).unwrap());
+        assert!(is_target_reached(&trade, &Num::from(66000)).unwrap());
+    }
+
+    #[test]
+    fn test_open_quantity_of_a_partially_filled_stop() {
+        let data = r#"{
+            "id": "dd4fdc18-f82b-40c4-9cee-9c1522e62e74",
+            "client_order_id": "8221d144-1bb7-4bcc-ad34-dd6b8f2c731b",
+            "status": "partially_filled",
+            "created_at": "2024-06-20T14:22:16.123456Z",
+            "updated_at": null,
+            "submitted_at": null,
+            "filled_at": null,
+            "expired_at": null,
+            "canceled_at": null,
+            "asset_class": "crypto",
+            "asset_id": "276e2673-764b-4ab6-a611-caf665ca6340",
+            "symbol": "BTC/USD",
+            "qty": "2",
+            "filled_qty": "0.5",
+            "type": "stop_limit",
+            "order_class": "simple",
+            "side": "sell",
+            "time_in_force": "gtc",
+            "limit_price": "58000",
+            "stop_price": "58000",
+            "trail_price": null,
+            "trail_percent": null,
+            "filled_avg_price": "58000",
+            "extended_hours": false,
+            "legs": null
+        }"#;
+        let stop: AlpacaOrder = serde_json::from_str(data).unwrap();
+
+        assert_eq!(open_quantity(&stop).unwrap(), Num::new(3, 2));
+    }
+}
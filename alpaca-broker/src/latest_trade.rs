@@ -0,0 +1,111 @@
+//! Price of the last trade of a crypto pair.
+//!
+//! apca only reads the market data of stocks, so this endpoint reads the latest
+//! trades of the crypto pairs from Alpaca's market data.
+
+use crate::request::Api;
+use apca::ApiError;
+use http_endpoint::Str;
+use num_decimal::Num;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Base URL of Alpaca's market data
+const DATA_BASE_URL: &str = "https://data.alpaca.markets";
+
+/// The last trade of a crypto pair
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct LatestTrade {
+    /// Price of the trade
+    #[serde(rename = "p")]
+    pub price: Num,
+}
+
+/// The latest trades of the requested pairs, by symbol
+#[derive(Deserialize)]
+struct Response {
+    trades: BTreeMap<String, LatestTrade>,
+}
+
+http_endpoint::EndpointDef! {
+    /// GET /v1beta3/crypto/us/latest/trades, for a pair like `BTC/USD`
+    pub GetLatestTrades(String),
+    Ok => BTreeMap<String, LatestTrade>, [
+        OK,
+    ],
+    Err => GetLatestTradesError, [
+        /// The symbol is not a crypto pair
+        BAD_REQUEST => InvalidInput,
+        /// The request was not permitted
+        FORBIDDEN => NotPermitted,
+        /// The rate limit was exceeded
+        TOO_MANY_REQUESTS => RateLimitExceeded,
+    ],
+    ConversionErr => serde_json::Error,
+    ApiErr => ApiError,
+
+    fn base_url() -> Option<Str> {
+        Some(DATA_BASE_URL.into())
+    }
+
+    fn path(_input: &Self::Input) -> Str {
+        "/v1beta3/crypto/us/latest/trades".into()
+    }
+
+    fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+        Ok(Some(query(input).into()))
+    }
+
+    fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+        serde_json::from_slice::<Response>(body).map(|response| response.trades)
+    }
+
+    fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+        serde_json::from_slice(body).map_err(|_| body.to_vec())
+    }
+}
+
+/// Returns the price of the last trade of a crypto pair
+pub async fn latest_price(client: &Api, symbol: &str) -> Result<Num, Box<dyn Error + Send + Sync>> {
+    let symbol = symbol.to_uppercase();
+    let mut trades = client.issue::<GetLatestTrades>(&symbol).await?;
+    trades
+        .remove(&symbol)
+        .map(|trade| trade.price)
+        .ok_or_else(|| format!("Alpaca has no trade of {symbol}").into())
+}
+
+fn query(symbol: &str) -> String {
+    format!("symbols={}", symbol.replace('/', "%2F"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_endpoint::Endpoint;
+
+    #[test]
+    fn test_parse_latest_trades() {
+        let data = br#"{
+            "trades": {
+                "BTC/USD": {
+                    "i": 1354285127812014000,
+                    "p": 61234.5,
+                    "s": 0.0021,
+                    "t": "2024-06-20T14:22:16.123456Z",
+                    "tks": "B"
+                }
+            }
+        }"#;
+
+        let trades = GetLatestTrades::parse(data).unwrap();
+
+        assert_eq!(trades.get("BTC/USD").unwrap().price, Num::new(122469, 2));
+    }
+
+    #[test]
+    fn test_query() {
+        assert_eq!(query("BTC/USD"), "symbols=BTC%2FUSD");
+    }
+}
@@ -30,33 +30,62 @@
 // Standard Rust lints for code quality
 #![warn(missing_docs, rust_2018_idioms, missing_debug_implementations)]
 
-use model::{Account, Broker, BrokerLog, Environment, Order, OrderIds, Status, Trade};
+use model::{
+    Account, AsyncBroker, AsyncBrokerError, Broker, BrokerActivity, BrokerCapabilities,
+    BrokerFuture, BrokerLog, BrokerPosition, Environment, Order, OrderCategory, OrderIds,
+    PartialClose, Status, TimeInForce, Trade,
+};
+use request::Api;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::{Arc, Mutex};
+use tokio::runtime::Runtime;
 use uuid::Uuid;
 
+mod activities;
 mod cancel_trade;
+mod close_partial;
 mod close_trade;
+mod crypto;
 mod keys;
+mod latest_trade;
+mod modify_entry;
 mod modify_stop;
 mod modify_target;
+mod order_links;
 mod order_mapper;
+mod positions;
+mod request;
 mod submit_trade;
 mod sync_trade;
 pub use keys::Keys;
+pub use request::{AlpacaError, RetryPolicy};
 
-#[derive(Default)]
 /// Alpaca broker implementation
-#[derive(Debug)]
-pub struct AlpacaBroker;
+///
+/// The broker creates a single Tokio runtime and one client per account the
+/// first time they are needed, and reuses them in the following calls.
+/// The requests of a client are retried with the retry policy of the broker,
+/// and kept under the rate limit that Alpaca reports for its account.
+#[derive(Debug, Default)]
+pub struct AlpacaBroker {
+    runtime: Mutex<Option<Arc<Runtime>>>,
+    clients: Mutex<HashMap<Uuid, Arc<Api>>>,
+    retry_policy: RetryPolicy,
+}
 
 /// Generic Broker API
+///
+/// Every call blocks on the shared runtime of the broker. It must not be used
+/// from inside another runtime, use the `AsyncBroker` API there instead.
 impl Broker for AlpacaBroker {
     fn submit_trade(
         &self,
         trade: &Trade,
         account: &Account,
     ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
-        submit_trade::submit_sync(trade, account)
+        self.block_on(AsyncBroker::submit_trade(self, trade, account))
     }
 
     fn sync_trade(
@@ -64,7 +93,7 @@ impl Broker for AlpacaBroker {
         trade: &Trade,
         account: &Account,
     ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
-        sync_trade::sync(trade, account)
+        self.block_on(AsyncBroker::sync_trade(self, trade, account))
     }
 
     fn close_trade(
@@ -72,35 +101,257 @@ impl Broker for AlpacaBroker {
         trade: &Trade,
         account: &Account,
     ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
-        close_trade::close(trade, account)
+        self.block_on(AsyncBroker::close_trade(self, trade, account))
     }
 
-    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
-        println!("Canceling trade: {trade:?}");
-        cancel_trade::cancel(trade, account)
+    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<BrokerLog, Box<dyn Error>> {
+        self.block_on(AsyncBroker::cancel_trade(self, trade, account))
+    }
+
+    fn close_partial(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        quantity: u64,
+    ) -> Result<(PartialClose, BrokerLog), Box<dyn Error>> {
+        self.block_on(AsyncBroker::close_partial(self, trade, account, quantity))
     }
 
     fn modify_stop(
         &self,
         trade: &Trade,
         account: &Account,
-        new_stop_price: rust_decimal::Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
-        modify_stop::modify(trade, account, new_stop_price)
+        new_stop_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        self.block_on(AsyncBroker::modify_stop(
+            self,
+            trade,
+            account,
+            new_stop_price,
+        ))
     }
 
     fn modify_target(
         &self,
         trade: &Trade,
         account: &Account,
-        new_target_price: rust_decimal::Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
-        modify_target::modify(trade, account, new_target_price)
+        new_target_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        self.block_on(AsyncBroker::modify_target(
+            self,
+            trade,
+            account,
+            new_target_price,
+        ))
+    }
+
+    fn modify_entry(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        self.block_on(AsyncBroker::modify_entry(
+            self,
+            trade,
+            account,
+            new_price,
+            new_quantity,
+        ))
+    }
+
+    /// Trades are submitted as bracket orders, which Alpaca only accepts for
+    /// whole quantities of stocks during regular hours, with a day or GTC time in force.
+    /// Crypto trades are submitted as a GTC entry. Once it is filled the sync places
+    /// the stop, and sells at market when the latest price reaches the target.
+    /// The stop is a stop limit order with its limit 1% below the stop price, so it
+    /// fills when the price gaps through the stop by less than that.
+    /// Trades are sized in whole units, so fractional crypto quantities are not supported.
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities {
+            order_categories: vec![
+                OrderCategory::Market,
+                OrderCategory::Limit,
+                OrderCategory::Stop,
+            ],
+            time_in_force: vec![TimeInForce::Day, TimeInForce::UntilCanceled],
+            bracket_orders: true,
+            modify_stop: true,
+            modify_target: true,
+            modify_entry: true,
+            partial_close: true,
+            fractional: false,
+            short_selling: true,
+            extended_hours: false,
+            crypto: true,
+        }
+    }
+
+    fn positions(&self, account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        self.block_on(Box::pin(async move {
+            let client = self.client(account)?;
+            positions::positions(&client).await
+        }))
+    }
+
+    fn activities(&self, account: &Account) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        self.block_on(Box::pin(async move {
+            let client = self.client(account)?;
+            activities::activities(&client).await
+        }))
+    }
+}
+
+/// Asynchronous Broker API
+impl AsyncBroker for AlpacaBroker {
+    fn submit_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, (BrokerLog, OrderIds)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            submit_trade::submit_trade(&client, trade, account).await
+        })
+    }
+
+    fn sync_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, (Status, Vec<Order>, BrokerLog)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            sync_trade::sync(&client, trade, account).await
+        })
+    }
+
+    fn close_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, (Order, BrokerLog)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            close_trade::close(&client, trade, account).await
+        })
+    }
+
+    fn cancel_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, BrokerLog> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            cancel_trade::cancel(&client, trade, account).await
+        })
+    }
+
+    fn close_partial<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        quantity: u64,
+    ) -> BrokerFuture<'a, (PartialClose, BrokerLog)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            close_partial::close(&client, trade, account, quantity).await
+        })
+    }
+
+    fn modify_stop<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        new_stop_price: Decimal,
+    ) -> BrokerFuture<'a, (Uuid, BrokerLog)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            modify_stop::modify(&client, trade, account, new_stop_price).await
+        })
+    }
+
+    fn modify_target<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        new_price: Decimal,
+    ) -> BrokerFuture<'a, (Uuid, BrokerLog)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            modify_target::modify(&client, trade, account, new_price).await
+        })
+    }
+
+    fn modify_entry<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> BrokerFuture<'a, (Uuid, BrokerLog)> {
+        Box::pin(async move {
+            let client = self.client(account)?;
+            modify_entry::modify(&client, trade, account, new_price, new_quantity).await
+        })
+    }
+}
+
+/// Shared runtime and clients
+impl AlpacaBroker {
+    /// Returns the runtime of the broker, creating it in the first call.
+    fn runtime(&self) -> Result<Arc<Runtime>, Box<dyn Error>> {
+        let mut runtime = self
+            .runtime
+            .lock()
+            .map_err(|e| format!("Failed to acquire runtime lock: {e}"))?;
+        match runtime.as_ref() {
+            Some(runtime) => Ok(Arc::clone(runtime)),
+            None => {
+                let created = Arc::new(Runtime::new()?);
+                *runtime = Some(Arc::clone(&created));
+                Ok(created)
+            }
+        }
+    }
+
+    /// Returns the client of an account, creating it with the keys of the account in the first call.
+    fn client(&self, account: &Account) -> Result<Arc<Api>, AsyncBrokerError> {
+        let mut clients = self
+            .clients
+            .lock()
+            .map_err(|e| format!("Failed to acquire clients lock: {e}"))?;
+        if let Some(client) = clients.get(&account.id) {
+            return Ok(Arc::clone(client));
+        }
+
+        let api_info = keys::read_api_key(&account.environment, account)
+            .map_err(|e| format!("Failed to read the keys of the account: {e}"))?;
+        let client = Arc::new(Api::new(api_info, self.retry_policy.clone()));
+        clients.insert(account.id, Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Runs a call of the asynchronous API until it finishes.
+    fn block_on<T>(&self, future: BrokerFuture<'_, T>) -> Result<T, Box<dyn Error>> {
+        self.runtime()?
+            .block_on(future)
+            .map_err(|e| e as Box<dyn Error>)
     }
 }
 
 /// Alpaca-specific Broker API
 impl AlpacaBroker {
+    /// Creates a broker that retries the failed requests with the given policy
+    pub fn with_retry_policy(retry_policy: RetryPolicy) -> Self {
+        AlpacaBroker {
+            retry_policy,
+            ..Default::default()
+        }
+    }
+
     /// Setup and store API keys for Alpaca broker
     pub fn setup_keys(
         key_id: &str,
@@ -126,3 +377,35 @@ impl AlpacaBroker {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_is_shared_between_calls() {
+        let broker = AlpacaBroker::default();
+        let first = broker.runtime().unwrap();
+        let second = broker.runtime().unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn test_async_broker_can_be_shared_between_tasks() {
+        fn assert_shareable<T: AsyncBroker + 'static>() {}
+        assert_shareable::<AlpacaBroker>();
+
+        let broker: Arc<dyn AsyncBroker> = Arc::new(AlpacaBroker::default());
+        let runtime = Runtime::new().unwrap();
+        let handle = runtime.spawn(async move {
+            let account = Account::default();
+            let trade = Trade {
+                account_id: account.id,
+                ..Default::default()
+            };
+            // Without keys in the keychain the client can not be created
+            broker.cancel_trade(&trade, &account).await.is_err()
+        });
+        assert!(runtime.block_on(handle).unwrap());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::request::Api;
+use apca::api::v2::order::{Change, ChangeReq, Id};
+use model::{Account, BrokerError, BrokerLog, OrderCategory, Trade};
+use num_decimal::Num;
+use rust_decimal::Decimal;
+use std::{error::Error, str::FromStr};
+use uuid::Uuid;
+
+/// Replaces the entry with a new price and quantity.
+///
+/// Alpaca creates a new order that replaces the entry, and the sync follows the
+/// legs of a bracket order to the orders that replaced them.
+pub async fn modify(
+    client: &Api,
+    trade: &Trade,
+    account: &Account,
+    price: Decimal,
+    quantity: u64,
+) -> Result<(Uuid, BrokerLog), Box<dyn Error + Send + Sync>> {
+    assert!(trade.account_id == account.id); // Verify that the trade is for the account
+
+    let entry_order_id = trade
+        .entry
+        .broker_order_id
+        .ok_or("Entry order ID is missing")?;
+
+    let request = request(trade.entry.category, price, quantity)?;
+    let sent = format!(
+        "PATCH /v2/orders/{entry_order_id} {}",
+        serde_json::to_string(&request)?
+    );
+    let alpaca_order = client
+        .issue::<Change>(&(Id(entry_order_id), request))
+        .await
+        .map_err(|e| BrokerError::sent(trade.id, &sent, e))?;
+
+    let log = BrokerLog {
+        trade_id: trade.id,
+        request: sent,
+        response: serde_json::to_string(&alpaca_order)?,
+        ..Default::default()
+    };
+    Ok((alpaca_order.id.0, log))
+}
+
+fn request(
+    category: OrderCategory,
+    price: Decimal,
+    quantity: u64,
+) -> Result<ChangeReq, Box<dyn Error + Send + Sync>> {
+    let price = Num::from_str(&price.to_string())
+        .map_err(|e| format!("Failed to parse entry price: {e:?}"))?;
+    let (limit_price, stop_price) = match category {
+        OrderCategory::Limit => (Some(price), None),
+        OrderCategory::Stop => (None, Some(price)),
+        OrderCategory::Market => return Err("A market entry has no price to modify".into()),
+    };
+    Ok(ChangeReq {
+        quantity: Some(Num::from(quantity)),
+        limit_price,
+        stop_price,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_request() {
+        let change = request(OrderCategory::Limit, dec!(39.5), 120).unwrap();
+
+        assert_eq!(change.quantity, Some(Num::from(120)));
+        assert_eq!(change.limit_price, Some(Num::new(395, 10)));
+        assert_eq!(change.stop_price, None);
+        assert!(request(OrderCategory::Market, dec!(39.5), 120).is_err());
+    }
+}
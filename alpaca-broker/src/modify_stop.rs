@@ -1,49 +1,58 @@
-use crate::keys;
-use apca::api::v2::order::{Change, ChangeReq, Id, Order};
-use apca::Client;
-use model::{Account, Trade};
+use crate::crypto;
+use crate::request::Api;
+use apca::api::v2::order::{Change, ChangeReq, Id};
+use model::{Account, BrokerError, BrokerLog, Trade};
 use num_decimal::Num;
 use rust_decimal::Decimal;
 use std::{error::Error, str::FromStr};
-use tokio::runtime::Runtime;
 use uuid::Uuid;
 
-pub fn modify(trade: &Trade, account: &Account, price: Decimal) -> Result<Uuid, Box<dyn Error>> {
+pub async fn modify(
+    client: &Api,
+    trade: &Trade,
+    account: &Account,
+    price: Decimal,
+) -> Result<(Uuid, BrokerLog), Box<dyn Error + Send + Sync>> {
     assert!(trade.account_id == account.id); // Verify that the trade is for the account
 
-    let api_info = keys::read_api_key(&account.environment, account)?;
-    let client = Client::new(api_info);
-
     // Modify the stop order.
     let stop_order_id = trade
         .safety_stop
         .broker_order_id
         .ok_or("Safety stop order ID is missing")?;
 
-    let alpaca_order = Runtime::new()
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?
-        .block_on(submit(&client, stop_order_id, price))?;
-
-    // TODO LOG
-
-    Ok(alpaca_order.id.0)
-}
-
-async fn submit(client: &Client, order_id: Uuid, price: Decimal) -> Result<Order, Box<dyn Error>> {
-    let request = ChangeReq {
-        stop_price: Some(
-            Num::from_str(&price.to_string())
-                .map_err(|e| format!("Failed to parse stop price: {e:?}"))?,
-        ),
+    let request = request(price, crypto::is_crypto(trade))?;
+    let sent = format!(
+        "PATCH /v2/orders/{stop_order_id} {}",
+        serde_json::to_string(&request)?
+    );
+    let alpaca_order = client
+        .issue::<Change>(&(Id(stop_order_id), request))
+        .await
+        .map_err(|e| BrokerError::sent(trade.id, &sent, e))?;
+
+    let log = BrokerLog {
+        trade_id: trade.id,
+        request: sent,
+        response: serde_json::to_string(&alpaca_order)?,
         ..Default::default()
     };
+    Ok((alpaca_order.id.0, log))
+}
 
-    let result = client.issue::<Change>(&(Id(order_id), request)).await;
-    match result {
-        Ok(log) => Ok(log),
-        Err(e) => {
-            eprintln!("Error modify stop: {e:?}");
-            Err(Box::new(e))
-        }
-    }
+fn request(price: Decimal, crypto: bool) -> Result<ChangeReq, Box<dyn Error + Send + Sync>> {
+    let parse = |price: Decimal| {
+        Num::from_str(&price.to_string()).map_err(|e| format!("Failed to parse stop price: {e:?}"))
+    };
+    // The stop of a crypto trade is a stop limit order with its limit below the stop price
+    let limit_price = if crypto {
+        Some(parse(crypto::stop_limit_price(price)?)?)
+    } else {
+        None
+    };
+    Ok(ChangeReq {
+        limit_price,
+        stop_price: Some(parse(price)?),
+        ..Default::default()
+    })
 }
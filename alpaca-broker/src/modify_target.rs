@@ -1,49 +1,50 @@
-use crate::keys;
-use apca::api::v2::order::{Change, ChangeReq, Id, Order};
-use apca::Client;
-use model::{Account, Trade};
+use crate::request::Api;
+use apca::api::v2::order::{Change, ChangeReq, Id};
+use model::{Account, BrokerError, BrokerLog, Trade};
 use num_decimal::Num;
 use rust_decimal::Decimal;
 use std::{error::Error, str::FromStr};
-use tokio::runtime::Runtime;
 use uuid::Uuid;
 
-pub fn modify(trade: &Trade, account: &Account, price: Decimal) -> Result<Uuid, Box<dyn Error>> {
+pub async fn modify(
+    client: &Api,
+    trade: &Trade,
+    account: &Account,
+    price: Decimal,
+) -> Result<(Uuid, BrokerLog), Box<dyn Error + Send + Sync>> {
     assert!(trade.account_id == account.id); // Verify that the trade is for the account
 
-    let api_info = keys::read_api_key(&account.environment, account)?;
-    let client = Client::new(api_info);
-
-    // Modify the stop order.
+    // Modify the target order.
     let target_order_id = trade
         .target
         .broker_order_id
         .ok_or("Target order ID is missing")?;
 
-    let alpaca_order = Runtime::new()
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?
-        .block_on(submit(&client, target_order_id, price))?;
-
-    // TODO LOG
-
-    Ok(alpaca_order.id.0)
+    let request = request(price)?;
+    let sent = format!(
+        "PATCH /v2/orders/{target_order_id} {}",
+        serde_json::to_string(&request)?
+    );
+    let alpaca_order = client
+        .issue::<Change>(&(Id(target_order_id), request))
+        .await
+        .map_err(|e| BrokerError::sent(trade.id, &sent, e))?;
+
+    let log = BrokerLog {
+        trade_id: trade.id,
+        request: sent,
+        response: serde_json::to_string(&alpaca_order)?,
+        ..Default::default()
+    };
+    Ok((alpaca_order.id.0, log))
 }
 
-async fn submit(client: &Client, order_id: Uuid, price: Decimal) -> Result<Order, Box<dyn Error>> {
-    let request = ChangeReq {
+fn request(price: Decimal) -> Result<ChangeReq, Box<dyn Error + Send + Sync>> {
+    Ok(ChangeReq {
         limit_price: Some(
             Num::from_str(&price.to_string())
                 .map_err(|e| format!("Failed to parse limit price: {e:?}"))?,
         ),
         ..Default::default()
-    };
-
-    let result = client.issue::<Change>(&(Id(order_id), request)).await;
-    match result {
-        Ok(log) => Ok(log),
-        Err(e) => {
-            eprintln!("Error modify stop: {e:?}");
-            Err(Box::new(e))
-        }
-    }
+    })
 }
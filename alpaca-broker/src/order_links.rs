@@ -0,0 +1,234 @@
+//! Links between an Alpaca order and the order that replaced it.
+//!
+//! apca's `Order` does not deserialize the `replaced_by` and `replaces` fields of
+//! Alpaca's orders, so these endpoints read them from the JSON of the orders.
+
+use crate::request::{self, Api, PAGE_SIZE};
+use apca::api::v2::order::{Id, Order as AlpacaOrder};
+use apca::ApiError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use http_endpoint::Str;
+use serde::Deserialize;
+use std::error::Error;
+use uuid::Uuid;
+
+/// An order with the order that it replaced and the order that replaced it
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct Links {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub replaced_by: Option<Uuid>,
+    pub replaces: Option<Uuid>,
+}
+
+/// The orders of a symbol submitted after a time, oldest first
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListLinksReq {
+    pub symbol: String,
+    pub after: DateTime<Utc>,
+}
+
+http_endpoint::EndpointDef! {
+    /// GET /v2/orders/{order-id}, reading the links of the order
+    pub GetLinks(Id),
+    Ok => Links, [
+        OK,
+    ],
+    Err => GetLinksError, [
+        /// The request was not permitted
+        FORBIDDEN => NotPermitted,
+        /// No order was found with the given ID
+        NOT_FOUND => NotFound,
+        /// The rate limit was exceeded
+        TOO_MANY_REQUESTS => RateLimitExceeded,
+    ],
+    ConversionErr => serde_json::Error,
+    ApiErr => ApiError,
+
+    fn path(input: &Self::Input) -> Str {
+        format!("/v2/orders/{}", input.as_simple()).into()
+    }
+
+    fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+        serde_json::from_slice(body)
+    }
+
+    fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+        serde_json::from_slice(body).map_err(|_| body.to_vec())
+    }
+}
+
+http_endpoint::EndpointDef! {
+    /// GET /v2/orders, reading the links of a page of orders
+    pub ListLinks(ListLinksReq),
+    Ok => Vec<Links>, [
+        OK,
+    ],
+    Err => ListLinksError, [
+        /// The request was not permitted
+        FORBIDDEN => NotPermitted,
+        /// The rate limit was exceeded
+        TOO_MANY_REQUESTS => RateLimitExceeded,
+    ],
+    ConversionErr => serde_json::Error,
+    ApiErr => ApiError,
+
+    fn path(_input: &Self::Input) -> Str {
+        "/v2/orders".into()
+    }
+
+    fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+        Ok(Some(query(input).into()))
+    }
+
+    fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+        serde_json::from_slice(body)
+    }
+
+    fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+        serde_json::from_slice(body).map_err(|_| body.to_vec())
+    }
+}
+
+/// Returns the ID of the order that replaced an order.
+///
+/// Alpaca links the replaced order to its replacement. Without that link, the
+/// orders of the symbol submitted after the replaced order are paged through for
+/// the one that links back to it.
+pub async fn replaced_by(
+    client: &Api,
+    replaced: &AlpacaOrder,
+) -> Result<Uuid, Box<dyn Error + Send + Sync>> {
+    let links = client.issue::<GetLinks>(&replaced.id).await?;
+    if let Some(replacement) = links.replaced_by {
+        return Ok(replacement);
+    }
+
+    let mut request = ListLinksReq {
+        symbol: replaced.symbol.clone(),
+        after: replaced.created_at,
+    };
+    loop {
+        let page = client.issue::<ListLinks>(&request).await?;
+        if let Some(replacement) = find_replacing(&page, replaced.id.0) {
+            return Ok(replacement);
+        }
+        match request::next_page(&page, Some(request.after), submitted) {
+            Some(after) => request.after = after,
+            None => {
+                return Err(format!(
+                    "Order {} was replaced, but the order that replaced it was not found",
+                    replaced.id.0
+                )
+                .into())
+            }
+        }
+    }
+}
+
+fn submitted(links: &Links) -> DateTime<Utc> {
+    links.submitted_at.unwrap_or(links.created_at)
+}
+
+/// The order of a page that replaces the given order
+fn find_replacing(page: &[Links], replaced: Uuid) -> Option<Uuid> {
+    page.iter()
+        .find(|links| links.replaces == Some(replaced))
+        .map(|links| links.id)
+}
+
+fn query(request: &ListLinksReq) -> String {
+    format!(
+        "status=all&direction=asc&nested=false&limit={PAGE_SIZE}&symbols={}&after={}",
+        request.symbol,
+        request.after.to_rfc3339_opts(SecondsFormat::Micros, true)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(id: &str, replaces: Option<&str>) -> Links {
+        Links {
+            id: Uuid::parse_str(id).unwrap(),
+            created_at: "2023-06-20T14:25:00Z".parse().unwrap(),
+            submitted_at: None,
+            replaced_by: None,
+            replaces: replaces.map(|id| Uuid::parse_str(id).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_links_from_order_json() {
+        let data = r#"{
+            "id": "dd4fdc18-f82b-40c4-9cee-9c1522e62e74",
+            "client_order_id": "8221d144-1bb7-4bcc-ad34-dd6b8f2c731b",
+            "created_at": "2023-06-20T14:22:16.123456Z",
+            "submitted_at": "2023-06-20T14:22:16.223456Z",
+            "status": "replaced",
+            "symbol": "TSLA",
+            "replaced_by": "22222222-f82b-40c4-9cee-9c1522e62e74",
+            "replaces": null
+        }"#;
+
+        let links: Links = serde_json::from_str(data).unwrap();
+
+        assert_eq!(
+            links.replaced_by,
+            Some(Uuid::parse_str("22222222-f82b-40c4-9cee-9c1522e62e74").unwrap())
+        );
+        assert_eq!(links.replaces, None);
+    }
+
+    #[test]
+    fn test_find_replacing() {
+        let replaced = "dd4fdc18-f82b-40c4-9cee-9c1522e62e74";
+        let page = vec![
+            links("11111111-f82b-40c4-9cee-9c1522e62e74", None),
+            links(
+                "22222222-f82b-40c4-9cee-9c1522e62e74",
+                Some("33333333-f82b-40c4-9cee-9c1522e62e74"),
+            ),
+            links("44444444-f82b-40c4-9cee-9c1522e62e74", Some(replaced)),
+        ];
+
+        assert_eq!(
+            find_replacing(&page, Uuid::parse_str(replaced).unwrap()),
+            Some(Uuid::parse_str("44444444-f82b-40c4-9cee-9c1522e62e74").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_next_page() {
+        let after: DateTime<Utc> = "2023-06-20T14:00:00Z".parse().unwrap();
+        let page = vec![links("11111111-f82b-40c4-9cee-9c1522e62e74", None); PAGE_SIZE];
+        assert_eq!(
+            request::next_page(&page, Some(after), submitted),
+            Some("2023-06-20T14:25:00Z".parse().unwrap())
+        );
+
+        // A page that is not full is the last one
+        assert_eq!(
+            request::next_page(page.get(1..).unwrap(), Some(after), submitted),
+            None
+        );
+
+        // A page that does not move forward is not requested again
+        let last: DateTime<Utc> = "2023-06-20T14:25:00Z".parse().unwrap();
+        assert_eq!(request::next_page(&page, Some(last), submitted), None);
+    }
+
+    #[test]
+    fn test_query() {
+        let request = ListLinksReq {
+            symbol: "TSLA".to_string(),
+            after: "2023-06-20T14:22:16Z".parse().unwrap(),
+        };
+        assert_eq!(
+            query(&request),
+            "status=all&direction=asc&nested=false&limit=500&symbols=TSLA&after=2023-06-20T14:22:16.000000Z"
+        );
+    }
+}
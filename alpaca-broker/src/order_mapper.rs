@@ -1,4 +1,6 @@
-use apca::api::v2::order::{Order as AlpacaOrder, Status as AlpacaStatus};
+use apca::api::v2::order::{
+    Amount, Order as AlpacaOrder, Status as AlpacaStatus, TimeInForce, Type,
+};
 use chrono::DateTime;
 use chrono::NaiveDateTime;
 use chrono::Utc;
@@ -9,7 +11,10 @@ use std::str::FromStr;
 use uuid::Uuid;
 
 /// Maps an Alpaca order to our domain model.
-pub fn map_entry(alpaca_order: AlpacaOrder, trade: &Trade) -> Result<Vec<Order>, Box<dyn Error>> {
+pub fn map_entry(
+    alpaca_order: AlpacaOrder,
+    trade: &Trade,
+) -> Result<Vec<Order>, Box<dyn Error + Send + Sync>> {
     // 1. Updated orders and trade status
     let mut updated_orders = vec![];
 
@@ -73,7 +78,10 @@ pub fn map_entry(alpaca_order: AlpacaOrder, trade: &Trade) -> Result<Vec<Order>,
     Ok(updated_orders)
 }
 
-pub fn map_target(alpaca_order: AlpacaOrder, trade: &Trade) -> Result<Vec<Order>, Box<dyn Error>> {
+pub fn map_target(
+    alpaca_order: AlpacaOrder,
+    trade: &Trade,
+) -> Result<Vec<Order>, Box<dyn Error + Send + Sync>> {
     Ok(vec![map(&alpaca_order, trade.target.clone())?])
 }
 
@@ -109,8 +117,12 @@ pub fn map_trade_status(trade: &Trade, updated_orders: &[Order]) -> Status {
         return Status::ClosedTarget;
     }
 
-    if has_recent_fill(trade.entry.id, updated_orders) {
-        return Status::Filled;
+    if let Some(status) = updated_orders
+        .iter()
+        .find(|order| order.id == trade.entry.id)
+        .and_then(entry_status)
+    {
+        return status;
     }
 
     // Priority 2: Recent unfills (orders that became not filled)
@@ -131,14 +143,23 @@ pub fn map_trade_status(trade: &Trade, updated_orders: &[Order]) -> Status {
         return Status::ClosedTarget;
     }
 
-    if current_entry.status == OrderStatus::Filled {
-        return Status::Filled;
-    }
+    entry_status(&current_entry).unwrap_or(trade.status)
+}
 
-    trade.status
+/// The status of a trade whose entry has fills. An entry whose remaining quantity
+/// was canceled or expired is done filling, so the trade is filled with what it got.
+fn entry_status(entry: &Order) -> Option<Status> {
+    match entry.status {
+        OrderStatus::Filled => Some(Status::Filled),
+        OrderStatus::PartiallyFilled => Some(Status::PartiallyFilled),
+        OrderStatus::Canceled | OrderStatus::Expired if entry.filled_quantity > 0 => {
+            Some(Status::Filled)
+        }
+        _ => None,
+    }
 }
 
-fn map(alpaca_order: &AlpacaOrder, order: Order) -> Result<Order, Box<dyn Error>> {
+fn map(alpaca_order: &AlpacaOrder, order: Order) -> Result<Order, Box<dyn Error + Send + Sync>> {
     let broker_order_id = order
         .broker_order_id
         .ok_or("order does not have a broker id. It can not be mapped into an alpaca order")?;
@@ -165,7 +186,49 @@ fn map(alpaca_order: &AlpacaOrder, order: Order) -> Result<Order, Box<dyn Error>
     Ok(order)
 }
 
-pub fn map_close_order(alpaca_order: &AlpacaOrder, target: Order) -> Result<Order, Box<dyn Error>> {
+/// Maps an Alpaca order that Trust did not submit, to import it into a trade.
+/// The price is the stop price of stop orders, the limit price of limit orders,
+/// and the average fill price of market orders.
+pub fn map_imported(alpaca_order: &AlpacaOrder) -> Result<Order, Box<dyn Error + Send + Sync>> {
+    let price = alpaca_order
+        .stop_price
+        .as_ref()
+        .or(alpaca_order.limit_price.as_ref())
+        .or(alpaca_order.average_fill_price.as_ref())
+        .ok_or_else(|| format!("Order {} does not have a price", alpaca_order.id.0))?;
+    let quantity = match &alpaca_order.amount {
+        Amount::Quantity { quantity } => quantity.to_u64(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("Order {} does not have a whole quantity", alpaca_order.id.0))?;
+
+    let order = Order {
+        broker_order_id: Some(alpaca_order.id.0),
+        unit_price: Decimal::from_str(&price.to_string())
+            .map_err(|e| format!("Failed to parse price: {e}"))?,
+        quantity,
+        category: match alpaca_order.type_ {
+            Type::Market => OrderCategory::Market,
+            Type::Limit => OrderCategory::Limit,
+            _ => OrderCategory::Stop,
+        },
+        time_in_force: match alpaca_order.time_in_force {
+            TimeInForce::Day => model::TimeInForce::Day,
+            TimeInForce::UntilMarketOpen => model::TimeInForce::UntilMarketOpen,
+            TimeInForce::UntilMarketClose => model::TimeInForce::UntilMarketClose,
+            _ => model::TimeInForce::UntilCanceled,
+        },
+        extended_hours: alpaca_order.extended_hours,
+        submitted_at: map_date(alpaca_order.submitted_at),
+        ..Default::default()
+    };
+    map(alpaca_order, order)
+}
+
+pub fn map_close_order(
+    alpaca_order: &AlpacaOrder,
+    target: Order,
+) -> Result<Order, Box<dyn Error + Send + Sync>> {
     let mut order = target;
     order.broker_order_id = Some(
         Uuid::parse_str(&alpaca_order.id.to_string())
@@ -503,6 +566,52 @@ mod tests {
         assert_eq!(map_trade_status(&trade, &updated_orders), Status::Filled);
     }
 
+    #[test]
+    fn test_map_status_partially_filled_entry() {
+        let entry = Order {
+            status: OrderStatus::New,
+            quantity: 100,
+            ..Default::default()
+        };
+        let trade = Trade {
+            entry: entry.clone(),
+            status: Status::Submitted,
+            ..Default::default()
+        };
+        let partially_filled = Order {
+            status: OrderStatus::PartiallyFilled,
+            filled_quantity: 40,
+            ..entry.clone()
+        };
+        assert_eq!(
+            map_trade_status(&trade, std::slice::from_ref(&partially_filled)),
+            Status::PartiallyFilled
+        );
+
+        // Without updates the entry stays partially filled
+        let trade = Trade {
+            entry: partially_filled,
+            status: Status::PartiallyFilled,
+            ..trade
+        };
+        assert_eq!(map_trade_status(&trade, &[]), Status::PartiallyFilled);
+
+        // The rest of the entry expired, so the trade is filled with 40
+        let expired = Order {
+            status: OrderStatus::Expired,
+            filled_quantity: 40,
+            ..entry.clone()
+        };
+        assert_eq!(map_trade_status(&trade, &[expired]), Status::Filled);
+
+        // An entry that expired without fills is not filled
+        let expired = Order {
+            status: OrderStatus::Expired,
+            ..entry
+        };
+        assert_eq!(map_trade_status(&trade, &[expired]), Status::Submitted);
+    }
+
     #[test]
     fn test_map_status_filled_target() {
         let target_id = Uuid::new_v4();
@@ -0,0 +1,393 @@
+use crate::order_mapper;
+use crate::request::{self, Api, PAGE_SIZE};
+use apca::api::v2::asset::Class as AssetClass;
+use apca::api::v2::order::{Class, Order as AlpacaOrder, Side, Status as AlpacaStatus, Type};
+use apca::api::v2::orders::{List, ListReq, Status as AlpacaRequestStatus};
+use apca::api::v2::position::{Position, Side as PositionSide};
+use apca::api::v2::positions;
+use apca::ApiError;
+use chrono::{DateTime, SecondsFormat, Utc};
+use http_endpoint::Str;
+use model::{BrokerPosition, Currency, OrderStatus, TradeCategory, TradingVehicleCategory};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Broker of the trading vehicles created for the positions
+const BROKER: &str = "alpaca";
+
+/// The closed orders of some symbols submitted after a time, oldest first.
+/// apca does not send the cursor of the list of orders, so this endpoint does.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListClosedReq {
+    pub symbols: Vec<String>,
+    pub after: Option<DateTime<Utc>>,
+}
+
+http_endpoint::EndpointDef! {
+    /// GET /v2/orders, reading a page of closed orders
+    pub ListClosed(ListClosedReq),
+    Ok => Vec<AlpacaOrder>, [
+        OK,
+    ],
+    Err => ListClosedError, [
+        /// The request was not permitted
+        FORBIDDEN => NotPermitted,
+        /// The rate limit was exceeded
+        TOO_MANY_REQUESTS => RateLimitExceeded,
+    ],
+    ConversionErr => serde_json::Error,
+    ApiErr => ApiError,
+
+    fn path(_input: &Self::Input) -> Str {
+        "/v2/orders".into()
+    }
+
+    fn query(input: &Self::Input) -> Result<Option<Str>, Self::ConversionError> {
+        Ok(Some(query(input).into()))
+    }
+
+    fn parse(body: &[u8]) -> Result<Self::Output, Self::ConversionError> {
+        serde_json::from_slice(body)
+    }
+
+    fn parse_err(body: &[u8]) -> Result<Self::ApiError, Vec<u8>> {
+        serde_json::from_slice(body).map_err(|_| body.to_vec())
+    }
+}
+
+/// Lists the open positions and the working bracket entries of the account.
+///
+/// The entry of a position is the newest filled order of its symbol on its side,
+/// with the quantity and the average price of the position. Its stop and target
+/// are the open stop and limit orders of the symbol on the other side. A position
+/// with a fractional quantity is listed with the reason to skip it.
+pub async fn positions(client: &Api) -> Result<Vec<BrokerPosition>, Box<dyn Error + Send + Sync>> {
+    let positions = client.issue::<positions::List>(&()).await?;
+    let symbols: Vec<String> = positions
+        .iter()
+        .map(|position| position.symbol.clone())
+        .collect();
+
+    let mut imported = Vec::new();
+    if !symbols.is_empty() {
+        let open = list(client, &symbols, AlpacaRequestStatus::Open, false).await?;
+        let closed = list_closed(client, &symbols).await?;
+        for position in &positions {
+            imported.push(map_position(position, &open, &closed)?);
+        }
+    }
+
+    let working = list(client, &[], AlpacaRequestStatus::Open, true).await?;
+    for entry in working
+        .iter()
+        .filter(|order| order.class == Class::Bracket && !symbols.contains(&order.symbol))
+    {
+        imported.push(map_working_entry(entry)?);
+    }
+    Ok(imported)
+}
+
+async fn list(
+    client: &Api,
+    symbols: &[String],
+    status: AlpacaRequestStatus,
+    nested: bool,
+) -> Result<Vec<AlpacaOrder>, Box<dyn Error + Send + Sync>> {
+    let request = ListReq {
+        symbols: symbols.to_vec(),
+        status,
+        limit: Some(PAGE_SIZE),
+        nested,
+        ..Default::default()
+    };
+    client
+        .issue::<List>(&request)
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+}
+
+/// Pages through the closed orders of the symbols, as the entry of an old position
+/// can be older than the newest page of orders.
+async fn list_closed(
+    client: &Api,
+    symbols: &[String],
+) -> Result<Vec<AlpacaOrder>, Box<dyn Error + Send + Sync>> {
+    let mut request = ListClosedReq {
+        symbols: symbols.to_vec(),
+        after: None,
+    };
+    let mut orders = Vec::new();
+    loop {
+        let page = client.issue::<ListClosed>(&request).await?;
+        let next = request::next_page(&page, request.after, submitted);
+        orders.extend(page);
+        match next {
+            Some(after) => request.after = Some(after),
+            None => return Ok(orders),
+        }
+    }
+}
+
+fn submitted(order: &AlpacaOrder) -> DateTime<Utc> {
+    order.submitted_at.unwrap_or(order.created_at)
+}
+
+fn query(request: &ListClosedReq) -> String {
+    let query = format!(
+        "status=closed&direction=asc&nested=false&limit={PAGE_SIZE}&symbols={}",
+        request.symbols.join(",")
+    );
+    match request.after {
+        Some(after) => format!(
+            "{query}&after={}",
+            after.to_rfc3339_opts(SecondsFormat::Micros, true)
+        ),
+        None => query,
+    }
+}
+
+fn map_position(
+    position: &Position,
+    open: &[AlpacaOrder],
+    closed: &[AlpacaOrder],
+) -> Result<BrokerPosition, Box<dyn Error + Send + Sync>> {
+    let (category, side) = match position.side {
+        PositionSide::Long => (TradeCategory::Long, Side::Buy),
+        PositionSide::Short => (TradeCategory::Short, Side::Sell),
+    };
+    let whole = position.quantity.fract().is_zero();
+    let (quantity, skip_reason) = match position.quantity.to_u64().filter(|_| whole) {
+        Some(quantity) => (quantity, None),
+        None => (
+            0,
+            Some(format!(
+                "The position of {} has a fractional quantity of {}, which Trust can not import",
+                position.symbol, position.quantity
+            )),
+        ),
+    };
+    let price = Decimal::from_str(&position.average_entry_price.to_string())
+        .map_err(|e| format!("Failed to parse the average entry price: {e}"))?;
+
+    let of_symbol = |order: &&AlpacaOrder| order.symbol == position.symbol;
+    let entry = closed
+        .iter()
+        .filter(of_symbol)
+        .filter(|order| order.side == side && order.status == AlpacaStatus::Filled)
+        .max_by_key(|order| order.filled_at)
+        .map(order_mapper::map_imported)
+        .transpose()?
+        .unwrap_or_default();
+    let exits: Vec<&AlpacaOrder> = open
+        .iter()
+        .filter(of_symbol)
+        .filter(|order| order.side == !side)
+        .collect();
+
+    Ok(BrokerPosition {
+        broker: BROKER.to_string(),
+        symbol: position.symbol.clone(),
+        vehicle_category: vehicle_category(position.asset_class),
+        category,
+        currency: Currency::USD,
+        entry: model::Order {
+            unit_price: price,
+            quantity,
+            status: OrderStatus::Filled,
+            filled_quantity: quantity,
+            average_filled_price: Some(price),
+            ..entry
+        },
+        safety_stop: find_exit(&exits, |type_| type_ != Type::Limit)?,
+        target: find_exit(&exits, |type_| type_ == Type::Limit)?,
+        skip_reason,
+    })
+}
+
+fn map_working_entry(entry: &AlpacaOrder) -> Result<BrokerPosition, Box<dyn Error + Send + Sync>> {
+    let legs: Vec<&AlpacaOrder> = entry.legs.iter().collect();
+    Ok(BrokerPosition {
+        broker: BROKER.to_string(),
+        symbol: entry.symbol.clone(),
+        vehicle_category: vehicle_category(entry.asset_class),
+        category: match entry.side {
+            Side::Buy => TradeCategory::Long,
+            Side::Sell => TradeCategory::Short,
+        },
+        currency: Currency::USD,
+        entry: order_mapper::map_imported(entry)?,
+        safety_stop: find_exit(&legs, |type_| type_ != Type::Limit)?,
+        target: find_exit(&legs, |type_| type_ == Type::Limit)?,
+        skip_reason: None,
+    })
+}
+
+fn find_exit(
+    orders: &[&AlpacaOrder],
+    is_type: impl Fn(Type) -> bool,
+) -> Result<Option<model::Order>, Box<dyn Error + Send + Sync>> {
+    orders
+        .iter()
+        .find(|order| order.type_ != Type::Market && is_type(order.type_))
+        .map(|order| order_mapper::map_imported(order))
+        .transpose()
+}
+
+fn vehicle_category(class: AssetClass) -> TradingVehicleCategory {
+    match class {
+        AssetClass::Crypto => TradingVehicleCategory::Crypto,
+        _ => TradingVehicleCategory::Stock,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn order(id: &str, side: &str, type_: &str, status: &str, price: &str) -> AlpacaOrder {
+        let (limit, stop) = if type_ == "stop" {
+            ("null".to_string(), format!("\"{price}\""))
+        } else {
+            (format!("\"{price}\""), "null".to_string())
+        };
+        let data = format!(
+            r#"{{
+                "id": "{id}",
+                "client_order_id": "{id}",
+                "status": "{status}",
+                "created_at": "2023-06-12T16:22:06Z",
+                "updated_at": null,
+                "submitted_at": "2023-06-12T16:22:06Z",
+                "filled_at": "2023-06-12T16:22:49Z",
+                "expired_at": null,
+                "canceled_at": null,
+                "asset_class": "us_equity",
+                "asset_id": "8ccae427-5dd0-45b3-b5fe-7ba5e422c766",
+                "symbol": "TSLA",
+                "qty": "10",
+                "filled_qty": "10",
+                "type": "{type_}",
+                "order_class": "simple",
+                "side": "{side}",
+                "time_in_force": "gtc",
+                "limit_price": {limit},
+                "stop_price": {stop},
+                "trail_price": null,
+                "trail_percent": null,
+                "filled_avg_price": "246.15",
+                "extended_hours": false,
+                "legs": []
+            }}"#
+        );
+        serde_json::from_str(&data).unwrap()
+    }
+
+    fn position() -> Position {
+        position_of("10")
+    }
+
+    fn position_of(quantity: &str) -> Position {
+        let data = r#"{
+            "asset_id": "8ccae427-5dd0-45b3-b5fe-7ba5e422c766",
+            "symbol": "TSLA",
+            "exchange": "NASDAQ",
+            "asset_class": "us_equity",
+            "avg_entry_price": "246.5",
+            "qty": "QTY",
+            "qty_available": "QTY",
+            "side": "long",
+            "market_value": "2500",
+            "cost_basis": "2465",
+            "unrealized_pl": "35",
+            "unrealized_plpc": "0.014",
+            "unrealized_intraday_pl": "35",
+            "unrealized_intraday_plpc": "0.014",
+            "current_price": "250",
+            "lastday_price": "245",
+            "change_today": "0.02"
+        }"#;
+        serde_json::from_str(&data.replace("QTY", quantity)).unwrap()
+    }
+
+    #[test]
+    fn test_query_of_closed_orders() {
+        let mut request = ListClosedReq {
+            symbols: vec!["TSLA".to_string(), "AAPL".to_string()],
+            after: None,
+        };
+        assert_eq!(
+            query(&request),
+            "status=closed&direction=asc&nested=false&limit=500&symbols=TSLA,AAPL"
+        );
+
+        request.after = Some("2023-06-12T16:22:06Z".parse().unwrap());
+        assert_eq!(
+            query(&request),
+            "status=closed&direction=asc&nested=false&limit=500&symbols=TSLA,AAPL&after=2023-06-12T16:22:06.000000Z"
+        );
+    }
+
+    #[test]
+    fn test_map_position() {
+        let entry_id = "66b4dfbf-2905-4a25-a388-873fec1a15de";
+        let stop_id = "ef022523-1f49-49e6-a1c1-98e2efd2ff35";
+        let target_id = "99106145-92dc-477e-b1c5-fcfdee452633";
+        let closed = vec![order(entry_id, "buy", "limit", "filled", "246.2")];
+        let open = vec![
+            order(stop_id, "sell", "stop", "new", "240"),
+            order(target_id, "sell", "limit", "new", "260"),
+        ];
+
+        let imported = map_position(&position(), &open, &closed).unwrap();
+
+        assert_eq!(imported.symbol, "TSLA");
+        assert_eq!(imported.category, TradeCategory::Long);
+        assert_eq!(imported.vehicle_category, TradingVehicleCategory::Stock);
+        assert_eq!(
+            imported.entry.broker_order_id,
+            Some(Uuid::parse_str(entry_id).unwrap())
+        );
+        assert_eq!(imported.entry.unit_price, dec!(246.5));
+        assert_eq!(imported.entry.quantity, 10);
+        assert_eq!(imported.entry.status, OrderStatus::Filled);
+        let stop = imported.safety_stop.unwrap();
+        assert_eq!(
+            stop.broker_order_id,
+            Some(Uuid::parse_str(stop_id).unwrap())
+        );
+        assert_eq!(stop.unit_price, dec!(240));
+        let target = imported.target.unwrap();
+        assert_eq!(
+            target.broker_order_id,
+            Some(Uuid::parse_str(target_id).unwrap())
+        );
+        assert_eq!(target.unit_price, dec!(260));
+    }
+
+    #[test]
+    fn test_map_position_without_orders() {
+        let imported = map_position(&position(), &[], &[]).unwrap();
+
+        assert_eq!(imported.entry.broker_order_id, None);
+        assert_eq!(imported.entry.quantity, 10);
+        assert_eq!(imported.safety_stop, None);
+        assert_eq!(imported.target, None);
+        assert_eq!(imported.skip_reason, None);
+    }
+
+    #[test]
+    fn test_map_fractional_position() {
+        let imported = map_position(&position_of("0.5"), &[], &[]).unwrap();
+
+        assert_eq!(
+            imported.skip_reason,
+            Some(
+                "The position of TSLA has a fractional quantity of 0.5, which Trust can not import"
+                    .to_string()
+            )
+        );
+    }
+}
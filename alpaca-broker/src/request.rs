@@ -0,0 +1,611 @@
+//! Shared layer of the requests to Alpaca.
+//!
+//! Every call to the API goes through `Api::issue`, which keeps the account under
+//! the rate limit of Alpaca and retries the failures that can pass: timeouts,
+//! lost connections, `429 Too Many Requests` and errors of the server.
+//!
+//! The requests are built from the endpoints of apca, but sent with a client of
+//! this crate, because apca drops the headers of the responses. Alpaca reports the
+//! rate limit in the `X-RateLimit-Remaining` and `X-RateLimit-Reset` headers, and
+//! the next requests wait for the reset when it is spent.
+
+use apca::api::v2::order::{Create, CreateReq, GetByClientId, Order};
+use apca::ApiInfo;
+use broker_sync::BackoffConfig;
+use chrono::{DateTime, Utc};
+use http::{HeaderMap, Request};
+use http_body_util::{BodyExt, Full};
+use http_endpoint::Endpoint;
+use hyper::body::Bytes;
+use hyper_tls::HttpsConnector;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::time::{sleep, timeout};
+use url::Url;
+
+/// Requests that Alpaca accepts from an account in a window, as sent in `X-RateLimit-Limit`
+const RATE_LIMIT: u32 = 200;
+
+/// Window of the rate limit of Alpaca
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Header with the requests left in the window of the rate limit
+const RATE_LIMIT_REMAINING: &str = "X-RateLimit-Remaining";
+
+/// Header with the Unix time, in seconds, when the window of the rate limit resets
+const RATE_LIMIT_RESET: &str = "X-RateLimit-Reset";
+
+/// Headers with the keys of the account, as apca sends them
+const KEY_ID: &str = "APCA-API-KEY-ID";
+const SECRET: &str = "APCA-API-SECRET-KEY";
+
+/// Status of a request that Alpaca rejected because of the rate limit
+const TOO_MANY_REQUESTS: u16 = 429;
+
+/// Status of a request that the server did not receive in time
+const REQUEST_TIMEOUT: u16 = 408;
+
+/// Status of a request for something that Alpaca does not have
+pub const NOT_FOUND: u16 = 404;
+
+/// Largest page of orders that Alpaca returns
+pub const PAGE_SIZE: usize = 500;
+
+/// How the requests to Alpaca are retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts of a request, the first one included
+    pub max_attempts: u32,
+    /// Delay between the attempts
+    pub backoff: BackoffConfig,
+    /// Time to wait for the response of an attempt
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            backoff: BackoffConfig {
+                base_delay_ms: 500,
+                max_delay_ms: 10_000,
+                max_exponent: 4,
+                jitter_percent: 20,
+            },
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Error of a request to Alpaca
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlpacaError {
+    /// The request failed for a reason that can pass: a timeout, a lost connection,
+    /// the rate limit or an error of the server. It is returned once the attempts
+    /// are spent, or at once when sending the request again could repeat its effect.
+    Retryable {
+        /// HTTP status of the response, if there was one
+        status: Option<u16>,
+        /// Description of the failure
+        message: String,
+        /// Attempts made before giving up
+        attempts: u32,
+    },
+    /// Alpaca rejected the request, and sending it again gets the same answer.
+    Permanent {
+        /// HTTP status of the response, if there was one
+        status: Option<u16>,
+        /// Description of the failure
+        message: String,
+    },
+}
+
+impl AlpacaError {
+    /// Whether the request can succeed if it is sent again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AlpacaError::Retryable { .. })
+    }
+
+    /// HTTP status of the response, if there was one
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            AlpacaError::Retryable { status, .. } | AlpacaError::Permanent { status, .. } => {
+                *status
+            }
+        }
+    }
+
+    fn retryable(status: Option<u16>, message: String) -> Self {
+        AlpacaError::Retryable {
+            status,
+            message,
+            attempts: 1,
+        }
+    }
+
+    fn with_attempts(self, attempts: u32) -> Self {
+        match self {
+            AlpacaError::Retryable {
+                status, message, ..
+            } => AlpacaError::Retryable {
+                status,
+                message,
+                attempts,
+            },
+            permanent => permanent,
+        }
+    }
+}
+
+impl fmt::Display for AlpacaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlpacaError::Retryable {
+                message, attempts, ..
+            } => write!(
+                f,
+                "Alpaca request failed after {attempts} attempts: {message}"
+            ),
+            AlpacaError::Permanent { message, .. } => {
+                write!(f, "Alpaca rejected the request: {message}")
+            }
+        }
+    }
+}
+
+impl Error for AlpacaError {}
+
+/// Client of an account that sends every request through the retry policy and the rate limit
+#[derive(Debug)]
+pub struct Api {
+    api_info: ApiInfo,
+    client: Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
+    policy: RetryPolicy,
+    rate_limit: Mutex<RateLimit>,
+}
+
+impl Api {
+    pub fn new(api_info: ApiInfo, policy: RetryPolicy) -> Self {
+        Api {
+            api_info,
+            client: Client::builder(TokioExecutor::new()).build(HttpsConnector::new()),
+            policy,
+            rate_limit: Mutex::new(RateLimit::new(Instant::now())),
+        }
+    }
+
+    /// Sends a request, retrying it while it fails for a reason that can pass.
+    ///
+    /// Only the requests that can be repeated without repeating their effect are
+    /// retried after a timeout or an error of the server, as the first attempt may
+    /// have reached Alpaca. A request rejected by the rate limit was not processed,
+    /// so it is always retried, once the window of the rate limit resets.
+    pub async fn issue<R>(&self, input: &R::Input) -> Result<R::Output, AlpacaError>
+    where
+        R: Endpoint,
+        http_endpoint::Error<R::ConversionError>: From<R::Error>,
+    {
+        let idempotent = is_idempotent(R::method().as_str());
+        let mut attempt: u32 = 1;
+        loop {
+            self.wait_for_rate_limit().await;
+            let error = match timeout(self.policy.timeout, self.send::<R>(input)).await {
+                Ok(Ok(output)) => return Ok(output),
+                Ok(Err(error)) => error,
+                Err(_) => AlpacaError::retryable(
+                    None,
+                    format!("No response in {} seconds", self.policy.timeout.as_secs()),
+                ),
+            };
+
+            let rate_limited = error.status() == Some(TOO_MANY_REQUESTS);
+            let retry = error.is_retryable() && (idempotent || rate_limited);
+            if !retry || attempt >= self.policy.max_attempts {
+                return Err(error.with_attempts(attempt));
+            }
+            // The rate limit is spent, and the next attempt waits for its reset
+            if !rate_limited {
+                sleep(self.policy.backoff.delay(attempt)).await;
+            }
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    /// Sends a request once, and takes the rate limit from the headers of the response.
+    async fn send<R>(&self, input: &R::Input) -> Result<R::Output, AlpacaError>
+    where
+        R: Endpoint,
+        http_endpoint::Error<R::ConversionError>: From<R::Error>,
+    {
+        let request = self.request::<R>(input)?;
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|error| AlpacaError::retryable(None, describe(&error)))?;
+
+        let (parts, body) = response.into_parts();
+        let window = Window::read(&parts.headers, SystemTime::now());
+        self.rate_limit().update(
+            Instant::now(),
+            window,
+            parts.status.as_u16() == TOO_MANY_REQUESTS,
+        );
+
+        let body = body
+            .collect()
+            .await
+            .map_err(|error| AlpacaError::retryable(None, describe(&error)))?
+            .to_bytes();
+        R::evaluate(parts.status, &body).map_err(classify::<R>)
+    }
+
+    /// Builds the request of an endpoint like apca builds it.
+    fn request<R>(&self, input: &R::Input) -> Result<Request<Full<Bytes>>, AlpacaError>
+    where
+        R: Endpoint,
+        http_endpoint::Error<R::ConversionError>: From<R::Error>,
+    {
+        let mut url = match R::base_url() {
+            Some(base_url) => Url::parse(&base_url).map_err(|error| AlpacaError::Permanent {
+                status: None,
+                message: format!("Invalid URL {base_url}: {error}"),
+            })?,
+            None => self.api_info.api_base_url.clone(),
+        };
+        url.set_path(&R::path(input));
+        let query = R::query(input).map_err(|error| classify::<R>(R::Error::from(error)))?;
+        url.set_query(query.as_deref());
+
+        let body = match R::body(input).map_err(|error| classify::<R>(R::Error::from(error)))? {
+            None => Bytes::new(),
+            Some(Cow::Borrowed(body)) => Bytes::from(body),
+            Some(Cow::Owned(body)) => Bytes::from(body),
+        };
+
+        Request::builder()
+            .method(R::method())
+            .uri(url.as_str())
+            .header(KEY_ID, self.api_info.key_id.as_str())
+            .header(SECRET, self.api_info.secret.as_str())
+            .body(Full::new(body))
+            .map_err(|error| classify::<R>(R::Error::from(error)))
+    }
+
+    /// Submits an order, retrying it like `issue` retries the idempotent requests
+    /// when it has a client order ID.
+    ///
+    /// A failed attempt may have reached Alpaca, so the order is looked up by its
+    /// client order ID before it is sent again. Alpaca rejects a second order with
+    /// the same client order ID, so an order is never placed twice.
+    pub async fn create_order(&self, request: &CreateReq) -> Result<Order, AlpacaError> {
+        let Some(client_order_id) = &request.client_order_id else {
+            return self.issue::<Create>(request).await;
+        };
+        let mut attempt: u32 = 1;
+        loop {
+            let error = match self.issue::<Create>(request).await {
+                Ok(order) => return Ok(order),
+                Err(error) if may_have_reached(&error) => error,
+                Err(error) => return Err(error),
+            };
+
+            match self.issue::<GetByClientId>(client_order_id).await {
+                Ok(order) => return Ok(order),
+                Err(lookup) if lookup.status() == Some(NOT_FOUND) => {}
+                Err(_) => return Err(error.with_attempts(attempt)),
+            }
+            if attempt >= self.policy.max_attempts {
+                return Err(error.with_attempts(attempt));
+            }
+            sleep(self.policy.backoff.delay(attempt)).await;
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    /// Waits until the rate limit of the account accepts another request.
+    async fn wait_for_rate_limit(&self) {
+        loop {
+            let wait = self.rate_limit().acquire(Instant::now());
+            match wait {
+                Some(wait) => sleep(wait).await,
+                None => return,
+            }
+        }
+    }
+
+    fn rate_limit(&self) -> std::sync::MutexGuard<'_, RateLimit> {
+        // The count stays valid if a request panicked while holding the lock
+        self.rate_limit
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// The rate limit of an account, as Alpaca sent it with the last response.
+///
+/// The requests sent since then are counted here, so the requests sent at the same
+/// time do not go over the limit. A `429` response spends what is left of the window.
+#[derive(Debug)]
+struct RateLimit {
+    remaining: u32,
+    reset: Instant,
+}
+
+impl RateLimit {
+    fn new(now: Instant) -> Self {
+        RateLimit {
+            remaining: RATE_LIMIT,
+            reset: now.checked_add(RATE_LIMIT_WINDOW).unwrap_or(now),
+        }
+    }
+
+    /// Takes a request from the window, or returns how long to wait for the next window.
+    fn acquire(&mut self, now: Instant) -> Option<Duration> {
+        if now >= self.reset {
+            *self = RateLimit::new(now);
+        }
+        if self.remaining == 0 {
+            return Some(self.reset.saturating_duration_since(now));
+        }
+        self.remaining = self.remaining.saturating_sub(1);
+        None
+    }
+
+    /// Takes the rate limit that Alpaca sent with a response. Without the headers,
+    /// the window is kept as it was counted.
+    fn update(&mut self, now: Instant, window: Option<Window>, rejected: bool) {
+        match window {
+            Some(window) => {
+                self.remaining = window.remaining;
+                self.reset = now.checked_add(window.reset_in).unwrap_or(now);
+            }
+            None if now >= self.reset => *self = RateLimit::new(now),
+            None => {}
+        }
+        if rejected {
+            self.remaining = 0;
+        }
+    }
+}
+
+/// The window of the rate limit in the headers of a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Window {
+    /// Requests left in the window
+    remaining: u32,
+    /// Time until the window resets
+    reset_in: Duration,
+}
+
+impl Window {
+    /// Reads the window from the headers, if Alpaca sent it. The reset is a time of
+    /// the clock of Alpaca, so it is kept within a window in case the clocks differ.
+    fn read(headers: &HeaderMap, now: SystemTime) -> Option<Window> {
+        let header = |name: &str| headers.get(name)?.to_str().ok()?.trim().parse::<u64>().ok();
+        let remaining = u32::try_from(header(RATE_LIMIT_REMAINING)?).unwrap_or(u32::MAX);
+        let reset = UNIX_EPOCH.checked_add(Duration::from_secs(header(RATE_LIMIT_RESET)?))?;
+        let reset_in = reset
+            .duration_since(now)
+            .unwrap_or(Duration::ZERO)
+            .min(RATE_LIMIT_WINDOW);
+        Some(Window {
+            remaining,
+            reset_in,
+        })
+    }
+}
+
+/// The time after which the next page of orders listed oldest first starts, if the page
+/// was full. A page that does not move past the previous one is not requested again.
+pub fn next_page<T>(
+    page: &[T],
+    after: Option<DateTime<Utc>>,
+    submitted: impl Fn(&T) -> DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if page.len() < PAGE_SIZE {
+        return None;
+    }
+    page.last()
+        .map(submitted)
+        .filter(|next| after.map_or(true, |after| *next > after))
+}
+
+/// Whether sending a request with the HTTP method again does not repeat its effect
+fn is_idempotent(method: &str) -> bool {
+    matches!(method, "GET" | "HEAD" | "PUT" | "DELETE" | "OPTIONS")
+}
+
+/// Whether a request that was not retried may have reached Alpaca. `issue` retries
+/// the requests rejected by the rate limit until the attempts are spent.
+fn may_have_reached(error: &AlpacaError) -> bool {
+    error.is_retryable() && error.status() != Some(TOO_MANY_REQUESTS)
+}
+
+/// The endpoint reports the status of Alpaca's response, or a request or a response
+/// that can not be converted, which fails again the same way. The failures of the
+/// transport, like a lost connection, can pass and are reported by `send`.
+fn classify<R>(error: R::Error) -> AlpacaError
+where
+    R: Endpoint,
+    http_endpoint::Error<R::ConversionError>: From<R::Error>,
+{
+    match http_endpoint::Error::from(error) {
+        http_endpoint::Error::HttpStatus(status, body) => {
+            from_status(status.as_u16(), &String::from_utf8_lossy(&body))
+        }
+        error => AlpacaError::Permanent {
+            status: None,
+            message: describe(&error),
+        },
+    }
+}
+
+fn from_status(status: u16, body: &str) -> AlpacaError {
+    let message = format!("HTTP {status}: {body}");
+    if status == TOO_MANY_REQUESTS || status == REQUEST_TIMEOUT || (500..600).contains(&status) {
+        AlpacaError::retryable(Some(status), message)
+    } else {
+        AlpacaError::Permanent {
+            status: Some(status),
+            message,
+        }
+    }
+}
+
+/// The error followed by its sources, which have the details of the transport errors
+fn describe(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status() {
+        assert!(from_status(429, "rate limit exceeded").is_retryable());
+        assert!(from_status(408, "").is_retryable());
+        assert!(from_status(503, "").is_retryable());
+        assert!(!from_status(403, "forbidden").is_retryable());
+        assert!(!from_status(404, "order not found").is_retryable());
+        assert!(!from_status(422, "qty must be > 0").is_retryable());
+
+        let error = from_status(422, "qty must be > 0");
+        assert_eq!(error.status(), Some(422));
+        assert_eq!(
+            error.to_string(),
+            "Alpaca rejected the request: HTTP 422: qty must be > 0"
+        );
+    }
+
+    #[test]
+    fn test_retryable_error_counts_the_attempts() {
+        let error = from_status(503, "unavailable").with_attempts(4);
+        assert_eq!(
+            error.to_string(),
+            "Alpaca request failed after 4 attempts: HTTP 503: unavailable"
+        );
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        assert!(is_idempotent("GET"));
+        assert!(is_idempotent("DELETE"));
+        assert!(!is_idempotent("POST"));
+        assert!(!is_idempotent("PATCH"));
+    }
+
+    #[test]
+    fn test_may_have_reached() {
+        assert!(may_have_reached(&from_status(503, "unavailable")));
+        assert!(may_have_reached(&AlpacaError::retryable(
+            None,
+            "No response in 30 seconds".to_string()
+        )));
+        assert!(!may_have_reached(&from_status(429, "rate limit exceeded")));
+        assert!(!may_have_reached(&from_status(422, "qty must be > 0")));
+    }
+
+    #[test]
+    fn test_rate_limit_waits_for_the_next_window() {
+        let start = Instant::now();
+        let mut rate_limit = RateLimit::new(start);
+        for _ in 0..RATE_LIMIT {
+            assert_eq!(rate_limit.acquire(start), None);
+        }
+
+        let later = start.checked_add(Duration::from_secs(20)).unwrap();
+        assert_eq!(rate_limit.acquire(later), Some(Duration::from_secs(40)));
+
+        let reset = start.checked_add(RATE_LIMIT_WINDOW).unwrap();
+        assert_eq!(rate_limit.acquire(reset), None);
+    }
+
+    #[test]
+    fn test_rate_limit_is_spent_by_a_rejected_request() {
+        let start = Instant::now();
+        let mut rate_limit = RateLimit::new(start);
+        assert_eq!(rate_limit.acquire(start), None);
+
+        rate_limit.update(start, None, true);
+        assert_eq!(rate_limit.acquire(start), Some(RATE_LIMIT_WINDOW));
+    }
+
+    #[test]
+    fn test_rate_limit_follows_the_headers() {
+        let start = Instant::now();
+        let mut rate_limit = RateLimit::new(start);
+        let window = Window {
+            remaining: 1,
+            reset_in: Duration::from_secs(15),
+        };
+        rate_limit.update(start, Some(window), false);
+        assert_eq!(rate_limit.acquire(start), None);
+        assert_eq!(rate_limit.acquire(start), Some(Duration::from_secs(15)));
+
+        let reset = start.checked_add(Duration::from_secs(15)).unwrap();
+        assert_eq!(rate_limit.acquire(reset), None);
+    }
+
+    #[test]
+    fn test_rejected_request_waits_for_the_reset_of_the_headers() {
+        let start = Instant::now();
+        let mut rate_limit = RateLimit::new(start);
+        let window = Window {
+            remaining: 150,
+            reset_in: Duration::from_secs(5),
+        };
+        rate_limit.update(start, Some(window), true);
+        assert_eq!(rate_limit.acquire(start), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_window_read() {
+        let now = UNIX_EPOCH
+            .checked_add(Duration::from_secs(1_700_000_000))
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(Window::read(&headers, now), None);
+
+        headers.insert(RATE_LIMIT_REMAINING, "42".parse().unwrap());
+        assert_eq!(Window::read(&headers, now), None);
+
+        headers.insert(RATE_LIMIT_RESET, "1700000020".parse().unwrap());
+        assert_eq!(
+            Window::read(&headers, now),
+            Some(Window {
+                remaining: 42,
+                reset_in: Duration::from_secs(20),
+            })
+        );
+
+        // A reset that passed on the clock of Alpaca allows the next request at once
+        headers.insert(RATE_LIMIT_RESET, "1699999990".parse().unwrap());
+        assert_eq!(
+            Window::read(&headers, now).map(|window| window.reset_in),
+            Some(Duration::ZERO)
+        );
+
+        headers.insert(RATE_LIMIT_RESET, "1700003600".parse().unwrap());
+        assert_eq!(
+            Window::read(&headers, now).map(|window| window.reset_in),
+            Some(RATE_LIMIT_WINDOW)
+        );
+
+        headers.insert(RATE_LIMIT_REMAINING, "many".parse().unwrap());
+        assert_eq!(Window::read(&headers, now), None);
+    }
+}
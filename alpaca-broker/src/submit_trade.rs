@@ -1,91 +1,112 @@
+use crate::request::{Api, PAGE_SIZE};
 use apca::api::v2::order::{
-    Amount, Class, Create, CreateReq, CreateReqInit, Order as AlpacaOrder, Side, StopLoss,
-    TakeProfit, TimeInForce, Type,
+    Amount, Class, CreateReq, CreateReqInit, Order as AlpacaOrder, Side, StopLoss, TakeProfit,
+    TimeInForce, Type,
 };
-use apca::Client;
+use apca::api::v2::orders::{List, ListReq, Status};
 use num_decimal::Num;
 
 use std::str::FromStr;
-use tokio::runtime::Runtime;
-use uuid::Uuid;
 
-use model::{Account, BrokerLog, Order, OrderIds, Trade, TradeCategory};
+use model::{Account, BrokerError, BrokerLog, Order, OrderIds, Trade, TradeCategory};
 use std::error::Error;
 
-use crate::keys;
-
-pub fn submit_sync(
+/// Submits the trade as a bracket order with the ID of the entry as its client order ID.
+///
+/// When the request fails, for example with a timeout or because the trade was
+/// already submitted, the order is looked up by its client order ID. An order that
+/// Alpaca accepted is returned instead of the error, so a trade can be submitted again.
+pub async fn submit_trade(
+    client: &Api,
     trade: &Trade,
     account: &Account,
-) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+) -> Result<(BrokerLog, OrderIds), Box<dyn Error + Send + Sync>> {
     assert!(trade.account_id == account.id); // Verify that the trade is for the account
 
-    let api_info = keys::read_api_key(&account.environment, account)?;
-    let client = Client::new(api_info);
+    if crate::crypto::is_crypto(trade) {
+        return crate::crypto::submit_trade(client, trade).await;
+    }
 
     let request = new_request(trade)?;
-    let order = Runtime::new()
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?
-        .block_on(submit(client, request))?;
+    let sent = format!("POST /v2/orders {}", serde_json::to_string(&request)?);
+    let order = match submit(client, request).await {
+        // An order found by its client order ID after a failed attempt has no legs
+        Ok(order) if order.legs.is_empty() => find_submitted(client, trade).await.unwrap_or(order),
+        Ok(order) => order,
+        Err(error) => match find_submitted(client, trade).await {
+            Some(order) => order,
+            None => return Err(BrokerError::sent(trade.id, &sent, error).into()),
+        },
+    };
 
     let log = BrokerLog {
         trade_id: trade.id,
-        log: serde_json::to_string(&order)?,
+        request: sent,
+        response: serde_json::to_string(&order)?,
         ..Default::default()
     };
-    let ids = extract_ids(&order, trade)?;
+    let ids = extract_ids(&order)?;
     Ok((log, ids))
 }
 
 async fn submit(
-    client: Client,
+    client: &Api,
     request: CreateReq,
-) -> Result<apca::api::v2::order::Order, Box<dyn Error>> {
-    let result = client.issue::<Create>(&request).await;
-
-    match result {
-        Ok(order) => Ok(order),
-        Err(e) => {
-            eprintln!("Error submitting trade: {e:?}. Are the US market open?");
-            Err(Box::new(e))
-        }
-    }
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    client
+        .create_order(&request)
+        .await
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+}
+
+/// Find the bracket order of a trade that Alpaca already accepted.
+/// The orders are listed with their legs, which are not returned when reading
+/// a single order, and the newest orders come first.
+async fn find_submitted(client: &Api, trade: &Trade) -> Option<AlpacaOrder> {
+    let request = ListReq {
+        symbols: vec![trade.trading_vehicle.symbol.to_uppercase()],
+        status: Status::All,
+        limit: Some(PAGE_SIZE),
+        ..Default::default()
+    };
+    let orders = client.issue::<List>(&request).await.ok()?;
+    find_by_client_order_id(orders, trade)
+}
+
+fn find_by_client_order_id(orders: Vec<AlpacaOrder>, trade: &Trade) -> Option<AlpacaOrder> {
+    let client_order_id = trade.entry.id.to_string();
+    orders
+        .into_iter()
+        .find(|order| order.client_order_id == client_order_id)
 }
 
-fn extract_ids(order: &AlpacaOrder, trade: &Trade) -> Result<OrderIds, Box<dyn Error>> {
+/// Identify the legs of the bracket order by their type. Both close the entry,
+/// so they are on the other side: the target is a limit order and the stop a stop order.
+fn extract_ids(order: &AlpacaOrder) -> Result<OrderIds, Box<dyn Error + Send + Sync>> {
     let mut stop_id = None;
     let mut target_id = None;
 
-    for leg in &order.legs {
-        let leg_price = match (leg.limit_price.clone(), leg.stop_price.clone()) {
-            (Some(limit_price), None) => limit_price,
-            (None, Some(stop_price)) => stop_price,
-            _ => return Err(format!("No price found for leg: {:?}", leg.id).into()),
+    for leg in order.legs.iter().filter(|leg| leg.side == !order.side) {
+        let id = match leg.type_ {
+            Type::Limit => &mut target_id,
+            Type::Stop | Type::StopLimit | Type::TrailingStop => &mut stop_id,
+            _ => continue,
         };
-
-        if leg_price.to_string() == trade.target.unit_price.to_string() {
-            target_id = Some(leg.id);
-        }
-
-        if leg_price.to_string() == trade.safety_stop.unit_price.to_string() {
-            stop_id = Some(leg.id);
+        if id.replace(leg.id.0).is_some() {
+            return Err(
+                format!("Order {} has more than one {:?} leg", order.id.0, leg.type_).into(),
+            );
         }
     }
 
-    let stop_id = stop_id.ok_or("Stop ID not found")?;
-    let target_id = target_id.ok_or("Target ID not found")?;
-
     Ok(OrderIds {
-        stop: Uuid::from_str(&stop_id.to_string())
-            .map_err(|e| format!("Failed to parse stop UUID: {e}"))?,
-        entry: Uuid::from_str(&order.id.to_string())
-            .map_err(|e| format!("Failed to parse entry UUID: {e}"))?,
-        target: Uuid::from_str(&target_id.to_string())
-            .map_err(|e| format!("Failed to parse target UUID: {e}"))?,
+        stop: Some(stop_id.ok_or("Stop ID not found")?),
+        entry: order.id.0,
+        target: Some(target_id.ok_or("Target ID not found")?),
     })
 }
 
-fn new_request(trade: &Trade) -> Result<CreateReq, Box<dyn Error>> {
+fn new_request(trade: &Trade) -> Result<CreateReq, Box<dyn Error + Send + Sync>> {
     let entry = Num::from_str(&trade.entry.unit_price.to_string())
         .map_err(|e| format!("Failed to parse entry price: {e:?}"))?;
     let stop = Num::from_str(&trade.safety_stop.unit_price.to_string())
@@ -275,31 +296,14 @@ mod tests {
     fn test_extract_ids_stop_order() {
         // Create a sample AlpacaOrder with a Stop type
         let entry = default();
-        let trade = Trade {
-            safety_stop: Order {
-                id: Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap(),
-                unit_price: dec!(12.52),
-                ..Default::default()
-            },
-            entry: Order {
-                id: Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap(),
-                ..Default::default()
-            },
-            target: Order {
-                id: Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap(),
-                unit_price: dec!(12.58),
-                ..Default::default()
-            },
-            ..Default::default()
-        };
 
         // Call the extract_ids function
-        let result = extract_ids(&entry, &trade).unwrap();
+        let result = extract_ids(&entry).unwrap();
 
-        // Check that the stop ID is correct and the target ID is a new UUID
+        // Check that the legs are identified by their type
         assert_eq!(
             result.stop,
-            Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()
+            Some(Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
         );
         assert_eq!(
             result.entry,
@@ -307,10 +311,59 @@ mod tests {
         );
         assert_eq!(
             result.target,
-            Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap()
+            Some(Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap())
         );
     }
 
+    #[test]
+    fn test_extract_ids_with_the_same_price() {
+        // Alpaca normalizes the prices, and the stop and the target can have the same price
+        let mut entry = default();
+        for leg in entry.legs.iter_mut() {
+            leg.limit_price = leg.limit_price.as_ref().map(|_| Num::from(12));
+            leg.stop_price = leg.stop_price.as_ref().map(|_| Num::from(12));
+        }
+
+        let result = extract_ids(&entry).unwrap();
+
+        assert_eq!(
+            result.stop,
+            Some(Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
+        );
+        assert_eq!(
+            result.target,
+            Some(Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_extract_ids_without_legs() {
+        let mut entry = default();
+        entry.legs.retain(|leg| leg.type_ == Type::Limit);
+
+        let error = extract_ids(&entry).unwrap_err();
+
+        assert_eq!(error.to_string(), "Stop ID not found");
+    }
+
+    #[test]
+    fn test_find_by_client_order_id() {
+        let trade = Trade {
+            entry: Order {
+                id: Uuid::parse_str("9fbce7ef-b98b-4930-80c1-ab929d52cfa3").unwrap(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut other = default();
+        other.client_order_id = Uuid::new_v4().to_string();
+
+        let found = find_by_client_order_id(vec![other, default()], &trade);
+
+        assert_eq!(found, Some(default()));
+        assert_eq!(find_by_client_order_id(vec![], &trade), None);
+    }
+
     #[test]
     fn test_side_long_trade() {
         // Create a sample Trade with Long category
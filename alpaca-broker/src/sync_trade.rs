@@ -1,40 +1,89 @@
-use crate::keys;
-use crate::order_mapper;
-use apca::api::v2::order::Order as AlpacaOrder;
-use apca::api::v2::orders::{List, ListReq, Status as AlpacaRequestStatus};
-use apca::Client;
-use model::{Account, BrokerLog, Order, Status, Trade};
+use crate::request::Api;
+use crate::{crypto, order_links, order_mapper};
+use apca::api::v2::order::{Get, GetByClientId, Id, Order as AlpacaOrder, Status as AlpacaStatus};
+use model::{Account, BrokerError, BrokerLog, Order, Status, Trade};
+use num_decimal::Num;
+use rust_decimal::Decimal;
 use std::error::Error;
-use tokio::runtime::Runtime;
+use std::str::FromStr;
+use uuid::Uuid;
 
-pub fn sync(
+/// Broker ID of an order that was replaced and the order that replaced it.
+/// An order that was not placed yet has no broker ID, so its own ID is used.
+pub type Replacement = (Uuid, AlpacaOrder);
+
+pub async fn sync(
+    client: &Api,
     trade: &Trade,
     account: &Account,
-) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error + Send + Sync>> {
     assert!(trade.account_id == account.id); // Verify that the trade is for the account
 
-    let api_info = keys::read_api_key(&account.environment, account)?;
-    let client = Client::new(api_info);
-
-    let orders = Runtime::new()
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?
-        .block_on(get_closed_orders(&client, trade))?;
+    let (orders, replacements) = read_orders(client, trade)
+        .await
+        .map_err(|e| BrokerError::sent(trade.id, &requested_orders(trade), e))?;
 
     let log = BrokerLog {
         trade_id: trade.id,
-        log: serde_json::to_string(&orders)?,
+        request: requested_orders(trade),
+        response: serde_json::to_string(&orders)?,
         ..Default::default()
     };
 
-    let (status, updated_orders) = sync_trade(trade, orders)?;
+    let replaced = replace_orders(trade, &replacements)?;
+    let (status, mut updated_orders) = sync_trade(&replaced, orders)?;
+
+    // A replacement that did not change the status is still a new order at the broker
+    for (original, current) in [
+        (&trade.entry, &replaced.entry),
+        (&trade.target, &replaced.target),
+        (&trade.safety_stop, &replaced.safety_stop),
+    ] {
+        if original != current && !updated_orders.iter().any(|order| order.id == current.id) {
+            updated_orders.push(current.clone());
+        }
+    }
+
     Ok((status, updated_orders, log))
 }
 
+/// Read the orders of the trade, with the orders that replaced them
+async fn read_orders(
+    client: &Api,
+    trade: &Trade,
+) -> Result<(Vec<AlpacaOrder>, Vec<Replacement>), Box<dyn Error + Send + Sync>> {
+    match trade.status {
+        Status::Canceled => Ok((vec![get_target(client, trade).await?], vec![])),
+        _ => {
+            let (entry, replacements) = if crypto::is_crypto(trade) {
+                crypto::get_trade_orders(client, trade).await?
+            } else {
+                get_trade_orders(client, trade).await?
+            };
+            Ok((vec![entry], replacements))
+        }
+    }
+}
+
+/// The orders of the trade that are read from Alpaca, as the request of the log
+fn requested_orders(trade: &Trade) -> String {
+    let orders = match trade.status {
+        Status::Canceled => vec![&trade.target],
+        _ => vec![&trade.entry, &trade.target, &trade.safety_stop],
+    };
+    orders
+        .into_iter()
+        .filter_map(|order| order.broker_order_id)
+        .map(|id| format!("GET /v2/orders/{id}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
 /// Sync Trade with Alpaca and return updated orders and status
 fn sync_trade(
     trade: &Trade,
     orders: Vec<AlpacaOrder>,
-) -> Result<(Status, Vec<Order>), Box<dyn Error>> {
+) -> Result<(Status, Vec<Order>), Box<dyn Error + Send + Sync>> {
     let updated_orders = match trade.status {
         Status::Canceled => {
             find_target(orders, trade).and_then(|order| order_mapper::map_target(order, trade))
@@ -47,34 +96,150 @@ fn sync_trade(
     Ok((status, updated_orders))
 }
 
-/// Get closed orders from Alpaca
-async fn get_closed_orders(
-    client: &Client,
+/// Get the entry of the trade with the target and the stop as its legs.
+///
+/// Every order is read by its ID, so open, partially filled and old orders are
+/// found no matter how many orders the symbol has. The orders that were replaced
+/// are swapped by the orders that replaced them.
+async fn get_trade_orders(
+    client: &Api,
     trade: &Trade,
-) -> Result<Vec<AlpacaOrder>, Box<dyn Error>> {
-    let request: ListReq = ListReq {
-        symbols: vec![trade.trading_vehicle.symbol.to_string()],
-        status: AlpacaRequestStatus::Closed,
-        ..Default::default()
-    };
+) -> Result<(AlpacaOrder, Vec<Replacement>), Box<dyn Error + Send + Sync>> {
+    let mut orders = vec![get_entry(client, trade).await?];
+    for leg in [&trade.target, &trade.safety_stop] {
+        if let Some(broker_order_id) = leg.broker_order_id {
+            orders.push(get_order(client, broker_order_id).await?);
+        }
+    }
+
+    let replacements = follow_replacements(client, &mut orders).await?;
+    Ok((into_entry(orders)?, replacements))
+}
+
+/// Swap the orders that were replaced by the orders that replaced them
+pub async fn follow_replacements(
+    client: &Api,
+    orders: &mut [AlpacaOrder],
+) -> Result<Vec<Replacement>, Box<dyn Error + Send + Sync>> {
+    let mut replacements = Vec::new();
+    for order in orders
+        .iter_mut()
+        .filter(|order| order.status == AlpacaStatus::Replaced)
+    {
+        let replacement = find_replacement(client, order).await?;
+        replacements.push((order.id.0, replacement.clone()));
+        *order = replacement;
+    }
+    Ok(replacements)
+}
+
+/// Nest the orders after the entry as its legs
+pub fn into_entry(orders: Vec<AlpacaOrder>) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    let mut orders = orders.into_iter();
+    let mut entry = orders.next().ok_or("Entry order not found")?;
+    entry.legs = orders.collect();
+    Ok(entry)
+}
+
+/// Get the entry by its broker ID, or by the ID of the entry if it was not saved
+pub async fn get_entry(
+    client: &Api,
+    trade: &Trade,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    match trade.entry.broker_order_id {
+        Some(broker_order_id) => get_order(client, broker_order_id).await,
+        None => client
+            .issue::<GetByClientId>(&trade.entry.id.to_string())
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>),
+    }
+}
 
-    let orders = client
-        .issue::<List>(&request)
+/// Get the target, which is the market order that closed the trade when it was closed manually
+async fn get_target(
+    client: &Api,
+    trade: &Trade,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    let target_order_id = trade
+        .target
+        .broker_order_id
+        .ok_or("Target order ID is missing")?;
+    get_order(client, target_order_id).await
+}
+
+pub async fn get_order(
+    client: &Api,
+    broker_order_id: Uuid,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    client
+        .issue::<Get>(&Id(broker_order_id))
         .await
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?;
-    Ok(orders)
+        .map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)
+}
+
+/// Find the order that replaced an order, following the replacements of the replacement
+async fn find_replacement(
+    client: &Api,
+    replaced: &AlpacaOrder,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
+    let mut current = replaced.clone();
+    while current.status == AlpacaStatus::Replaced {
+        let replacement = order_links::replaced_by(client, &current).await?;
+        current = get_order(client, replacement).await?;
+    }
+    Ok(current)
 }
 
-/// Find entry order from closed orders
-pub fn find_entry(orders: Vec<AlpacaOrder>, trade: &Trade) -> Result<AlpacaOrder, Box<dyn Error>> {
+/// Point the orders of the trade to the orders that replaced them, with their new prices
+fn replace_orders(
+    trade: &Trade,
+    replacements: &[Replacement],
+) -> Result<Trade, Box<dyn Error + Send + Sync>> {
+    let mut trade = trade.clone();
+    let stop_id = trade.safety_stop.id;
+    for (replaced_id, replacement) in replacements {
+        for order in [&mut trade.entry, &mut trade.target, &mut trade.safety_stop] {
+            if order.broker_order_id.unwrap_or(order.id) != *replaced_id {
+                continue;
+            }
+            let price = if order.id == stop_id {
+                &replacement.stop_price
+            } else {
+                &replacement.limit_price
+            };
+            if let Some(price) = price {
+                order.unit_price = parse_price(price)?;
+            }
+            order.broker_order_id = Some(replacement.id.0);
+        }
+    }
+    Ok(trade)
+}
+
+pub fn parse_price(price: &Num) -> Result<Decimal, Box<dyn Error + Send + Sync>> {
+    Decimal::from_str(&price.to_string())
+        .map_err(|e| format!("Failed to parse price {price}: {e}").into())
+}
+
+/// Find entry order by the ID of the entry, or by its broker ID when it was replaced
+pub fn find_entry(
+    orders: Vec<AlpacaOrder>,
+    trade: &Trade,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
     orders
         .into_iter()
-        .find(|x| x.client_order_id == trade.entry.id.to_string())
+        .find(|x| {
+            x.client_order_id == trade.entry.id.to_string()
+                || Some(x.id.0) == trade.entry.broker_order_id
+        })
         .ok_or_else(|| "Entry order not found, it can be that is not filled yet".into())
 }
 
 /// Find the target order that is on the first level of the JSON
-pub fn find_target(orders: Vec<AlpacaOrder>, trade: &Trade) -> Result<AlpacaOrder, Box<dyn Error>> {
+pub fn find_target(
+    orders: Vec<AlpacaOrder>,
+    trade: &Trade,
+) -> Result<AlpacaOrder, Box<dyn Error + Send + Sync>> {
     let target_order_id = trade
         .target
         .broker_order_id
@@ -553,4 +718,62 @@ mod tests {
             "Should not find entry order"
         );
     }
+
+    fn order(id: &str, created_at: &str, status: AlpacaStatus) -> AlpacaOrder {
+        AlpacaOrder {
+            id: Id(Uuid::parse_str(id).unwrap()),
+            status,
+            created_at: created_at.parse().unwrap(),
+            symbol: "TSLA".to_owned(),
+            type_: Type::Stop,
+            side: Side::Sell,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_sync_trade_with_replaced_stop() {
+        let entry_id = Uuid::parse_str("8ff773c7-f7ac-4220-9824-613d5921fbad").unwrap();
+        let entry_broker_id = Uuid::parse_str("66b4dfbf-2905-4a25-a388-873fec1a15de").unwrap();
+        let target_id = Uuid::parse_str("99106145-92dc-477e-b1c5-fcfdee452633").unwrap();
+        let replaced_stop_id = Uuid::parse_str("0b8f1c6c-6f63-4d5a-9b1e-5d1c1d1f0a11").unwrap();
+        let stop_id = Uuid::parse_str("ef022523-1f49-49e6-a1c1-98e2efd2ff35").unwrap();
+
+        let trade = Trade {
+            entry: Order {
+                id: entry_id,
+                broker_order_id: Some(entry_broker_id),
+                unit_price: dec!(246.2),
+                ..Default::default()
+            },
+            target: Order {
+                broker_order_id: Some(target_id),
+                unit_price: dec!(247),
+                ..Default::default()
+            },
+            safety_stop: Order {
+                broker_order_id: Some(replaced_stop_id),
+                unit_price: dec!(235),
+                ..Default::default()
+            },
+            status: Status::Filled,
+            ..Default::default()
+        };
+        let replacement = AlpacaOrder {
+            stop_price: Some(Num::from(240)),
+            ..order(
+                "ef022523-1f49-49e6-a1c1-98e2efd2ff35",
+                "2023-06-12T16:25:00Z",
+                AlpacaStatus::New,
+            )
+        };
+
+        let replaced = replace_orders(&trade, &[(replaced_stop_id, replacement)]).unwrap();
+        let (status, _) = sync_trade(&replaced, default_from_json()).unwrap();
+
+        assert_eq!(replaced.safety_stop.broker_order_id, Some(stop_id));
+        assert_eq!(replaced.safety_stop.unit_price, dec!(240));
+        assert_eq!(replaced.target, trade.target);
+        assert_eq!(status, Status::ClosedTarget);
+    }
 }
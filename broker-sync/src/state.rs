@@ -27,6 +27,13 @@ impl Default for BackoffConfig {
     }
 }
 
+impl BackoffConfig {
+    /// Delay before the given retry attempt, starting at 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        BrokerState::calculate_backoff_with_config(attempt.max(1), self)
+    }
+}
+
 /// Errors that can occur during state transitions
 #[derive(Debug, Clone, Error, PartialEq)]
 pub enum StateError {
@@ -266,3 +266,19 @@ fn test_concurrent_jitter_different_values() {
         unique_delays.len()
     );
 }
+
+#[test]
+fn test_delay_grows_with_the_attempt() {
+    let config = BackoffConfig {
+        base_delay_ms: 500,
+        max_delay_ms: 4_000,
+        max_exponent: 6,
+        jitter_percent: 0,
+    };
+
+    assert_eq!(config.delay(0), Duration::from_millis(500));
+    assert_eq!(config.delay(1), Duration::from_millis(500));
+    assert_eq!(config.delay(2), Duration::from_millis(1_000));
+    assert_eq!(config.delay(3), Duration::from_millis(2_000));
+    assert_eq!(config.delay(5), Duration::from_millis(4_000));
+}
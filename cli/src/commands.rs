@@ -1,6 +1,9 @@
 mod account_command;
 mod key_command;
+mod price_command;
+mod report_command;
 mod rule_command;
+mod sim_command;
 mod trade_command;
 mod trading_vehicle_command;
 mod transaction_command;
@@ -8,7 +11,10 @@ mod transaction_command;
 // Re-export the types from the cli crate.
 pub use account_command::AccountCommandBuilder;
 pub use key_command::KeysCommandBuilder;
+pub use price_command::PriceCommandBuilder;
+pub use report_command::ReportCommandBuilder;
 pub use rule_command::RuleCommandBuilder;
+pub use sim_command::SimCommandBuilder;
 pub use trade_command::TradeCommandBuilder;
 pub use trading_vehicle_command::TradingVehicleCommandBuilder;
 pub use transaction_command::TransactionCommandBuilder;
@@ -30,4 +30,24 @@ impl AccountCommandBuilder {
             .push(Command::new("search").about("search an account by name"));
         self
     }
+
+    pub fn position_sizing(mut self) -> Self {
+        self.subcommands
+            .push(Command::new("sizing").about("Select the position sizing model of an account"));
+        self
+    }
+
+    pub fn benchmark(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("benchmark")
+                .about("Select the index the account returns are compared with"),
+        );
+        self
+    }
+
+    pub fn fee_schedule(mut self) -> Self {
+        self.subcommands
+            .push(Command::new("fees").about("Set the commissions and fees charged to an account"));
+        self
+    }
 }
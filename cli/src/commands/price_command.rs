@@ -0,0 +1,28 @@
+use clap::Command;
+
+pub struct PriceCommandBuilder {
+    command: Command,
+    subcommands: Vec<Command>,
+}
+
+impl PriceCommandBuilder {
+    pub fn new() -> Self {
+        PriceCommandBuilder {
+            command: Command::new("price")
+                .about("Manage the daily price series used as benchmarks")
+                .arg_required_else_help(true),
+            subcommands: Vec::new(),
+        }
+    }
+
+    pub fn build(self) -> Command {
+        self.command.subcommands(self.subcommands)
+    }
+
+    pub fn import(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("import").about("Import the daily prices of a symbol from a CSV file"),
+        );
+        self
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Command;
+
+pub struct ReportCommandBuilder {
+    command: Command,
+    subcommands: Vec<Command>,
+}
+
+impl ReportCommandBuilder {
+    pub fn new() -> Self {
+        ReportCommandBuilder {
+            command: Command::new("report")
+                .about("Analyze the performance of the trades of an account")
+                .arg_required_else_help(true),
+            subcommands: Vec::new(),
+        }
+    }
+
+    pub fn build(self) -> Command {
+        self.command.subcommands(self.subcommands)
+    }
+
+    pub fn slippage(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("slippage")
+                .about("Show the slippage of entries, stops and targets of filled orders"),
+        );
+        self
+    }
+
+    pub fn benchmark(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("benchmark").about("Compare the returns of an account with its benchmark"),
+        );
+        self
+    }
+}
@@ -0,0 +1,37 @@
+use clap::Command;
+
+pub struct SimCommandBuilder {
+    command: Command,
+    subcommands: Vec<Command>,
+}
+
+impl SimCommandBuilder {
+    pub fn new() -> Self {
+        SimCommandBuilder {
+            command: Command::new("sim")
+                .about("Feed prices into the simulated broker used with --broker sim")
+                .arg_required_else_help(true),
+            subcommands: Vec::new(),
+        }
+    }
+
+    pub fn build(self) -> Command {
+        self.command.subcommands(self.subcommands)
+    }
+
+    pub fn tick(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("tick")
+                .about("Fill the simulated orders that a new price of a symbol crosses"),
+        );
+        self
+    }
+
+    pub fn import(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("import")
+                .about("Feed the prices of a CSV file with Symbol and Price columns, row by row"),
+        );
+        self
+    }
+}
@@ -81,6 +81,15 @@ impl TradeCommandBuilder {
         self
     }
 
+    pub fn modify_entry(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("modify-entry").about(
+                "Modify the entry price and quantity of a submitted trade that is not filled.",
+            ),
+        );
+        self
+    }
+
     pub fn manually_target(mut self) -> Self {
         self.subcommands
             .push(Command::new("manually-target").about("Execute manually the target of a trade"));
@@ -93,9 +102,34 @@ impl TradeCommandBuilder {
         self
     }
 
+    pub fn import_positions(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("import").about(
+                "Import the open positions and working orders of the broker as funded trades",
+            ),
+        );
+        self
+    }
+
+    pub fn logs(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("logs")
+                .about("Show every call made to the broker for a trade, with its answer"),
+        );
+        self
+    }
+
     pub fn manually_close(mut self) -> Self {
         self.subcommands
             .push(Command::new("manually-close").about("Manually close a trade"));
         self
     }
+
+    pub fn close_partial(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("close-partial")
+                .about("Close a quantity or a percentage of a filled trade, the rest stays open."),
+        );
+        self
+    }
 }
@@ -9,7 +9,7 @@ impl TransactionCommandBuilder {
     pub fn new() -> Self {
         TransactionCommandBuilder {
             command: Command::new("transaction")
-                .about("Withdraw or deposit money from an account")
+                .about("Withdraw, deposit or record income and fees of an account")
                 .arg_required_else_help(true),
             subcommands: Vec::new(),
         }
@@ -30,4 +30,46 @@ impl TransactionCommandBuilder {
             .push(Command::new("withdraw").about("Withdraw money from an account"));
         self
     }
+
+    pub fn interest(mut self) -> Self {
+        self.subcommands
+            .push(Command::new("interest").about("Record interest paid on the cash of an account"));
+        self
+    }
+
+    pub fn dividend(mut self) -> Self {
+        self.subcommands
+            .push(Command::new("dividend").about("Record a dividend paid to a long trade"));
+        self
+    }
+
+    pub fn borrow_fee(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("borrow-fee")
+                .about("Record a borrow or margin fee charged to a short trade"),
+        );
+        self
+    }
+
+    pub fn transfer(mut self) -> Self {
+        self.subcommands
+            .push(Command::new("transfer").about("Transfer money between two accounts"));
+        self
+    }
+
+    pub fn import(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("import")
+                .about("Import the deposits, withdrawals, income, fees and fills of the broker"),
+        );
+        self
+    }
+
+    pub fn withdraw_earnings(mut self) -> Self {
+        self.subcommands.push(
+            Command::new("withdraw-earnings")
+                .about("Withdraw the earnings set aside from profitable trades"),
+        );
+        self
+    }
 }
@@ -1,13 +1,25 @@
 mod account_dialog;
+mod activity_import_dialog;
+mod backtest_dialog;
+mod doctor_dialog;
 mod keys_dialog;
+mod manual_fill_dialog;
 mod modify_dialog;
+mod modify_entry_dialog;
+mod price_dialog;
+mod report_dialog;
 mod rule_dialog;
+mod sim_dialog;
+mod simulation_dialog;
 mod trade_cancel_dialog;
 mod trade_close_dialog;
+mod trade_close_partial_dialog;
 mod trade_create_dialog;
 mod trade_exit_dialog;
 mod trade_fill_dialog;
 mod trade_funding_dialog;
+mod trade_import_dialog;
+mod trade_logs_dialog;
 mod trade_search_dialog;
 mod trade_submit_dialog;
 mod trade_sync_dialog;
@@ -16,20 +28,38 @@ mod transaction_dialog;
 
 pub use account_dialog::AccountDialogBuilder;
 pub use account_dialog::AccountSearchDialog;
+pub use account_dialog::BenchmarkDialogBuilder;
+pub use account_dialog::FeeScheduleDialogBuilder;
+pub use account_dialog::PositionSizingDialogBuilder;
+pub use activity_import_dialog::ActivityImportDialogBuilder;
+pub use backtest_dialog::BacktestDialogBuilder;
+pub use doctor_dialog::DoctorDialogBuilder;
 pub use keys_dialog::KeysDeleteDialogBuilder;
 pub use keys_dialog::KeysReadDialogBuilder;
 pub use keys_dialog::KeysWriteDialogBuilder;
+pub use manual_fill_dialog::ManualFillPrompt;
 pub use modify_dialog::ModifyDialogBuilder;
+pub use modify_entry_dialog::ModifyEntryDialogBuilder;
+pub use price_dialog::PriceImportDialogBuilder;
+pub use report_dialog::BenchmarkReportDialogBuilder;
+pub use report_dialog::SlippageReportDialogBuilder;
 pub use rule_dialog::RuleDialogBuilder;
 pub use rule_dialog::RuleRemoveDialogBuilder;
+pub use sim_dialog::{SimImportDialogBuilder, SimTickDialogBuilder};
+pub use simulation_dialog::SimulationDialogBuilder;
 pub use trade_cancel_dialog::CancelDialogBuilder;
 pub use trade_close_dialog::CloseDialogBuilder;
+pub use trade_close_partial_dialog::ClosePartialDialogBuilder;
 pub use trade_create_dialog::TradeDialogBuilder;
 pub use trade_exit_dialog::ExitDialogBuilder;
 pub use trade_fill_dialog::FillTradeDialogBuilder;
 pub use trade_funding_dialog::FundingDialogBuilder;
+pub use trade_import_dialog::TradeImportDialogBuilder;
+pub use trade_logs_dialog::TradeLogsDialogBuilder;
 pub use trade_search_dialog::TradeSearchDialogBuilder;
 pub use trade_submit_dialog::SubmitDialogBuilder;
 pub use trade_sync_dialog::SyncTradeDialogBuilder;
 pub use trading_vehicle_dialog::{TradingVehicleDialogBuilder, TradingVehicleSearchDialogBuilder};
+pub use transaction_dialog::TradeTransactionDialogBuilder;
 pub use transaction_dialog::TransactionDialogBuilder;
+pub use transaction_dialog::TransferDialogBuilder;
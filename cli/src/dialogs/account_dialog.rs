@@ -12,10 +12,10 @@
 
 use std::error::Error;
 
-use crate::views::{AccountBalanceView, AccountView, RuleView};
+use crate::views::{AccountBalanceView, AccountView, FeeScheduleView, RuleView};
 use core::TrustFacade;
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
-use model::{Account, Environment};
+use model::{Account, Environment, FeeSchedule, OpenRisk, PositionSizing};
 use rust_decimal::Decimal;
 
 pub struct AccountDialogBuilder {
@@ -137,8 +137,17 @@ impl AccountSearchDialog {
                 let rules = trust
                     .search_all_rules(account.id)
                     .expect("Error searching account rules");
+                let open_risk: Vec<OpenRisk> = balances
+                    .iter()
+                    .filter_map(|balance| {
+                        trust
+                            .calculate_open_risk(account.id, &balance.currency)
+                            .ok()
+                    })
+                    .collect();
                 let name = account.name.clone();
-                AccountView::display_account(account);
+                let account_id = account.id;
+                AccountView::display_account_with_open_risk(account, &open_risk);
                 if balances.is_empty() {
                     println!("No transactions found");
                 } else {
@@ -148,6 +157,15 @@ impl AccountSearchDialog {
                 println!();
                 println!("Rules:");
                 RuleView::display_rules(rules, &name);
+                match trust.search_fee_schedule(account_id) {
+                    Ok(Some(schedule)) => {
+                        println!();
+                        println!("Fees:");
+                        FeeScheduleView::display(schedule);
+                    }
+                    Ok(None) => {}
+                    Err(error) => println!("Error searching fee schedule: {error:?}"),
+                }
             }
             Err(error) => println!("Error searching account: {error:?}"),
         }
@@ -177,3 +195,245 @@ impl AccountSearchDialog {
         self
     }
 }
+
+pub struct PositionSizingDialogBuilder {
+    account: Option<Account>,
+    position_sizing: Option<PositionSizing>,
+    result: Option<Result<Account, Box<dyn Error>>>,
+}
+
+impl PositionSizingDialogBuilder {
+    pub fn new() -> Self {
+        PositionSizingDialogBuilder {
+            account: None,
+            position_sizing: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> PositionSizingDialogBuilder {
+        self.result = Some(
+            trust.update_position_sizing(
+                &self
+                    .account
+                    .clone()
+                    .expect("Did you forget to select an account?"),
+                self.position_sizing
+                    .expect("Did you forget to select a position sizing model?"),
+            ),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(account) => AccountView::display_account(account),
+            Err(error) => println!("Error updating position sizing: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn position_sizing(mut self) -> Self {
+        let available_sizing = PositionSizing::all();
+
+        let selected = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Position sizing:")
+            .items(&available_sizing[..])
+            .interact()
+            .map(|index| available_sizing.get(index).unwrap())
+            .unwrap();
+
+        let value = match selected {
+            PositionSizing::FixedFractional => Decimal::default(),
+            PositionSizing::FixedDollar(_) => Input::new()
+                .with_prompt("Amount to risk per trade")
+                .interact()
+                .unwrap(),
+            PositionSizing::Volatility(_) => Input::new()
+                .with_prompt("ATR multiplier")
+                .interact()
+                .unwrap(),
+            PositionSizing::Kelly(_) => Input::new()
+                .with_prompt("Kelly fraction (0.5 is half Kelly)")
+                .interact()
+                .unwrap(),
+        };
+
+        self.position_sizing = Some(
+            PositionSizing::parse(&selected.to_string(), value)
+                .expect("Position sizing should be parsed"),
+        );
+        self
+    }
+}
+
+pub struct BenchmarkDialogBuilder {
+    account: Option<Account>,
+    benchmark: Option<String>,
+    result: Option<Result<Account, Box<dyn Error>>>,
+}
+
+impl BenchmarkDialogBuilder {
+    pub fn new() -> Self {
+        BenchmarkDialogBuilder {
+            account: None,
+            benchmark: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> BenchmarkDialogBuilder {
+        self.result = Some(
+            trust.update_benchmark(
+                &self
+                    .account
+                    .clone()
+                    .expect("Did you forget to select an account?"),
+                self.benchmark.as_deref(),
+            ),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(account) => AccountView::display_account(account),
+            Err(error) => println!("Error updating benchmark: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn benchmark(mut self) -> Self {
+        let current = self
+            .account
+            .as_ref()
+            .and_then(|account| account.benchmark.clone())
+            .unwrap_or_default();
+
+        let symbol: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Benchmark symbol, like SPY (empty to remove it)")
+            .with_initial_text(current)
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+
+        let symbol = symbol.trim().to_uppercase();
+        self.benchmark = if symbol.is_empty() {
+            None
+        } else {
+            Some(symbol)
+        };
+        self
+    }
+}
+
+pub struct FeeScheduleDialogBuilder {
+    account: Option<Account>,
+    schedule: FeeSchedule,
+    result: Option<Result<FeeSchedule, Box<dyn Error>>>,
+}
+
+impl FeeScheduleDialogBuilder {
+    pub fn new() -> Self {
+        FeeScheduleDialogBuilder {
+            account: None,
+            schedule: FeeSchedule::default(),
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> FeeScheduleDialogBuilder {
+        self.result = Some(
+            trust.set_fee_schedule(
+                &self
+                    .account
+                    .clone()
+                    .expect("Did you forget to select an account?"),
+                &self.schedule,
+            ),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(schedule) => FeeScheduleView::display(schedule),
+            Err(error) => println!("Error setting fee schedule: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn commission(mut self) -> Self {
+        self.schedule.per_share = FeeScheduleDialogBuilder::input("Commission per share");
+        self.schedule.per_order = FeeScheduleDialogBuilder::input("Commission per order");
+        self.schedule.percentage =
+            FeeScheduleDialogBuilder::input("Commission as % of the order value");
+        self.schedule.minimum = FeeScheduleDialogBuilder::input("Minimum commission per order");
+
+        let maximum: String = Input::new()
+            .with_prompt("Maximum commission per order (empty for no maximum)")
+            .allow_empty(true)
+            .validate_with(|input: &String| -> Result<(), &str> {
+                if input.is_empty() || input.parse::<Decimal>().is_ok() {
+                    Ok(())
+                } else {
+                    Err("Please enter a valid number")
+                }
+            })
+            .interact_text()
+            .unwrap();
+        self.schedule.maximum = maximum.parse::<Decimal>().ok();
+        self
+    }
+
+    pub fn regulatory(mut self) -> Self {
+        self.schedule.sec_fee_rate =
+            FeeScheduleDialogBuilder::input("SEC fee rate on sells (e.g. 0.0000278)");
+        self.schedule.taf_per_share =
+            FeeScheduleDialogBuilder::input("FINRA TAF per share sold (e.g. 0.000166)");
+        self.schedule.taf_maximum =
+            FeeScheduleDialogBuilder::input("FINRA TAF maximum per order (e.g. 8.30)");
+        self
+    }
+
+    fn input(prompt: &str) -> Decimal {
+        Input::new()
+            .with_prompt(prompt)
+            .default(Decimal::default())
+            .interact()
+            .unwrap()
+    }
+}
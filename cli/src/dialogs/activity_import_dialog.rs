@@ -0,0 +1,125 @@
+//! Activity import dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::{ActivityDiscrepancyView, TransactionView};
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
+use model::{Account, ActivityImport, Currency};
+use shellexpand::tilde;
+use std::error::Error;
+use std::fs;
+
+pub struct ActivityImportDialogBuilder {
+    account: Option<Account>,
+    path: Option<String>,
+    currency: Option<Currency>,
+    result: Option<Result<ActivityImport, Box<dyn Error>>>,
+}
+
+impl ActivityImportDialogBuilder {
+    pub fn new() -> Self {
+        ActivityImportDialogBuilder {
+            account: None,
+            path: None,
+            currency: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> ActivityImportDialogBuilder {
+        let account = self
+            .account
+            .clone()
+            .expect("No account found, did you forget to select one?");
+
+        self.result = Some(match &self.path {
+            Some(path) => {
+                let currency = self
+                    .currency
+                    .expect("Did you forget to select the currency?");
+                fs::read_to_string(tilde(path).as_ref())
+                    .map_err(|error| format!("Failed to read {path}: {error}").into())
+                    .and_then(|csv| trust.import_activities_csv(&account, &csv, &currency))
+            }
+            None => trust.import_activities(&account),
+        });
+        self
+    }
+
+    pub fn display(self) {
+        let account = self.account.unwrap();
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(import) => {
+                if import.transactions.is_empty() {
+                    println!("No transactions were created");
+                } else {
+                    println!("Created transactions:");
+                    TransactionView::display_transactions(
+                        import.transactions.iter().collect(),
+                        &account.name,
+                    );
+                }
+                println!(
+                    "{} activities matched what Trust recorded, {} were imported before",
+                    import.matched.len(),
+                    import.duplicates
+                );
+                if !import.discrepancies.is_empty() {
+                    println!("Activities that differ from what Trust recorded:");
+                    ActivityDiscrepancyView::display(import.discrepancies);
+                }
+            }
+            Err(error) => println!("Error importing the activities: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn file(mut self) -> Self {
+        let path: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(
+                "CSV file with ID, Date, Type and Amount columns, or empty to read the broker",
+            )
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        let path = path.trim();
+        self.path = (!path.is_empty()).then(|| path.to_string());
+        self
+    }
+
+    pub fn currency(mut self) -> Self {
+        if self.path.is_none() {
+            return self;
+        }
+        let currencies = Currency::all();
+        let currency = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Currency of the amounts:")
+            .items(&currencies[..])
+            .interact()
+            .map(|index| currencies.get(index).unwrap())
+            .unwrap();
+        self.currency = Some(*currency);
+        self
+    }
+}
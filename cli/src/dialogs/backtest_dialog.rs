@@ -0,0 +1,150 @@
+//! Backtest dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::{AccountBalanceView, SkippedPlanView, TradeView};
+use core::TrustFacade;
+use db_sqlite::SqliteDatabase;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
+use model::{Account, BacktestParameters, BacktestReport, Currency, Status};
+use rust_decimal::Decimal;
+use shellexpand::tilde;
+use std::error::Error;
+use std::fs;
+
+pub struct BacktestDialogBuilder {
+    account: Option<Account>,
+    path: Option<String>,
+    starting_capital: Option<Decimal>,
+    currency: Option<Currency>,
+    result: Option<Result<BacktestReport, Box<dyn Error>>>,
+}
+
+impl BacktestDialogBuilder {
+    pub fn new() -> Self {
+        BacktestDialogBuilder {
+            account: None,
+            path: None,
+            starting_capital: None,
+            currency: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> BacktestDialogBuilder {
+        let account = self
+            .account
+            .as_ref()
+            .expect("Did you forget to select an account?");
+        let path = self.path.clone().expect("Did you forget to select a file?");
+        let parameters = BacktestParameters {
+            starting_capital: self
+                .starting_capital
+                .expect("Did you forget to enter the starting capital?"),
+            currency: self.currency.expect("Did you forget to select a currency?"),
+        };
+
+        self.result = Some(
+            fs::read_to_string(tilde(&path).as_ref())
+                .map_err(|error| format!("Failed to read {path}: {error}").into())
+                .and_then(|csv| TrustFacade::parse_trade_plans(&csv))
+                .and_then(|plans| {
+                    trust.backtest(
+                        account,
+                        &plans,
+                        &parameters,
+                        Box::new(SqliteDatabase::new_in_memory()),
+                    )
+                })
+                .map(|(report, _)| report),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(report) => {
+                println!();
+                println!("Backtest from {} to {}:", report.from, report.to);
+                let count = |status: Status| {
+                    report
+                        .trades
+                        .iter()
+                        .filter(|trade| trade.status == status)
+                        .count()
+                };
+                println!(
+                    "{} trades: {} closed at the target, {} at the stop, {} still open and {} not filled",
+                    report.trades.len(),
+                    count(Status::ClosedTarget),
+                    count(Status::ClosedStopLoss),
+                    count(Status::Filled),
+                    count(Status::Canceled)
+                );
+                TradeView::display_trades(report.trades, &report.account.name);
+                AccountBalanceView::display(report.balance, &report.account.name);
+                if !report.skipped.is_empty() {
+                    println!("Skipped plans:");
+                    SkippedPlanView::display(report.skipped);
+                }
+            }
+            Err(error) => println!("Error running the backtest: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn file(mut self) -> Self {
+        let path: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(
+                "CSV file with Date, Symbol, Category, Entry, Stop, Target and ATR columns",
+            )
+            .interact_text()
+            .unwrap();
+        self.path = Some(path.trim().to_string());
+        self
+    }
+
+    pub fn starting_capital(mut self) -> Self {
+        self.starting_capital = Some(
+            Input::with_theme(&ColorfulTheme::default())
+                .with_prompt("Starting capital")
+                .interact_text()
+                .unwrap(),
+        );
+        self
+    }
+
+    pub fn currency(mut self) -> Self {
+        let currencies = Currency::all();
+        let selected_currency = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Currency:")
+            .items(&currencies[..])
+            .default(0)
+            .interact()
+            .map(|index| currencies.get(index).unwrap())
+            .unwrap();
+
+        self.currency = Some(*selected_currency);
+        self
+    }
+}
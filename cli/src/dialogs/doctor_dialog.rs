@@ -0,0 +1,82 @@
+//! Doctor dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::views::InconsistencyView;
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use model::Inconsistency;
+use std::error::Error;
+
+pub struct DoctorDialogBuilder {
+    inconsistencies: Option<Result<Vec<Inconsistency>, Box<dyn Error>>>,
+    repaired: Option<Result<usize, Box<dyn Error>>>,
+}
+
+impl DoctorDialogBuilder {
+    pub fn new() -> Self {
+        DoctorDialogBuilder {
+            inconsistencies: None,
+            repaired: None,
+        }
+    }
+
+    pub fn diagnose(mut self, trust: &mut TrustFacade) -> Self {
+        let inconsistencies = trust.diagnose();
+        match &inconsistencies {
+            Ok(inconsistencies) if inconsistencies.is_empty() => {
+                println!("No inconsistencies found");
+            }
+            Ok(inconsistencies) => {
+                println!("Inconsistencies found:");
+                InconsistencyView::display(inconsistencies);
+            }
+            Err(error) => println!("Error checking accounts: {error}"),
+        }
+        self.inconsistencies = Some(inconsistencies);
+        self
+    }
+
+    pub fn repair(mut self, trust: &mut TrustFacade) -> Self {
+        let Some(Ok(inconsistencies)) = &self.inconsistencies else {
+            return self;
+        };
+
+        let repairable = inconsistencies
+            .iter()
+            .filter(|inconsistency| inconsistency.category.is_repairable())
+            .count();
+        if repairable == 0 {
+            return self;
+        }
+
+        let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "Recalculate {repairable} cached balances from the transactions?"
+            ))
+            .default(false)
+            .interact()
+            .unwrap();
+
+        if confirmed {
+            self.repaired = Some(trust.repair(inconsistencies));
+        }
+        self
+    }
+
+    pub fn display(self) {
+        match self.repaired {
+            Some(Ok(repaired)) => println!("{repaired} inconsistencies repaired"),
+            Some(Err(error)) => println!("Error repairing inconsistencies: {error}"),
+            None => {}
+        }
+    }
+}
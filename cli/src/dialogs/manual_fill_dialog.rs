@@ -0,0 +1,61 @@
+//! Manual fill dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use chrono::Utc;
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input};
+use manual_broker::{Fill, FillPrompt, Fills};
+use model::{Order, Trade};
+use std::error::Error;
+
+/// Asks the trader for the fills of a trade placed with the manual broker
+pub struct ManualFillPrompt;
+
+impl FillPrompt for ManualFillPrompt {
+    fn fills(&self, trade: &Trade) -> Result<Fills, Box<dyn Error>> {
+        let symbol = &trade.trading_vehicle.symbol;
+        let entry_filled = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Was the entry of {symbol} filled?"))
+            .default(false)
+            .interact()?;
+        if !entry_filled {
+            return Ok(Fills::default());
+        }
+
+        let mut fills = Fills {
+            entry: Some(fill("entry", &trade.entry)?),
+            ..Default::default()
+        };
+        let exits = ["Still open", "Stop", "Target"];
+        let exit = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Was {symbol} closed?"))
+            .items(&exits[..])
+            .default(0)
+            .interact()?;
+        match exit {
+            1 => fills.stop = Some(fill("stop", &trade.safety_stop)?),
+            2 => fills.target = Some(fill("target", &trade.target)?),
+            _ => {}
+        }
+        Ok(fills)
+    }
+}
+
+fn fill(leg: &str, order: &Order) -> Result<Fill, Box<dyn Error>> {
+    let price = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Average price of the {leg}"))
+        .default(order.unit_price)
+        .interact_text()?;
+    Ok(Fill {
+        price,
+        filled_at: Utc::now().naive_utc(),
+    })
+}
@@ -0,0 +1,143 @@
+//! UI Dialog Module - User Interaction Code
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::{OrderView, TradeBalanceView, TradeView, TransactionView};
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
+use model::{Account, Status, Trade, Transaction};
+use rust_decimal::Decimal;
+use std::error::Error;
+
+type ModifyEntryDialogBuilderResult = Option<Result<(Trade, Option<Transaction>), Box<dyn Error>>>;
+
+pub struct ModifyEntryDialogBuilder {
+    account: Option<Account>,
+    trade: Option<Trade>,
+    new_price: Option<Decimal>,
+    new_quantity: Option<u64>,
+    result: ModifyEntryDialogBuilderResult,
+}
+
+impl ModifyEntryDialogBuilder {
+    pub fn new() -> Self {
+        ModifyEntryDialogBuilder {
+            account: None,
+            trade: None,
+            new_price: None,
+            new_quantity: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> ModifyEntryDialogBuilder {
+        let trade = self
+            .trade
+            .clone()
+            .expect("No trade found, did you forget to call search?");
+        let account = self
+            .account
+            .clone()
+            .expect("No account found, did you forget to call account?");
+        let new_price = self
+            .new_price
+            .expect("No entry price found, did you forget to call entry?");
+        let new_quantity = self
+            .new_quantity
+            .expect("No quantity found, did you forget to call entry?");
+
+        self.result = Some(trust.modify_entry(&trade, &account, new_price, new_quantity));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok((trade, transaction)) => {
+                let account_name = self.account.unwrap().name;
+                println!("Trade updated:");
+                TradeView::display(&trade, &account_name);
+                TradeBalanceView::display(&trade.balance);
+
+                println!("Entry:");
+                OrderView::display(trade.entry);
+
+                match transaction {
+                    Some(transaction) => {
+                        println!("Funding adjusted:");
+                        TransactionView::display(&transaction, &account_name);
+                    }
+                    None => println!("The funding of the trade did not change"),
+                }
+            }
+            Err(error) => println!("Error modifying the entry: {error:?}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn search(mut self, trust: &mut TrustFacade) -> Self {
+        let trades = trust.search_trades(self.account.clone().unwrap().id, Status::Submitted);
+        match trades {
+            Ok(trades) => {
+                if trades.is_empty() {
+                    panic!(
+                        "No trade found with the status submitted, did you forget to submit one?"
+                    )
+                }
+                let trade = FuzzySelect::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Trade:")
+                    .items(&trades[..])
+                    .default(0)
+                    .interact_opt()
+                    .unwrap()
+                    .map(|index| trades.get(index).unwrap())
+                    .unwrap();
+
+                println!("Trade selected:");
+                TradeView::display(trade, &self.account.clone().unwrap().name);
+                self.trade = Some(trade.to_owned());
+            }
+            Err(error) => self.result = Some(Err(error)),
+        }
+
+        self
+    }
+
+    pub fn entry(mut self) -> Self {
+        let Some(trade) = self.trade.clone() else {
+            return self;
+        };
+        let new_price = Input::new()
+            .with_prompt("New entry price")
+            .with_initial_text(trade.entry.unit_price.to_string())
+            .interact()
+            .unwrap();
+        let new_quantity = Input::new()
+            .with_prompt("New quantity")
+            .with_initial_text(trade.entry.quantity.to_string())
+            .interact()
+            .unwrap();
+        self.new_price = Some(new_price);
+        self.new_quantity = Some(new_quantity);
+        self
+    }
+}
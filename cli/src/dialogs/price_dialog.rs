@@ -0,0 +1,88 @@
+//! Price dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, Input};
+use model::PriceBar;
+use shellexpand::tilde;
+use std::error::Error;
+use std::fs;
+
+pub struct PriceImportDialogBuilder {
+    symbol: Option<String>,
+    path: Option<String>,
+    result: Option<Result<Vec<PriceBar>, Box<dyn Error>>>,
+}
+
+impl PriceImportDialogBuilder {
+    pub fn new() -> Self {
+        PriceImportDialogBuilder {
+            symbol: None,
+            path: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> PriceImportDialogBuilder {
+        let symbol = self
+            .symbol
+            .clone()
+            .expect("Did you forget to select a symbol?");
+        let path = self.path.clone().expect("Did you forget to select a file?");
+
+        self.result = Some(
+            fs::read_to_string(tilde(&path).as_ref())
+                .map_err(|error| format!("Failed to read {path}: {error}").into())
+                .and_then(|csv| trust.import_prices(&symbol, &csv)),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(bars) => {
+                let first = bars.iter().map(|bar| bar.date).min();
+                let last = bars.iter().map(|bar| bar.date).max();
+                match (first, last) {
+                    (Some(first), Some(last)) => println!(
+                        "Imported {} prices of {} from {first} to {last}",
+                        bars.len(),
+                        self.symbol.unwrap().to_uppercase()
+                    ),
+                    _ => println!("No prices imported"),
+                }
+            }
+            Err(error) => println!("Error importing prices: {error}"),
+        }
+    }
+
+    pub fn symbol(mut self) -> Self {
+        let symbol: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Symbol, like SPY")
+            .interact_text()
+            .unwrap();
+        self.symbol = Some(symbol.trim().to_string());
+        self
+    }
+
+    pub fn file(mut self) -> Self {
+        let path: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("CSV file with Date, Open, High, Low, Close and Volume columns")
+            .interact_text()
+            .unwrap();
+        self.path = Some(path.trim().to_string());
+        self
+    }
+}
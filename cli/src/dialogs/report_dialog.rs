@@ -0,0 +1,176 @@
+//! Report dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::{BenchmarkView, SlippageSummaryView, SlippageView};
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+use model::{
+    Account, BenchmarkComparison, Currency, OrderSlippage, SlippageGrouping, SlippageSummary,
+};
+use std::error::Error;
+
+type SlippageReport = (
+    Vec<OrderSlippage>,
+    Vec<(SlippageGrouping, Vec<SlippageSummary>)>,
+);
+
+pub struct SlippageReportDialogBuilder {
+    account: Option<Account>,
+    currency: Option<Currency>,
+    result: Option<Result<SlippageReport, Box<dyn Error>>>,
+}
+
+impl SlippageReportDialogBuilder {
+    pub fn new() -> Self {
+        SlippageReportDialogBuilder {
+            account: None,
+            currency: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> SlippageReportDialogBuilder {
+        let account_id = self
+            .account
+            .as_ref()
+            .expect("Did you forget to select an account?")
+            .id;
+        let currency = self.currency.expect("Did you forget to select a currency?");
+
+        self.result = Some(
+            trust
+                .calculate_slippage(account_id, &currency)
+                .and_then(|slippages| {
+                    let summaries = SlippageGrouping::all()
+                        .into_iter()
+                        .map(|grouping| {
+                            trust
+                                .summarize_slippage(&slippages, grouping)
+                                .map(|summary| (grouping, summary))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((slippages, summaries))
+                }),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok((slippages, _)) if slippages.is_empty() => {
+                println!("No filled orders found");
+            }
+            Ok((slippages, summaries)) => {
+                println!("Slippage of filled orders (positive is worse than planned):");
+                SlippageView::display(slippages);
+                for (grouping, summary) in summaries {
+                    println!();
+                    println!("By {grouping}:");
+                    SlippageSummaryView::display(summary);
+                }
+            }
+            Err(error) => println!("Error calculating slippage: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn currency(mut self) -> Self {
+        let currencies = Currency::all();
+        let selected_currency = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Currency:")
+            .items(&currencies[..])
+            .default(0)
+            .interact()
+            .map(|index| currencies.get(index).unwrap())
+            .unwrap();
+
+        self.currency = Some(*selected_currency);
+        self
+    }
+}
+
+pub struct BenchmarkReportDialogBuilder {
+    account: Option<Account>,
+    currency: Option<Currency>,
+    result: Option<Result<BenchmarkComparison, Box<dyn Error>>>,
+}
+
+impl BenchmarkReportDialogBuilder {
+    pub fn new() -> Self {
+        BenchmarkReportDialogBuilder {
+            account: None,
+            currency: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> BenchmarkReportDialogBuilder {
+        let account = self
+            .account
+            .as_ref()
+            .expect("Did you forget to select an account?");
+        let currency = self.currency.expect("Did you forget to select a currency?");
+
+        self.result = Some(trust.compare_with_benchmark(account, &currency));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(comparison) => BenchmarkView::display(&comparison),
+            Err(error) => println!("Error comparing with the benchmark: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => {
+                if account.benchmark.is_none() {
+                    panic!("The account has no benchmark, select one with `account benchmark`")
+                }
+                self.account = Some(account)
+            }
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn currency(mut self) -> Self {
+        let currencies = Currency::all();
+        let selected_currency = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Currency:")
+            .items(&currencies[..])
+            .default(0)
+            .interact()
+            .map(|index| currencies.get(index).unwrap())
+            .unwrap();
+
+        self.currency = Some(*selected_currency);
+        self
+    }
+}
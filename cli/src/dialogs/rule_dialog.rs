@@ -139,6 +139,7 @@ impl RuleDialogBuilder {
         self.name = Some(match name {
             RuleName::RiskPerMonth(_) => RuleName::RiskPerMonth(risk),
             RuleName::RiskPerTrade(_) => RuleName::RiskPerTrade(risk),
+            RuleName::MaxOpenRisk(_) => RuleName::MaxOpenRisk(risk),
         });
         self
     }
@@ -0,0 +1,140 @@
+//! Simulated broker dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::views::SimFillView;
+use dialoguer::{theme::ColorfulTheme, Input};
+use rust_decimal::Decimal;
+use shellexpand::tilde;
+use sim_broker::{SimBroker, SimFill};
+use std::error::Error;
+use std::fs;
+
+pub struct SimTickDialogBuilder {
+    symbol: Option<String>,
+    price: Option<Decimal>,
+    result: Option<Result<Vec<SimFill>, Box<dyn Error>>>,
+}
+
+impl SimTickDialogBuilder {
+    pub fn new() -> Self {
+        SimTickDialogBuilder {
+            symbol: None,
+            price: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, broker: &SimBroker) -> SimTickDialogBuilder {
+        let symbol = self
+            .symbol
+            .clone()
+            .expect("Did you forget to select a symbol?");
+        let price = self.price.expect("Did you forget to select a price?");
+
+        self.result = Some(broker.tick(&symbol, price));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(fills) => display_fills(fills),
+            Err(error) => println!("Error feeding the price: {error}"),
+        }
+    }
+
+    pub fn symbol(mut self) -> Self {
+        let symbol: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Symbol, like AAPL")
+            .interact_text()
+            .unwrap();
+        self.symbol = Some(symbol.trim().to_uppercase());
+        self
+    }
+
+    pub fn price(mut self) -> Self {
+        let price = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Price")
+            .validate_with({
+                |input: &String| -> Result<(), &str> {
+                    match input.parse::<Decimal>() {
+                        Ok(parsed) if parsed > Decimal::ZERO => Ok(()),
+                        Ok(_) => Err("Please enter a positive price"),
+                        Err(_) => Err("Please enter a valid number for the price"),
+                    }
+                }
+            })
+            .interact_text()
+            .unwrap()
+            .parse::<Decimal>()
+            .unwrap();
+
+        self.price = Some(price);
+        self
+    }
+}
+
+pub struct SimImportDialogBuilder {
+    path: Option<String>,
+    result: Option<Result<Vec<SimFill>, Box<dyn Error>>>,
+}
+
+impl SimImportDialogBuilder {
+    pub fn new() -> Self {
+        SimImportDialogBuilder {
+            path: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, broker: &SimBroker) -> SimImportDialogBuilder {
+        let path = self.path.clone().expect("Did you forget to select a file?");
+
+        self.result = Some(
+            fs::read_to_string(tilde(&path).as_ref())
+                .map_err(|error| format!("Failed to read {path}: {error}").into())
+                .and_then(|csv| broker.import_ticks(&csv)),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(fills) => display_fills(fills),
+            Err(error) => println!("Error importing prices: {error}"),
+        }
+    }
+
+    pub fn file(mut self) -> Self {
+        let path: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("CSV file with Symbol and Price columns")
+            .interact_text()
+            .unwrap();
+        self.path = Some(path.trim().to_string());
+        self
+    }
+}
+
+fn display_fills(fills: Vec<SimFill>) {
+    if fills.is_empty() {
+        println!("No orders were filled");
+        return;
+    }
+    println!("Filled orders:");
+    SimFillView::display(fills);
+    println!("Run trust --broker sim trade sync to update the trades");
+}
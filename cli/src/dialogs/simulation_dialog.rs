@@ -0,0 +1,116 @@
+//! Simulation dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::SimulationView;
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
+use model::{Account, Currency, Simulation, SimulationParameters};
+use std::error::Error;
+
+pub struct SimulationDialogBuilder {
+    account: Option<Account>,
+    currency: Option<Currency>,
+    parameters: SimulationParameters,
+    result: Option<Result<Simulation, Box<dyn Error>>>,
+}
+
+impl SimulationDialogBuilder {
+    pub fn new() -> Self {
+        SimulationDialogBuilder {
+            account: None,
+            currency: None,
+            parameters: SimulationParameters::default(),
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> SimulationDialogBuilder {
+        let account = self
+            .account
+            .as_ref()
+            .expect("Did you forget to select an account?");
+        let currency = self.currency.expect("Did you forget to select a currency?");
+
+        self.result = Some(trust.simulate(account.id, &currency, &self.parameters));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(simulation) => SimulationView::display(&simulation),
+            Err(error) => println!("Error running the simulation: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn currency(mut self) -> Self {
+        let currencies = Currency::all();
+        let selected_currency = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Currency:")
+            .items(&currencies[..])
+            .default(0)
+            .interact()
+            .map(|index| currencies.get(index).unwrap())
+            .unwrap();
+
+        self.currency = Some(*selected_currency);
+        self
+    }
+
+    pub fn paths(mut self) -> Self {
+        self.parameters.paths = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Number of simulated paths")
+            .default(self.parameters.paths)
+            .interact_text()
+            .unwrap();
+        self
+    }
+
+    pub fn trades(mut self) -> Self {
+        self.parameters.trades = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Number of trades in each path")
+            .default(self.parameters.trades)
+            .interact_text()
+            .unwrap();
+        self
+    }
+
+    pub fn drawdown(mut self) -> Self {
+        self.parameters.drawdown_percentage = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Drawdown percentage to report")
+            .default(self.parameters.drawdown_percentage)
+            .interact_text()
+            .unwrap();
+        self
+    }
+
+    pub fn ruin(mut self) -> Self {
+        self.parameters.ruin_percentage = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Loss percentage considered ruin")
+            .default(self.parameters.ruin_percentage)
+            .interact_text()
+            .unwrap();
+        self
+    }
+}
@@ -0,0 +1,160 @@
+//! UI Dialog Module - User Interaction Code
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::{LogView, OrderView, TradeBalanceView, TradeView, TransactionView};
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
+use model::{Account, BrokerLog, Status, Trade, Transaction};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use std::str::FromStr;
+
+type ClosePartialDialogBuilderResult =
+    Option<Result<(Trade, Transaction, BrokerLog), Box<dyn Error>>>;
+
+pub struct ClosePartialDialogBuilder {
+    account: Option<Account>,
+    trade: Option<Trade>,
+    quantity: Option<u64>,
+    result: ClosePartialDialogBuilderResult,
+}
+
+impl ClosePartialDialogBuilder {
+    pub fn new() -> Self {
+        ClosePartialDialogBuilder {
+            account: None,
+            trade: None,
+            quantity: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> ClosePartialDialogBuilder {
+        let trade: Trade = self
+            .trade
+            .clone()
+            .expect("No trade found, did you forget to select one?");
+        let quantity = self
+            .quantity
+            .expect("No quantity found, did you forget to call quantity?");
+
+        self.result = Some(trust.close_partial(&trade, quantity));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call search?")
+        {
+            Ok((trade, transaction, log)) => {
+                let account_name = self.account.unwrap().name;
+
+                println!("Trade partially closed:");
+                TradeView::display(&trade, &account_name);
+                TradeBalanceView::display(&trade.balance);
+
+                println!("Transaction of the closed quantity:");
+                TransactionView::display(&transaction, &account_name);
+
+                println!("Stop and target that stay open:");
+                OrderView::display(trade.safety_stop);
+                OrderView::display(trade.target);
+                LogView::display(&log);
+            }
+            Err(error) => println!("Error closing part of the trade: {error:?}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn search(mut self, trust: &mut TrustFacade) -> Self {
+        let trades = trust.search_trades(self.account.clone().unwrap().id, Status::Filled);
+        match trades {
+            Ok(trades) => {
+                if trades.is_empty() {
+                    panic!("No trade found with the status filled, did you forget to sync one?")
+                }
+                let trade = FuzzySelect::with_theme(&ColorfulTheme::default())
+                    .with_prompt("Trade:")
+                    .items(&trades[..])
+                    .default(0)
+                    .interact_opt()
+                    .unwrap()
+                    .map(|index| trades.get(index).unwrap())
+                    .unwrap();
+
+                println!("Trade selected:");
+                TradeView::display(trade, &self.account.clone().unwrap().name);
+                self.trade = Some(trade.to_owned());
+            }
+            Err(error) => self.result = Some(Err(error)),
+        }
+
+        self
+    }
+
+    pub fn quantity(mut self) -> Self {
+        let Some(trade) = self.trade.clone() else {
+            return self;
+        };
+        // The stop is resized on every close, so it has the open quantity
+        let open = trade.safety_stop.quantity;
+        println!("Open quantity: {open}");
+
+        let quantity = Input::new()
+            .with_prompt("Quantity to close, or a percentage like 25%")
+            .validate_with(|input: &String| -> Result<(), String> {
+                parse_quantity(input, open).map(|_| ())
+            })
+            .interact_text()
+            .unwrap();
+        self.quantity = Some(parse_quantity(&quantity, open).unwrap());
+        self
+    }
+}
+
+/// A quantity, or a percentage of the open quantity rounded down to whole units.
+fn parse_quantity(input: &str, open: u64) -> Result<u64, String> {
+    let input = input.trim();
+    let quantity = match input.strip_suffix('%') {
+        Some(percentage) => {
+            let percentage = Decimal::from_str(percentage.trim())
+                .map_err(|_| "Please enter a valid percentage.".to_string())?;
+            Decimal::from(open)
+                .checked_mul(percentage)
+                .and_then(|total| total.checked_div(dec!(100)))
+                .map(|total| total.floor())
+                .and_then(|total| u64::try_from(total).ok())
+                .ok_or_else(|| "Please enter a percentage between 0% and 100%.".to_string())?
+        }
+        None => input
+            .parse::<u64>()
+            .map_err(|_| "Please enter a valid number.".to_string())?,
+    };
+
+    if quantity == 0 || quantity >= open {
+        return Err(format!(
+            "Please enter a quantity above 0 and below {open}, or close the whole trade."
+        ));
+    }
+    Ok(quantity)
+}
@@ -17,7 +17,7 @@ use crate::{
 };
 use core::TrustFacade;
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
-use model::{Account, Currency, DraftTrade, Trade, TradeCategory, TradingVehicle};
+use model::{Account, Currency, DraftTrade, PositionSizing, Trade, TradeCategory, TradingVehicle};
 use rust_decimal::Decimal;
 use std::error::Error;
 
@@ -27,6 +27,7 @@ pub struct TradeDialogBuilder {
     category: Option<TradeCategory>,
     entry_price: Option<Decimal>,
     stop_price: Option<Decimal>,
+    atr: Option<Decimal>,
     currency: Option<Currency>,
     quantity: Option<i64>,
     target_price: Option<Decimal>,
@@ -41,6 +42,7 @@ impl TradeDialogBuilder {
             category: None,
             entry_price: None,
             stop_price: None,
+            atr: None,
             currency: None,
             quantity: None,
             target_price: None,
@@ -95,6 +97,7 @@ impl TradeDialogBuilder {
 
     pub fn trading_vehicle(mut self, trust: &mut TrustFacade) -> Self {
         let tv = TradingVehicleSearchDialogBuilder::new()
+            .supported_by_broker(trust)
             .search(trust)
             .build();
         match tv {
@@ -104,8 +107,12 @@ impl TradeDialogBuilder {
         self
     }
 
-    pub fn category(mut self) -> Self {
-        let available_categories = TradeCategory::all();
+    pub fn category(mut self, trust: &mut TrustFacade) -> Self {
+        let short_selling = trust.broker_capabilities().short_selling;
+        let available_categories: Vec<TradeCategory> = TradeCategory::all()
+            .into_iter()
+            .filter(|category| short_selling || *category != TradeCategory::Short)
+            .collect();
 
         let selected_category = FuzzySelect::with_theme(&ColorfulTheme::default())
             .with_prompt("Category:")
@@ -132,6 +139,20 @@ impl TradeDialogBuilder {
         self
     }
 
+    pub fn atr(mut self) -> Self {
+        // Only volatility sizing needs the ATR of the trading vehicle.
+        if let Some(PositionSizing::Volatility(_)) =
+            self.account.as_ref().map(|account| account.position_sizing)
+        {
+            let atr = Input::new()
+                .with_prompt("ATR (average true range)")
+                .interact()
+                .unwrap();
+            self.atr = Some(atr);
+        }
+        self
+    }
+
     pub fn currency(mut self, trust: &mut TrustFacade) -> Self {
         let currencies: Vec<Currency> = trust
             .search_all_balances(self.account.clone().unwrap().id)
@@ -157,6 +178,7 @@ impl TradeDialogBuilder {
                 self.account.clone().unwrap().id,
                 self.entry_price.unwrap(),
                 self.stop_price.unwrap(),
+                self.atr,
                 &self.currency.unwrap(),
             )
             .unwrap_or_else(|error| {
@@ -120,9 +120,27 @@ impl ExitDialogBuilder {
         self
     }
 
-    pub fn fee(mut self) -> Self {
+    pub fn stop_fee(self, trust: &mut TrustFacade) -> Self {
+        let trade = self.trade.clone().unwrap();
+        let scheduled_fee = trust
+            .calculate_closing_fee(&trade, &trade.safety_stop)
+            .unwrap_or_default();
+        self.fee(scheduled_fee)
+    }
+
+    pub fn target_fee(self, trust: &mut TrustFacade) -> Self {
+        let trade = self.trade.clone().unwrap();
+        let scheduled_fee = trust
+            .calculate_closing_fee(&trade, &trade.target)
+            .unwrap_or_default();
+        self.fee(scheduled_fee)
+    }
+
+    // Default to the fee of the schedule of the account, if any.
+    fn fee(mut self, scheduled_fee: Decimal) -> Self {
         let fee_price = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Fee")
+            .default(scheduled_fee.to_string())
             .validate_with({
                 |input: &String| -> Result<(), &str> {
                     match input.parse::<Decimal>() {
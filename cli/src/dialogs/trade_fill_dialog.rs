@@ -18,7 +18,7 @@ use model::{Account, Status, Trade, Transaction};
 use rust_decimal::Decimal;
 use std::error::Error;
 
-type EntryDialogBuilderResult = Option<Result<(Trade, Transaction), Box<dyn Error>>>;
+type EntryDialogBuilderResult = Option<Result<(Trade, Option<Transaction>), Box<dyn Error>>>;
 
 pub struct FillTradeDialogBuilder {
     account: Option<Account>,
@@ -59,7 +59,9 @@ impl FillTradeDialogBuilder {
                 println!("Trade entry executed:");
                 TradeView::display(&trade, name.as_str());
                 TradeBalanceView::display(&trade.balance);
-                TransactionView::display(&tx, name.as_str());
+                if let Some(tx) = tx {
+                    TransactionView::display(&tx, name.as_str());
+                }
             }
             Err(error) => println!("Error approving trade: {error:?}"),
         }
@@ -74,9 +76,14 @@ impl FillTradeDialogBuilder {
         self
     }
 
-    pub fn fee(mut self) -> Self {
+    pub fn fee(mut self, trust: &mut TrustFacade) -> Self {
+        // Default to the fee of the schedule of the account, if any.
+        let scheduled_fee = trust
+            .calculate_opening_fee(self.trade.as_ref().unwrap())
+            .unwrap_or_default();
         let fee_price = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Fee")
+            .default(scheduled_fee.to_string())
             .validate_with({
                 |input: &String| -> Result<(), &str> {
                     match input.parse::<Decimal>() {
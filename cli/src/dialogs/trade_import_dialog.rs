@@ -0,0 +1,73 @@
+//! Trade import dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::{SkippedPositionView, TradeView};
+use core::TrustFacade;
+use model::{Account, SkippedPosition, Trade};
+use std::error::Error;
+
+type ImportResult = Option<Result<(Vec<Trade>, Vec<SkippedPosition>), Box<dyn Error>>>;
+
+pub struct TradeImportDialogBuilder {
+    account: Option<Account>,
+    result: ImportResult,
+}
+
+impl TradeImportDialogBuilder {
+    pub fn new() -> Self {
+        TradeImportDialogBuilder {
+            account: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> TradeImportDialogBuilder {
+        let account = self
+            .account
+            .clone()
+            .expect("No account found, did you forget to select one?");
+        self.result = Some(trust.import_positions(&account));
+        self
+    }
+
+    pub fn display(self) {
+        let account = self.account.unwrap();
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok((trades, skipped)) => {
+                if trades.is_empty() {
+                    println!("No positions were imported");
+                } else {
+                    println!("Imported trades:");
+                    TradeView::display_trades(trades, &account.name);
+                }
+                if !skipped.is_empty() {
+                    println!("Skipped positions:");
+                    SkippedPositionView::display(skipped);
+                }
+            }
+            Err(error) => println!("Error importing the positions: {error}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+}
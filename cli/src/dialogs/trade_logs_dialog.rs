@@ -0,0 +1,90 @@
+//! Trade logs dialog - UI interaction module
+//!
+//! TEMPORARY SAFETY ALLOWANCE: This dialog module contains user interaction code
+//! that uses .unwrap() and .expect() for UI input handling. While not ideal,
+//! these are less critical than business logic safety violations.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing
+)]
+
+use crate::dialogs::AccountSearchDialog;
+use crate::views::BrokerCallView;
+use core::TrustFacade;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+use model::{Account, BrokerLog, Status, Trade};
+use std::error::Error;
+
+pub struct TradeLogsDialogBuilder {
+    account: Option<Account>,
+    trade: Option<Trade>,
+    result: Option<Result<Vec<BrokerLog>, Box<dyn Error>>>,
+}
+
+impl TradeLogsDialogBuilder {
+    pub fn new() -> Self {
+        TradeLogsDialogBuilder {
+            account: None,
+            trade: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> TradeLogsDialogBuilder {
+        let trade = self
+            .trade
+            .clone()
+            .expect("No trade found, did you forget to select one?");
+        self.result = Some(trust.read_trade_logs(trade.id));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok(logs) if logs.is_empty() => println!("No calls were made to the broker"),
+            Ok(logs) => {
+                println!("Calls to the broker:");
+                BrokerCallView::display(&logs);
+            }
+            Err(error) => println!("Error reading the logs: {error:?}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn search(mut self, trust: &mut TrustFacade) -> Self {
+        let account_id = self.account.clone().unwrap().id;
+        let trades: Vec<Trade> = Status::all()
+            .into_iter()
+            .flat_map(|status| trust.search_trades(account_id, status).unwrap())
+            .collect();
+
+        if trades.is_empty() {
+            panic!("No trade found for the account")
+        }
+
+        let trade = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Trade:")
+            .items(&trades[..])
+            .default(0)
+            .interact_opt()
+            .unwrap()
+            .map(|index| trades.get(index).unwrap())
+            .unwrap();
+
+        self.trade = Some(trade.to_owned());
+        self
+    }
+}
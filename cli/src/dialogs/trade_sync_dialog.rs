@@ -83,6 +83,11 @@ impl SyncTradeDialogBuilder {
         let mut trades = trust
             .search_trades(self.account.clone().unwrap().id, Status::Submitted)
             .unwrap();
+        trades.append(
+            &mut trust
+                .search_trades(self.account.clone().unwrap().id, Status::PartiallyFilled)
+                .unwrap(),
+        );
         trades.append(
             &mut trust
                 .search_trades(self.account.clone().unwrap().id, Status::Filled)
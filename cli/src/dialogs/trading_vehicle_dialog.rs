@@ -14,7 +14,7 @@ use std::error::Error;
 
 use core::TrustFacade;
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
-use model::{TradingVehicle, TradingVehicleCategory};
+use model::{BrokerCapabilities, TradingVehicle, TradingVehicleCategory};
 
 use crate::views::TradingVehicleView;
 
@@ -127,12 +127,22 @@ impl TradingVehicleDialogBuilder {
 }
 
 pub struct TradingVehicleSearchDialogBuilder {
+    capabilities: Option<BrokerCapabilities>,
     result: Option<Result<TradingVehicle, Box<dyn Error>>>,
 }
 
 impl TradingVehicleSearchDialogBuilder {
     pub fn new() -> Self {
-        TradingVehicleSearchDialogBuilder { result: None }
+        TradingVehicleSearchDialogBuilder {
+            capabilities: None,
+            result: None,
+        }
+    }
+
+    /// Only offers the trading vehicles that the broker can trade
+    pub fn supported_by_broker(mut self, trust: &mut TrustFacade) -> Self {
+        self.capabilities = Some(trust.broker_capabilities());
+        self
     }
 
     pub fn build(self) -> Result<TradingVehicle, Box<dyn Error>> {
@@ -155,7 +165,12 @@ impl TradingVehicleSearchDialogBuilder {
     pub fn search(mut self, trust: &mut TrustFacade) -> Self {
         let trading_vehicles = trust.search_trading_vehicles();
         match trading_vehicles {
-            Ok(tvs) => {
+            Ok(mut tvs) => {
+                if let Some(capabilities) = &self.capabilities {
+                    tvs.retain(|tv| {
+                        capabilities.crypto || tv.category != TradingVehicleCategory::Crypto
+                    });
+                }
                 if tvs.is_empty() {
                     panic!("No trading vehicles found, did you forget to add one?")
                 }
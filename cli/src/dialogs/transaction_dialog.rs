@@ -11,7 +11,7 @@
 )]
 
 use crate::dialogs::account_dialog::AccountSearchDialog;
-use crate::views::{AccountBalanceView, TransactionView};
+use crate::views::{AccountBalanceView, TradeBalanceView, TransactionView, TransferView};
 use core::TrustFacade;
 use dialoguer::{theme::ColorfulTheme, FuzzySelect, Input};
 use model::Account;
@@ -19,8 +19,11 @@ use model::AccountBalance;
 use model::Currency;
 use model::Transaction;
 use model::TransactionCategory;
+use model::Transfer;
+use model::{Status, Trade, TradeBalance, TradeCategory};
 use rust_decimal::Decimal;
 use std::error::Error;
+use uuid::Uuid;
 
 pub struct TransactionDialogBuilder {
     amount: Option<Decimal>,
@@ -100,6 +103,24 @@ impl TransactionDialogBuilder {
             }
         }
 
+        // Show earnings set aside if withdrawal of earnings.
+        if self.category == TransactionCategory::WithdrawalEarnings {
+            let account_id = self
+                .account
+                .clone()
+                .expect("No account found, did you forget to call account?")
+                .id;
+            let currency = self
+                .currency
+                .expect("No currency found, did you forget to call currency?");
+            match trust.calculate_earnings(account_id, &currency) {
+                Ok(earnings) => {
+                    println!("Earnings available for withdrawal: {earnings} {currency}")
+                }
+                Err(error) => println!("Error calculating earnings: {error:?}"),
+            }
+        }
+
         let amount = Input::with_theme(&ColorfulTheme::default())
             .with_prompt(message)
             .validate_with({
@@ -122,7 +143,10 @@ impl TransactionDialogBuilder {
     pub fn currency(mut self, trust: &mut TrustFacade) -> Self {
         let mut currencies = Vec::new();
 
-        if self.category == TransactionCategory::Withdrawal {
+        if self.category == TransactionCategory::Withdrawal
+            || self.category == TransactionCategory::WithdrawalEarnings
+            || self.category == TransactionCategory::Interest
+        {
             let account_id = self
                 .account
                 .clone()
@@ -163,3 +187,321 @@ impl TransactionDialogBuilder {
         self
     }
 }
+
+type TradeTransactionDialogBuilderResult =
+    Option<Result<(Transaction, AccountBalance, TradeBalance), Box<dyn Error>>>;
+
+pub struct TradeTransactionDialogBuilder {
+    amount: Option<Decimal>,
+    account: Option<Account>,
+    trade: Option<Trade>,
+    category: fn(Uuid) -> TransactionCategory,
+    result: TradeTransactionDialogBuilderResult,
+}
+
+impl TradeTransactionDialogBuilder {
+    pub fn new(category: fn(Uuid) -> TransactionCategory) -> Self {
+        TradeTransactionDialogBuilder {
+            amount: None,
+            account: None,
+            trade: None,
+            category,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> TradeTransactionDialogBuilder {
+        let trade = self
+            .trade
+            .clone()
+            .expect("No trade found, did you forget to call search?");
+        let amount = self
+            .amount
+            .expect("No amount found, did you forget to call amount?");
+        self.result =
+            Some(trust.create_trade_transaction(&trade, &(self.category)(trade.id), amount));
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok((transaction, balance, trade_balance)) => {
+                let name = self.account.unwrap().name;
+                println!("Transaction created in account:  {name}");
+                TransactionView::display(&transaction, &name);
+                println!("Now the trade balance is:");
+                TradeBalanceView::display(&trade_balance);
+                println!("Now the account {name} balance is:");
+                AccountBalanceView::display(balance, &name);
+            }
+            Err(error) => println!("Error creating transaction: {error:?}"),
+        }
+    }
+
+    pub fn account(mut self, trust: &mut TrustFacade) -> Self {
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.account = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn search(mut self, trust: &mut TrustFacade) -> Self {
+        // Dividends are paid to long positions and borrow fees are charged to short positions.
+        let trade_category = match (self.category)(Uuid::nil()) {
+            TransactionCategory::BorrowFee(_) => TradeCategory::Short,
+            _ => TradeCategory::Long,
+        };
+
+        let account_id = self
+            .account
+            .clone()
+            .expect("No account found, did you forget to call account?")
+            .id;
+        let mut trades = Vec::new();
+        for status in [
+            Status::Filled,
+            Status::ClosedTarget,
+            Status::ClosedStopLoss,
+            Status::Canceled,
+        ] {
+            match trust.search_trades(account_id, status) {
+                Ok(found) => trades.extend(
+                    found
+                        .into_iter()
+                        .filter(|trade| trade.category == trade_category),
+                ),
+                Err(error) => println!("Error searching trades: {error:?}"),
+            }
+        }
+
+        if trades.is_empty() {
+            panic!("No {trade_category} trade found, did you forget to create one?")
+        }
+
+        let trade = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Trade:")
+            .items(&trades[..])
+            .default(0)
+            .interact_opt()
+            .unwrap()
+            .map(|index| trades.get(index).unwrap())
+            .unwrap();
+
+        self.trade = Some(trade.to_owned());
+        self
+    }
+
+    pub fn amount(mut self) -> Self {
+        let message = format!("How much was the {}?", (self.category)(Uuid::nil()));
+
+        let amount = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(message)
+            .validate_with({
+                |input: &String| -> Result<(), &str> {
+                    match input.parse::<Decimal>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err("Please enter a valid number."),
+                    }
+                }
+            })
+            .interact_text()
+            .unwrap()
+            .parse::<Decimal>()
+            .unwrap();
+
+        self.amount = Some(amount);
+        self
+    }
+}
+
+type TransferDialogBuilderResult =
+    Option<Result<(Transfer, AccountBalance, AccountBalance), Box<dyn Error>>>;
+
+pub struct TransferDialogBuilder {
+    from: Option<Account>,
+    currency: Option<Currency>,
+    to: Option<Account>,
+    to_currency: Option<Currency>,
+    exchange_rate: Option<Decimal>,
+    amount: Option<Decimal>,
+    result: TransferDialogBuilderResult,
+}
+
+impl TransferDialogBuilder {
+    pub fn new() -> Self {
+        TransferDialogBuilder {
+            from: None,
+            currency: None,
+            to: None,
+            to_currency: None,
+            exchange_rate: None,
+            amount: None,
+            result: None,
+        }
+    }
+
+    pub fn build(mut self, trust: &mut TrustFacade) -> TransferDialogBuilder {
+        self.result = Some(
+            trust.transfer(
+                &self
+                    .from
+                    .clone()
+                    .expect("No account found, did you forget to call source?"),
+                &self
+                    .to
+                    .clone()
+                    .expect("No account found, did you forget to call destination?"),
+                self.amount
+                    .expect("No amount found, did you forget to call amount?"),
+                &self
+                    .currency
+                    .expect("No currency found, did you forget to call currency?"),
+                &self
+                    .to_currency
+                    .expect("No currency found, did you forget to call destination_currency?"),
+                self.exchange_rate,
+            ),
+        );
+        self
+    }
+
+    pub fn display(self) {
+        match self
+            .result
+            .expect("No result found, did you forget to call build?")
+        {
+            Ok((transfer, from_balance, to_balance)) => {
+                let from_name = self.from.unwrap().name;
+                let to_name = self.to.unwrap().name;
+                println!("Transfer created from {from_name} to {to_name}");
+                TransferView::display(&transfer, &from_name, &to_name);
+                println!("Now the account {from_name} balance is:");
+                AccountBalanceView::display(from_balance, &from_name);
+                println!("Now the account {to_name} balance is:");
+                AccountBalanceView::display(to_balance, &to_name);
+            }
+            Err(error) => println!("Error creating transfer: {error:?}"),
+        }
+    }
+
+    pub fn source(mut self, trust: &mut TrustFacade) -> Self {
+        println!("Account to transfer from:");
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.from = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn currency(mut self, trust: &mut TrustFacade) -> Self {
+        let account_id = self
+            .from
+            .clone()
+            .expect("No account found, did you forget to call source?")
+            .id;
+        let balances = match trust.search_all_balances(account_id) {
+            Ok(balances) => balances,
+            Err(error) => panic!("Error searching account: {error:?}"),
+        };
+
+        let currencies: Vec<Currency> = balances.iter().map(|balance| balance.currency).collect();
+        let selected = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Which currency do you want to transfer?")
+            .items(&currencies[..])
+            .interact()
+            .unwrap();
+
+        let balance = &balances[selected];
+        println!(
+            "Available for transfer: {} {}",
+            balance.total_available, balance.currency
+        );
+        self.currency = Some(balance.currency);
+        self
+    }
+
+    pub fn destination(mut self, trust: &mut TrustFacade) -> Self {
+        println!("Account to transfer to:");
+        let account = AccountSearchDialog::new().search(trust).build();
+        match account {
+            Ok(account) => self.to = Some(account),
+            Err(error) => println!("Error searching account: {error:?}"),
+        }
+        self
+    }
+
+    pub fn destination_currency(mut self) -> Self {
+        let currencies = Currency::all();
+        let selected_currency = FuzzySelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Which currency does the destination account receive?")
+            .items(&currencies[..])
+            .interact()
+            .map(|index| currencies.get(index).unwrap())
+            .unwrap();
+
+        self.to_currency = Some(*selected_currency);
+        self
+    }
+
+    pub fn exchange_rate(mut self) -> Self {
+        let currency = self
+            .currency
+            .expect("No currency found, did you forget to call currency?");
+        let to_currency = self
+            .to_currency
+            .expect("No currency found, did you forget to call destination_currency?");
+
+        // The rate is only needed when money is converted to another currency.
+        if currency == to_currency {
+            return self;
+        }
+
+        let rate = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("How many {to_currency} is 1 {currency}?"))
+            .validate_with({
+                |input: &String| -> Result<(), &str> {
+                    match input.parse::<Decimal>() {
+                        Ok(rate) if rate.is_sign_positive() && !rate.is_zero() => Ok(()),
+                        _ => Err("Please enter a positive number."),
+                    }
+                }
+            })
+            .interact_text()
+            .unwrap()
+            .parse::<Decimal>()
+            .unwrap();
+
+        self.exchange_rate = Some(rate);
+        self
+    }
+
+    pub fn amount(mut self) -> Self {
+        let currency = self
+            .currency
+            .expect("No currency found, did you forget to call currency?");
+
+        let amount = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("How much {currency} do you want to transfer?"))
+            .validate_with({
+                |input: &String| -> Result<(), &str> {
+                    match input.parse::<Decimal>() {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err("Please enter a valid number."),
+                    }
+                }
+            })
+            .interact_text()
+            .unwrap()
+            .parse::<Decimal>()
+            .unwrap();
+
+        self.amount = Some(amount);
+        self
+    }
+}
@@ -1,34 +1,60 @@
 use crate::dialogs::{
-    AccountDialogBuilder, AccountSearchDialog, CancelDialogBuilder, CloseDialogBuilder,
-    ExitDialogBuilder, FillTradeDialogBuilder, FundingDialogBuilder, KeysDeleteDialogBuilder,
-    KeysReadDialogBuilder, KeysWriteDialogBuilder, ModifyDialogBuilder, SubmitDialogBuilder,
-    SyncTradeDialogBuilder, TradeDialogBuilder, TradeSearchDialogBuilder,
+    AccountDialogBuilder, AccountSearchDialog, ActivityImportDialogBuilder, BacktestDialogBuilder,
+    BenchmarkDialogBuilder, BenchmarkReportDialogBuilder, CancelDialogBuilder, CloseDialogBuilder,
+    ClosePartialDialogBuilder, DoctorDialogBuilder, ExitDialogBuilder, FeeScheduleDialogBuilder,
+    FillTradeDialogBuilder, FundingDialogBuilder, KeysDeleteDialogBuilder, KeysReadDialogBuilder,
+    KeysWriteDialogBuilder, ManualFillPrompt, ModifyDialogBuilder, ModifyEntryDialogBuilder,
+    PositionSizingDialogBuilder, PriceImportDialogBuilder, SimImportDialogBuilder,
+    SimTickDialogBuilder, SimulationDialogBuilder, SlippageReportDialogBuilder,
+    SubmitDialogBuilder, SyncTradeDialogBuilder, TradeDialogBuilder, TradeImportDialogBuilder,
+    TradeLogsDialogBuilder, TradeSearchDialogBuilder, TradeTransactionDialogBuilder,
     TradingVehicleDialogBuilder, TradingVehicleSearchDialogBuilder, TransactionDialogBuilder,
+    TransferDialogBuilder,
 };
 use crate::dialogs::{RuleDialogBuilder, RuleRemoveDialogBuilder};
 use alpaca_broker::AlpacaBroker;
 use clap::ArgMatches;
 use core::TrustFacade;
 use db_sqlite::SqliteDatabase;
-use model::TransactionCategory;
+use manual_broker::ManualBroker;
+use model::{Broker, TransactionCategory};
 use shellexpand::tilde;
+use sim_broker::SimBroker;
 use std::ffi::OsString;
 use std::fs;
+use std::path::Path;
+use uuid::Uuid;
 
 pub struct ArgDispatcher {
     trust: TrustFacade,
 }
 
 impl ArgDispatcher {
-    pub fn new_sqlite() -> Self {
+    pub fn new_sqlite(matches: &ArgMatches) -> Self {
         create_dir_if_necessary();
         let database = SqliteDatabase::new(ArgDispatcher::database_url().as_str());
+        let broker: Box<dyn Broker> = match matches.get_one::<String>("broker").map(String::as_str)
+        {
+            Some("sim") => Box::new(ArgDispatcher::sim_broker()),
+            Some("manual") => Box::new(
+                ManualBroker::new(Path::new(tilde("~/.trust/tickets").as_ref()))
+                    .with_prompt(Box::new(ManualFillPrompt)),
+            ),
+            _ => Box::<AlpacaBroker>::default(),
+        };
 
         ArgDispatcher {
-            trust: TrustFacade::new(Box::new(database), Box::<AlpacaBroker>::default()),
+            trust: TrustFacade::new(Box::new(database), broker),
         }
     }
 
+    fn sim_broker() -> SimBroker {
+        SimBroker::new(ArgDispatcher::sim_database_url().as_str()).unwrap_or_else(|error| {
+            eprintln!("Error opening the simulated broker: {error}");
+            std::process::exit(1);
+        })
+    }
+
     #[cfg(debug_assertions)]
     fn database_url() -> String {
         tilde("~/.trust/debug.db").to_string()
@@ -39,6 +65,16 @@ impl ArgDispatcher {
         tilde("~/.trust/production.db").to_string()
     }
 
+    #[cfg(debug_assertions)]
+    fn sim_database_url() -> String {
+        tilde("~/.trust/sim-debug.db").to_string()
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn sim_database_url() -> String {
+        tilde("~/.trust/sim-production.db").to_string()
+    }
+
     pub fn dispatch(mut self, matches: ArgMatches) {
         match matches.subcommand() {
             Some(("keys", sub_matches)) => match sub_matches.subcommand() {
@@ -50,11 +86,20 @@ impl ArgDispatcher {
             Some(("account", sub_matches)) => match sub_matches.subcommand() {
                 Some(("create", _)) => self.create_account(),
                 Some(("search", _)) => self.search_account(),
+                Some(("sizing", _)) => self.position_sizing(),
+                Some(("fees", _)) => self.fee_schedule(),
+                Some(("benchmark", _)) => self.benchmark(),
                 _ => unreachable!("No subcommand provided"),
             },
             Some(("transaction", sub_matches)) => match sub_matches.subcommand() {
                 Some(("deposit", _)) => self.deposit(),
                 Some(("withdraw", _)) => self.withdraw(),
+                Some(("withdraw-earnings", _)) => self.withdraw_earnings(),
+                Some(("interest", _)) => self.interest(),
+                Some(("dividend", _)) => self.trade_transaction(TransactionCategory::Dividend),
+                Some(("borrow-fee", _)) => self.trade_transaction(TransactionCategory::BorrowFee),
+                Some(("transfer", _)) => self.transfer(),
+                Some(("import", _)) => self.import_activities(),
                 _ => unreachable!("No subcommand provided"),
             },
             Some(("rule", sub_matches)) => match sub_matches.subcommand() {
@@ -67,21 +112,20 @@ impl ArgDispatcher {
                 Some(("search", _)) => self.search_trading_vehicle(),
                 _ => unreachable!("No subcommand provided"),
             },
-            Some(("trade", sub_matches)) => match sub_matches.subcommand() {
-                Some(("create", _)) => self.create_trade(),
-                Some(("fund", _)) => self.create_funding(),
-                Some(("cancel", _)) => self.create_cancel(),
-                Some(("submit", _)) => self.create_submit(),
-                Some(("manually-fill", _)) => self.create_fill(),
-                Some(("manually-stop", _)) => self.create_stop(),
-                Some(("manually-target", _)) => self.create_target(),
-                Some(("manually-close", _)) => self.close(),
-                Some(("sync", _)) => self.create_sync(),
-                Some(("search", _)) => self.search_trade(),
-                Some(("modify-stop", _)) => self.modify_stop(),
-                Some(("modify-target", _)) => self.modify_target(),
+            Some(("trade", sub_matches)) => self.dispatch_trade(sub_matches),
+            Some(("doctor", _)) => self.doctor(),
+            Some(("simulate", _)) => self.simulate(),
+            Some(("backtest", _)) => self.backtest(),
+            Some(("report", sub_matches)) => match sub_matches.subcommand() {
+                Some(("slippage", _)) => self.slippage_report(),
+                Some(("benchmark", _)) => self.benchmark_report(),
+                _ => unreachable!("No subcommand provided"),
+            },
+            Some(("price", sub_matches)) => match sub_matches.subcommand() {
+                Some(("import", _)) => self.import_prices(),
                 _ => unreachable!("No subcommand provided"),
             },
+            Some(("sim", sub_matches)) => self.dispatch_sim(sub_matches),
             Some((ext, sub_matches)) => {
                 let args = sub_matches
                     .get_many::<OsString>("")
@@ -113,6 +157,31 @@ impl ArgDispatcher {
             .search(&mut self.trust)
             .display(&mut self.trust);
     }
+
+    fn position_sizing(&mut self) {
+        PositionSizingDialogBuilder::new()
+            .account(&mut self.trust)
+            .position_sizing()
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn benchmark(&mut self) {
+        BenchmarkDialogBuilder::new()
+            .account(&mut self.trust)
+            .benchmark()
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn fee_schedule(&mut self) {
+        FeeScheduleDialogBuilder::new()
+            .account(&mut self.trust)
+            .commission()
+            .regulatory()
+            .build(&mut self.trust)
+            .display();
+    }
 }
 
 // Transaction
@@ -134,6 +203,54 @@ impl ArgDispatcher {
             .build(&mut self.trust)
             .display();
     }
+
+    fn interest(&mut self) {
+        TransactionDialogBuilder::new(TransactionCategory::Interest)
+            .account(&mut self.trust)
+            .currency(&mut self.trust)
+            .amount(&mut self.trust)
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn trade_transaction(&mut self, category: fn(Uuid) -> TransactionCategory) {
+        TradeTransactionDialogBuilder::new(category)
+            .account(&mut self.trust)
+            .search(&mut self.trust)
+            .amount()
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn withdraw_earnings(&mut self) {
+        TransactionDialogBuilder::new(TransactionCategory::WithdrawalEarnings)
+            .account(&mut self.trust)
+            .currency(&mut self.trust)
+            .amount(&mut self.trust)
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn transfer(&mut self) {
+        TransferDialogBuilder::new()
+            .source(&mut self.trust)
+            .currency(&mut self.trust)
+            .destination(&mut self.trust)
+            .destination_currency()
+            .exchange_rate()
+            .amount()
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn import_activities(&mut self) {
+        ActivityImportDialogBuilder::new()
+            .account(&mut self.trust)
+            .file()
+            .currency()
+            .build(&mut self.trust)
+            .display();
+    }
 }
 
 // Rules
@@ -183,9 +300,10 @@ impl ArgDispatcher {
         TradeDialogBuilder::new()
             .account(&mut self.trust)
             .trading_vehicle(&mut self.trust)
-            .category()
+            .category(&mut self.trust)
             .entry_price()
             .stop_price()
+            .atr()
             .currency(&mut self.trust)
             .quantity(&mut self.trust)
             .target_price()
@@ -221,7 +339,7 @@ impl ArgDispatcher {
         FillTradeDialogBuilder::new()
             .account(&mut self.trust)
             .search(&mut self.trust)
-            .fee()
+            .fee(&mut self.trust)
             .build(&mut self.trust)
             .display();
     }
@@ -230,7 +348,7 @@ impl ArgDispatcher {
         ExitDialogBuilder::new()
             .account(&mut self.trust)
             .search(&mut self.trust)
-            .fee()
+            .stop_fee(&mut self.trust)
             .build_stop(&mut self.trust)
             .display();
     }
@@ -239,11 +357,24 @@ impl ArgDispatcher {
         ExitDialogBuilder::new()
             .account(&mut self.trust)
             .search(&mut self.trust)
-            .fee()
+            .target_fee(&mut self.trust)
             .build_target(&mut self.trust)
             .display();
     }
 
+    fn modify_entry(&mut self) {
+        if !self.trust.broker_capabilities().modify_entry {
+            println!("The broker does not support modifying the entry of a trade");
+            return;
+        }
+        ModifyEntryDialogBuilder::new()
+            .account(&mut self.trust)
+            .search(&mut self.trust)
+            .entry()
+            .build(&mut self.trust)
+            .display();
+    }
+
     fn create_sync(&mut self) {
         SyncTradeDialogBuilder::new()
             .account(&mut self.trust)
@@ -269,7 +400,39 @@ impl ArgDispatcher {
             .display();
     }
 
+    fn close_partial(&mut self) {
+        if !self.trust.broker_capabilities().partial_close {
+            println!("The broker does not support closing part of a trade");
+            return;
+        }
+        ClosePartialDialogBuilder::new()
+            .account(&mut self.trust)
+            .search(&mut self.trust)
+            .quantity()
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn import_positions(&mut self) {
+        TradeImportDialogBuilder::new()
+            .account(&mut self.trust)
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn trade_logs(&mut self) {
+        TradeLogsDialogBuilder::new()
+            .account(&mut self.trust)
+            .search(&mut self.trust)
+            .build(&mut self.trust)
+            .display();
+    }
+
     fn modify_stop(&mut self) {
+        if !self.trust.broker_capabilities().modify_stop {
+            println!("The broker does not support modifying the stop of a trade");
+            return;
+        }
         ModifyDialogBuilder::new()
             .account(&mut self.trust)
             .search(&mut self.trust)
@@ -279,6 +442,10 @@ impl ArgDispatcher {
     }
 
     fn modify_target(&mut self) {
+        if !self.trust.broker_capabilities().modify_target {
+            println!("The broker does not support modifying the target of a trade");
+            return;
+        }
         ModifyDialogBuilder::new()
             .account(&mut self.trust)
             .search(&mut self.trust)
@@ -288,6 +455,122 @@ impl ArgDispatcher {
     }
 }
 
+// Doctor
+impl ArgDispatcher {
+    fn doctor(&mut self) {
+        DoctorDialogBuilder::new()
+            .diagnose(&mut self.trust)
+            .repair(&mut self.trust)
+            .display();
+    }
+}
+
+// Simulation
+impl ArgDispatcher {
+    fn simulate(&mut self) {
+        SimulationDialogBuilder::new()
+            .account(&mut self.trust)
+            .currency()
+            .paths()
+            .trades()
+            .drawdown()
+            .ruin()
+            .build(&mut self.trust)
+            .display();
+    }
+}
+
+// Backtest
+impl ArgDispatcher {
+    fn backtest(&mut self) {
+        BacktestDialogBuilder::new()
+            .account(&mut self.trust)
+            .file()
+            .starting_capital()
+            .currency()
+            .build(&mut self.trust)
+            .display();
+    }
+}
+
+// Report
+impl ArgDispatcher {
+    fn slippage_report(&mut self) {
+        SlippageReportDialogBuilder::new()
+            .account(&mut self.trust)
+            .currency()
+            .build(&mut self.trust)
+            .display();
+    }
+
+    fn benchmark_report(&mut self) {
+        BenchmarkReportDialogBuilder::new()
+            .account(&mut self.trust)
+            .currency()
+            .build(&mut self.trust)
+            .display();
+    }
+}
+
+// Price
+impl ArgDispatcher {
+    fn import_prices(&mut self) {
+        PriceImportDialogBuilder::new()
+            .symbol()
+            .file()
+            .build(&mut self.trust)
+            .display();
+    }
+}
+
+// Simulated broker
+impl ArgDispatcher {
+    fn dispatch_trade(&mut self, matches: &ArgMatches) {
+        match matches.subcommand() {
+            Some(("create", _)) => self.create_trade(),
+            Some(("fund", _)) => self.create_funding(),
+            Some(("cancel", _)) => self.create_cancel(),
+            Some(("submit", _)) => self.create_submit(),
+            Some(("manually-fill", _)) => self.create_fill(),
+            Some(("manually-stop", _)) => self.create_stop(),
+            Some(("manually-target", _)) => self.create_target(),
+            Some(("manually-close", _)) => self.close(),
+            Some(("close-partial", _)) => self.close_partial(),
+            Some(("sync", _)) => self.create_sync(),
+            Some(("search", _)) => self.search_trade(),
+            Some(("import", _)) => self.import_positions(),
+            Some(("logs", _)) => self.trade_logs(),
+            Some(("modify-stop", _)) => self.modify_stop(),
+            Some(("modify-target", _)) => self.modify_target(),
+            Some(("modify-entry", _)) => self.modify_entry(),
+            _ => unreachable!("No subcommand provided"),
+        }
+    }
+
+    fn dispatch_sim(&mut self, matches: &ArgMatches) {
+        match matches.subcommand() {
+            Some(("tick", _)) => self.sim_tick(),
+            Some(("import", _)) => self.sim_import(),
+            _ => unreachable!("No subcommand provided"),
+        }
+    }
+
+    fn sim_tick(&mut self) {
+        SimTickDialogBuilder::new()
+            .symbol()
+            .price()
+            .build(&ArgDispatcher::sim_broker())
+            .display();
+    }
+
+    fn sim_import(&mut self) {
+        SimImportDialogBuilder::new()
+            .file()
+            .build(&ArgDispatcher::sim_broker())
+            .display();
+    }
+}
+
 impl ArgDispatcher {
     fn create_keys(&mut self) {
         KeysWriteDialogBuilder::new()
@@ -31,11 +31,12 @@
 #![warn(missing_docs, rust_2018_idioms, missing_debug_implementations)]
 
 use crate::commands::{
-    AccountCommandBuilder, KeysCommandBuilder, TradeCommandBuilder, TradingVehicleCommandBuilder,
+    AccountCommandBuilder, KeysCommandBuilder, PriceCommandBuilder, ReportCommandBuilder,
+    SimCommandBuilder, TradeCommandBuilder, TradingVehicleCommandBuilder,
     TransactionCommandBuilder,
 };
 use crate::dispatcher::ArgDispatcher;
-use clap::Command;
+use clap::{Arg, Command};
 use commands::RuleCommandBuilder;
 mod commands;
 mod dialogs;
@@ -48,6 +49,7 @@ fn main() {
         .version(env!("CARGO_PKG_VERSION"))
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(broker_arg())
         .subcommand(
             KeysCommandBuilder::new()
                 .create_keys()
@@ -59,12 +61,21 @@ fn main() {
             AccountCommandBuilder::new()
                 .create_account()
                 .read_account()
+                .position_sizing()
+                .fee_schedule()
+                .benchmark()
                 .build(),
         )
         .subcommand(
             TransactionCommandBuilder::new()
                 .deposit()
                 .withdraw()
+                .withdraw_earnings()
+                .interest()
+                .dividend()
+                .borrow_fee()
+                .transfer()
+                .import()
                 .build(),
         )
         .subcommand(
@@ -79,24 +90,55 @@ fn main() {
                 .search_trading_vehicle()
                 .build(),
         )
+        .subcommand(trade_command())
+        .subcommand(PriceCommandBuilder::new().import().build())
+        .subcommand(SimCommandBuilder::new().tick().import().build())
+        .subcommand(ReportCommandBuilder::new().slippage().benchmark().build())
         .subcommand(
-            TradeCommandBuilder::new()
-                .create_trade()
-                .search_trade()
-                .fund_trade()
-                .cancel_trade()
-                .submit_trade()
-                .sync_trade()
-                .manually_fill()
-                .manually_stop()
-                .manually_target()
-                .manually_close()
-                .modify_stop()
-                .modify_target()
-                .build(),
+            Command::new("doctor")
+                .about("Check that balances, trades, orders and transactions are consistent"),
         )
+        .subcommand(Command::new("simulate").about(
+            "Simulate the future equity of an account from the R-multiples of its closed trades",
+        ))
+        .subcommand(backtest_command())
         .get_matches();
 
-    let dispatcher = ArgDispatcher::new_sqlite();
+    let dispatcher = ArgDispatcher::new_sqlite(&matches);
     dispatcher.dispatch(matches);
 }
+
+fn trade_command() -> Command {
+    TradeCommandBuilder::new()
+        .create_trade()
+        .search_trade()
+        .fund_trade()
+        .cancel_trade()
+        .submit_trade()
+        .sync_trade()
+        .manually_fill()
+        .manually_stop()
+        .manually_target()
+        .manually_close()
+        .close_partial()
+        .modify_stop()
+        .modify_target()
+        .modify_entry()
+        .import_positions()
+        .logs()
+        .build()
+}
+
+fn backtest_command() -> Command {
+    Command::new("backtest")
+        .about("Test trade plans against imported prices with the rules and sizing of an account")
+}
+
+fn broker_arg() -> Arg {
+    Arg::new("broker")
+        .long("broker")
+        .global(true)
+        .value_parser(["alpaca", "sim", "manual"])
+        .default_value("alpaca")
+        .help("Broker that receives the orders of the trades")
+}
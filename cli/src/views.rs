@@ -1,18 +1,34 @@
 mod account_view;
+mod activity_view;
+mod backtest_view;
+mod benchmark_view;
+mod inconsistency_view;
 mod log_view;
 mod order_view;
+mod position_view;
 mod rule_view;
+mod sim_view;
+mod simulation_view;
+mod slippage_view;
 mod trade_view;
 mod trading_vehicle_view;
 mod transaction_view;
 
-pub use account_view::{AccountBalanceView, AccountView};
-pub use log_view::LogView;
+pub use account_view::{AccountBalanceView, AccountView, FeeScheduleView};
+pub use activity_view::ActivityDiscrepancyView;
+pub use backtest_view::SkippedPlanView;
+pub use benchmark_view::BenchmarkView;
+pub use inconsistency_view::InconsistencyView;
+pub use log_view::{BrokerCallView, LogView};
 pub use order_view::OrderView;
+pub use position_view::SkippedPositionView;
 pub use rule_view::RuleView;
+pub use sim_view::SimFillView;
+pub use simulation_view::SimulationView;
+pub use slippage_view::{SlippageSummaryView, SlippageView};
 pub use trade_view::{TradeBalanceView, TradeView};
 pub use trading_vehicle_view::TradingVehicleView;
-pub use transaction_view::TransactionView;
+pub use transaction_view::{TransactionView, TransferView};
 
 fn uppercase_first(data: &str) -> String {
     // Uppercase first letter.
@@ -1,4 +1,4 @@
-use model::{Account, AccountBalance};
+use model::{Account, AccountBalance, FeeSchedule, OpenRisk, PositionSizing};
 use tabled::settings::style::Style;
 use tabled::Table;
 use tabled::Tabled;
@@ -8,27 +8,85 @@ pub struct AccountView {
     pub name: String,
     pub description: String,
     pub env: String,
+    pub position_sizing: String,
+    pub benchmark: String,
+    pub open_risk: String,
 }
 
 impl AccountView {
-    fn new(account: Account) -> AccountView {
+    fn new(account: Account, open_risk: &[OpenRisk]) -> AccountView {
         AccountView {
             name: account.name,
             description: account.description,
             env: account.environment.to_string(),
+            position_sizing: AccountView::position_sizing(account.position_sizing),
+            benchmark: account.benchmark.unwrap_or_else(|| "-".to_string()),
+            open_risk: AccountView::open_risk(open_risk),
+        }
+    }
+
+    fn open_risk(open_risk: &[OpenRisk]) -> String {
+        if open_risk.is_empty() {
+            return "-".to_string();
+        }
+        open_risk
+            .iter()
+            .map(|risk| format!("{} {} ({}%)", risk.amount, risk.currency, risk.percentage))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn position_sizing(sizing: PositionSizing) -> String {
+        match sizing {
+            PositionSizing::FixedFractional => sizing.to_string(),
+            _ => format!("{} ({})", sizing, sizing.value()),
         }
     }
 
     pub fn display_account(a: Account) {
+        AccountView::display_account_with_open_risk(a, &[]);
+    }
+
+    pub fn display_account_with_open_risk(a: Account, open_risk: &[OpenRisk]) {
         println!();
         println!("Account: {}", a.id);
-        AccountView::display_accounts(vec![a]);
+        let mut table = Table::new(vec![AccountView::new(a, open_risk)]);
+        table.with(Style::modern());
+        println!("{table}");
         println!();
     }
+}
 
-    pub fn display_accounts(accounts: Vec<Account>) {
-        let views: Vec<AccountView> = accounts.into_iter().map(AccountView::new).collect();
-        let mut table = Table::new(views);
+#[derive(Tabled)]
+pub struct FeeScheduleView {
+    pub per_share: String,
+    pub per_order: String,
+    pub percentage: String,
+    pub minimum: String,
+    pub maximum: String,
+    pub sec_fee_rate: String,
+    pub taf_per_share: String,
+    pub taf_maximum: String,
+}
+
+impl FeeScheduleView {
+    fn new(schedule: FeeSchedule) -> FeeScheduleView {
+        FeeScheduleView {
+            per_share: schedule.per_share.to_string(),
+            per_order: schedule.per_order.to_string(),
+            percentage: format!("{}%", schedule.percentage),
+            minimum: schedule.minimum.to_string(),
+            maximum: schedule
+                .maximum
+                .map_or_else(|| "-".to_string(), |maximum| maximum.to_string()),
+            sec_fee_rate: schedule.sec_fee_rate.to_string(),
+            taf_per_share: schedule.taf_per_share.to_string(),
+            taf_maximum: schedule.taf_maximum.to_string(),
+        }
+    }
+
+    pub fn display(schedule: FeeSchedule) {
+        let mut table = Table::new(vec![FeeScheduleView::new(schedule)]);
         table.with(Style::modern());
         println!("{table}");
     }
@@ -41,6 +99,7 @@ pub struct AccountBalanceView {
     pub total_available: String,
     pub total_in_trade: String,
     pub taxed: String,
+    pub earnings: String,
     pub currency: String,
 }
 
@@ -52,6 +111,7 @@ impl AccountBalanceView {
             total_available: balance.total_available.to_string(),
             total_in_trade: balance.total_in_trade.to_string(),
             taxed: balance.taxed.to_string(),
+            earnings: balance.total_earnings.to_string(),
             currency: balance.currency.to_string(),
         }
     }
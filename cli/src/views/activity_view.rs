@@ -0,0 +1,38 @@
+use model::ActivityDiscrepancy;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct ActivityDiscrepancyView {
+    pub date: String,
+    pub category: String,
+    pub description: String,
+    pub symbol: String,
+    pub amount: String,
+    pub reason: String,
+}
+
+impl ActivityDiscrepancyView {
+    fn new(discrepancy: ActivityDiscrepancy) -> ActivityDiscrepancyView {
+        let activity = discrepancy.activity;
+        ActivityDiscrepancyView {
+            date: activity.date.date().to_string(),
+            category: activity.category.to_string(),
+            description: activity.description,
+            symbol: activity.symbol.unwrap_or_else(|| "-".to_string()),
+            amount: format!("{} {}", activity.amount, activity.currency),
+            reason: discrepancy.reason,
+        }
+    }
+
+    pub fn display(discrepancies: Vec<ActivityDiscrepancy>) {
+        let views: Vec<ActivityDiscrepancyView> = discrepancies
+            .into_iter()
+            .map(ActivityDiscrepancyView::new)
+            .collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
@@ -0,0 +1,36 @@
+use model::SkippedPlan;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct SkippedPlanView {
+    pub signal_date: String,
+    pub symbol: String,
+    pub category: String,
+    pub entry: String,
+    pub stop: String,
+    pub target: String,
+    pub reason: String,
+}
+
+impl SkippedPlanView {
+    fn new(skipped: SkippedPlan) -> SkippedPlanView {
+        SkippedPlanView {
+            signal_date: skipped.plan.signal_date.to_string(),
+            symbol: skipped.plan.symbol,
+            category: skipped.plan.category.to_string(),
+            entry: skipped.plan.entry.to_string(),
+            stop: skipped.plan.stop.to_string(),
+            target: skipped.plan.target.to_string(),
+            reason: skipped.reason,
+        }
+    }
+
+    pub fn display(skipped: Vec<SkippedPlan>) {
+        let views: Vec<SkippedPlanView> = skipped.into_iter().map(SkippedPlanView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
@@ -0,0 +1,83 @@
+use model::{BenchmarkComparison, EquityPoint};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct BenchmarkView {
+    pub benchmark: String,
+    pub from: String,
+    pub to: String,
+    pub account_return: String,
+    pub benchmark_return: String,
+    pub alpha: String,
+    pub beta: String,
+    pub correlation: String,
+}
+
+impl BenchmarkView {
+    fn new(comparison: &BenchmarkComparison) -> BenchmarkView {
+        BenchmarkView {
+            benchmark: comparison.symbol.clone(),
+            from: comparison.from.to_string(),
+            to: comparison.to.to_string(),
+            account_return: percentage(comparison.account_return),
+            benchmark_return: percentage(comparison.benchmark_return),
+            alpha: comparison.alpha.map_or_else(|| "-".to_string(), percentage),
+            beta: optional(comparison.beta),
+            correlation: optional(comparison.correlation),
+        }
+    }
+
+    pub fn display(comparison: &BenchmarkComparison) {
+        println!();
+        println!("Equity curve in {}:", comparison.currency);
+        EquityPointView::display(&comparison.points);
+        println!();
+        let mut table = Table::new(vec![BenchmarkView::new(comparison)]);
+        table.with(Style::modern());
+        println!("{table}");
+        println!();
+    }
+}
+
+#[derive(Tabled)]
+pub struct EquityPointView {
+    pub date: String,
+    pub equity: String,
+    pub account_return: String,
+    pub benchmark_close: String,
+    pub benchmark_return: String,
+}
+
+impl EquityPointView {
+    fn new(point: &EquityPoint) -> EquityPointView {
+        EquityPointView {
+            date: point.date.to_string(),
+            equity: point.equity.to_string(),
+            account_return: percentage(point.account_return),
+            benchmark_close: point.benchmark_close.to_string(),
+            benchmark_return: percentage(point.benchmark_return),
+        }
+    }
+
+    pub fn display(points: &[EquityPoint]) {
+        let views: Vec<EquityPointView> = points.iter().map(EquityPointView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
+
+fn percentage(fraction: Decimal) -> String {
+    fraction.checked_mul(dec!(100)).map_or_else(
+        || "-".to_string(),
+        |value| format!("{}%", value.round_dp(2)),
+    )
+}
+
+fn optional(value: Option<Decimal>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.round_dp(2).to_string())
+}
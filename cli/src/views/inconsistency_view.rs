@@ -0,0 +1,31 @@
+use model::Inconsistency;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct InconsistencyView {
+    pub category: String,
+    pub id: String,
+    pub repairable: String,
+    pub description: String,
+}
+
+impl InconsistencyView {
+    fn new(inconsistency: &Inconsistency) -> InconsistencyView {
+        InconsistencyView {
+            category: inconsistency.category.to_string(),
+            id: inconsistency.entity_id.to_string(),
+            repairable: inconsistency.category.is_repairable().to_string(),
+            description: inconsistency.message.clone(),
+        }
+    }
+
+    pub fn display(inconsistencies: &[Inconsistency]) {
+        let views: Vec<InconsistencyView> =
+            inconsistencies.iter().map(InconsistencyView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
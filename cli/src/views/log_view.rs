@@ -1,4 +1,8 @@
 use model::BrokerLog;
+use tabled::settings::style::Style;
+use tabled::settings::Width;
+use tabled::Table;
+use tabled::Tabled;
 
 pub struct LogView;
 
@@ -6,7 +10,37 @@ impl LogView {
     pub fn display(log: &BrokerLog) {
         println!();
         println!("Log: {}", log.id);
-        println!("{}", log.log);
+        println!("{}", log.response);
         println!();
     }
 }
+
+#[derive(Tabled)]
+pub struct BrokerCallView {
+    pub date: String,
+    pub action: String,
+    pub latency: String,
+    pub request: String,
+    pub response: String,
+    pub error: String,
+}
+
+impl BrokerCallView {
+    fn new(log: &BrokerLog) -> BrokerCallView {
+        BrokerCallView {
+            date: log.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            action: log.action.clone(),
+            latency: format!("{} ms", log.latency_ms),
+            request: log.request.clone(),
+            response: log.response.clone(),
+            error: log.error.clone().unwrap_or_else(|| "-".to_string()),
+        }
+    }
+
+    pub fn display(logs: &[BrokerLog]) {
+        let views: Vec<BrokerCallView> = logs.iter().map(BrokerCallView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern()).with(Width::wrap(200));
+        println!("{table}");
+    }
+}
@@ -8,6 +8,7 @@ pub struct OrderView {
     pub unit_price: String,
     pub average_filled_price: String,
     pub quantity: String,
+    pub filled_quantity: String,
     pub category: String,
     pub action: String,
     pub time_in_force: String,
@@ -24,6 +25,7 @@ impl OrderView {
                 .map(|d| d.to_string())
                 .unwrap_or_default(),
             quantity: order.quantity.to_string(),
+            filled_quantity: order.filled_quantity.to_string(),
             category: order.category.to_string(),
             action: order.action.to_string(),
             time_in_force: order.time_in_force.to_string(),
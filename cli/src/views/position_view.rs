@@ -0,0 +1,40 @@
+use model::SkippedPosition;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct SkippedPositionView {
+    pub symbol: String,
+    pub category: String,
+    pub quantity: String,
+    pub entry: String,
+    pub stop: String,
+    pub target: String,
+    pub reason: String,
+}
+
+impl SkippedPositionView {
+    fn new(skipped: SkippedPosition) -> SkippedPositionView {
+        let price = |order: Option<model::Order>| {
+            order.map_or_else(|| "-".to_string(), |order| order.unit_price.to_string())
+        };
+        SkippedPositionView {
+            symbol: skipped.position.symbol,
+            category: skipped.position.category.to_string(),
+            quantity: skipped.position.entry.quantity.to_string(),
+            entry: skipped.position.entry.unit_price.to_string(),
+            stop: price(skipped.position.safety_stop),
+            target: price(skipped.position.target),
+            reason: skipped.reason,
+        }
+    }
+
+    pub fn display(skipped: Vec<SkippedPosition>) {
+        let views: Vec<SkippedPositionView> =
+            skipped.into_iter().map(SkippedPositionView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
@@ -0,0 +1,36 @@
+use sim_broker::SimFill;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct SimFillView {
+    pub trade: String,
+    pub symbol: String,
+    pub leg: String,
+    pub side: String,
+    pub quantity: String,
+    pub price: String,
+    pub filled_at: String,
+}
+
+impl SimFillView {
+    fn new(fill: SimFill) -> SimFillView {
+        SimFillView {
+            trade: fill.trade_id.to_string(),
+            symbol: fill.symbol,
+            leg: fill.leg.to_string(),
+            side: fill.action.to_string(),
+            quantity: fill.quantity.to_string(),
+            price: fill.price.to_string(),
+            filled_at: fill.filled_at.to_string(),
+        }
+    }
+
+    pub fn display(fills: Vec<SimFill>) {
+        let views: Vec<SimFillView> = fills.into_iter().map(SimFillView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
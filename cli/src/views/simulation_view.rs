@@ -0,0 +1,89 @@
+use model::{EquityPercentile, Simulation};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct SimulationView {
+    pub starting_equity: String,
+    pub risk_per_trade: String,
+    pub risk_per_month: String,
+    pub trades_per_month: u32,
+    pub sample_size: usize,
+    pub expectancy: String,
+    pub median_max_drawdown: String,
+    pub drawdown_probability: String,
+    pub ruin_probability: String,
+}
+
+impl SimulationView {
+    fn new(simulation: &Simulation) -> SimulationView {
+        SimulationView {
+            starting_equity: format!("{} {}", simulation.starting_equity, simulation.currency),
+            risk_per_trade: format!("{}%", simulation.risk_per_trade),
+            risk_per_month: simulation
+                .risk_per_month
+                .map_or_else(|| "-".to_string(), |risk| format!("{risk}%")),
+            trades_per_month: simulation.trades_per_month,
+            sample_size: simulation.sample_size,
+            expectancy: format!("{}R", simulation.expectancy),
+            median_max_drawdown: format!("{}%", simulation.median_max_drawdown),
+            drawdown_probability: format!(
+                "{} (>= {}%)",
+                probability(simulation.drawdown_probability),
+                simulation.parameters.drawdown_percentage
+            ),
+            ruin_probability: format!(
+                "{} (>= {}%)",
+                probability(simulation.ruin_probability),
+                simulation.parameters.ruin_percentage
+            ),
+        }
+    }
+
+    pub fn display(simulation: &Simulation) {
+        println!();
+        println!(
+            "Final equity after {} trades in {} paths:",
+            simulation.parameters.trades, simulation.parameters.paths
+        );
+        EquityPercentileView::display(&simulation.percentiles);
+        println!();
+        let mut table = Table::new(vec![SimulationView::new(simulation)]);
+        table.with(Style::modern());
+        println!("{table}");
+        println!();
+    }
+}
+
+#[derive(Tabled)]
+pub struct EquityPercentileView {
+    pub percentile: String,
+    pub equity: String,
+}
+
+impl EquityPercentileView {
+    fn new(percentile: &EquityPercentile) -> EquityPercentileView {
+        EquityPercentileView {
+            percentile: format!("P{}", percentile.percentile),
+            equity: percentile.equity.to_string(),
+        }
+    }
+
+    pub fn display(percentiles: &[EquityPercentile]) {
+        let views: Vec<EquityPercentileView> =
+            percentiles.iter().map(EquityPercentileView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
+
+fn probability(fraction: Decimal) -> String {
+    fraction.checked_mul(dec!(100)).map_or_else(
+        || "-".to_string(),
+        |value| format!("{}%", value.round_dp(2)),
+    )
+}
@@ -0,0 +1,75 @@
+use model::{OrderSlippage, SlippageSummary};
+use tabled::settings::style::Style;
+use tabled::Table;
+use tabled::Tabled;
+
+#[derive(Tabled)]
+pub struct SlippageView {
+    pub trade: String,
+    pub symbol: String,
+    pub leg: String,
+    pub order_type: String,
+    pub filled_at: String,
+    pub planned: String,
+    pub filled: String,
+    pub quantity: String,
+    pub slippage: String,
+    pub bps: String,
+}
+
+impl SlippageView {
+    fn new(slippage: OrderSlippage) -> SlippageView {
+        SlippageView {
+            trade: slippage.trade_id.to_string(),
+            symbol: slippage.symbol,
+            leg: slippage.leg.to_string(),
+            order_type: slippage.category.to_string(),
+            filled_at: slippage
+                .filled_at
+                .map_or_else(|| "-".to_string(), |date| date.to_string()),
+            planned: slippage.planned_price.to_string(),
+            filled: slippage.filled_price.to_string(),
+            quantity: slippage.quantity.to_string(),
+            slippage: format!("{} {}", slippage.amount, slippage.currency),
+            bps: slippage.bps.to_string(),
+        }
+    }
+
+    pub fn display(slippages: Vec<OrderSlippage>) {
+        let views: Vec<SlippageView> = slippages.into_iter().map(SlippageView::new).collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
+
+#[derive(Tabled)]
+pub struct SlippageSummaryView {
+    pub group: String,
+    pub orders: String,
+    pub total_slippage: String,
+    pub average_bps: String,
+    pub worst_bps: String,
+}
+
+impl SlippageSummaryView {
+    fn new(summary: SlippageSummary) -> SlippageSummaryView {
+        SlippageSummaryView {
+            group: summary.group,
+            orders: summary.orders.to_string(),
+            total_slippage: summary.total_amount.to_string(),
+            average_bps: summary.average_bps.to_string(),
+            worst_bps: summary.worst_bps.to_string(),
+        }
+    }
+
+    pub fn display(summaries: Vec<SlippageSummary>) {
+        let views: Vec<SlippageSummaryView> = summaries
+            .into_iter()
+            .map(SlippageSummaryView::new)
+            .collect();
+        let mut table = Table::new(views);
+        table.with(Style::modern());
+        println!("{table}");
+    }
+}
@@ -10,6 +10,7 @@ pub struct TradeView {
     pub account: String,
     pub currency: String,
     pub quantity: String,
+    pub filled_quantity: String,
     pub stop_price: String,
     pub entry_price: String,
     pub target_price: String,
@@ -24,6 +25,7 @@ impl TradeView {
             account: crate::views::uppercase_first(account_name),
             currency: trade.currency.to_string(),
             quantity: trade.entry.quantity.to_string(),
+            filled_quantity: trade.entry.filled_quantity.to_string(),
             stop_price: trade.safety_stop.unit_price.to_string(),
             entry_price: trade.entry.unit_price.to_string(),
             target_price: trade.target.unit_price.to_string(),
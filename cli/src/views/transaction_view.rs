@@ -1,4 +1,4 @@
-use model::Transaction;
+use model::{Transaction, Transfer};
 use tabled::settings::style::Style;
 use tabled::Table;
 use tabled::Tabled;
@@ -38,3 +38,40 @@ impl TransactionView {
         println!("{table}");
     }
 }
+
+#[derive(Tabled)]
+pub struct TransferView {
+    pub from: String,
+    pub amount: String,
+    pub currency: String,
+    pub to: String,
+    pub converted_amount: String,
+    pub converted_currency: String,
+    pub rate: String,
+}
+
+impl TransferView {
+    fn new(transfer: &Transfer, from_name: &str, to_name: &str) -> TransferView {
+        TransferView {
+            from: crate::views::uppercase_first(from_name),
+            amount: transfer.amount.to_string(),
+            currency: transfer.currency.to_string(),
+            to: crate::views::uppercase_first(to_name),
+            converted_amount: transfer.converted_amount.to_string(),
+            converted_currency: transfer.converted_currency.to_string(),
+            rate: transfer
+                .rate()
+                .map(|rate| rate.normalize().to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn display(transfer: &Transfer, from_name: &str, to_name: &str) {
+        println!();
+        println!("Transfer: {}", transfer.id);
+        let mut table = Table::new(vec![TransferView::new(transfer, from_name, to_name)]);
+        table.with(Style::modern());
+        println!("{table}");
+        println!();
+    }
+}
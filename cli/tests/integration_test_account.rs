@@ -2,8 +2,8 @@ use core::TrustFacade;
 use db_sqlite::SqliteDatabase;
 use model::Broker;
 use model::{
-    Account, BrokerLog, Currency, Order, OrderIds, RuleLevel, RuleName, Status, Trade,
-    TransactionCategory,
+    Account, BrokerLog, Currency, Order, OrderIds, PositionSizing, RuleLevel, RuleName, Status,
+    Trade, TransactionCategory,
 };
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -148,6 +148,163 @@ fn test_multiple_transactions() {
     assert_eq!(balance.taxed, dec!(0));
 }
 
+#[test]
+fn test_transfer_between_accounts() {
+    let mut trust = create_trust();
+
+    let main = trust
+        .create_account(
+            "main",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let europe = trust
+        .create_account(
+            "europe",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &main,
+            &TransactionCategory::Deposit,
+            dec!(10000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // Same currency transfer
+    let (transfer, from_balance, to_balance) = trust
+        .transfer(
+            &main,
+            &europe,
+            dec!(2500),
+            &Currency::USD,
+            &Currency::USD,
+            None,
+        )
+        .unwrap();
+    assert_eq!(transfer.from_account_id, main.id);
+    assert_eq!(transfer.to_account_id, europe.id);
+    assert_eq!(transfer.converted_amount, dec!(2500));
+    assert_eq!(from_balance.total_available, dec!(7500));
+    assert_eq!(from_balance.total_balance, dec!(7500));
+    assert_eq!(to_balance.total_available, dec!(2500));
+    assert_eq!(to_balance.total_balance, dec!(2500));
+
+    // Transfer with a currency conversion
+    let (transfer, from_balance, to_balance) = trust
+        .transfer(
+            &main,
+            &europe,
+            dec!(1000),
+            &Currency::USD,
+            &Currency::EUR,
+            Some(dec!(0.92)),
+        )
+        .unwrap();
+    assert_eq!(transfer.converted_amount, dec!(920));
+    assert_eq!(transfer.converted_currency, Currency::EUR);
+    assert_eq!(transfer.rate(), Some(dec!(0.92)));
+    assert_eq!(from_balance.total_available, dec!(6500));
+    assert_eq!(to_balance.currency, Currency::EUR);
+    assert_eq!(to_balance.total_available, dec!(920));
+
+    // Both sides of each transfer are recorded
+    let transfers = trust.search_transfers(europe.id).unwrap();
+    assert_eq!(transfers.len(), 2);
+    let transfers = trust.search_transfers(main.id).unwrap();
+    assert_eq!(transfers.len(), 2);
+
+    // Transfers can not exceed the available amount or use a missing rate
+    assert!(trust
+        .transfer(
+            &main,
+            &europe,
+            dec!(6500.01),
+            &Currency::USD,
+            &Currency::USD,
+            None
+        )
+        .is_err());
+    assert!(trust
+        .transfer(
+            &main,
+            &europe,
+            dec!(10),
+            &Currency::USD,
+            &Currency::EUR,
+            None
+        )
+        .is_err());
+    assert!(trust
+        .transfer(&main, &main, dec!(10), &Currency::USD, &Currency::USD, None)
+        .is_err());
+
+    assert!(trust.diagnose().unwrap().is_empty());
+}
+
+#[test]
+fn test_benchmark_comparison() {
+    let mut trust = create_trust();
+
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(10000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // Without a benchmark there is nothing to compare with
+    assert!(trust
+        .compare_with_benchmark(&account, &Currency::USD)
+        .is_err());
+
+    let account = trust.update_benchmark(&account, Some("spy")).unwrap();
+    assert_eq!(account.benchmark, Some("SPY".to_string()));
+    assert!(trust.update_benchmark(&account, Some("S P Y")).is_err());
+
+    let today = chrono::Utc::now().date_naive();
+    let csv = format!(
+        "Date,Open,High,Low,Close,Adj Close,Volume\n\
+         {},470,473,469,472,470,1000\n\
+         {today},472,475,471,474,472,1200\n",
+        today.pred_opt().unwrap()
+    );
+    let bars = trust.import_prices("SPY", &csv).unwrap();
+    assert_eq!(bars.len(), 2);
+
+    // Invalid prices are not imported
+    let csv = format!("Date,Open,High,Low,Close\n{today},472,470,471,474\n");
+    assert!(trust.import_prices("SPY", &csv).is_err());
+
+    // The account has a single day of history, so one price is not enough to compare
+    let error = trust
+        .compare_with_benchmark(&account, &Currency::USD)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "benchmark: at least two prices of SPY are needed"
+    );
+}
+
 #[test]
 fn test_risk_rules() {
     let mut trust = create_trust();
@@ -189,10 +346,174 @@ fn test_risk_rules() {
         .unwrap();
 
     let quantity = trust
-        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), &Currency::USD)
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), None, &Currency::USD)
+        .unwrap();
+
+    assert_eq!(quantity, 500);
+}
+
+#[test]
+fn test_position_sizing_capped_by_risk_rules() {
+    let mut trust = create_trust();
+
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(2.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    // Risking 200 per trade is below the 1000 allowed by the rule.
+    let account = trust
+        .update_position_sizing(&account, PositionSizing::FixedDollar(dec!(200)))
+        .unwrap();
+    let quantity = trust
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), None, &Currency::USD)
         .unwrap();
+    assert_eq!(quantity, 100);
 
+    // Risking 5000 per trade is capped by the rule.
+    let account = trust
+        .update_position_sizing(&account, PositionSizing::FixedDollar(dec!(5000)))
+        .unwrap();
+    let quantity = trust
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), None, &Currency::USD)
+        .unwrap();
     assert_eq!(quantity, 500);
+
+    // An ATR of 2.5 with a multiplier of 2 risks 5 per share.
+    let account = trust
+        .update_position_sizing(&account, PositionSizing::Volatility(dec!(2)))
+        .unwrap();
+    let quantity = trust
+        .calculate_maximum_quantity(
+            account.id,
+            dec!(40),
+            dec!(38),
+            Some(dec!(2.5)),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(quantity, 200);
+}
+
+#[test]
+fn test_kelly_position_sizing_without_closed_trades() {
+    let mut trust = create_trust();
+
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // Without rules a new account risks 1% of its capital: 500.
+    let account = trust
+        .update_position_sizing(&account, PositionSizing::Kelly(dec!(0.5)))
+        .unwrap();
+    let quantity = trust
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), None, &Currency::USD)
+        .unwrap();
+    assert_eq!(quantity, 250);
+
+    // With a rule it risks what the rule allows: 1000.
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(2.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+    let quantity = trust
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), None, &Currency::USD)
+        .unwrap();
+    assert_eq!(quantity, 500);
+}
+
+#[test]
+fn test_volatility_position_sizing_without_rules() {
+    let mut trust = create_trust();
+
+    let account = trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+
+    // Without rules it risks 1% of the capital: 500, at 5 per share.
+    let account = trust
+        .update_position_sizing(&account, PositionSizing::Volatility(dec!(2)))
+        .unwrap();
+    let quantity = trust
+        .calculate_maximum_quantity(
+            account.id,
+            dec!(40),
+            dec!(38),
+            Some(dec!(2.5)),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(quantity, 100);
+
+    // A stop wider than the ATR distance is the risk per share: 500 at 10 per share.
+    let quantity = trust
+        .calculate_maximum_quantity(
+            account.id,
+            dec!(40),
+            dec!(30),
+            Some(dec!(2.5)),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(quantity, 50);
 }
 
 struct MockBroker;
@@ -221,7 +542,7 @@ impl Broker for MockBroker {
         unimplemented!()
     }
 
-    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
+    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<BrokerLog, Box<dyn Error>> {
         unimplemented!("Cancel trade: {:?} {:?}", trade, account)
     }
 
@@ -230,7 +551,7 @@ impl Broker for MockBroker {
         trade: &Trade,
         account: &Account,
         new_stop_price: Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
         unimplemented!(
             "Modify stop: {:?} {:?} {:?}",
             trade,
@@ -244,7 +565,7 @@ impl Broker for MockBroker {
         trade: &Trade,
         account: &Account,
         new_target_price: rust_decimal::Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
         unimplemented!(
             "Modify target: {:?} {:?} {:?}",
             trade,
@@ -0,0 +1,313 @@
+use chrono::{NaiveDate, Utc};
+use core::TrustFacade;
+use db_sqlite::SqliteDatabase;
+use model::{
+    Account, ActivityCategory, Broker, BrokerActivity, BrokerLog, BrokerPosition, Currency, Order,
+    OrderCategory, OrderIds, OrderStatus, Status, Trade, TradeCategory, TradingVehicleCategory,
+    TransactionCategory,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+fn create_account(broker: ActivityBroker) -> (TrustFacade, Account) {
+    let mut trust = TrustFacade::new(Box::new(SqliteDatabase::new_in_memory()), Box::new(broker));
+    trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let account = trust.search_account("alpaca").unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+    (trust, account)
+}
+
+#[test]
+fn test_import_activities_csv() {
+    let (mut trust, account) = create_account(ActivityBroker::default());
+    let today = Utc::now().date_naive();
+    let csv = format!(
+        "ID,Date,Type,Amount,Symbol,Order ID,Quantity\n\
+               1,{today},deposit,50000,,,\n\
+               2,2024-01-03,deposit,1000,,,\n\
+               3,2024-01-31,interest,5,,,\n\
+               4,2024-02-01,withdrawal,-200,,,\n\
+               5,2024-02-02,split,0,AAPL,,\n\
+               6,2024-02-03,fill,185,AAPL,904837e3-3b76-47ec-b432-046db621571b,10\n\
+               7,2024-02-04,dividend,12,AAPL,,\n"
+    );
+
+    let import = trust
+        .import_activities_csv(&account, &csv, &Currency::USD)
+        .unwrap();
+
+    // The first deposit was already recorded by hand
+    assert_eq!(import.matched.len(), 1);
+    assert_eq!(import.matched.first().unwrap().id, "1");
+    let categories: Vec<TransactionCategory> =
+        import.transactions.iter().map(|t| t.category).collect();
+    assert_eq!(
+        categories,
+        vec![
+            TransactionCategory::Deposit,
+            TransactionCategory::Interest,
+            TransactionCategory::Withdrawal
+        ]
+    );
+    assert_eq!(import.transactions.last().unwrap().amount, dec!(200));
+    let reasons: Vec<&str> = import
+        .discrepancies
+        .iter()
+        .map(|d| d.reason.as_str())
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            "Trust does not import split activities",
+            "The order 904837e3-3b76-47ec-b432-046db621571b of the fill is not part of any trade",
+            "No trade of AAPL had a position on 2024-02-04",
+        ]
+    );
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(50805));
+
+    // Importing again only checks the discrepancies again
+    let import = trust
+        .import_activities_csv(&account, &csv, &Currency::USD)
+        .unwrap();
+    assert!(import.transactions.is_empty());
+    assert_eq!(import.duplicates, 4);
+    assert_eq!(import.discrepancies.len(), 3);
+}
+
+#[test]
+fn test_import_activities_csv_recorded_long_before() {
+    let (mut trust, account) = create_account(ActivityBroker::default());
+    let csv = "ID,Date,Type,Amount\n1,2024-01-02,deposit,50000\n";
+
+    let import = trust
+        .import_activities_csv(&account, csv, &Currency::USD)
+        .unwrap();
+
+    // The deposit recorded by hand today is not the deposit of 2024
+    assert!(import.matched.is_empty());
+    assert_eq!(import.transactions.len(), 1);
+}
+
+#[test]
+fn test_import_activities_of_trade() {
+    let filled_at = NaiveDate::from_ymd_opt(2024, 1, 2)
+        .unwrap()
+        .and_hms_opt(15, 0, 0)
+        .unwrap();
+    let entry = Order {
+        filled_quantity: 100,
+        average_filled_price: Some(dec!(40)),
+        filled_at: Some(filled_at),
+        ..order(dec!(40), OrderCategory::Limit, OrderStatus::Filled)
+    };
+    let stop = order(dec!(38), OrderCategory::Stop, OrderStatus::New);
+    let activity =
+        |id: &str, category: ActivityCategory, amount: Decimal, order: &Order| BrokerActivity {
+            id: id.to_string(),
+            date: filled_at,
+            category,
+            description: category.to_string(),
+            symbol: Some("TSLA".to_string()),
+            amount,
+            currency: Currency::USD,
+            order_id: order.broker_order_id,
+            quantity: dec!(100),
+        };
+    let activities = vec![
+        activity("fill", ActivityCategory::Fill, dec!(40), &entry),
+        activity("stop", ActivityCategory::Fill, dec!(38), &stop),
+        activity("dividend", ActivityCategory::Dividend, dec!(25), &entry),
+        activity("fee", ActivityCategory::Fee, dec!(-1), &entry),
+    ];
+    let position = BrokerPosition {
+        broker: "alpaca".to_string(),
+        symbol: "TSLA".to_string(),
+        vehicle_category: TradingVehicleCategory::Stock,
+        category: TradeCategory::Long,
+        currency: Currency::USD,
+        entry,
+        safety_stop: Some(stop.clone()),
+        target: Some(order(dec!(60), OrderCategory::Limit, OrderStatus::Held)),
+        skip_reason: None,
+    };
+    let (mut trust, account) = create_account(ActivityBroker {
+        positions: vec![position],
+        activities,
+    });
+    let (trades, _) = trust.import_positions(&account).unwrap();
+    let trade = trades.first().unwrap();
+
+    let import = trust.import_activities(&account).unwrap();
+
+    // The fill of the entry matches the trade, and the dividend is paid to it
+    assert_eq!(import.matched.len(), 1);
+    assert_eq!(import.matched.first().unwrap().id, "fill");
+    assert_eq!(import.transactions.len(), 1);
+    let dividend = import.transactions.first().unwrap();
+    assert_eq!(dividend.category, TransactionCategory::Dividend(trade.id));
+    assert_eq!(dividend.amount, dec!(25));
+    let reasons: Vec<&str> = import
+        .discrepancies
+        .iter()
+        .map(|d| d.reason.as_str())
+        .collect();
+    assert_eq!(reasons.len(), 2);
+    assert!(reasons
+        .first()
+        .unwrap()
+        .ends_with("as New. Sync its trade to record the fill"));
+    assert!(reasons
+        .last()
+        .unwrap()
+        .starts_with("The broker charged a fee of 1 for the long trade"));
+}
+
+#[test]
+fn test_import_dividend_of_trade_closed_by_hand() {
+    let filled_at = NaiveDate::from_ymd_opt(2024, 1, 2)
+        .unwrap()
+        .and_hms_opt(15, 0, 0)
+        .unwrap();
+    let entry = Order {
+        filled_quantity: 100,
+        average_filled_price: Some(dec!(40)),
+        filled_at: Some(filled_at),
+        ..order(dec!(40), OrderCategory::Limit, OrderStatus::Filled)
+    };
+    let dividend = BrokerActivity {
+        id: "dividend".to_string(),
+        date: filled_at,
+        category: ActivityCategory::Dividend,
+        description: "dividend".to_string(),
+        symbol: Some("TSLA".to_string()),
+        amount: dec!(25),
+        currency: Currency::USD,
+        order_id: None,
+        quantity: Decimal::ZERO,
+    };
+    let position = BrokerPosition {
+        broker: "alpaca".to_string(),
+        symbol: "TSLA".to_string(),
+        vehicle_category: TradingVehicleCategory::Stock,
+        category: TradeCategory::Long,
+        currency: Currency::USD,
+        entry,
+        safety_stop: Some(order(dec!(38), OrderCategory::Stop, OrderStatus::New)),
+        target: Some(order(dec!(60), OrderCategory::Limit, OrderStatus::Held)),
+        skip_reason: None,
+    };
+    let (mut trust, account) = create_account(ActivityBroker {
+        positions: vec![position],
+        activities: vec![dividend],
+    });
+    let (trades, _) = trust.import_positions(&account).unwrap();
+    let trade = trades.first().unwrap();
+    trust.close_trade(trade).unwrap();
+
+    let import = trust.import_activities(&account).unwrap();
+
+    // The trade was closed by hand, and it held the position on the date of the dividend
+    assert!(import.discrepancies.is_empty());
+    let dividend = import.transactions.first().unwrap();
+    assert_eq!(dividend.category, TransactionCategory::Dividend(trade.id));
+}
+
+fn order(price: Decimal, category: OrderCategory, status: OrderStatus) -> Order {
+    Order {
+        broker_order_id: Some(Uuid::new_v4()),
+        unit_price: price,
+        quantity: 100,
+        category,
+        status,
+        ..Default::default()
+    }
+}
+
+#[derive(Default)]
+struct ActivityBroker {
+    positions: Vec<BrokerPosition>,
+    activities: Vec<BrokerActivity>,
+}
+
+impl Broker for ActivityBroker {
+    fn submit_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn sync_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn close_trade(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        let order = Order {
+            category: OrderCategory::Market,
+            status: OrderStatus::Accepted,
+            ..trade.target.clone()
+        };
+        Ok((order, BrokerLog::default()))
+    }
+
+    fn cancel_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerLog, Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn modify_stop(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _new_stop_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn modify_target(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _new_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn positions(&self, _account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        Ok(self.positions.clone())
+    }
+
+    fn activities(&self, _account: &Account) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        Ok(self.activities.clone())
+    }
+}
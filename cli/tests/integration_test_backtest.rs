@@ -0,0 +1,140 @@
+use core::TrustFacade;
+use db_sqlite::SqliteDatabase;
+use model::{Account, BacktestParameters, Currency, RuleLevel, RuleName, Status, TradeCategory};
+use rust_decimal_macros::dec;
+
+fn create_account(trust: &mut TrustFacade) -> Account {
+    trust
+        .create_account(
+            "live",
+            "default",
+            model::Environment::Live,
+            dec!(0),
+            dec!(0),
+        )
+        .unwrap();
+    let account = trust.search_account("live").unwrap();
+    trust
+        .create_rule(
+            &account,
+            &RuleName::RiskPerTrade(2.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    let csv = "Date,Open,High,Low,Close\n\
+               2024-01-02,100,101,99,100\n\
+               2024-01-03,101,102,99.5,101\n\
+               2024-01-04,104,111,103,110\n\
+               2024-01-05,110,112,108,111\n\
+               2024-01-08,111,113,110,112\n";
+    trust.import_prices("AAPL", csv).unwrap();
+    account
+}
+
+#[test]
+fn test_backtest() {
+    let mut trust = TrustFacade::new(
+        Box::new(SqliteDatabase::new_in_memory()),
+        Box::<alpaca_broker::AlpacaBroker>::default(),
+    );
+    let account = create_account(&mut trust);
+    let plans = TrustFacade::parse_trade_plans(
+        "Date,Symbol,Category,Entry,Stop,Target\n\
+         2024-01-02,AAPL,long,100,95,110\n\
+         2024-01-03,AAPL,long,90,85,100\n\
+         2024-01-03,AAPL,short,120,125,100\n\
+         2024-01-08,AAPL,long,110,105,120\n",
+    )
+    .unwrap();
+    let parameters = BacktestParameters {
+        starting_capital: dec!(10000),
+        currency: Currency::USD,
+    };
+
+    let (report, mut backtest) = trust
+        .backtest(
+            &account,
+            &plans,
+            &parameters,
+            Box::new(SqliteDatabase::new_in_memory()),
+        )
+        .unwrap();
+
+    assert_eq!(report.account.name, "live backtest");
+    assert_eq!(report.from.to_string(), "2024-01-03");
+    assert_eq!(report.to.to_string(), "2024-01-08");
+
+    // 2% of 10000 risked with a stop 5 below the entry
+    let winner = report.trades.first().unwrap();
+    assert_eq!(winner.category, TradeCategory::Long);
+    assert_eq!(winner.entry.quantity, 40);
+    assert_eq!(winner.status, Status::ClosedTarget);
+    assert_eq!(winner.target.average_filled_price, Some(dec!(110)));
+
+    // The entry is never reached, so it is canceled after the last bar
+    let unfilled = report.trades.last().unwrap();
+    assert_eq!(report.trades.len(), 2);
+    assert_eq!(unfilled.entry.unit_price, dec!(90));
+    assert_eq!(unfilled.status, Status::Canceled);
+
+    // Fixed fractional sizing only sizes trades with the stop below the entry
+    let short = report.skipped.first().unwrap();
+    assert_eq!(short.plan.category, TradeCategory::Short);
+    assert_eq!(
+        short.reason,
+        "The position sizing and the rules of the account allow no quantity"
+    );
+    let last = report.skipped.last().unwrap();
+    assert_eq!(report.skipped.len(), 2);
+    assert_eq!(last.reason, "No prices after the signal date");
+
+    assert_eq!(report.balance.total_balance, dec!(10400));
+    assert_eq!(report.balance.total_available, dec!(10400));
+
+    // The backtest account supports the same reports as a live account
+    let closed = backtest
+        .search_trades(report.account.id, Status::ClosedTarget)
+        .unwrap();
+    assert_eq!(closed.first().unwrap().balance.total_performance, dec!(400));
+
+    // The live account is not modified
+    assert!(trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
+        .is_empty());
+    assert!(trust.search_account("live backtest").is_err());
+}
+
+#[test]
+fn test_backtest_without_prices() {
+    let mut trust = TrustFacade::new(
+        Box::new(SqliteDatabase::new_in_memory()),
+        Box::<alpaca_broker::AlpacaBroker>::default(),
+    );
+    let account = create_account(&mut trust);
+    let plans = TrustFacade::parse_trade_plans(
+        "Date,Symbol,Category,Entry,Stop,Target\n2024-01-02,TSLA,long,100,95,110\n",
+    )
+    .unwrap();
+    let parameters = BacktestParameters {
+        starting_capital: dec!(10000),
+        currency: Currency::USD,
+    };
+
+    let error = trust
+        .backtest(
+            &account,
+            &plans,
+            &parameters,
+            Box::new(SqliteDatabase::new_in_memory()),
+        )
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        error.to_string(),
+        "There are no prices of TSLA since 2024-01-02, import them with trust price import"
+    );
+}
@@ -126,7 +126,11 @@ impl Broker for MockBroker {
         unimplemented!()
     }
 
-    fn cancel_trade(&self, _trade: &Trade, _account: &Account) -> Result<(), Box<dyn Error>> {
+    fn cancel_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerLog, Box<dyn Error>> {
         unimplemented!("Cancel trade not implemented")
     }
 
@@ -135,7 +139,7 @@ impl Broker for MockBroker {
         trade: &Trade,
         account: &Account,
         new_stop_price: Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
         unimplemented!(
             "Modify stop: {:?} {:?} {:?}",
             trade,
@@ -149,7 +153,7 @@ impl Broker for MockBroker {
         trade: &Trade,
         account: &Account,
         new_target_price: rust_decimal::Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
         unimplemented!(
             "Modify target: {:?} {:?} {:?}",
             trade,
@@ -0,0 +1,218 @@
+use core::TrustFacade;
+use db_sqlite::SqliteDatabase;
+use model::{
+    Account, Broker, BrokerLog, BrokerPosition, Currency, Order, OrderCategory, OrderIds,
+    OrderStatus, Status, Trade, TradeCategory, TradingVehicleCategory, TransactionCategory,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+use uuid::Uuid;
+
+fn order(price: Decimal, category: OrderCategory, status: OrderStatus) -> Order {
+    Order {
+        broker_order_id: Some(Uuid::new_v4()),
+        unit_price: price,
+        quantity: 100,
+        category,
+        status,
+        ..Default::default()
+    }
+}
+
+fn position(symbol: &str, entry: Order, stop: Option<Order>) -> BrokerPosition {
+    BrokerPosition {
+        broker: "alpaca".to_string(),
+        symbol: symbol.to_string(),
+        vehicle_category: TradingVehicleCategory::Stock,
+        category: TradeCategory::Long,
+        currency: Currency::USD,
+        entry,
+        safety_stop: stop,
+        target: Some(order(dec!(60), OrderCategory::Limit, OrderStatus::Held)),
+        skip_reason: None,
+    }
+}
+
+fn filled(price: Decimal) -> Order {
+    Order {
+        filled_quantity: 100,
+        average_filled_price: Some(price),
+        ..order(price, OrderCategory::Limit, OrderStatus::Filled)
+    }
+}
+
+fn create_account(positions: Vec<BrokerPosition>) -> (TrustFacade, Account) {
+    let mut trust = TrustFacade::new(
+        Box::new(SqliteDatabase::new_in_memory()),
+        Box::new(PositionsBroker { positions }),
+    );
+    trust
+        .create_account(
+            "alpaca",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let account = trust.search_account("alpaca").unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(50000),
+            &Currency::USD,
+        )
+        .unwrap();
+    (trust, account)
+}
+
+#[test]
+fn test_import_positions() {
+    let stop = Some(order(dec!(38), OrderCategory::Stop, OrderStatus::New));
+    let (mut trust, account) = create_account(vec![
+        position("TSLA", filled(dec!(40)), stop.clone()),
+        position(
+            "AAPL",
+            order(dec!(45), OrderCategory::Limit, OrderStatus::New),
+            stop,
+        ),
+        position("NVDA", filled(dec!(40)), None),
+    ]);
+
+    let (trades, skipped) = trust.import_positions(&account).unwrap();
+
+    assert_eq!(trades.len(), 2);
+    let tsla = trades.first().unwrap();
+    assert_eq!(tsla.trading_vehicle.symbol, "TSLA");
+    assert_eq!(tsla.status, Status::Filled);
+    assert_eq!(tsla.entry.quantity, 100);
+    assert_eq!(tsla.entry.average_filled_price, Some(dec!(40)));
+    assert_eq!(tsla.safety_stop.unit_price, dec!(38));
+    assert_eq!(tsla.target.unit_price, dec!(60));
+    let aapl = trades.last().unwrap();
+    assert_eq!(aapl.status, Status::Submitted);
+    assert!(aapl.entry.broker_order_id.is_some());
+
+    assert_eq!(skipped.len(), 1);
+    let nvda = skipped.first().unwrap();
+    assert_eq!(nvda.position.symbol, "NVDA");
+    assert_eq!(nvda.reason, "There is no stop order at the broker");
+
+    // Both trades are funded from the deposit
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(41500));
+    assert_eq!(balance.total_in_trade, dec!(8500));
+}
+
+#[test]
+fn test_import_positions_twice() {
+    let stop = Some(order(dec!(38), OrderCategory::Stop, OrderStatus::New));
+    let (mut trust, account) = create_account(vec![position("TSLA", filled(dec!(40)), stop)]);
+    trust.import_positions(&account).unwrap();
+
+    let (trades, skipped) = trust.import_positions(&account).unwrap();
+
+    assert!(trades.is_empty());
+    assert!(skipped
+        .first()
+        .unwrap()
+        .reason
+        .ends_with("is already held by an open trade"));
+}
+
+#[test]
+fn test_import_positions_after_adding_to_a_position() {
+    let stop = Some(order(dec!(38), OrderCategory::Stop, OrderStatus::New));
+    let (mut trust, account) = create_account(vec![
+        position("TSLA", filled(dec!(40)), stop.clone()),
+        // The position was added to, so its newest fill is another order
+        position("TSLA", filled(dec!(41)), stop),
+    ]);
+
+    let (trades, skipped) = trust.import_positions(&account).unwrap();
+
+    assert_eq!(trades.len(), 1);
+    assert_eq!(
+        skipped.first().unwrap().reason,
+        "TSLA is already held by an open trade"
+    );
+}
+
+#[test]
+fn test_import_positions_skips_the_reported_positions() {
+    let stop = Some(order(dec!(38), OrderCategory::Stop, OrderStatus::New));
+    let (mut trust, account) = create_account(vec![BrokerPosition {
+        skip_reason: Some("BTC/USD has a fractional quantity of 0.5".to_string()),
+        ..position("BTC/USD", filled(dec!(40)), stop)
+    }]);
+
+    let (trades, skipped) = trust.import_positions(&account).unwrap();
+
+    assert!(trades.is_empty());
+    assert_eq!(
+        skipped.first().unwrap().reason,
+        "BTC/USD has a fractional quantity of 0.5"
+    );
+}
+
+struct PositionsBroker {
+    positions: Vec<BrokerPosition>,
+}
+
+impl Broker for PositionsBroker {
+    fn submit_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn sync_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn close_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn cancel_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerLog, Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn modify_stop(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _new_stop_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn modify_target(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _new_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        unimplemented!()
+    }
+
+    fn positions(&self, _account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        Ok(self.positions.clone())
+    }
+}
@@ -0,0 +1,138 @@
+use core::TrustFacade;
+use db_sqlite::SqliteDatabase;
+use manual_broker::ManualBroker;
+use model::{
+    Account, Currency, DraftTrade, OrderCategory, OrderStatus, Status, Trade, TradeCategory,
+    TradingVehicleCategory, TransactionCategory,
+};
+use rust_decimal_macros::dec;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+fn create_trust() -> (TrustFacade, PathBuf) {
+    let directory = std::env::temp_dir().join(format!("trust-tickets-{}", Uuid::new_v4()));
+    let db = SqliteDatabase::new_in_memory();
+    let broker = ManualBroker::new(&directory);
+    let trust = TrustFacade::new(Box::new(db), Box::new(broker));
+    (trust, directory)
+}
+
+fn submit_trade(trust: &mut TrustFacade) -> (Account, Trade) {
+    trust
+        .create_account(
+            "paper",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let account = trust.search_account("paper").unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv,
+        quantity: 500,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+    };
+    trust
+        .create_trade(draft, dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    trust.fund_trade(&trade).unwrap();
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap();
+    let (trade, _) = trust.submit_trade(trade.first().unwrap()).unwrap();
+    (account, trade)
+}
+
+fn sync(trust: &mut TrustFacade, account: &Account, trade: &Trade, status: Status) -> Trade {
+    trust.sync_trade(trade, account).unwrap();
+    find(trust, account, trade, status)
+}
+
+fn find(trust: &mut TrustFacade, account: &Account, trade: &Trade, status: Status) -> Trade {
+    trust
+        .search_trades(account.id, status)
+        .unwrap()
+        .into_iter()
+        .find(|found| found.id == trade.id)
+        .unwrap()
+}
+
+fn write_fills(directory: &Path, trade: &Trade, content: &str) {
+    fs::write(directory.join(format!("{}-fills.json", trade.id)), content).unwrap();
+}
+
+#[test]
+fn test_manual_broker_fills_the_target() {
+    let (mut trust, directory) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+
+    let ticket = fs::read_to_string(directory.join(format!("{}-submit.txt", trade.id))).unwrap();
+    assert!(ticket.contains("Entry: buy 500 TSLA limit 40, until_canceled"));
+    assert!(directory.join(format!("{}-submit.json", trade.id)).exists());
+
+    // Nothing happens until the fills are recorded
+    let trade = sync(&mut trust, &account, &trade, Status::Submitted);
+
+    write_fills(&directory, &trade, r#"{"entry": {"price": "39.9"}}"#);
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
+
+    write_fills(
+        &directory,
+        &trade,
+        r#"{"entry": {"price": "39.9"}, "target": {"price": "50.1"}}"#,
+    );
+    let trade = sync(&mut trust, &account, &trade, Status::ClosedTarget);
+    assert_eq!(trade.safety_stop.status, OrderStatus::Canceled);
+    assert_eq!(trade.balance.total_performance, dec!(5100));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(105100));
+}
+
+#[test]
+fn test_manual_broker_closes_at_the_market() {
+    let (mut trust, directory) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+    write_fills(&directory, &trade, r#"{"entry": {"price": "40"}}"#);
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+
+    trust.close_trade(&trade).unwrap();
+    let ticket = fs::read_to_string(directory.join(format!("{}-close.txt", trade.id))).unwrap();
+    assert!(ticket.contains("Close: sell 500 TSLA market, until_canceled"));
+    let trade = find(&mut trust, &account, &trade, Status::Canceled);
+    assert_eq!(trade.target.category, OrderCategory::Market);
+
+    write_fills(
+        &directory,
+        &trade,
+        r#"{"entry": {"price": "40"}, "target": {"price": "41.5"}}"#,
+    );
+    let trade = sync(&mut trust, &account, &trade, Status::ClosedTarget);
+    assert_eq!(trade.target.average_filled_price, Some(dec!(41.5)));
+    assert_eq!(trade.balance.total_performance, dec!(750));
+}
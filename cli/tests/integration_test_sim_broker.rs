@@ -0,0 +1,252 @@
+use core::TrustFacade;
+use db_sqlite::SqliteDatabase;
+use model::{
+    Account, Currency, DraftTrade, FeeSchedule, Status, Trade, TradeCategory,
+    TradingVehicleCategory, TransactionCategory,
+};
+use rust_decimal_macros::dec;
+use sim_broker::{Leg, SimBroker};
+
+fn create_trust() -> (TrustFacade, SimBroker) {
+    let db = SqliteDatabase::new_in_memory();
+    let broker = SimBroker::new_in_memory().unwrap();
+    let trust = TrustFacade::new(Box::new(db), Box::new(broker.clone()));
+    (trust, broker)
+}
+
+fn submit_trade(trust: &mut TrustFacade) -> (Account, Trade) {
+    trust
+        .create_account(
+            "paper",
+            "default",
+            model::Environment::Paper,
+            dec!(20),
+            dec!(10),
+        )
+        .unwrap();
+    let account = trust.search_account("paper").unwrap();
+    trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Deposit,
+            dec!(100000),
+            &Currency::USD,
+        )
+        .unwrap();
+    let tv = trust
+        .create_trading_vehicle(
+            "TSLA",
+            "US88160R1014",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle: tv,
+        quantity: 500,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+    };
+    trust
+        .create_trade(draft, dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    trust.fund_trade(&trade).unwrap();
+    let trade = trust.search_trades(account.id, Status::Funded).unwrap();
+    let (trade, _) = trust.submit_trade(trade.first().unwrap()).unwrap();
+    (account, trade)
+}
+
+fn sync(trust: &mut TrustFacade, account: &Account, trade: &Trade, status: Status) -> Trade {
+    trust.sync_trade(trade, account).unwrap();
+    find(trust, account, trade, status)
+}
+
+fn find(trust: &mut TrustFacade, account: &Account, trade: &Trade, status: Status) -> Trade {
+    trust
+        .search_trades(account.id, status)
+        .unwrap()
+        .into_iter()
+        .find(|found| found.id == trade.id)
+        .unwrap()
+}
+
+#[test]
+fn test_sim_broker_fills_the_target() {
+    let (mut trust, broker) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+
+    assert!(broker.tick("TSLA", dec!(40.5)).unwrap().is_empty());
+    let fills = broker.tick("TSLA", dec!(39.9)).unwrap();
+    assert_eq!(fills.first().unwrap().leg, Leg::Entry);
+
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+    assert_eq!(trade.entry.average_filled_price, Some(dec!(39.9)));
+
+    let fills = broker.tick("TSLA", dec!(50.1)).unwrap();
+    assert_eq!(fills.first().unwrap().leg, Leg::Target);
+
+    let trade = sync(&mut trust, &account, &trade, Status::ClosedTarget);
+    assert_eq!(trade.safety_stop.status, model::OrderStatus::Canceled);
+    assert_eq!(trade.balance.total_performance, dec!(5100));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(105100));
+}
+
+#[test]
+fn test_sim_broker_closes_at_the_last_price() {
+    let (mut trust, broker) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+
+    broker
+        .import_ticks("Symbol,Price\nTSLA,40\nTSLA,42\nTSLA,41.5")
+        .unwrap();
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+
+    trust.close_trade(&trade).unwrap();
+    let trade = find(&mut trust, &account, &trade, Status::Canceled);
+    let trade = sync(&mut trust, &account, &trade, Status::ClosedTarget);
+
+    assert_eq!(trade.status, Status::ClosedTarget);
+    assert_eq!(trade.target.average_filled_price, Some(dec!(41.5)));
+    assert_eq!(trade.balance.total_performance, dec!(750));
+}
+
+#[test]
+fn test_sim_broker_logs_every_call() {
+    let (mut trust, broker) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+
+    broker.tick("TSLA", dec!(40)).unwrap();
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+    trust.modify_stop(&trade, &account, dec!(39)).unwrap();
+    broker.tick("TSLA", dec!(38.9)).unwrap();
+    let trade = find(&mut trust, &account, &trade, Status::Filled);
+    assert!(trust.modify_target(&trade, &account, dec!(55)).is_err());
+
+    let logs = trust.read_trade_logs(trade.id).unwrap();
+    let actions: Vec<&str> = logs.iter().map(|log| log.action.as_str()).collect();
+    assert_eq!(
+        actions,
+        vec!["submit_trade", "sync_trade", "modify_stop", "modify_target"]
+    );
+    let modify_stop = logs.get(2).unwrap();
+    assert_eq!(modify_stop.request, "Change the stop price to 39");
+    assert!(modify_stop.response.contains("\"price\":\"39\""));
+    assert_eq!(modify_stop.error, None);
+    let modify_target = logs.last().unwrap();
+    assert_eq!(modify_target.request, "Change the target price to 55");
+    assert!(modify_target.response.is_empty());
+    assert!(modify_target
+        .error
+        .as_ref()
+        .unwrap()
+        .ends_with("is not open, it can not be modified"));
+}
+
+#[test]
+fn test_sim_broker_modifies_the_entry() {
+    let (mut trust, broker) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+
+    // A smaller entry returns the capital that it no longer needs
+    let (trade, transaction) = trust.modify_entry(&trade, &account, dec!(39), 400).unwrap();
+    let transaction = transaction.unwrap();
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::PaymentFromTrade(trade.id)
+    );
+    assert_eq!(transaction.amount, dec!(4400));
+    assert_eq!(trade.entry.unit_price, dec!(39));
+    assert_eq!(trade.entry.quantity, 400);
+    assert_eq!(trade.safety_stop.quantity, 400);
+    assert_eq!(trade.target.quantity, 400);
+
+    // A larger entry is funded with more capital
+    let (trade, transaction) = trust.modify_entry(&trade, &account, dec!(41), 600).unwrap();
+    let transaction = transaction.unwrap();
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::FundTrade(trade.id)
+    );
+    assert_eq!(transaction.amount, dec!(9000));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(75400));
+
+    // The entry must stay between the stop and the target
+    assert!(trust.modify_entry(&trade, &account, dec!(37), 600).is_err());
+
+    broker.tick("TSLA", dec!(41)).unwrap();
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+    assert_eq!(trade.entry.filled_quantity, 600);
+    assert!(trust.modify_entry(&trade, &account, dec!(40), 600).is_err());
+}
+
+#[test]
+fn test_sim_broker_closes_part_of_the_trade() {
+    let (mut trust, broker) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+
+    broker.tick("TSLA", dec!(40)).unwrap();
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+    assert!(trust.close_partial(&trade, 500).is_err());
+
+    broker.tick("TSLA", dec!(44)).unwrap();
+    let (trade, transaction, log) = trust.close_partial(&trade, 200).unwrap();
+
+    // The closed quantity comes back into the trade, and the rest stays in the market
+    assert_eq!(log.action, "close_partial");
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::ClosePartial(trade.id)
+    );
+    assert_eq!(transaction.amount, dec!(8800));
+    assert_eq!(trade.status, Status::Filled);
+    assert_eq!(trade.safety_stop.quantity, 300);
+    assert_eq!(trade.target.quantity, 300);
+    assert_eq!(trade.balance.capital_in_market, dec!(12000));
+    assert_eq!(trade.balance.capital_out_market, dec!(8800));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(88800));
+
+    // The target closes the open quantity
+    broker.tick("TSLA", dec!(50.1)).unwrap();
+    let trade = sync(&mut trust, &account, &trade, Status::ClosedTarget);
+    assert_eq!(trade.target.filled_quantity, 300);
+    assert_eq!(trade.balance.capital_in_market, dec!(0));
+    assert_eq!(trade.balance.total_performance, dec!(3830));
+
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(103830));
+}
+
+#[test]
+fn test_sim_broker_charges_the_fee_of_a_partial_close() {
+    let (mut trust, broker) = create_trust();
+    let (account, trade) = submit_trade(&mut trust);
+    trust
+        .set_fee_schedule(
+            &account,
+            &FeeSchedule {
+                per_share: dec!(0.01),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    broker.tick("TSLA", dec!(40)).unwrap();
+    let trade = sync(&mut trust, &account, &trade, Status::Filled);
+    broker.tick("TSLA", dec!(44)).unwrap();
+    trust.close_partial(&trade, 200).unwrap();
+
+    // Opening: 500 * 0.01, closing the part: 200 * 0.01
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_balance, dec!(88793));
+}
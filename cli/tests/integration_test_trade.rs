@@ -2,7 +2,8 @@ use chrono::Utc;
 use core::TrustFacade;
 use db_sqlite::SqliteDatabase;
 use model::{
-    Account, BrokerLog, Currency, Order, OrderCategory, OrderIds, RuleLevel, RuleName, Status,
+    Account, BrokerLog, Currency, FeeSchedule, InconsistencyCategory, Order, OrderCategory,
+    OrderIds, RuleLevel, RuleName, SimulationParameters, SlippageGrouping, SlippageLeg, Status,
     Trade, TradeCategory, TradingVehicleCategory, TransactionCategory,
 };
 use model::{Broker, DraftTrade, OrderStatus};
@@ -126,6 +127,26 @@ fn test_trade_submit_entry_accepted() {
     assert_entry_accepted(&trade, &mut trust);
 }
 
+#[test]
+fn test_trade_sync_saves_the_price_of_a_replaced_stop() {
+    let (mut trust, account, trade) = create_trade(BrokerResponse::orders_stop_replaced, None);
+
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::Submitted)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    assert_eq!(
+        trade.safety_stop.broker_order_id,
+        Some(Uuid::parse_str("3c7b3d6e-2f0a-4a8e-9d55-6f1c2b7a9e10").unwrap())
+    );
+    assert_eq!(trade.safety_stop.unit_price, dec!(39));
+    assert_eq!(trade.target.unit_price, dec!(50));
+}
+
 #[test]
 fn test_trade_submit_entry_accepted_multiple_times() {
     let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
@@ -224,6 +245,91 @@ fn test_trade_entry_filled_multiple_times() {
     assert_entry_filled(&trade, &mut trust);
 }
 
+#[test]
+fn test_trade_entry_partially_filled() {
+    let (mut trust, account, trade) =
+        create_trade(BrokerResponse::orders_entry_partially_filled, None);
+
+    // Only the capital of the 200 filled shares goes into the market
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::PartiallyFilled)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    assert_eq!(trade.entry.quantity, 500);
+    assert_eq!(trade.entry.filled_quantity, 200);
+    assert_eq!(trade.safety_stop.quantity, 200);
+    assert_eq!(trade.target.quantity, 200);
+    assert_eq!(trade.balance.capital_in_market, dec!(7980)); // 200 * 39.9
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(30000));
+    assert_eq!(balance.total_in_trade, dec!(7980));
+
+    // 100 more shares are filled before the rest of the entry expires
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    assert_eq!(trade.entry.filled_quantity, 300);
+    assert_eq!(trade.safety_stop.quantity, 300);
+    assert_eq!(trade.target.quantity, 300);
+    assert_eq!(trade.balance.capital_in_market, dec!(11940)); // 300 * 39.8
+
+    // The better price of the 300 shares and the funding of the 200 that were never
+    // filled go back to the account
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(38060)); // 30000 + 300 * 0.2 + 200 * 40
+    assert_eq!(balance.total_in_trade, dec!(11940));
+}
+
+#[test]
+fn test_trade_entry_partially_filled_charges_the_opening_fee_of_every_fill() {
+    let (mut trust, account, trade) =
+        create_trade(BrokerResponse::orders_entry_partially_filled, None);
+    trust
+        .set_fee_schedule(
+            &account,
+            &FeeSchedule {
+                per_order: dec!(1),
+                per_share: dec!(0.01),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // The first 200 shares pay the fee per order and 2 for the shares
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::PartiallyFilled)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(29997)); // 30000 - 3
+
+    // The 100 shares filled later only pay for the shares
+    trust.sync_trade(&trade, &account).unwrap();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(38056)); // 38060 - 4 in opening fees
+
+    // A sync without new fills charges nothing
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    trust.sync_trade(&trade, &account).unwrap();
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(38056));
+}
+
 fn assert_entry_filled(trade: &Trade, trust: &mut TrustFacade) {
     // Assert Status
     assert_eq!(trade.status, Status::Filled);
@@ -328,12 +434,227 @@ fn assert_target_filled(trade: &Trade, trust: &mut TrustFacade) {
     let account = trust.search_account("alpaca").unwrap();
     let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
     assert_eq!(balance.currency, Currency::USD);
-    assert_eq!(balance.total_available, dec!(56500.0)); // Including the 50 USD from the difference of the target unit price and average filled price
+    assert_eq!(balance.total_available, dec!(55850.0)); // Including the 50 USD from the difference of the target unit price and average filled price, minus the 650 USD of earnings set aside
     assert_eq!(balance.total_balance, dec!(56500.0));
     assert_eq!(balance.total_in_trade, dec!(0));
     assert_eq!(balance.taxed, dec!(0));
 }
 
+#[test]
+fn test_trade_target_filled_with_fee_schedule() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust
+        .set_fee_schedule(
+            &account,
+            &FeeSchedule {
+                per_order: dec!(1),
+                sec_fee_rate: dec!(0.0000278),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // 9. Sync trade with the Broker - Target is filled and fees are charged automatically
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    assert_eq!(trade.balance.total_performance, dec!(6497.26));
+
+    // Opening: 1, Closing: 1 + 26450 * 0.0000278 rounded up to 1.74
+    // Earnings: 10% of 6497.26 rounded to 649.73
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_available, dec!(55847.53));
+    assert_eq!(balance.total_balance, dec!(56497.26));
+}
+
+#[test]
+fn test_trade_target_filled_sets_aside_earnings() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+
+    // 9. Sync trade with the Broker - Target is filled with a profit of 6500
+    trust.sync_trade(&trade, &account).unwrap();
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // 10. The account sets aside 10% of the profit only once
+    let earnings = trust
+        .calculate_earnings(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(earnings, dec!(650));
+    let balance = trust.search_balance(account.id, &Currency::USD).unwrap();
+    assert_eq!(balance.total_earnings, dec!(650));
+
+    // 11. Withdrawing more than the earnings is not allowed
+    let result = trust.create_transaction(
+        &account,
+        &TransactionCategory::WithdrawalEarnings,
+        dec!(650.01),
+        &Currency::USD,
+    );
+    assert!(result.is_err());
+
+    // 12. Withdraw the earnings
+    let (transaction, balance) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::WithdrawalEarnings,
+            dec!(650),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::WithdrawalEarnings
+    );
+    assert_eq!(balance.total_available, dec!(55850));
+    assert_eq!(balance.total_balance, dec!(55850));
+    assert_eq!(balance.total_earnings, dec!(0));
+    assert_eq!(
+        trust
+            .calculate_earnings(account.id, &Currency::USD)
+            .unwrap(),
+        dec!(0)
+    );
+}
+
+#[test]
+fn test_trade_dividend_interest_and_borrow_fee() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+    let trade = trust
+        .search_trades(account.id, Status::ClosedTarget)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    // A dividend is part of the trade performance and is paid to the account
+    let (transaction, balance, trade_balance) = trust
+        .create_trade_transaction(&trade, &TransactionCategory::Dividend(trade.id), dec!(100))
+        .unwrap();
+    assert_eq!(
+        transaction.category,
+        TransactionCategory::Dividend(trade.id)
+    );
+    // The account sets aside 20% of the dividend for taxes
+    assert_eq!(trade_balance.total_performance, dec!(6580));
+    assert_eq!(trade_balance.taxed, dec!(20));
+    assert_eq!(balance.taxed, dec!(20));
+    assert_eq!(balance.total_available, dec!(55950));
+    assert_eq!(balance.total_balance, dec!(56600));
+
+    // Borrow fees can only be charged to short trades
+    let result =
+        trust.create_trade_transaction(&trade, &TransactionCategory::BorrowFee(trade.id), dec!(10));
+    assert!(result.is_err());
+
+    // Interest is paid on the cash of the account
+    let (_, balance) = trust
+        .create_transaction(
+            &account,
+            &TransactionCategory::Interest,
+            dec!(25),
+            &Currency::USD,
+        )
+        .unwrap();
+    assert_eq!(balance.total_available, dec!(55975));
+    assert_eq!(balance.total_balance, dec!(56625));
+    assert_eq!(balance.taxed, dec!(25));
+
+    // The cached balances agree with the transactions
+    assert_eq!(trust.diagnose().unwrap(), vec![]);
+}
+
+#[test]
+fn test_trade_funding_includes_estimated_fees() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+    trust
+        .set_fee_schedule(
+            &account,
+            &FeeSchedule {
+                per_order: dec!(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // The same trade risks exactly 2% of the account, the fees push it over the rule.
+    trust
+        .create_trade(
+            DraftTrade {
+                account: account.clone(),
+                trading_vehicle: trade.trading_vehicle.clone(),
+                quantity: 500,
+                currency: Currency::USD,
+                category: TradeCategory::Long,
+            },
+            dec!(38),
+            dec!(40),
+            dec!(50),
+        )
+        .unwrap();
+    let new_trade = trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+
+    let error = trust.fund_trade(&new_trade).unwrap_err();
+    assert!(error.to_string().contains("including 2 in fees"));
+}
+
+#[test]
+fn test_trade_maximum_quantity_leaves_room_for_fees() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+    trust
+        .set_fee_schedule(
+            &account,
+            &FeeSchedule {
+                per_order: dec!(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // 2% of the 30000 available is 600: 300 shares risk it all, 299 leave 2 for the fees.
+    let quantity = trust
+        .calculate_maximum_quantity(account.id, dec!(40), dec!(38), None, &Currency::USD)
+        .unwrap();
+    assert_eq!(quantity, 299);
+
+    trust
+        .create_trade(
+            DraftTrade {
+                account: account.clone(),
+                trading_vehicle: trade.trading_vehicle.clone(),
+                quantity,
+                currency: Currency::USD,
+                category: TradeCategory::Long,
+            },
+            dec!(38),
+            dec!(40),
+            dec!(50),
+        )
+        .unwrap();
+    let new_trade = trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    trust.fund_trade(&new_trade).unwrap();
+}
+
 #[test]
 fn test_trade_stop_filled() {
     let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled, None);
@@ -440,6 +761,170 @@ fn test_trade_stop_filled_slippage() {
     assert_eq!(balance.taxed, dec!(0));
 }
 
+#[test]
+fn test_trade_slippage_report() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled_slippage, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let slippages = trust
+        .calculate_slippage(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(slippages.len(), 2);
+
+    // Entry filled 0.1 below the planned 40 is a favorable slippage
+    let entry = slippages.first().unwrap();
+    assert_eq!(entry.leg, SlippageLeg::Entry);
+    assert_eq!(entry.symbol, "TSLA");
+    assert_eq!(entry.amount, dec!(-50));
+    assert_eq!(entry.bps, dec!(-25));
+
+    // Stop filled at 30.2 instead of 38
+    let stop = slippages.get(1).unwrap();
+    assert_eq!(stop.leg, SlippageLeg::Stop);
+    assert_eq!(stop.amount, dec!(3900));
+    assert_eq!(stop.bps, dec!(2052.63));
+
+    let by_symbol = trust
+        .summarize_slippage(&slippages, SlippageGrouping::Symbol)
+        .unwrap();
+    assert_eq!(by_symbol.len(), 1);
+    assert_eq!(by_symbol.first().unwrap().total_amount, dec!(3850));
+    assert_eq!(by_symbol.first().unwrap().worst_bps, dec!(2052.63));
+
+    assert!(trust
+        .calculate_slippage(account.id, &Currency::EUR)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_trade_open_risk() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_entry_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    // The entry filled at 39.9 with the stop at 38 for 500 shares
+    let open_risk = trust
+        .calculate_open_risk(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(open_risk.amount, dec!(950));
+    assert_eq!(open_risk.percentage, dec!(1.9)); // Of the 50000 of capital, including the capital in trades
+
+    // 3% of 50000 allows 1500 at risk in open trades
+    trust
+        .create_rule(
+            &account,
+            &RuleName::MaxOpenRisk(3.0),
+            "description",
+            &RuleLevel::Error,
+        )
+        .unwrap();
+
+    let draft = |quantity| DraftTrade {
+        account: account.clone(),
+        trading_vehicle: trade.trading_vehicle.clone(),
+        quantity,
+        currency: Currency::USD,
+        category: TradeCategory::Long,
+    };
+
+    // A trade risking 600 more is rejected, even if it is within the risk per trade
+    trust
+        .create_trade(draft(300), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    let rejected = trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .first()
+        .unwrap()
+        .clone();
+    let error = trust.fund_trade(&rejected).unwrap_err();
+    assert!(error.to_string().contains("Max open risk exceeded"));
+
+    // A trade risking 400 more is funded
+    trust
+        .create_trade(draft(200), dec!(38), dec!(40), dec!(50))
+        .unwrap();
+    let accepted = trust
+        .search_trades(account.id, Status::New)
+        .unwrap()
+        .into_iter()
+        .find(|trade| trade.entry.quantity == 200)
+        .unwrap();
+    trust.fund_trade(&accepted).unwrap();
+
+    // The funded trade is still part of the balance, so the capital stays 50000
+    let open_risk = trust
+        .calculate_open_risk(account.id, &Currency::USD)
+        .unwrap();
+    assert_eq!(open_risk.amount, dec!(1350));
+    assert_eq!(open_risk.percentage, dec!(2.7));
+}
+
+#[test]
+fn test_trade_simulation() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_target_filled, None);
+    let mut trust = trust;
+    trust.sync_trade(&trade, &account).unwrap();
+
+    let parameters = SimulationParameters {
+        paths: 100,
+        trades: 2,
+        seed: Some(7),
+        ..Default::default()
+    };
+    let simulation = trust
+        .simulate(account.id, &Currency::USD, &parameters)
+        .unwrap();
+
+    // The only closed trade risked 1000 (500 shares from 40 to 38) and made 6500
+    assert_eq!(simulation.sample_size, 1);
+    assert_eq!(simulation.expectancy, dec!(6.5));
+    assert_eq!(simulation.starting_equity, dec!(56500));
+    assert_eq!(simulation.risk_per_trade, dec!(2));
+    assert_eq!(simulation.risk_per_month, Some(dec!(6)));
+
+    // Every path wins 13% of the equity in both trades
+    let median = simulation.percentiles.get(2).unwrap();
+    assert_eq!(median.percentile, 50);
+    assert_eq!(median.equity, dec!(72144.85));
+    assert_eq!(simulation.ruin_probability, dec!(0));
+    assert_eq!(simulation.drawdown_probability, dec!(0));
+
+    assert!(trust
+        .simulate(account.id, &Currency::EUR, &parameters)
+        .is_err());
+}
+
+#[test]
+fn test_trade_doctor_repairs_account_balance() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_stop_filled_slippage, None);
+    let mut trust = trust;
+
+    // The account balance is not recalculated when the trade is submitted
+    let inconsistencies = trust.diagnose().unwrap();
+    assert_eq!(inconsistencies.len(), 1);
+    let inconsistency = inconsistencies.first().unwrap();
+    assert_eq!(
+        inconsistency.category,
+        InconsistencyCategory::AccountBalance
+    );
+    assert_eq!(inconsistency.entity_id, account.id);
+    assert!(inconsistency
+        .message
+        .contains("total_in_trade is 20000, expected 0"));
+
+    assert_eq!(trust.repair(&inconsistencies).unwrap(), 1);
+    assert_eq!(trust.diagnose().unwrap(), vec![]);
+
+    // A trade closed with slippage is consistent
+    trust.sync_trade(&trade, &account).unwrap();
+    let inconsistencies = trust.diagnose().unwrap();
+    assert_eq!(inconsistencies, vec![]);
+    assert_eq!(trust.repair(&inconsistencies).unwrap(), 0);
+}
+
 #[test]
 fn test_trade_close() {
     let (trust, account, trade) = create_trade(
@@ -571,12 +1056,67 @@ fn test_trade_modify_target() {
     );
 }
 
+#[test]
+fn test_trade_modify_target_that_is_not_placed() {
+    let (trust, account, trade) =
+        create_trade(BrokerResponse::orders_entry_filled_target_not_placed, None);
+    let mut trust = trust;
+
+    // 1. Sync trade with the Broker - Entry is filled and the target is not placed
+    trust
+        .sync_trade(&trade, &account)
+        .expect("Failed to sync trade with broker when entry is filled");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status filled")
+        .first()
+        .unwrap()
+        .clone();
+    assert_eq!(trade.target.broker_order_id, None);
+
+    // 2. Modify the target without calling the broker
+    trust
+        .modify_target(&trade, &account, dec!(100.1))
+        .expect("Failed to modify target");
+
+    let trade = trust
+        .search_trades(account.id, Status::Filled)
+        .expect("Failed to find trade with status filled")
+        .first()
+        .unwrap()
+        .clone();
+
+    assert_eq!(trade.target.unit_price, dec!(100.1));
+    assert_eq!(trade.target.broker_order_id, None);
+}
+
+#[test]
+fn test_trade_modify_entry_not_supported_by_the_broker() {
+    let (trust, account, trade) = create_trade(BrokerResponse::orders_accepted, None);
+    let mut trust = trust;
+
+    // The broker does not declare that it can modify the entry
+    let error = trust
+        .modify_entry(
+            &trade,
+            &account,
+            trade.entry.unit_price,
+            trade.entry.quantity,
+        )
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("The broker does not support modifying the entry"));
+}
+
 struct BrokerResponse;
 
 impl BrokerResponse {
     fn orders_accepted(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
+            unit_price: trade.entry.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -589,6 +1129,7 @@ impl BrokerResponse {
 
         let target = Order {
             id: trade.target.id,
+            unit_price: trade.target.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -601,6 +1142,7 @@ impl BrokerResponse {
 
         let stop = Order {
             id: trade.safety_stop.id,
+            unit_price: trade.safety_stop.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -614,9 +1156,20 @@ impl BrokerResponse {
         (Status::Submitted, vec![entry, target, stop])
     }
 
+    fn orders_stop_replaced(trade: &Trade) -> (Status, Vec<Order>) {
+        let (status, mut orders) = BrokerResponse::orders_accepted(trade);
+        if let Some(stop) = orders.get_mut(2) {
+            stop.broker_order_id =
+                Some(Uuid::parse_str("3c7b3d6e-2f0a-4a8e-9d55-6f1c2b7a9e10").unwrap());
+            stop.unit_price = dec!(39);
+        }
+        (status, orders)
+    }
+
     fn orders_entry_filled(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
+            unit_price: trade.entry.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(39.9)),
@@ -629,6 +1182,7 @@ impl BrokerResponse {
 
         let target = Order {
             id: trade.target.id,
+            unit_price: trade.target.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -641,6 +1195,7 @@ impl BrokerResponse {
 
         let stop = Order {
             id: trade.safety_stop.id,
+            unit_price: trade.safety_stop.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -654,9 +1209,45 @@ impl BrokerResponse {
         (Status::Filled, vec![entry, target, stop])
     }
 
+    fn orders_entry_filled_target_not_placed(trade: &Trade) -> (Status, Vec<Order>) {
+        let (status, mut orders) = BrokerResponse::orders_entry_filled(trade);
+        for order in orders
+            .iter_mut()
+            .filter(|order| order.id == trade.target.id)
+        {
+            order.broker_order_id = None;
+        }
+        (status, orders)
+    }
+
+    fn orders_entry_partially_filled(trade: &Trade) -> (Status, Vec<Order>) {
+        let entry = Order {
+            id: trade.entry.id,
+            unit_price: trade.entry.unit_price,
+            broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
+            filled_quantity: 200,
+            average_filled_price: Some(dec!(39.9)),
+            status: OrderStatus::PartiallyFilled,
+            ..Default::default()
+        };
+        if trade.status == Status::Submitted {
+            return (Status::PartiallyFilled, vec![entry]);
+        }
+
+        let entry = Order {
+            filled_quantity: 300,
+            average_filled_price: Some(dec!(39.8)),
+            status: OrderStatus::Expired,
+            expired_at: Some(Utc::now().naive_utc()),
+            ..entry
+        };
+        (Status::Filled, vec![entry])
+    }
+
     fn orders_target_filled(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
+            unit_price: trade.entry.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(39.9)),
@@ -669,6 +1260,7 @@ impl BrokerResponse {
 
         let target = Order {
             id: trade.target.id,
+            unit_price: trade.target.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(52.9)),
@@ -681,6 +1273,7 @@ impl BrokerResponse {
 
         let stop = Order {
             id: trade.safety_stop.id,
+            unit_price: trade.safety_stop.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -697,6 +1290,7 @@ impl BrokerResponse {
     fn orders_stop_filled(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
+            unit_price: trade.entry.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(39.9)),
@@ -709,6 +1303,7 @@ impl BrokerResponse {
 
         let target = Order {
             id: trade.target.id,
+            unit_price: trade.target.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 0,
             average_filled_price: None,
@@ -721,6 +1316,7 @@ impl BrokerResponse {
 
         let stop = Order {
             id: trade.safety_stop.id,
+            unit_price: trade.safety_stop.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(39)),
@@ -737,6 +1333,7 @@ impl BrokerResponse {
     fn orders_stop_filled_slippage(trade: &Trade) -> (Status, Vec<Order>) {
         let entry = Order {
             id: trade.entry.id,
+            unit_price: trade.entry.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(39.9)),
@@ -749,6 +1346,7 @@ impl BrokerResponse {
 
         let stop = Order {
             id: trade.safety_stop.id,
+            unit_price: trade.safety_stop.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             filled_quantity: 500,
             average_filled_price: Some(dec!(30.2)),
@@ -765,6 +1363,7 @@ impl BrokerResponse {
     fn closed_order(trade: &Trade) -> Option<Order> {
         Some(Order {
             id: trade.target.id,
+            unit_price: trade.target.unit_price,
             broker_order_id: Some(Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap()),
             status: OrderStatus::PendingNew,
             category: OrderCategory::Market,
@@ -802,8 +1401,8 @@ impl Broker for MockBroker {
         let log = BrokerLog::default();
         let ids = OrderIds {
             entry: Uuid::parse_str("b6b12dc0-8e21-4d2e-8315-907d3116a6b8").unwrap(),
-            target: Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap(),
-            stop: Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap(),
+            target: Some(Uuid::parse_str("90e41b1e-9089-444d-9f68-c204a4d32914").unwrap()),
+            stop: Some(Uuid::parse_str("8654f70e-3b42-4014-a9ac-5a7101989aad").unwrap()),
         };
         Ok((log, ids))
     }
@@ -828,8 +1427,12 @@ impl Broker for MockBroker {
         Ok((order, log))
     }
 
-    fn cancel_trade(&self, _trade: &Trade, _account: &Account) -> Result<(), Box<dyn Error>> {
-        Ok(())
+    fn cancel_trade(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+    ) -> Result<BrokerLog, Box<dyn Error>> {
+        Ok(BrokerLog::default())
     }
 
     fn modify_stop(
@@ -837,12 +1440,13 @@ impl Broker for MockBroker {
         trade: &Trade,
         account: &Account,
         new_stop_price: Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
         assert_eq!(trade.account_id, account.id);
         assert_eq!(trade.safety_stop.unit_price, dec!(38));
         assert_eq!(new_stop_price, dec!(39));
 
-        Ok(Uuid::parse_str("7654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
+        let id = Uuid::parse_str("7654f70e-3b42-4014-a9ac-5a7101989aad").unwrap();
+        Ok((id, BrokerLog::default()))
     }
 
     fn modify_target(
@@ -850,12 +1454,13 @@ impl Broker for MockBroker {
         trade: &Trade,
         account: &Account,
         new_target_price: rust_decimal::Decimal,
-    ) -> Result<Uuid, Box<dyn Error>> {
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
         assert_eq!(trade.account_id, account.id);
         assert_eq!(trade.target.unit_price, dec!(50));
         assert_eq!(new_target_price, dec!(100.1));
 
-        Ok(Uuid::parse_str("5654f70e-3b42-4014-a9ac-5a7101989aad").unwrap())
+        let id = Uuid::parse_str("5654f70e-3b42-4014-a9ac-5a7101989aad").unwrap();
+        Ok((id, BrokerLog::default()))
     }
 }
 
@@ -993,6 +1598,7 @@ fn test_short_trade_funding_with_better_entry_execution() {
 fn orders_short_trade_filled(trade: &Trade) -> (Status, Vec<Order>) {
     let entry = Order {
         id: trade.entry.id,
+        unit_price: trade.entry.unit_price,
         broker_order_id: Some(Uuid::new_v4()),
         filled_quantity: 6,
         average_filled_price: Some(dec!(11)), // Better than expected $10
@@ -1003,6 +1609,7 @@ fn orders_short_trade_filled(trade: &Trade) -> (Status, Vec<Order>) {
 
     let target = Order {
         id: trade.target.id,
+        unit_price: trade.target.unit_price,
         broker_order_id: Some(Uuid::new_v4()),
         status: OrderStatus::Accepted,
         ..Default::default()
@@ -1010,6 +1617,7 @@ fn orders_short_trade_filled(trade: &Trade) -> (Status, Vec<Order>) {
 
     let stop = Order {
         id: trade.safety_stop.id,
+        unit_price: trade.safety_stop.unit_price,
         broker_order_id: Some(Uuid::new_v4()),
         status: OrderStatus::Held,
         ..Default::default()
@@ -0,0 +1,7 @@
+mod bar_broker;
+mod engine;
+mod plans;
+
+pub use bar_broker::BarBroker;
+pub use engine::Backtest;
+pub use plans::parse;
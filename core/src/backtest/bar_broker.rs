@@ -0,0 +1,593 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use model::{
+    Account, Broker, BrokerCapabilities, BrokerLog, Order, OrderCategory, OrderIds, OrderStatus,
+    PriceBar, Status, Trade, TradeCategory,
+};
+use rust_decimal::Decimal;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::rc::Rc;
+use uuid::Uuid;
+
+/// Broker that fills the orders of a backtest with daily bars.
+///
+/// The order of the prices inside a bar is unknown, so the fills are pessimistic:
+/// - Entries and targets are limit orders, filled at their price or at a better open.
+/// - Stops are filled at their price or at a worse open.
+/// - When a bar reaches the stop and the target, the stop is filled.
+/// - The bar that fills an entry can only fill the stop of the trade.
+///
+/// Clones share the same bars and orders, so the backtest can move the day forward.
+#[derive(Debug, Clone, Default)]
+pub struct BarBroker {
+    book: Rc<RefCell<Book>>,
+}
+
+#[derive(Debug, Default)]
+struct Book {
+    bars: HashMap<String, BTreeMap<NaiveDate, PriceBar>>,
+    date: Option<NaiveDate>,
+    trades: HashMap<Uuid, Legs>,
+}
+
+#[derive(Debug)]
+struct Legs {
+    entry: Leg,
+    stop: Leg,
+    target: Leg,
+}
+
+#[derive(Debug, Clone)]
+struct Leg {
+    broker_order_id: Uuid,
+    price: Decimal,
+    category: Option<OrderCategory>,
+    status: OrderStatus,
+    filled_price: Option<Decimal>,
+    filled_at: Option<NaiveDateTime>,
+    cancelled_at: Option<NaiveDateTime>,
+}
+
+impl Leg {
+    fn new(price: Decimal, status: OrderStatus) -> Leg {
+        Leg {
+            broker_order_id: Uuid::new_v4(),
+            price,
+            category: None,
+            status,
+            filled_price: None,
+            filled_at: None,
+            cancelled_at: None,
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::New | OrderStatus::Held)
+    }
+
+    fn fill(&mut self, price: Decimal, at: NaiveDateTime) {
+        self.status = OrderStatus::Filled;
+        self.filled_price = Some(price);
+        self.filled_at = Some(at);
+    }
+
+    fn cancel(&mut self, at: NaiveDateTime) {
+        if self.is_open() {
+            self.status = OrderStatus::Canceled;
+            self.cancelled_at = Some(at);
+        }
+    }
+
+    fn map(&self, order: &Order) -> Order {
+        let mut order = order.clone();
+        order.broker_order_id = Some(self.broker_order_id);
+        order.category = self.category.unwrap_or(order.category);
+        order.status = self.status;
+        order.filled_quantity = match self.status {
+            OrderStatus::Filled => order.quantity,
+            _ => 0,
+        };
+        order.average_filled_price = self.filled_price;
+        order.filled_at = self.filled_at;
+        order.cancelled_at = self.cancelled_at;
+        order
+    }
+}
+
+impl BarBroker {
+    pub fn new(bars: Vec<PriceBar>) -> BarBroker {
+        let mut book = Book::default();
+        for bar in bars {
+            book.bars
+                .entry(bar.symbol.to_uppercase())
+                .or_default()
+                .insert(bar.date, bar);
+        }
+        BarBroker {
+            book: Rc::new(RefCell::new(book)),
+        }
+    }
+
+    /// Days with a bar of any symbol, the oldest first.
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        let mut dates: Vec<NaiveDate> = self
+            .book
+            .borrow()
+            .bars
+            .values()
+            .flat_map(|bars| bars.keys().copied())
+            .collect();
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// Moves the backtest to the bars of a day.
+    pub fn set_date(&self, date: NaiveDate) {
+        self.book.borrow_mut().date = Some(date);
+    }
+
+    /// True when the symbol has a bar on the current day.
+    pub fn has_bar(&self, symbol: &str) -> bool {
+        self.book.borrow().bar(symbol).is_some()
+    }
+}
+
+impl Book {
+    fn date(&self) -> Result<NaiveDate, Box<dyn Error>> {
+        self.date
+            .ok_or_else(|| "The backtest has not started yet".into())
+    }
+
+    fn bar(&self, symbol: &str) -> Option<&PriceBar> {
+        let date = self.date?;
+        self.bars.get(&symbol.to_uppercase())?.get(&date)
+    }
+
+    /// The close of the current bar, or of the last bar before it.
+    fn last_close(&self, symbol: &str) -> Option<Decimal> {
+        let date = self.date?;
+        self.bars
+            .get(&symbol.to_uppercase())?
+            .range(..=date)
+            .next_back()
+            .map(|(_, bar)| bar.close)
+    }
+
+    fn legs(&mut self, trade: &Trade) -> Result<&mut Legs, Box<dyn Error>> {
+        self.trades
+            .get_mut(&trade.id)
+            .ok_or_else(|| format!("Trade {} was not submitted to the backtest", trade.id).into())
+    }
+}
+
+impl Legs {
+    fn orders(&self, trade: &Trade) -> Vec<Order> {
+        vec![
+            self.entry.map(&trade.entry),
+            self.stop.map(&trade.safety_stop),
+            self.target.map(&trade.target),
+        ]
+    }
+
+    fn status(&self, trade: &Trade) -> Status {
+        if self.stop.status == OrderStatus::Filled {
+            Status::ClosedStopLoss
+        } else if self.target.status == OrderStatus::Filled {
+            Status::ClosedTarget
+        } else if self.entry.status == OrderStatus::Filled {
+            Status::Filled
+        } else {
+            trade.status
+        }
+    }
+
+    fn fill(&mut self, category: TradeCategory, bar: &PriceBar, at: NaiveDateTime) {
+        let long = category == TradeCategory::Long;
+
+        if self.entry.is_open() {
+            let reached = if long {
+                bar.low <= self.entry.price
+            } else {
+                bar.high >= self.entry.price
+            };
+            if reached {
+                let filled = better(long, bar.open, self.entry.price);
+                self.entry.fill(filled, at);
+                self.stop.status = OrderStatus::New;
+                self.target.status = OrderStatus::New;
+                // The stop is reached after the entry, so the entry takes the place of the open
+                if stopped(long, bar, self.stop.price) {
+                    self.close_at_stop(worse(!long, filled, self.stop.price), at);
+                }
+            }
+            return;
+        }
+
+        if !self.stop.is_open() || !self.target.is_open() {
+            return;
+        }
+        if stopped(long, bar, self.stop.price) {
+            let price = worse(!long, bar.open, self.stop.price);
+            self.close_at_stop(price, at);
+        } else if reached_target(long, bar, self.target.price) {
+            self.target
+                .fill(better(!long, bar.open, self.target.price), at);
+            self.stop.cancel(at);
+        }
+    }
+
+    fn close_at_stop(&mut self, price: Decimal, at: NaiveDateTime) {
+        self.stop.fill(price, at);
+        self.target.cancel(at);
+    }
+}
+
+/// Limit orders that gap are filled at the open, which is better than their price.
+fn better(buys: bool, open: Decimal, price: Decimal) -> Decimal {
+    if buys {
+        open.min(price)
+    } else {
+        open.max(price)
+    }
+}
+
+/// Stop orders that gap are filled at the open, which is worse than their price.
+fn worse(buys: bool, open: Decimal, price: Decimal) -> Decimal {
+    if buys {
+        open.max(price)
+    } else {
+        open.min(price)
+    }
+}
+
+fn stopped(long: bool, bar: &PriceBar, stop: Decimal) -> bool {
+    if long {
+        bar.low <= stop
+    } else {
+        bar.high >= stop
+    }
+}
+
+fn reached_target(long: bool, bar: &PriceBar, target: Decimal) -> bool {
+    if long {
+        bar.high >= target
+    } else {
+        bar.low <= target
+    }
+}
+
+fn end_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_opt(16, 0, 0).unwrap_or(NaiveTime::MIN))
+}
+
+fn log(trade: &Trade, message: String) -> BrokerLog {
+    BrokerLog {
+        trade_id: trade.id,
+        response: message,
+        ..Default::default()
+    }
+}
+
+impl Broker for BarBroker {
+    fn submit_trade(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let date = book.date()?;
+        let legs = Legs {
+            entry: Leg::new(trade.entry.unit_price, OrderStatus::New),
+            stop: Leg::new(trade.safety_stop.unit_price, OrderStatus::Held),
+            target: Leg::new(trade.target.unit_price, OrderStatus::Held),
+        };
+        let ids = OrderIds {
+            stop: Some(legs.stop.broker_order_id),
+            entry: legs.entry.broker_order_id,
+            target: Some(legs.target.broker_order_id),
+        };
+        book.trades.insert(trade.id, legs);
+
+        let message = format!(
+            "Backtest: submitted {} {} on {date}",
+            trade.category, trade.trading_vehicle.symbol
+        );
+        Ok((log(trade, message), ids))
+    }
+
+    fn sync_trade(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let date = book.date()?;
+        let bar = book.bar(&trade.trading_vehicle.symbol).cloned();
+        let legs = book.legs(trade)?;
+
+        let message = match bar {
+            Some(bar) => {
+                legs.fill(trade.category, &bar, end_of_day(date));
+                format!(
+                    "Backtest: bar of {date} open {} high {} low {} close {}",
+                    bar.open, bar.high, bar.low, bar.close
+                )
+            }
+            None => format!("Backtest: no bar of {date}"),
+        };
+        Ok((legs.status(trade), legs.orders(trade), log(trade, message)))
+    }
+
+    fn close_trade(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let date = book.date()?;
+        let close = book
+            .last_close(&trade.trading_vehicle.symbol)
+            .ok_or_else(|| format!("No price of {} to close at", trade.trading_vehicle.symbol))?;
+        let legs = book.legs(trade)?;
+
+        let at = end_of_day(date);
+        legs.stop.cancel(at);
+        legs.target.cancel(at);
+        let mut market = Leg::new(close, OrderStatus::New);
+        market.category = Some(OrderCategory::Market);
+        market.fill(close, at);
+        legs.target = market;
+
+        let message = format!("Backtest: closed at the close of {date}, {close}");
+        Ok((legs.target.map(&trade.target), log(trade, message)))
+    }
+
+    fn cancel_trade(&self, trade: &Trade, _account: &Account) -> Result<BrokerLog, Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let date = book.date()?;
+        let at = end_of_day(date);
+        let legs = book.legs(trade)?;
+        legs.entry.cancel(at);
+        legs.stop.cancel(at);
+        legs.target.cancel(at);
+        Ok(log(trade, format!("Backtest: canceled on {date}")))
+    }
+
+    fn modify_stop(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+        new_stop_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let stop = &mut book.legs(trade)?.stop;
+        stop.price = new_stop_price;
+        let message = format!("Backtest: stop moved to {new_stop_price}");
+        Ok((stop.broker_order_id, log(trade, message)))
+    }
+
+    fn modify_target(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+        new_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let target = &mut book.legs(trade)?.target;
+        target.price = new_price;
+        let message = format!("Backtest: target moved to {new_price}");
+        Ok((target.broker_order_id, log(trade, message)))
+    }
+
+    /// The quantity of the legs is read from the orders of the trade on every sync.
+    fn modify_entry(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        let mut book = self.book.borrow_mut();
+        let entry = &mut book.legs(trade)?.entry;
+        entry.price = new_price;
+        let message = format!("Backtest: entry moved to {new_quantity} at {new_price}");
+        Ok((entry.broker_order_id, log(trade, message)))
+    }
+
+    /// Any symbol with imported prices can be backtested.
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities {
+            crypto: true,
+            modify_entry: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::TradingVehicle;
+    use rust_decimal_macros::dec;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn bar(date: u32, open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> PriceBar {
+        PriceBar {
+            symbol: "AAPL".to_string(),
+            date: day(date),
+            open,
+            high,
+            low,
+            close,
+            ..Default::default()
+        }
+    }
+
+    fn trade(category: TradeCategory, entry: Decimal, stop: Decimal, target: Decimal) -> Trade {
+        let order = |price: Decimal| Order {
+            id: Uuid::new_v4(),
+            unit_price: price,
+            quantity: 10,
+            ..Default::default()
+        };
+        Trade {
+            category,
+            status: Status::Submitted,
+            trading_vehicle: TradingVehicle {
+                symbol: "AAPL".to_string(),
+                ..Default::default()
+            },
+            entry: order(entry),
+            safety_stop: order(stop),
+            target: order(target),
+            ..Default::default()
+        }
+    }
+
+    /// Submits the trade on the first day and syncs it with the bars of the next days.
+    fn run(broker: &BarBroker, trade: &Trade) -> (Status, Vec<Order>) {
+        let account = Account::default();
+        let mut dates = broker.dates().into_iter();
+        broker.set_date(dates.next().unwrap());
+        broker.submit_trade(trade, &account).unwrap();
+
+        let mut result = (trade.status, Vec::new());
+        for date in dates {
+            broker.set_date(date);
+            let (status, orders, _) = broker.sync_trade(trade, &account).unwrap();
+            result = (status, orders);
+        }
+        result
+    }
+
+    fn filled_price(orders: &[Order], index: usize) -> Option<Decimal> {
+        orders.get(index).unwrap().average_filled_price
+    }
+
+    #[test]
+    fn test_entry_fills_at_limit_or_better_open() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(97), dec!(99), dec!(96), dec!(98)),
+        ]);
+        let trade = trade(TradeCategory::Long, dec!(98), dec!(90), dec!(110));
+
+        let (status, orders) = run(&broker, &trade);
+
+        assert_eq!(status, Status::Filled);
+        assert_eq!(filled_price(&orders, 0), Some(dec!(97)));
+        assert_eq!(orders.get(1).unwrap().status, OrderStatus::New);
+        assert_eq!(orders.get(2).unwrap().status, OrderStatus::New);
+    }
+
+    #[test]
+    fn test_entry_not_reached_stays_open() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(102), dec!(99), dec!(101)),
+        ]);
+        let trade = trade(TradeCategory::Long, dec!(98), dec!(90), dec!(110));
+
+        let (status, orders) = run(&broker, &trade);
+
+        assert_eq!(status, Status::Submitted);
+        assert_eq!(orders.first().unwrap().status, OrderStatus::New);
+        assert_eq!(orders.get(1).unwrap().status, OrderStatus::Held);
+    }
+
+    #[test]
+    fn test_entry_bar_only_fills_the_stop() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(120), dec!(99), dec!(115)),
+        ]);
+        let trade = trade(TradeCategory::Long, dec!(100), dec!(95), dec!(110));
+
+        let (status, _) = run(&broker, &trade);
+        assert_eq!(status, Status::Filled);
+
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(120), dec!(90), dec!(115)),
+        ]);
+        let (status, orders) = run(&broker, &trade);
+        assert_eq!(status, Status::ClosedStopLoss);
+        assert_eq!(filled_price(&orders, 1), Some(dec!(95)));
+        assert_eq!(orders.get(2).unwrap().status, OrderStatus::Canceled);
+    }
+
+    #[test]
+    fn test_bar_with_stop_and_target_fills_the_stop() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(4, dec!(100), dec!(120), dec!(90), dec!(100)),
+        ]);
+        let trade = trade(TradeCategory::Long, dec!(100), dec!(95), dec!(110));
+
+        let (status, orders) = run(&broker, &trade);
+
+        assert_eq!(status, Status::ClosedStopLoss);
+        assert_eq!(filled_price(&orders, 1), Some(dec!(95)));
+    }
+
+    #[test]
+    fn test_gaps_fill_at_the_open() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(4, dec!(92), dec!(93), dec!(91), dec!(92)),
+        ]);
+        let long = trade(TradeCategory::Long, dec!(100), dec!(95), dec!(110));
+        let (status, orders) = run(&broker, &long);
+        assert_eq!(status, Status::ClosedStopLoss);
+        assert_eq!(filled_price(&orders, 1), Some(dec!(92)));
+
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(4, dec!(112), dec!(115), dec!(111), dec!(114)),
+        ]);
+        let (status, orders) = run(&broker, &long);
+        assert_eq!(status, Status::ClosedTarget);
+        assert_eq!(filled_price(&orders, 2), Some(dec!(112)));
+        assert_eq!(orders.get(1).unwrap().status, OrderStatus::Canceled);
+    }
+
+    #[test]
+    fn test_short_trade() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(101), dec!(103), dec!(100), dec!(102)),
+            bar(4, dec!(95), dec!(96), dec!(88), dec!(89)),
+        ]);
+        let short = trade(TradeCategory::Short, dec!(102), dec!(106), dec!(90));
+
+        let (status, orders) = run(&broker, &short);
+
+        assert_eq!(status, Status::ClosedTarget);
+        assert_eq!(filled_price(&orders, 0), Some(dec!(102)));
+        assert_eq!(filled_price(&orders, 2), Some(dec!(90)));
+    }
+
+    #[test]
+    fn test_close_at_the_last_close() {
+        let broker = BarBroker::new(vec![
+            bar(2, dec!(100), dec!(101), dec!(99), dec!(100)),
+            bar(3, dec!(100), dec!(101), dec!(99), dec!(104)),
+        ]);
+        let trade = trade(TradeCategory::Long, dec!(100), dec!(95), dec!(110));
+        run(&broker, &trade);
+
+        let (order, _) = broker.close_trade(&trade, &Account::default()).unwrap();
+
+        assert_eq!(order.category, OrderCategory::Market);
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.average_filled_price, Some(dec!(104)));
+    }
+}
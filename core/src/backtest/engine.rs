@@ -0,0 +1,295 @@
+use crate::backtest::BarBroker;
+use crate::TrustFacade;
+use chrono::{NaiveDate, Utc};
+use model::{
+    Account, BacktestParameters, BacktestReport, DatabaseFactory, DraftTrade, Environment,
+    SkippedPlan, Status, Trade, TradePlan, TradingVehicle, TradingVehicleCategory,
+    TransactionCategory,
+};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Runs trade plans through a throwaway account that copies the rules, the
+/// position sizing and the fee schedule of a live account.
+///
+/// Every plan is sized, created, funded and submitted with the same commands as
+/// a live trade, after the close of its signal date. The bars of the following
+/// days fill the orders, and the trades are synced like with a real broker.
+pub struct Backtest {
+    trust: TrustFacade,
+    broker: BarBroker,
+    account: Account,
+    parameters: BacktestParameters,
+    vehicles: HashMap<String, TradingVehicle>,
+    skipped: Vec<SkippedPlan>,
+}
+
+impl std::fmt::Debug for Backtest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backtest")
+            .field("account", &self.account)
+            .field("parameters", &self.parameters)
+            .finish()
+    }
+}
+
+impl Backtest {
+    /// Runs the plans against the price bars of their symbols in `source`.
+    /// The backtest account and its trades are written to `database`, which is
+    /// returned inside a facade so the same reports as a live account can be built.
+    pub fn run(
+        account: &Account,
+        plans: &[TradePlan],
+        parameters: &BacktestParameters,
+        source: &mut dyn DatabaseFactory,
+        database: Box<dyn DatabaseFactory>,
+    ) -> Result<(BacktestReport, TrustFacade), Box<dyn Error>> {
+        let first_signal = plans
+            .iter()
+            .map(|plan| plan.signal_date)
+            .min()
+            .ok_or("There are no trade plans to backtest")?;
+        if parameters.starting_capital <= Decimal::ZERO {
+            return Err(format!(
+                "The starting capital must be positive, it is {}",
+                parameters.starting_capital
+            )
+            .into());
+        }
+
+        let broker = BarBroker::new(read_bars(plans, first_signal, source)?);
+        let mut trust = TrustFacade::new(database, Box::new(broker.clone()));
+        let backtest_account = copy_account(account, parameters, source, &mut trust)?;
+
+        let mut backtest = Backtest {
+            trust,
+            broker,
+            account: backtest_account,
+            parameters: *parameters,
+            vehicles: HashMap::new(),
+            skipped: Vec::new(),
+        };
+        backtest.create_vehicles(plans, source)?;
+
+        let dates: Vec<NaiveDate> = backtest
+            .broker
+            .dates()
+            .into_iter()
+            .filter(|date| *date > first_signal)
+            .collect();
+        let mut pending: Vec<&TradePlan> = plans.iter().collect();
+        pending.sort_by_key(|plan| plan.signal_date);
+
+        for date in &dates {
+            backtest.broker.set_date(*date);
+            let signaled = pending
+                .iter()
+                .take_while(|plan| plan.signal_date < *date)
+                .count();
+            for plan in pending.drain(..signaled) {
+                backtest.place(plan);
+            }
+            backtest.sync()?;
+        }
+
+        backtest.finish(pending, &dates)
+    }
+
+    fn create_vehicles(
+        &mut self,
+        plans: &[TradePlan],
+        source: &mut dyn DatabaseFactory,
+    ) -> Result<(), Box<dyn Error>> {
+        let live = source.trading_vehicle_read().read_all_trading_vehicles()?;
+        for plan in plans {
+            if self.vehicles.contains_key(&plan.symbol) {
+                continue;
+            }
+            let vehicle = match live.iter().find(|vehicle| vehicle.symbol == plan.symbol) {
+                Some(vehicle) => self.trust.create_trading_vehicle(
+                    &vehicle.symbol,
+                    &vehicle.isin,
+                    &vehicle.category,
+                    &vehicle.broker,
+                )?,
+                None => self.trust.create_trading_vehicle(
+                    &plan.symbol,
+                    &plan.symbol,
+                    &TradingVehicleCategory::Stock,
+                    "backtest",
+                )?,
+            };
+            self.vehicles.insert(plan.symbol.clone(), vehicle);
+        }
+        Ok(())
+    }
+
+    /// Places a plan like a live trade. A plan that the sizing or the rules reject is skipped.
+    fn place(&mut self, plan: &TradePlan) {
+        if let Err(error) = self.try_place(plan) {
+            self.skip(plan, error.to_string());
+        }
+    }
+
+    fn try_place(&mut self, plan: &TradePlan) -> Result<(), Box<dyn Error>> {
+        let quantity = self.trust.calculate_maximum_quantity(
+            self.account.id,
+            plan.entry,
+            plan.stop,
+            plan.atr,
+            &self.parameters.currency,
+        )?;
+        if quantity <= 0 {
+            return Err(
+                "The position sizing and the rules of the account allow no quantity".into(),
+            );
+        }
+
+        let trading_vehicle = self
+            .vehicles
+            .get(&plan.symbol)
+            .cloned()
+            .ok_or_else(|| format!("There is no trading vehicle for {}", plan.symbol))?;
+        let draft = DraftTrade {
+            account: self.account.clone(),
+            trading_vehicle,
+            quantity,
+            currency: self.parameters.currency,
+            category: plan.category,
+        };
+        let trade = self
+            .trust
+            .create_trade(draft, plan.stop, plan.entry, plan.target)?;
+        self.trust.fund_trade(&trade)?;
+        let funded = self
+            .trust
+            .search_trades(self.account.id, Status::Funded)?
+            .into_iter()
+            .find(|funded| funded.id == trade.id)
+            .ok_or_else(|| format!("Trade {} was not funded", trade.id))?;
+        self.trust.submit_trade(&funded)?;
+        Ok(())
+    }
+
+    /// Syncs the open trades that have a bar on the current day.
+    /// The trades are read before syncing, so a bar is never applied twice to a trade.
+    fn sync(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut trades = self
+            .trust
+            .search_trades(self.account.id, Status::Submitted)?;
+        trades.extend(self.trust.search_trades(self.account.id, Status::Filled)?);
+        for trade in trades {
+            if self.broker.has_bar(&trade.trading_vehicle.symbol) {
+                self.trust.sync_trade(&trade, &self.account)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Cancels the entries that were not filled by the last bar and builds the report.
+    /// Filled trades stay open.
+    fn finish(
+        mut self,
+        pending: Vec<&TradePlan>,
+        dates: &[NaiveDate],
+    ) -> Result<(BacktestReport, TrustFacade), Box<dyn Error>> {
+        for plan in pending {
+            self.skip(plan, "No prices after the signal date".to_string());
+        }
+        for trade in self
+            .trust
+            .search_trades(self.account.id, Status::Submitted)?
+        {
+            self.trust.cancel_submitted_trade(&trade)?;
+        }
+
+        let mut trades: Vec<Trade> = Vec::new();
+        for status in Status::all()
+            .into_iter()
+            .filter(|status| *status != Status::New)
+        {
+            trades.extend(self.trust.search_trades(self.account.id, status)?);
+        }
+        trades.sort_by_key(|trade| trade.created_at);
+
+        let balance = self
+            .trust
+            .search_balance(self.account.id, &self.parameters.currency)?;
+        let today = Utc::now().date_naive();
+        let report = BacktestReport {
+            account: self.account,
+            balance,
+            trades,
+            skipped: self.skipped,
+            from: dates.first().copied().unwrap_or(today),
+            to: dates.last().copied().unwrap_or(today),
+        };
+        Ok((report, self.trust))
+    }
+
+    fn skip(&mut self, plan: &TradePlan, reason: String) {
+        self.skipped.push(SkippedPlan {
+            plan: plan.clone(),
+            reason,
+        });
+    }
+}
+
+fn read_bars(
+    plans: &[TradePlan],
+    from: NaiveDate,
+    source: &mut dyn DatabaseFactory,
+) -> Result<Vec<model::PriceBar>, Box<dyn Error>> {
+    let mut symbols: Vec<&str> = plans.iter().map(|plan| plan.symbol.as_str()).collect();
+    symbols.sort_unstable();
+    symbols.dedup();
+
+    let to = Utc::now().date_naive();
+    let mut bars = Vec::new();
+    for symbol in symbols {
+        let prices = source.price_read().bars(symbol, from, to)?;
+        if prices.is_empty() {
+            return Err(format!(
+                "There are no prices of {symbol} since {from}, import them with trust price import"
+            )
+            .into());
+        }
+        bars.extend(prices);
+    }
+    Ok(bars)
+}
+
+/// Creates the throwaway account with the settings of the live account and deposits the capital.
+fn copy_account(
+    account: &Account,
+    parameters: &BacktestParameters,
+    source: &mut dyn DatabaseFactory,
+    trust: &mut TrustFacade,
+) -> Result<Account, Box<dyn Error>> {
+    let copy = trust.create_account(
+        &format!("{} backtest", account.name),
+        &format!("Backtest of the trade plans of {}", account.name),
+        Environment::Paper,
+        account.taxes_percentage,
+        account.earnings_percentage,
+    )?;
+    let copy = trust.update_position_sizing(&copy, account.position_sizing)?;
+
+    if let Some(schedule) = source
+        .fee_schedule_read()
+        .fee_schedule_for_account(account.id)?
+    {
+        trust.set_fee_schedule(&copy, &schedule)?;
+    }
+    for rule in source.rule_read().read_all_rules(account.id)? {
+        trust.create_rule(&copy, &rule.name, &rule.description, &rule.level)?;
+    }
+    trust.create_transaction(
+        &copy,
+        &TransactionCategory::Deposit,
+        parameters.starting_capital,
+        &parameters.currency,
+    )?;
+    Ok(copy)
+}
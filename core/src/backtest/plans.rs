@@ -0,0 +1,136 @@
+use chrono::NaiveDate;
+use model::{TradeCategory, TradePlan};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+
+struct Columns {
+    date: usize,
+    symbol: usize,
+    category: usize,
+    entry: usize,
+    stop: usize,
+    target: usize,
+    atr: Option<usize>,
+}
+
+/// Reads the trade plans of a CSV with a header row.
+/// The columns are found by name: Date, Symbol, Category (long or short), Entry,
+/// Stop, Target and an optional ATR. Other columns are ignored.
+pub fn parse(csv: &str) -> Result<Vec<TradePlan>, Box<dyn Error>> {
+    let mut lines = csv.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines.next().ok_or("The CSV is empty")?;
+    let columns = parse_header(header)?;
+
+    (2..)
+        .zip(lines)
+        .map(|(line_number, line)| {
+            parse_line(&columns, line)
+                .map_err(|error| format!("Line {line_number} of the CSV: {error}").into())
+        })
+        .collect()
+}
+
+fn parse_header(header: &str) -> Result<Columns, Box<dyn Error>> {
+    let names: Vec<String> = header
+        .split(',')
+        .map(|name| name.trim().trim_matches('"').to_lowercase())
+        .collect();
+    let position = |name: &str| -> Result<usize, Box<dyn Error>> {
+        names
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| format!("The CSV does not have a {name} column").into())
+    };
+
+    Ok(Columns {
+        date: position("date")?,
+        symbol: position("symbol")?,
+        category: position("category")?,
+        entry: position("entry")?,
+        stop: position("stop")?,
+        target: position("target")?,
+        atr: position("atr").ok(),
+    })
+}
+
+fn parse_line(columns: &Columns, line: &str) -> Result<TradePlan, Box<dyn Error>> {
+    let values: Vec<&str> = line
+        .split(',')
+        .map(|value| value.trim().trim_matches('"'))
+        .collect();
+    let value = |column: usize| -> Result<&str, Box<dyn Error>> {
+        values
+            .get(column)
+            .copied()
+            .ok_or_else(|| "missing columns".into())
+    };
+    let decimal = |column: usize| -> Result<Decimal, Box<dyn Error>> {
+        let value = value(column)?;
+        Decimal::from_str(value).map_err(|_| format!("'{value}' is not a number").into())
+    };
+
+    let date = value(columns.date)?;
+    let category = value(columns.category)?;
+    Ok(TradePlan {
+        signal_date: NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("'{date}' is not a date like 2024-01-31"))?,
+        symbol: value(columns.symbol)?.to_uppercase(),
+        category: TradeCategory::from_str(&category.to_lowercase())
+            .map_err(|_| format!("'{category}' is not long or short"))?,
+        entry: decimal(columns.entry)?,
+        stop: decimal(columns.stop)?,
+        target: decimal(columns.target)?,
+        atr: columns
+            .atr
+            .filter(|column| matches!(values.get(*column), Some(value) if !value.is_empty()))
+            .map(decimal)
+            .transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_plans() {
+        let csv = "Date,Symbol,Category,Entry,Stop,Target,ATR,Note\n\
+                   2024-01-02,aapl,Long,185,180,200,2.5,breakout\n\
+                   2024-01-03,TSLA,short,240,250,220,,\n";
+
+        let plans = parse(csv).unwrap();
+
+        assert_eq!(plans.len(), 2);
+        let first = plans.first().unwrap();
+        assert_eq!(
+            first.signal_date,
+            NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()
+        );
+        assert_eq!(first.symbol, "AAPL");
+        assert_eq!(first.category, TradeCategory::Long);
+        assert_eq!(first.entry, dec!(185));
+        assert_eq!(first.stop, dec!(180));
+        assert_eq!(first.target, dec!(200));
+        assert_eq!(first.atr, Some(dec!(2.5)));
+        let last = plans.last().unwrap();
+        assert_eq!(last.category, TradeCategory::Short);
+        assert_eq!(last.atr, None);
+    }
+
+    #[test]
+    fn test_parse_invalid_plans() {
+        let error = parse("Date,Symbol,Entry,Stop,Target\n2024-01-02,AAPL,1,1,1")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "The CSV does not have a category column");
+
+        let csv = "Date,Symbol,Category,Entry,Stop,Target\n2024-01-02,AAPL,up,1,1,1";
+        let error = parse(csv).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Line 2 of the CSV: 'up' is not long or short"
+        );
+    }
+}
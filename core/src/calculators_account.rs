@@ -1,11 +1,23 @@
+mod benchmark;
 mod capital_available;
 mod capital_balance;
 mod capital_beginning_of_month;
+mod capital_earnings;
 mod capital_in_trades;
 mod capital_taxable;
+mod kelly;
+mod monte_carlo;
+mod open_risk;
+mod slippage;
 
+pub use benchmark::AccountBenchmark;
 pub use capital_available::AccountCapitalAvailable;
 pub use capital_balance::AccountCapitalBalance;
 pub use capital_beginning_of_month::AccountCapitalBeginningOfMonth;
+pub use capital_earnings::AccountCapitalEarnings;
 pub use capital_in_trades::AccountCapitalInApprovedTrades;
 pub use capital_taxable::AccountCapitalTaxable;
+pub use kelly::AccountKellyPercentage;
+pub use monte_carlo::AccountMonteCarlo;
+pub use open_risk::AccountOpenRisk;
+pub use slippage::AccountSlippage;
@@ -0,0 +1,371 @@
+use chrono::NaiveDate;
+use model::{
+    Account, BenchmarkComparison, Currency, EquityPoint, PriceBar, ReadPriceDB, ReadTransactionDB,
+    Transaction, TransactionCategory,
+};
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub struct AccountBenchmark;
+
+impl AccountBenchmark {
+    /// Compares the returns of an account with the benchmark of the account, from the
+    /// first transaction of the account until the given day.
+    ///
+    /// The equity curve is built from the transactions: open trades are valued at the
+    /// price they were opened, so a trade changes the equity when it is closed.
+    /// The daily return of the account excludes deposits, withdrawals and transfers:
+    ///
+    /// return = (equity - flows - previous equity) / previous equity
+    ///
+    /// beta = covariance(account, benchmark) / variance(benchmark)
+    /// correlation = covariance(account, benchmark) / (deviation(account) * deviation(benchmark))
+    pub fn calculate(
+        account: &Account,
+        currency: &Currency,
+        to: NaiveDate,
+        transactions_database: &mut dyn ReadTransactionDB,
+        prices_database: &mut dyn ReadPriceDB,
+    ) -> Result<BenchmarkComparison, Box<dyn std::error::Error>> {
+        let symbol = account.benchmark.as_deref().ok_or_else(|| {
+            format!(
+                "benchmark: the account {} does not have a benchmark",
+                account.name
+            )
+        })?;
+
+        let mut transactions = transactions_database.all_transactions(account.id, currency)?;
+        transactions.sort_by_key(|tx| tx.created_at);
+        let from = transactions
+            .first()
+            .map(|tx| tx.created_at.date())
+            .ok_or("benchmark: the account does not have transactions")?;
+
+        let bars = prices_database.bars(symbol, from, to)?;
+        AccountBenchmark::compare(symbol, currency, transactions, &bars)
+    }
+
+    fn compare(
+        symbol: &str,
+        currency: &Currency,
+        transactions: Vec<Transaction>,
+        bars: &[PriceBar],
+    ) -> Result<BenchmarkComparison, Box<dyn std::error::Error>> {
+        let (Some(first), Some(last)) = (bars.first(), bars.last()) else {
+            return Err(format!("benchmark: there are no prices of {symbol}").into());
+        };
+        if bars.len() < 2 {
+            return Err(format!("benchmark: at least two prices of {symbol} are needed").into());
+        }
+
+        let mut pending = transactions.into_iter().peekable();
+        let mut open_trades = HashMap::new();
+        let mut equity = dec!(0);
+        let mut previous: Option<(Decimal, Decimal)> = None;
+        let mut account_index = dec!(1);
+        let mut account_returns = Vec::new();
+        let mut benchmark_returns = Vec::new();
+        let mut points = Vec::new();
+
+        for bar in bars {
+            // 1. Apply the transactions of the day
+            let mut flows = dec!(0);
+            while let Some(tx) = pending.next_if(|tx| tx.created_at.date() <= bar.date) {
+                let (change, flow) = AccountBenchmark::change(&tx, &mut open_trades)?;
+                equity = add(equity, change)?;
+                flows = add(flows, flow)?;
+            }
+
+            // 2. Calculate the returns of the day
+            if let Some((previous_equity, previous_close)) = previous {
+                let account_return = if previous_equity > dec!(0) {
+                    let gain = sub(sub(equity, flows)?, previous_equity)?;
+                    div(gain, previous_equity)?
+                } else {
+                    dec!(0)
+                };
+                account_index = mul(account_index, add(dec!(1), account_return)?)?;
+                account_returns.push(account_return);
+                benchmark_returns.push(sub(div(bar.close, previous_close)?, dec!(1))?);
+            }
+
+            points.push(EquityPoint {
+                date: bar.date,
+                equity,
+                account_return: sub(account_index, dec!(1))?.round_dp(4),
+                benchmark_close: bar.close,
+                benchmark_return: sub(div(bar.close, first.close)?, dec!(1))?.round_dp(4),
+            });
+            previous = Some((equity, bar.close));
+        }
+
+        let account_return = sub(account_index, dec!(1))?;
+        let benchmark_return = sub(div(last.close, first.close)?, dec!(1))?;
+        let (beta, correlation) =
+            AccountBenchmark::statistics(&account_returns, &benchmark_returns)?;
+        let alpha = match beta {
+            Some(beta) => Some(sub(account_return, mul(beta, benchmark_return)?)?.round_dp(4)),
+            None => None,
+        };
+
+        Ok(BenchmarkComparison {
+            symbol: symbol.to_string(),
+            currency: *currency,
+            from: first.date,
+            to: last.date,
+            account_return: account_return.round_dp(4),
+            benchmark_return: benchmark_return.round_dp(4),
+            beta: beta.map(|beta| beta.round_dp(4)),
+            alpha,
+            correlation: correlation.map(|correlation| correlation.round_dp(4)),
+            points,
+        })
+    }
+
+    /// Returns how a transaction changes the equity of the account and which part of
+    /// that change is money moved in or out of the account.
+    fn change(
+        tx: &Transaction,
+        open_trades: &mut HashMap<Uuid, Decimal>,
+    ) -> Result<(Decimal, Decimal), Box<dyn std::error::Error>> {
+        let change = match tx.category {
+            TransactionCategory::Deposit | TransactionCategory::TransferIn => {
+                (tx.amount, tx.amount)
+            }
+            TransactionCategory::Withdrawal
+            | TransactionCategory::WithdrawalTax
+            | TransactionCategory::WithdrawalEarnings
+            | TransactionCategory::TransferOut => {
+                let withdrawn = sub(dec!(0), tx.amount)?;
+                (withdrawn, withdrawn)
+            }
+            TransactionCategory::OpenTrade(trade_id) => {
+                let opened = open_trades.entry(trade_id).or_insert(dec!(0));
+                *opened = add(*opened, tx.amount)?;
+                (dec!(0), dec!(0))
+            }
+            TransactionCategory::CloseTarget(trade_id)
+            | TransactionCategory::CloseSafetyStop(trade_id)
+            | TransactionCategory::CloseSafetyStopSlippage(trade_id) => {
+                let opened = open_trades.remove(&trade_id).unwrap_or_default();
+                (sub(tx.amount, opened)?, dec!(0))
+            }
+            // The money of a partial close is counted with the rest of the trade when it closes.
+            TransactionCategory::ClosePartial(trade_id) => {
+                let opened = open_trades.entry(trade_id).or_insert(dec!(0));
+                *opened = sub(*opened, tx.amount)?;
+                (dec!(0), dec!(0))
+            }
+            TransactionCategory::FeeOpen(_)
+            | TransactionCategory::FeeClose(_)
+            | TransactionCategory::BorrowFee(_) => (sub(dec!(0), tx.amount)?, dec!(0)),
+            TransactionCategory::Dividend(_) | TransactionCategory::Interest => {
+                (tx.amount, dec!(0))
+            }
+            // Money moved between the account and its trades, or set aside, is still equity.
+            _ => (dec!(0), dec!(0)),
+        };
+        Ok(change)
+    }
+
+    /// Returns the beta and the correlation of the daily returns of the account with the
+    /// daily returns of the benchmark. They are not available without variance.
+    fn statistics(
+        account: &[Decimal],
+        benchmark: &[Decimal],
+    ) -> Result<(Option<Decimal>, Option<Decimal>), Box<dyn std::error::Error>> {
+        let covariance = covariance(account, benchmark)?;
+        let account_variance = covariance_of(account)?;
+        let benchmark_variance = covariance_of(benchmark)?;
+
+        if benchmark_variance.is_zero() {
+            return Ok((None, None));
+        }
+        let beta = div(covariance, benchmark_variance)?;
+
+        if account_variance.is_zero() {
+            return Ok((Some(beta), None));
+        }
+        let deviations = mul(account_variance, benchmark_variance)?
+            .sqrt()
+            .ok_or("benchmark: failed to calculate the standard deviation")?;
+        let correlation = div(covariance, deviations)?;
+
+        Ok((Some(beta), Some(correlation)))
+    }
+}
+
+fn covariance_of(values: &[Decimal]) -> Result<Decimal, Box<dyn std::error::Error>> {
+    covariance(values, values)
+}
+
+fn covariance(a: &[Decimal], b: &[Decimal]) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let mean_a = mean(a)?;
+    let mean_b = mean(b)?;
+    let mut total = dec!(0);
+    for (value_a, value_b) in a.iter().zip(b) {
+        total = add(total, mul(sub(*value_a, mean_a)?, sub(*value_b, mean_b)?)?)?;
+    }
+    div(total, Decimal::from(a.len()))
+}
+
+fn mean(values: &[Decimal]) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let mut total = dec!(0);
+    for value in values {
+        total = add(total, *value)?;
+    }
+    div(total, Decimal::from(values.len()))
+}
+
+fn add(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_add(b)
+        .ok_or_else(|| format!("Arithmetic overflow in addition: {a} + {b}").into())
+}
+
+fn sub(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_sub(b)
+        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {a} - {b}").into())
+}
+
+fn mul(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_mul(b)
+        .ok_or_else(|| format!("Arithmetic overflow in multiplication: {a} * {b}").into())
+}
+
+fn div(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_div(b)
+        .ok_or_else(|| format!("Arithmetic overflow in division: {a} / {b}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn at(day: u32) -> NaiveDateTime {
+        date(day).and_hms_opt(15, 0, 0).unwrap()
+    }
+
+    fn transaction(day: u32, category: TransactionCategory, amount: Decimal) -> Transaction {
+        Transaction {
+            created_at: at(day),
+            category,
+            amount,
+            ..Transaction::new(Uuid::new_v4(), category, &Currency::USD, amount)
+        }
+    }
+
+    fn bars(closes: &[(u32, Decimal)]) -> Vec<PriceBar> {
+        closes
+            .iter()
+            .map(|(day, close)| PriceBar {
+                symbol: "SPY".to_string(),
+                date: date(*day),
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_without_enough_prices() {
+        let transactions = vec![transaction(1, TransactionCategory::Deposit, dec!(100))];
+        let result = AccountBenchmark::compare(
+            "SPY",
+            &Currency::USD,
+            transactions,
+            &bars(&[(1, dec!(100))]),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_compare_excludes_deposits_and_withdrawals() {
+        let trade = Uuid::new_v4();
+        let transactions = vec![
+            transaction(1, TransactionCategory::Deposit, dec!(1000)),
+            transaction(2, TransactionCategory::OpenTrade(trade), dec!(500)),
+            // A deposit is not a return
+            transaction(2, TransactionCategory::Deposit, dec!(1000)),
+            // The trade earns 100
+            transaction(3, TransactionCategory::CloseTarget(trade), dec!(600)),
+            // A withdrawal is not a loss
+            transaction(4, TransactionCategory::Withdrawal, dec!(1100)),
+        ];
+
+        let result = AccountBenchmark::compare(
+            "SPY",
+            &Currency::USD,
+            transactions,
+            &bars(&[
+                (1, dec!(100)),
+                (2, dec!(101)),
+                (3, dec!(102)),
+                (4, dec!(103)),
+            ]),
+        )
+        .unwrap();
+
+        let equities: Vec<Decimal> = result.points.iter().map(|point| point.equity).collect();
+        assert_eq!(
+            equities,
+            vec![dec!(1000), dec!(2000), dec!(2100), dec!(1000)]
+        );
+        // Only the day of the trade has a return: 100 / 2000
+        assert_eq!(result.account_return, dec!(0.05));
+        assert_eq!(result.benchmark_return, dec!(0.03));
+        assert_eq!(result.from, date(1));
+        assert_eq!(result.to, date(4));
+    }
+
+    #[test]
+    fn test_compare_with_account_following_the_benchmark() {
+        // The account doubles the daily returns of the benchmark
+        let transactions = vec![
+            transaction(1, TransactionCategory::Deposit, dec!(1000)),
+            transaction(2, TransactionCategory::Interest, dec!(20)),
+            transaction(3, TransactionCategory::FeeOpen(Uuid::new_v4()), dec!(20.4)),
+        ];
+
+        let result = AccountBenchmark::compare(
+            "SPY",
+            &Currency::USD,
+            transactions,
+            &bars(&[(1, dec!(100)), (2, dec!(101)), (3, dec!(99.99))]),
+        )
+        .unwrap();
+
+        assert_eq!(result.beta, Some(dec!(2)));
+        assert_eq!(result.correlation, Some(dec!(1)));
+        assert_eq!(result.account_return, dec!(-0.0004));
+        assert_eq!(result.benchmark_return, dec!(-0.0001));
+        assert_eq!(result.alpha, Some(dec!(-0.0002)));
+    }
+
+    #[test]
+    fn test_compare_with_flat_benchmark() {
+        let transactions = vec![transaction(1, TransactionCategory::Deposit, dec!(1000))];
+
+        let result = AccountBenchmark::compare(
+            "SPY",
+            &Currency::USD,
+            transactions,
+            &bars(&[(1, dec!(100)), (2, dec!(100))]),
+        )
+        .unwrap();
+
+        assert_eq!(result.beta, None);
+        assert_eq!(result.alpha, None);
+        assert_eq!(result.correlation, None);
+        assert_eq!(result.account_return, dec!(0));
+    }
+}
@@ -22,9 +22,15 @@ impl AccountCapitalAvailable {
                     TransactionCategory::FundTrade(_) |
                     TransactionCategory::Withdrawal |
                     TransactionCategory::FeeOpen(_) |
-                    TransactionCategory::FeeClose(_) => acc.checked_sub(transaction.amount)
+                    TransactionCategory::FeeClose(_) |
+                    TransactionCategory::PaymentEarnings(_) |
+                    TransactionCategory::BorrowFee(_) |
+                    TransactionCategory::TransferOut => acc.checked_sub(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in subtraction: {} - {}", acc, transaction.amount).into()),
                     TransactionCategory::PaymentFromTrade(_) |
+                    TransactionCategory::Dividend(_) |
+                    TransactionCategory::Interest |
+                    TransactionCategory::TransferIn |
                     TransactionCategory::Deposit => acc.checked_add(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in addition: {} + {}", acc, transaction.amount).into()),
                     _ => Err(format!(
@@ -105,6 +111,55 @@ mod tests {
         assert_eq!(result.unwrap(), dec!(51));
     }
 
+    #[test]
+    fn test_capital_available_excludes_earnings() {
+        let mut database = MockDatabase::new();
+
+        // Transactions
+        database.set_transaction(TransactionCategory::Deposit, dec!(100));
+        database.set_transaction(
+            TransactionCategory::PaymentFromTrade(Uuid::new_v4()),
+            dec!(50),
+        );
+        database.set_transaction(
+            TransactionCategory::PaymentEarnings(Uuid::new_v4()),
+            dec!(5),
+        );
+
+        let result =
+            AccountCapitalAvailable::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(145));
+    }
+
+    #[test]
+    fn test_capital_available_with_dividends_interest_and_borrow_fees() {
+        let mut database = MockDatabase::new();
+
+        // Transactions
+        database.set_transaction(TransactionCategory::Deposit, dec!(100));
+        database.set_transaction(TransactionCategory::Dividend(Uuid::new_v4()), dec!(4));
+        database.set_transaction(TransactionCategory::Interest, dec!(2));
+        database.set_transaction(TransactionCategory::BorrowFee(Uuid::new_v4()), dec!(3));
+
+        let result =
+            AccountCapitalAvailable::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(103));
+    }
+
+    #[test]
+    fn test_capital_available_with_transfers() {
+        let mut database = MockDatabase::new();
+
+        // Transactions
+        database.set_transaction(TransactionCategory::Deposit, dec!(100));
+        database.set_transaction(TransactionCategory::TransferOut, dec!(40));
+        database.set_transaction(TransactionCategory::TransferIn, dec!(15));
+
+        let result =
+            AccountCapitalAvailable::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(75));
+    }
+
     #[test]
     fn test_capital_available_with_multiple_transactions() {
         let mut database = MockDatabase::new();
@@ -23,6 +23,8 @@ impl AccountCapitalBalance {
                         | TransactionCategory::WithdrawalEarnings
                         | TransactionCategory::FeeOpen(_)
                         | TransactionCategory::FeeClose(_)
+                        | TransactionCategory::BorrowFee(_)
+                        | TransactionCategory::TransferOut
                         | TransactionCategory::OpenTrade(_) => {
                             acc.checked_sub(tx.amount).ok_or_else(|| {
                                 format!(
@@ -33,9 +35,13 @@ impl AccountCapitalBalance {
                             })
                         }
                         TransactionCategory::Deposit
+                        | TransactionCategory::Dividend(_)
+                        | TransactionCategory::Interest
+                        | TransactionCategory::TransferIn
                         | TransactionCategory::CloseSafetyStop(_)
                         | TransactionCategory::CloseTarget(_)
-                        | TransactionCategory::CloseSafetyStopSlippage(_) => {
+                        | TransactionCategory::CloseSafetyStopSlippage(_)
+                        | TransactionCategory::ClosePartial(_) => {
                             acc.checked_add(tx.amount).ok_or_else(|| {
                                 format!("Arithmetic overflow in addition: {} + {}", acc, tx.amount)
                                     .into()
@@ -122,6 +128,19 @@ mod tests {
         assert_eq!(result.unwrap(), dec!(240));
     }
 
+    #[test]
+    fn test_total_balance_with_transfers() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::Deposit, dec!(1000));
+        database.set_transaction(TransactionCategory::TransferOut, dec!(300));
+        database.set_transaction(TransactionCategory::TransferIn, dec!(25.5));
+
+        let result =
+            AccountCapitalBalance::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(725.5));
+    }
+
     #[test]
     fn test_total_balance_with_mixed_transactions() {
         let mut database = MockDatabase::new();
@@ -19,8 +19,11 @@ impl AccountCapitalBeginningOfMonth {
             match transaction.category {
                 TransactionCategory::FundTrade(_)
                 | TransactionCategory::Withdrawal
+                | TransactionCategory::TransferOut
                 | TransactionCategory::FeeOpen(_)
-                | TransactionCategory::FeeClose(_) => {
+                | TransactionCategory::FeeClose(_)
+                | TransactionCategory::PaymentEarnings(_)
+                | TransactionCategory::BorrowFee(_) => {
                     total = total.checked_sub(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in subtraction: {} - {}", total, transaction.amount))?
                 }
@@ -28,10 +31,15 @@ impl AccountCapitalBeginningOfMonth {
                     total = total.checked_add(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in addition: {} + {}", total, transaction.amount))?
                 }
-                TransactionCategory::Deposit => {
+                TransactionCategory::Deposit
+                | TransactionCategory::TransferIn
+                | TransactionCategory::Dividend(_)
+                | TransactionCategory::Interest => {
                     total = total.checked_add(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in addition: {} + {}", total, transaction.amount))?
                 }
+                // Withdrawn earnings were already taken out of the capital when they were set aside.
+                TransactionCategory::WithdrawalEarnings => {}
                 default => return Err(format!(
                     "capital_at_beginning_of_month: does not know how to calculate transaction with category: {default}. Transaction: {transaction:?}"
                 ).into()),
@@ -153,6 +161,29 @@ mod tests {
         assert_eq!(result.unwrap(), dec!(3526));
     }
 
+    #[test]
+    fn test_capital_at_beginning_of_month_with_earnings_and_income() {
+        let mut database = MockDatabase::new();
+
+        // Transactions
+        database.set_transaction(TransactionCategory::Deposit, dec!(1000));
+        database.set_transaction(
+            TransactionCategory::PaymentEarnings(Uuid::new_v4()),
+            dec!(100),
+        );
+        database.set_transaction(TransactionCategory::WithdrawalEarnings, dec!(60));
+        database.set_transaction(TransactionCategory::Dividend(Uuid::new_v4()), dec!(20));
+        database.set_transaction(TransactionCategory::Interest, dec!(5));
+        database.set_transaction(TransactionCategory::BorrowFee(Uuid::new_v4()), dec!(3));
+
+        let result = AccountCapitalBeginningOfMonth::calculate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &mut database,
+        );
+        assert_eq!(result.unwrap(), dec!(922));
+    }
+
     #[test]
     #[should_panic(
         expected = "capital_at_beginning_of_month: does not know how to calculate transaction with category: withdrawal_tax"
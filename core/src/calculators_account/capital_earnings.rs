@@ -0,0 +1,72 @@
+use model::{Currency, ReadTransactionDB, TransactionCategory};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+pub struct AccountCapitalEarnings;
+
+impl AccountCapitalEarnings {
+    /// Calculates the earnings set aside from profitable trades that were not withdrawn yet.
+    pub fn calculate(
+        account_id: Uuid,
+        currency: &Currency,
+        database: &mut dyn ReadTransactionDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let mut total = dec!(0);
+        for tx in database.all_transactions(account_id, currency)? {
+            match tx.category {
+                TransactionCategory::PaymentEarnings(_) => {
+                    total = total.checked_add(tx.amount).ok_or_else(|| {
+                        format!("Arithmetic overflow in addition: {} + {}", total, tx.amount)
+                    })?
+                }
+                TransactionCategory::WithdrawalEarnings => {
+                    total = total.checked_sub(tx.amount).ok_or_else(|| {
+                        format!(
+                            "Arithmetic overflow in subtraction: {} - {}",
+                            total, tx.amount
+                        )
+                    })?
+                }
+                _ => {}
+            }
+        }
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::read_transaction_db_mocks::MockDatabase;
+
+    #[test]
+    fn test_capital_earnings_with_empty_transactions() {
+        let mut database = MockDatabase::new();
+
+        let result =
+            AccountCapitalEarnings::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(0));
+    }
+
+    #[test]
+    fn test_capital_earnings_with_withdrawals() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::Deposit, dec!(1000));
+        database.set_transaction(
+            TransactionCategory::PaymentEarnings(Uuid::new_v4()),
+            dec!(50),
+        );
+        database.set_transaction(
+            TransactionCategory::PaymentEarnings(Uuid::new_v4()),
+            dec!(25.5),
+        );
+        database.set_transaction(TransactionCategory::WithdrawalEarnings, dec!(30));
+        database.set_transaction(TransactionCategory::Withdrawal, dec!(100));
+
+        let result =
+            AccountCapitalEarnings::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(45.5));
+    }
+}
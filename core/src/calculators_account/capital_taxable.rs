@@ -18,7 +18,7 @@ impl AccountCapitalTaxable {
             .iter()
             .try_fold(Decimal::ZERO, |acc, transaction| {
                 match transaction.category {
-                    TransactionCategory::PaymentTax(_) => acc.checked_add(transaction.amount)
+                    TransactionCategory::PaymentTax(_) | TransactionCategory::InterestTax => acc.checked_add(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in addition: {} + {}", acc, transaction.amount).into()),
                     TransactionCategory::WithdrawalTax => acc.checked_sub(transaction.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in subtraction: {} - {}", acc, transaction.amount).into()),
@@ -90,6 +90,19 @@ mod tests {
         assert_eq!(result.unwrap(), dec!(903.378));
     }
 
+    #[test]
+    fn test_capital_taxable_includes_the_interest_tax() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::PaymentTax(Uuid::new_v4()), dec!(15));
+        database.set_transaction(TransactionCategory::InterestTax, dec!(4.5));
+        database.set_transaction(TransactionCategory::WithdrawalTax, dec!(10));
+
+        let result =
+            AccountCapitalTaxable::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(9.5));
+    }
+
     #[test]
     #[should_panic(
         expected = "capital_taxable: does not know how to calculate transaction with category: deposit"
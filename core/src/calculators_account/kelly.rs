@@ -0,0 +1,141 @@
+use model::{ReadTradeDB, Status};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+pub struct AccountKellyPercentage;
+
+impl AccountKellyPercentage {
+    /// Calculates the Kelly percentage of an account as a fraction between 0 and 1.
+    ///
+    /// The win rate and the payoff ratio are calculated from the performance
+    /// of all the closed trades of the account:
+    ///
+    /// kelly = win_rate - (1 - win_rate) / (average_win / average_loss)
+    ///
+    /// Returns None when the account does not have closed trades yet.
+    pub fn calculate(
+        account_id: Uuid,
+        database: &mut dyn ReadTradeDB,
+    ) -> Result<Option<Decimal>, Box<dyn std::error::Error>> {
+        let mut trades = database.read_trades_with_status(account_id, Status::ClosedTarget)?;
+        trades.extend(database.read_trades_with_status(account_id, Status::ClosedStopLoss)?);
+
+        // 1. Split the trades between wins and losses
+        let mut wins = dec!(0);
+        let mut total_won = dec!(0);
+        let mut losses = dec!(0);
+        let mut total_lost = dec!(0);
+
+        for trade in trades {
+            let performance = trade.balance.total_performance;
+            if performance.is_sign_positive() && !performance.is_zero() {
+                wins = wins
+                    .checked_add(dec!(1))
+                    .ok_or_else(|| format!("Arithmetic overflow in addition: {wins} + 1"))?;
+                total_won = total_won.checked_add(performance).ok_or_else(|| {
+                    format!("Arithmetic overflow in addition: {total_won} + {performance}")
+                })?;
+            } else {
+                losses = losses
+                    .checked_add(dec!(1))
+                    .ok_or_else(|| format!("Arithmetic overflow in addition: {losses} + 1"))?;
+                total_lost = total_lost.checked_sub(performance).ok_or_else(|| {
+                    format!("Arithmetic overflow in subtraction: {total_lost} - {performance}")
+                })?;
+            }
+        }
+
+        // 2. Without history there is no edge to size with
+        if wins.is_zero() && losses.is_zero() {
+            return Ok(None);
+        }
+
+        if wins.is_zero() {
+            return Ok(Some(dec!(0)));
+        }
+
+        if losses.is_zero() || total_lost.is_zero() {
+            return Ok(Some(dec!(1)));
+        }
+
+        // 3. Calculate the win rate and the payoff ratio
+        let total_trades = wins
+            .checked_add(losses)
+            .ok_or_else(|| format!("Arithmetic overflow in addition: {wins} + {losses}"))?;
+        let win_rate = wins
+            .checked_div(total_trades)
+            .ok_or_else(|| format!("Division error: {wins} / {total_trades}"))?;
+        let average_win = total_won
+            .checked_div(wins)
+            .ok_or_else(|| format!("Division error: {total_won} / {wins}"))?;
+        let average_loss = total_lost
+            .checked_div(losses)
+            .ok_or_else(|| format!("Division error: {total_lost} / {losses}"))?;
+        let payoff_ratio = average_win
+            .checked_div(average_loss)
+            .ok_or_else(|| format!("Division error: {average_win} / {average_loss}"))?;
+
+        // 4. Apply the Kelly formula
+        let loss_rate = dec!(1)
+            .checked_sub(win_rate)
+            .ok_or_else(|| format!("Arithmetic overflow in subtraction: 1 - {win_rate}"))?;
+        let loss_part = loss_rate
+            .checked_div(payoff_ratio)
+            .ok_or_else(|| format!("Division error: {loss_rate} / {payoff_ratio}"))?;
+        let kelly = win_rate.checked_sub(loss_part).ok_or_else(|| {
+            format!("Arithmetic overflow in subtraction: {win_rate} - {loss_part}")
+        })?;
+
+        Ok(Some(kelly.max(dec!(0))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::read_transaction_db_mocks::MockDatabase;
+
+    #[test]
+    fn test_kelly_without_closed_trades() {
+        let mut database = MockDatabase::new();
+        let result = AccountKellyPercentage::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_kelly_with_wins_and_losses() {
+        let mut database = MockDatabase::new();
+        // 60% win rate with a payoff ratio of 2
+        database.set_closed_trade(dec!(200));
+        database.set_closed_trade(dec!(200));
+        database.set_closed_trade(dec!(200));
+        database.set_closed_trade(dec!(-100));
+        database.set_closed_trade(dec!(-100));
+
+        let result = AccountKellyPercentage::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), Some(dec!(0.4)));
+    }
+
+    #[test]
+    fn test_kelly_without_edge() {
+        let mut database = MockDatabase::new();
+        // 25% win rate with a payoff ratio of 1
+        database.set_closed_trade(dec!(100));
+        database.set_closed_trade(dec!(-100));
+        database.set_closed_trade(dec!(-100));
+        database.set_closed_trade(dec!(-100));
+
+        let result = AccountKellyPercentage::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), Some(dec!(0)));
+    }
+
+    #[test]
+    fn test_kelly_only_wins() {
+        let mut database = MockDatabase::new();
+        database.set_closed_trade(dec!(100));
+
+        let result = AccountKellyPercentage::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), Some(dec!(1)));
+    }
+}
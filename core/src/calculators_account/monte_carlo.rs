@@ -0,0 +1,532 @@
+use model::{
+    Currency, EquityPercentile, ReadTradeDB, Rule, RuleName, Simulation, SimulationParameters,
+    Status, Trade,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+const PERCENTILES: [u32; 5] = [5, 25, 50, 75, 95];
+
+pub struct AccountMonteCarlo;
+
+/// How the trades of a simulated path are sized.
+struct Sizing {
+    starting_equity: Decimal,
+    risk_per_trade: Decimal,
+    risk_per_month: Option<Decimal>,
+    trades_per_month: u32,
+    trades: u32,
+    ruin_equity: Decimal,
+}
+
+/// The end of a simulated path.
+struct Outcome {
+    equity: Decimal,
+    max_drawdown: Decimal,
+    ruined: bool,
+}
+
+impl AccountMonteCarlo {
+    /// Simulates random sequences of future trades of an account.
+    ///
+    /// Every trade draws an R-multiple, the performance divided by the planned risk,
+    /// from the closed trades of the account and risks the `RiskPerTrade` percentage
+    /// of the current equity. When the account has a `RiskPerMonth` rule, no more trades
+    /// are taken in a month once the next trade could lose more than what is left of it.
+    /// A path that loses the ruin percentage of the starting equity stops trading.
+    pub fn simulate(
+        account_id: Uuid,
+        currency: &Currency,
+        parameters: &SimulationParameters,
+        rules: &[Rule],
+        starting_equity: Decimal,
+        database: &mut dyn ReadTradeDB,
+    ) -> Result<Simulation, Box<dyn std::error::Error>> {
+        AccountMonteCarlo::validate(parameters)?;
+
+        // 1. Draw the R-multiples from the history of the account
+        let mut trades = database.read_trades_with_status(account_id, Status::ClosedTarget)?;
+        trades.extend(database.read_trades_with_status(account_id, Status::ClosedStopLoss)?);
+        trades.retain(|trade| trade.currency == *currency);
+        let r_multiples = trades
+            .iter()
+            .map(AccountMonteCarlo::r_multiple)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        if r_multiples.is_empty() {
+            return Err(
+                "simulate: the account does not have closed trades with a planned risk".into(),
+            );
+        }
+
+        // 2. Size the trades like the rules of the account do
+        let (risk_per_trade, risk_per_month) = AccountMonteCarlo::rules(rules)?;
+        let sizing = Sizing {
+            starting_equity,
+            risk_per_trade: percentage_to_fraction(risk_per_trade)?,
+            risk_per_month: risk_per_month.map(percentage_to_fraction).transpose()?,
+            trades_per_month: AccountMonteCarlo::trades_per_month(&trades),
+            trades: parameters.trades,
+            ruin_equity: mul(
+                starting_equity,
+                sub(dec!(1), percentage_to_fraction(parameters.ruin_percentage)?)?,
+            )?,
+        };
+
+        // 3. Run the paths
+        let mut rng = StdRng::seed_from_u64(parameters.seed.unwrap_or_else(rand::random));
+        let outcomes = (0..parameters.paths)
+            .map(|_| AccountMonteCarlo::path(&r_multiples, &sizing, &mut rng))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        AccountMonteCarlo::summarize(
+            currency,
+            parameters,
+            &sizing,
+            &r_multiples,
+            &outcomes,
+            (risk_per_trade, risk_per_month),
+        )
+    }
+
+    fn validate(parameters: &SimulationParameters) -> Result<(), Box<dyn std::error::Error>> {
+        if parameters.paths == 0 || parameters.trades == 0 {
+            return Err("simulate: the number of paths and trades must be above 0".into());
+        }
+        for (name, value) in [
+            ("drawdown", parameters.drawdown_percentage),
+            ("ruin", parameters.ruin_percentage),
+        ] {
+            if value <= dec!(0) || value > dec!(100) {
+                return Err(format!(
+                    "simulate: the {name} percentage must be above 0 and up to 100"
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// The performance of a trade in units of its planned risk.
+    /// Trades without a planned risk are not part of the distribution.
+    fn r_multiple(trade: &Trade) -> Result<Option<Decimal>, Box<dyn std::error::Error>> {
+        let risk_per_share = sub(trade.entry.unit_price, trade.safety_stop.unit_price)?.abs();
+        let risk = mul(risk_per_share, Decimal::from(trade.entry.quantity))?;
+        if risk.is_zero() {
+            return Ok(None);
+        }
+        Ok(Some(div(trade.balance.total_performance, risk)?))
+    }
+
+    /// The percentages of the `RiskPerTrade` and `RiskPerMonth` rules of the account.
+    fn rules(rules: &[Rule]) -> Result<(Decimal, Option<Decimal>), Box<dyn std::error::Error>> {
+        let mut rules: Vec<&Rule> = rules.iter().collect();
+        rules.sort_by_key(|rule| rule.priority);
+
+        let mut risk_per_trade = None;
+        let mut risk_per_month = None;
+        for rule in rules {
+            match rule.name {
+                RuleName::RiskPerTrade(risk) if risk_per_trade.is_none() => {
+                    risk_per_trade = Some(to_decimal(risk)?);
+                }
+                RuleName::RiskPerMonth(risk) if risk_per_month.is_none() => {
+                    risk_per_month = Some(to_decimal(risk)?);
+                }
+                _ => {}
+            }
+        }
+
+        let risk_per_trade = risk_per_trade.ok_or(
+            "simulate: the account needs a risk_per_trade rule to size the simulated trades",
+        )?;
+        Ok((risk_per_trade, risk_per_month))
+    }
+
+    /// The average number of trades entered in the months the account traded.
+    fn trades_per_month(trades: &[Trade]) -> u32 {
+        let months: BTreeSet<String> = trades
+            .iter()
+            .map(|trade| {
+                trade
+                    .entry
+                    .filled_at
+                    .unwrap_or(trade.created_at)
+                    .format("%Y-%m")
+                    .to_string()
+            })
+            .collect();
+        let per_month = trades.len().checked_div(months.len()).unwrap_or(0).max(1);
+        u32::try_from(per_month).unwrap_or(u32::MAX)
+    }
+
+    fn path(
+        r_multiples: &[Decimal],
+        sizing: &Sizing,
+        rng: &mut StdRng,
+    ) -> Result<Outcome, Box<dyn std::error::Error>> {
+        let mut equity = sizing.starting_equity;
+        let mut peak = equity;
+        let mut max_drawdown = dec!(0);
+        let mut month_budget = dec!(0);
+
+        for trade in 0..sizing.trades {
+            if equity <= sizing.ruin_equity || equity <= dec!(0) {
+                return Ok(Outcome {
+                    equity,
+                    max_drawdown,
+                    ruined: true,
+                });
+            }
+
+            // A new month starts with the risk allowed by the rule
+            if trade.checked_rem(sizing.trades_per_month) == Some(0) {
+                month_budget = match sizing.risk_per_month {
+                    Some(risk) => mul(equity, risk)?,
+                    None => Decimal::MAX,
+                };
+            }
+
+            let risk = mul(equity, sizing.risk_per_trade)?;
+            if risk > month_budget {
+                continue;
+            }
+
+            let index = rng.random_range(0..r_multiples.len());
+            let r_multiple = r_multiples.get(index).copied().unwrap_or_default();
+            let result = mul(r_multiple, risk)?;
+            equity = add(equity, result)?;
+            if result.is_sign_negative() {
+                month_budget = add(month_budget, result)?;
+            }
+
+            peak = peak.max(equity);
+            if peak > dec!(0) {
+                max_drawdown = max_drawdown.max(div(sub(peak, equity)?, peak)?);
+            }
+        }
+
+        Ok(Outcome {
+            equity,
+            max_drawdown,
+            ruined: equity <= sizing.ruin_equity || equity <= dec!(0),
+        })
+    }
+
+    fn summarize(
+        currency: &Currency,
+        parameters: &SimulationParameters,
+        sizing: &Sizing,
+        r_multiples: &[Decimal],
+        outcomes: &[Outcome],
+        (risk_per_trade, risk_per_month): (Decimal, Option<Decimal>),
+    ) -> Result<Simulation, Box<dyn std::error::Error>> {
+        let mut equities: Vec<Decimal> = outcomes.iter().map(|outcome| outcome.equity).collect();
+        equities.sort();
+        let mut drawdowns: Vec<Decimal> = outcomes
+            .iter()
+            .map(|outcome| outcome.max_drawdown)
+            .collect();
+        drawdowns.sort();
+
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|percentile| {
+                Ok(EquityPercentile {
+                    percentile: *percentile,
+                    equity: percentile_of(&equities, *percentile)?.round_dp(2),
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+        let drawdown = percentage_to_fraction(parameters.drawdown_percentage)?;
+        let drawdowns_hit = outcomes
+            .iter()
+            .filter(|outcome| outcome.max_drawdown >= drawdown)
+            .count();
+        let ruined = outcomes.iter().filter(|outcome| outcome.ruined).count();
+
+        Ok(Simulation {
+            currency: *currency,
+            starting_equity: sizing.starting_equity,
+            risk_per_trade,
+            risk_per_month,
+            trades_per_month: sizing.trades_per_month,
+            sample_size: r_multiples.len(),
+            expectancy: mean(r_multiples)?.round_dp(2),
+            parameters: *parameters,
+            percentiles,
+            median_max_drawdown: mul(percentile_of(&drawdowns, 50)?, dec!(100))?.round_dp(2),
+            drawdown_probability: probability(drawdowns_hit, outcomes.len())?,
+            ruin_probability: probability(ruined, outcomes.len())?,
+        })
+    }
+}
+
+/// The value at a percentile of sorted values, by the nearest rank below.
+fn percentile_of(
+    sorted: &[Decimal],
+    percentile: u32,
+) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let last = sorted
+        .len()
+        .checked_sub(1)
+        .ok_or("simulate: there are no paths")?;
+    let index = last
+        .checked_mul(usize::try_from(percentile)?)
+        .and_then(|index| index.checked_div(100))
+        .ok_or("Arithmetic overflow in percentile")?;
+    sorted
+        .get(index)
+        .copied()
+        .ok_or_else(|| "simulate: percentile out of range".into())
+}
+
+fn probability(count: usize, total: usize) -> Result<Decimal, Box<dyn std::error::Error>> {
+    Ok(div(Decimal::from(count), Decimal::from(total))?.round_dp(4))
+}
+
+fn mean(values: &[Decimal]) -> Result<Decimal, Box<dyn std::error::Error>> {
+    let mut total = dec!(0);
+    for value in values {
+        total = add(total, *value)?;
+    }
+    div(total, Decimal::from(values.len()))
+}
+
+fn to_decimal(risk: f32) -> Result<Decimal, Box<dyn std::error::Error>> {
+    Decimal::from_f32_retain(risk)
+        .ok_or_else(|| format!("Failed to convert risk {risk} to Decimal").into())
+}
+
+fn percentage_to_fraction(percentage: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    div(percentage, dec!(100))
+}
+
+fn add(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_add(b)
+        .ok_or_else(|| format!("Arithmetic overflow in addition: {a} + {b}").into())
+}
+
+fn sub(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_sub(b)
+        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {a} - {b}").into())
+}
+
+fn mul(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_mul(b)
+        .ok_or_else(|| format!("Arithmetic overflow in multiplication: {a} * {b}").into())
+}
+
+fn div(a: Decimal, b: Decimal) -> Result<Decimal, Box<dyn std::error::Error>> {
+    a.checked_div(b)
+        .ok_or_else(|| format!("Arithmetic overflow in division: {a} / {b}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::read_transaction_db_mocks::MockDatabase;
+    use chrono::Utc;
+    use model::RuleLevel;
+
+    fn rule(name: RuleName) -> Rule {
+        let now = Utc::now().naive_utc();
+        Rule {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            name,
+            description: "".to_string(),
+            priority: 1,
+            level: RuleLevel::Error,
+            account_id: Uuid::new_v4(),
+            active: true,
+        }
+    }
+
+    fn parameters() -> SimulationParameters {
+        SimulationParameters {
+            paths: 200,
+            trades: 50,
+            seed: Some(42),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_simulate_without_closed_trades() {
+        let mut database = MockDatabase::new();
+        let rules = vec![rule(RuleName::RiskPerTrade(2.0))];
+        let result = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters(),
+            &rules,
+            dec!(10000),
+            &mut database,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simulate_without_risk_per_trade_rule() {
+        let mut database = MockDatabase::new();
+        database.set_closed_trade_with_risk(dec!(200), dec!(40), dec!(38), 50);
+        let result = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters(),
+            &[],
+            dec!(10000),
+            &mut database,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simulate_with_invalid_parameters() {
+        let mut database = MockDatabase::new();
+        database.set_closed_trade_with_risk(dec!(200), dec!(40), dec!(38), 50);
+        let rules = vec![rule(RuleName::RiskPerTrade(2.0))];
+        let parameters = SimulationParameters {
+            ruin_percentage: dec!(120),
+            ..parameters()
+        };
+        let result = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters,
+            &rules,
+            dec!(10000),
+            &mut database,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_simulate_only_winning_trades() {
+        let mut database = MockDatabase::new();
+        // Risking 2 per share on 50 shares, the trade made 2R
+        database.set_closed_trade_with_risk(dec!(200), dec!(40), dec!(38), 50);
+        let rules = vec![rule(RuleName::RiskPerTrade(1.0))];
+
+        let simulation = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters(),
+            &rules,
+            dec!(10000),
+            &mut database,
+        )
+        .unwrap();
+
+        assert_eq!(simulation.sample_size, 1);
+        assert_eq!(simulation.expectancy, dec!(2));
+        assert_eq!(simulation.risk_per_trade, dec!(1));
+        assert_eq!(simulation.ruin_probability, dec!(0));
+        assert_eq!(simulation.drawdown_probability, dec!(0));
+        assert_eq!(simulation.median_max_drawdown, dec!(0));
+        // Every path compounds 2% for 50 trades
+        let mut expected = dec!(10000);
+        for _ in 0..50 {
+            expected += expected * dec!(0.01) * dec!(2);
+        }
+        let expected = expected.round_dp(2);
+        assert!(simulation
+            .percentiles
+            .iter()
+            .all(|percentile| percentile.equity == expected));
+    }
+
+    #[test]
+    fn test_simulate_only_losing_trades() {
+        let mut database = MockDatabase::new();
+        database.set_closed_trade_with_risk(dec!(-100), dec!(40), dec!(38), 50);
+        let rules = vec![rule(RuleName::RiskPerTrade(5.0))];
+
+        let simulation = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters(),
+            &rules,
+            dec!(10000),
+            &mut database,
+        )
+        .unwrap();
+
+        assert_eq!(simulation.expectancy, dec!(-1));
+        assert_eq!(simulation.ruin_probability, dec!(1));
+        assert_eq!(simulation.drawdown_probability, dec!(1));
+    }
+
+    #[test]
+    fn test_simulate_risk_per_month_limits_the_losses() {
+        let mut database = MockDatabase::new();
+        database.set_closed_trade_with_risk(dec!(-100), dec!(40), dec!(38), 50);
+        database.set_closed_trade_with_risk(dec!(-100), dec!(40), dec!(38), 50);
+        let rules = vec![
+            rule(RuleName::RiskPerTrade(2.0)),
+            rule(RuleName::RiskPerMonth(3.0)),
+        ];
+        let parameters = SimulationParameters {
+            trades: 2,
+            ..parameters()
+        };
+
+        let simulation = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters,
+            &rules,
+            dec!(10000),
+            &mut database,
+        )
+        .unwrap();
+
+        // Both trades are in the same month, the second one would risk more than what is left
+        assert_eq!(simulation.trades_per_month, 2);
+        assert_eq!(simulation.risk_per_month, Some(dec!(3)));
+        assert_eq!(simulation.percentiles.get(2).unwrap().equity, dec!(9800));
+    }
+
+    #[test]
+    fn test_simulate_is_reproducible_with_a_seed() {
+        let mut database = MockDatabase::new();
+        database.set_closed_trade_with_risk(dec!(300), dec!(40), dec!(38), 50);
+        database.set_closed_trade_with_risk(dec!(-100), dec!(40), dec!(38), 50);
+        database.set_closed_trade_with_risk(dec!(-50), dec!(40), dec!(38), 50);
+        let rules = vec![rule(RuleName::RiskPerTrade(2.0))];
+
+        let first = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters(),
+            &rules,
+            dec!(10000),
+            &mut database,
+        )
+        .unwrap();
+        let second = AccountMonteCarlo::simulate(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &parameters(),
+            &rules,
+            dec!(10000),
+            &mut database,
+        )
+        .unwrap();
+
+        assert_eq!(first, second);
+        assert!(
+            first.percentiles.first().unwrap().equity <= first.percentiles.last().unwrap().equity
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::calculators_trade::TradeCapitalInMarket;
+use model::{
+    AccountBalance, Currency, ReadTradeDB, ReadTransactionDB, Status, Trade, TradeCategory,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use uuid::Uuid;
+
+/// Statuses of the trades that can still hit their stop.
+const OPEN_STATUSES: [Status; 4] = [
+    Status::Funded,
+    Status::Submitted,
+    Status::PartiallyFilled,
+    Status::Filled,
+];
+
+pub struct AccountOpenRisk;
+
+impl AccountOpenRisk {
+    /// Calculates the money that would be lost if the stops of all the open trades
+    /// of an account were hit, also known as portfolio heat.
+    ///
+    /// The risk of a trade is the distance from its entry to its stop times its quantity.
+    /// Filled entries use the average filled price. Stops moved past breakeven lock
+    /// in a profit, so they add no risk instead of reducing the risk of other trades.
+    ///
+    /// IMPORTANT: more capital can be at risk in case the safety stops has slippage.
+    pub fn calculate(
+        account_id: Uuid,
+        currency: &Currency,
+        database: &mut dyn ReadTradeDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        AccountOpenRisk::sum(account_id, currency, None, database)
+    }
+
+    /// Calculates the open risk of the account of a trade without counting the trade,
+    /// so a trade that is being funded is not counted twice.
+    pub fn calculate_excluding(
+        trade: &Trade,
+        database: &mut dyn ReadTradeDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        AccountOpenRisk::sum(trade.account_id, &trade.currency, Some(trade.id), database)
+    }
+
+    fn sum(
+        account_id: Uuid,
+        currency: &Currency,
+        excluded: Option<Uuid>,
+        database: &mut dyn ReadTradeDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let mut total = dec!(0);
+        for status in OPEN_STATUSES {
+            for trade in database.read_trades_with_status(account_id, status)? {
+                if trade.currency != *currency || Some(trade.id) == excluded {
+                    continue;
+                }
+                let risk = AccountOpenRisk::trade_risk(&trade)?;
+                total = total
+                    .checked_add(risk)
+                    .ok_or_else(|| format!("Arithmetic overflow in addition: {total} + {risk}"))?;
+            }
+        }
+        Ok(total)
+    }
+
+    /// The money that a single trade would lose if its stop was hit.
+    pub fn trade_risk(trade: &Trade) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let entry = trade
+            .entry
+            .average_filled_price
+            .unwrap_or(trade.entry.unit_price);
+        let stop = trade.safety_stop.unit_price;
+        let risk_per_share = match trade.category {
+            TradeCategory::Long => entry.checked_sub(stop),
+            TradeCategory::Short => stop.checked_sub(entry),
+        }
+        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {entry} - {stop}"))?;
+
+        if risk_per_share <= dec!(0) {
+            return Ok(dec!(0));
+        }
+
+        risk_per_share
+            .checked_mul(Decimal::from(trade.entry.quantity))
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow in multiplication: {risk_per_share} * {}",
+                    trade.entry.quantity
+                )
+                .into()
+            })
+    }
+
+    /// The capital of the open trades of an account that entered the market.
+    /// Funded trades that did not enter the market have no capital in it.
+    pub fn in_market(
+        account_id: Uuid,
+        currency: &Currency,
+        trades: &mut dyn ReadTradeDB,
+        transactions: &mut dyn ReadTransactionDB,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let mut total = dec!(0);
+        for status in OPEN_STATUSES {
+            for trade in trades.read_trades_with_status(account_id, status)? {
+                if trade.currency != *currency {
+                    continue;
+                }
+                let capital = TradeCapitalInMarket::calculate(trade.id, transactions)?;
+                total = total.checked_add(capital).ok_or_else(|| {
+                    format!("Arithmetic overflow in addition: {total} + {capital}")
+                })?;
+            }
+        }
+        Ok(total)
+    }
+
+    /// The capital that the open risk is measured against: the equity of the account.
+    /// The balance does not count the money that entered the market, while the money
+    /// of the funded trades is still part of it, so only the capital in the market is added.
+    pub fn capital(
+        balance: &AccountBalance,
+        in_market: Decimal,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        balance.total_balance.checked_add(in_market).ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in addition: {} + {in_market}",
+                balance.total_balance
+            )
+            .into()
+        })
+    }
+
+    /// The open risk as a percentage of the capital of the account.
+    pub fn percentage(
+        open_risk: Decimal,
+        capital: Decimal,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        if capital <= dec!(0) {
+            return Ok(dec!(0));
+        }
+        open_risk
+            .checked_mul(dec!(100))
+            .and_then(|risk| risk.checked_div(capital))
+            .map(|percentage| percentage.round_dp(2))
+            .ok_or_else(|| {
+                format!("Arithmetic overflow in percentage: {open_risk} * 100 / {capital}").into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mocks::read_transaction_db_mocks::MockDatabase;
+    use model::TransactionCategory;
+
+    #[test]
+    fn test_open_risk_without_trades() {
+        let mut database = MockDatabase::new();
+        let result = AccountOpenRisk::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(0));
+    }
+
+    #[test]
+    fn test_open_risk_with_long_and_short_trades() {
+        let mut database = MockDatabase::new();
+        database.set_open_trade(TradeCategory::Long, dec!(40), dec!(38), 500); // 1000
+        database.set_open_trade(TradeCategory::Short, dec!(20), dec!(21), 100); // 100
+
+        let result = AccountOpenRisk::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(1100));
+    }
+
+    #[test]
+    fn test_open_risk_with_stops_past_breakeven() {
+        let mut database = MockDatabase::new();
+        database.set_open_trade(TradeCategory::Long, dec!(40), dec!(38), 500); // 1000
+        database.set_open_trade(TradeCategory::Long, dec!(10), dec!(12), 100); // Locked profit
+        database.set_open_trade(TradeCategory::Short, dec!(20), dec!(18), 100); // Locked profit
+
+        let result = AccountOpenRisk::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(1000));
+    }
+
+    #[test]
+    fn test_open_risk_ignores_new_and_closed_trades() {
+        let mut database = MockDatabase::new();
+        database.set_trade(dec!(40), dec!(50), dec!(38), 500);
+        database.set_closed_trade(dec!(200));
+
+        let result = AccountOpenRisk::calculate(Uuid::new_v4(), &Currency::USD, &mut database);
+        assert_eq!(result.unwrap(), dec!(0));
+    }
+
+    #[test]
+    fn test_open_risk_excluding_a_trade() {
+        let mut database = MockDatabase::new();
+        database.set_open_trade(TradeCategory::Long, dec!(40), dec!(38), 500); // 1000
+        database.set_open_trade(TradeCategory::Long, dec!(10), dec!(9), 100); // 100
+        let trade = database
+            .read_trades_with_status(Uuid::new_v4(), Status::Filled)
+            .unwrap()
+            .pop()
+            .unwrap();
+
+        let result = AccountOpenRisk::calculate_excluding(&trade, &mut database);
+        assert_eq!(result.unwrap(), dec!(1000));
+    }
+
+    #[test]
+    fn test_open_risk_capital_counts_funded_trades_once() {
+        // 10000 funded a trade that did not fill and 20000 entered the market
+        let balance = AccountBalance {
+            total_balance: dec!(30000),
+            total_in_trade: dec!(30000),
+            ..Default::default()
+        };
+        let capital = AccountOpenRisk::capital(&balance, dec!(20000)).unwrap();
+        assert_eq!(capital, dec!(50000));
+        assert_eq!(
+            AccountOpenRisk::percentage(dec!(1500), capital).unwrap(),
+            dec!(3)
+        );
+        assert_eq!(
+            AccountOpenRisk::percentage(dec!(1500), dec!(0)).unwrap(),
+            dec!(0)
+        );
+    }
+
+    #[test]
+    fn test_open_risk_in_market() {
+        let mut trades = MockDatabase::new();
+        trades.set_open_trade(TradeCategory::Long, dec!(40), dec!(38), 500);
+        trades.set_trade(dec!(40), dec!(50), dec!(38), 500);
+        let mut transactions = MockDatabase::new();
+        transactions.set_transaction(TransactionCategory::FundTrade(Uuid::new_v4()), dec!(20000));
+        transactions.set_transaction(TransactionCategory::OpenTrade(Uuid::new_v4()), dec!(20000));
+
+        let result = AccountOpenRisk::in_market(
+            Uuid::new_v4(),
+            &Currency::USD,
+            &mut trades,
+            &mut transactions,
+        );
+        assert_eq!(result.unwrap(), dec!(20000));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::calculators_trade::TradeSlippage;
+use model::{Currency, OrderSlippage, ReadTradeDB, SlippageGrouping, SlippageSummary, Status};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+pub struct AccountSlippage;
+
+impl AccountSlippage {
+    /// Calculates the slippage of all the filled orders of the trades of an account in a currency.
+    pub fn calculate(
+        account_id: Uuid,
+        currency: &Currency,
+        database: &mut dyn ReadTradeDB,
+    ) -> Result<Vec<OrderSlippage>, Box<dyn std::error::Error>> {
+        // Canceled trades are included because trades closed manually are canceled.
+        let statuses = [
+            Status::Filled,
+            Status::ClosedStopLoss,
+            Status::ClosedTarget,
+            Status::Canceled,
+        ];
+
+        let mut slippages = Vec::new();
+        for status in statuses {
+            for trade in database.read_trades_with_status(account_id, status)? {
+                if trade.currency == *currency {
+                    slippages.extend(TradeSlippage::calculate(&trade)?);
+                }
+            }
+        }
+        Ok(slippages)
+    }
+
+    /// Aggregates the slippage of a list of orders by the given grouping.
+    /// The groups are sorted by name.
+    pub fn summarize(
+        slippages: &[OrderSlippage],
+        grouping: SlippageGrouping,
+    ) -> Result<Vec<SlippageSummary>, Box<dyn std::error::Error>> {
+        let mut groups: BTreeMap<String, Vec<&OrderSlippage>> = BTreeMap::new();
+        for slippage in slippages {
+            groups
+                .entry(AccountSlippage::group(slippage, grouping))
+                .or_default()
+                .push(slippage);
+        }
+
+        groups
+            .into_iter()
+            .map(|(group, slippages)| AccountSlippage::summary(group, &slippages))
+            .collect()
+    }
+
+    fn group(slippage: &OrderSlippage, grouping: SlippageGrouping) -> String {
+        match grouping {
+            SlippageGrouping::Leg => slippage.leg.to_string(),
+            SlippageGrouping::Symbol => slippage.symbol.clone(),
+            SlippageGrouping::OrderType => slippage.category.to_string(),
+            SlippageGrouping::HourOfDay => slippage.filled_at.map_or_else(
+                || "unknown".to_string(),
+                |filled_at| filled_at.format("%H:00").to_string(),
+            ),
+        }
+    }
+
+    fn summary(
+        group: String,
+        slippages: &[&OrderSlippage],
+    ) -> Result<SlippageSummary, Box<dyn std::error::Error>> {
+        let mut total_amount = dec!(0);
+        let mut total_bps = dec!(0);
+        let mut worst_bps: Option<Decimal> = None;
+
+        for slippage in slippages {
+            total_amount = total_amount.checked_add(slippage.amount).ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow in addition: {total_amount} + {}",
+                    slippage.amount
+                )
+            })?;
+            total_bps = total_bps.checked_add(slippage.bps).ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow in addition: {total_bps} + {}",
+                    slippage.bps
+                )
+            })?;
+            worst_bps = Some(worst_bps.map_or(slippage.bps, |worst| worst.max(slippage.bps)));
+        }
+
+        let orders = u64::try_from(slippages.len())?;
+        let average_bps = total_bps
+            .checked_div(Decimal::from(orders))
+            .ok_or_else(|| format!("Division error: {total_bps} / {orders}"))?
+            .round_dp(2);
+
+        Ok(SlippageSummary {
+            group,
+            orders,
+            total_amount,
+            average_bps,
+            worst_bps: worst_bps.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use model::{OrderCategory, SlippageLeg};
+
+    fn slippage(symbol: &str, category: OrderCategory, hour: u32, bps: Decimal) -> OrderSlippage {
+        OrderSlippage {
+            trade_id: Uuid::new_v4(),
+            leg: SlippageLeg::Stop,
+            symbol: symbol.to_string(),
+            category,
+            currency: Currency::USD,
+            filled_at: NaiveDate::from_ymd_opt(2024, 3, 1)
+                .unwrap()
+                .and_hms_opt(hour, 30, 0),
+            planned_price: dec!(100),
+            filled_price: dec!(100),
+            quantity: 10,
+            amount: bps,
+            bps,
+        }
+    }
+
+    #[test]
+    fn test_summarize_by_symbol() {
+        let slippages = vec![
+            slippage("TSLA", OrderCategory::Stop, 14, dec!(10)),
+            slippage("AAPL", OrderCategory::Limit, 15, dec!(-4)),
+            slippage("TSLA", OrderCategory::Market, 14, dec!(30)),
+        ];
+
+        let result = AccountSlippage::summarize(&slippages, SlippageGrouping::Symbol).unwrap();
+
+        assert_eq!(result.len(), 2);
+        let aapl = result.first().unwrap();
+        assert_eq!(aapl.group, "AAPL");
+        assert_eq!(aapl.orders, 1);
+        let tsla = result.get(1).unwrap();
+        assert_eq!(tsla.group, "TSLA");
+        assert_eq!(tsla.orders, 2);
+        assert_eq!(tsla.total_amount, dec!(40));
+        assert_eq!(tsla.average_bps, dec!(20));
+        assert_eq!(tsla.worst_bps, dec!(30));
+    }
+
+    #[test]
+    fn test_summarize_by_hour_and_order_type() {
+        let slippages = vec![
+            slippage("TSLA", OrderCategory::Stop, 9, dec!(10)),
+            slippage("AAPL", OrderCategory::Stop, 15, dec!(20)),
+        ];
+
+        let by_hour = AccountSlippage::summarize(&slippages, SlippageGrouping::HourOfDay).unwrap();
+        assert_eq!(by_hour.first().unwrap().group, "09:00");
+        assert_eq!(by_hour.get(1).unwrap().group, "15:00");
+
+        let by_type = AccountSlippage::summarize(&slippages, SlippageGrouping::OrderType).unwrap();
+        assert_eq!(by_type.len(), 1);
+        assert_eq!(by_type.first().unwrap().group, "stop");
+        assert_eq!(by_type.first().unwrap().average_bps, dec!(15));
+    }
+
+    #[test]
+    fn test_summarize_empty() {
+        assert!(AccountSlippage::summarize(&[], SlippageGrouping::Leg)
+            .unwrap()
+            .is_empty());
+    }
+}
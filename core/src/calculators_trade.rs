@@ -4,9 +4,12 @@ mod capital_not_at_risk;
 mod capital_out_of_market;
 mod capital_required;
 mod capital_taxable;
+mod earnings;
+mod fees;
 mod performance;
 mod quantity;
 mod risk;
+mod slippage;
 
 pub use capital_funded::TradeCapitalFunded;
 pub use capital_in_market::TradeCapitalInMarket;
@@ -14,6 +17,9 @@ pub use capital_not_at_risk::TradeCapitalNotAtRisk;
 pub use capital_out_of_market::TradeCapitalOutOfMarket;
 pub use capital_required::TradeCapitalRequired;
 pub use capital_taxable::TradeCapitalTaxable;
+pub use earnings::TradeEarnings;
+pub use fees::TradeFees;
 pub use performance::TradePerformance;
 pub use quantity::QuantityCalculator;
 pub use risk::RiskCalculator;
+pub use slippage::TradeSlippage;
@@ -27,8 +27,17 @@ impl TradeCapitalInMarket {
                 | TransactionCategory::CloseSafetyStopSlippage(_) => {
                     total = Decimal::from(0) // We have exited the market, so we have no money in the market.
                 },
-                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_)  => {
-                    // We ignore the fees because they are charged from the account and not from the trade.
+                TransactionCategory::ClosePartial(_) => {
+                    // The money of the close is not the cost of the quantity that left the market.
+                }
+                TransactionCategory::ClosePartialCost(_) => {
+                    // The cost of the quantity closed by a partial close leaves the market.
+                    total = total.checked_sub(tx.amount)
+                        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {} - {}", total, tx.amount))?
+                }
+                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_) |
+                TransactionCategory::Dividend(_) | TransactionCategory::BorrowFee(_) => {
+                    // We ignore the fees, dividends and borrow fees because they are paid to or charged from the account and not from the trade.
                 }
                 default => return Err(format!(
                     "TradeCapitalInMarket: does not know how to calculate transaction with category: {default}"
@@ -131,6 +140,21 @@ mod tests {
         assert_eq!(result.unwrap(), dec!(0));
     }
 
+    #[test]
+    fn test_calculate_with_transaction_close_partial() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::OpenTrade(Uuid::new_v4()), dec!(100));
+        database.set_transaction(TransactionCategory::ClosePartial(Uuid::new_v4()), dec!(30));
+        database.set_transaction(
+            TransactionCategory::ClosePartialCost(Uuid::new_v4()),
+            dec!(25),
+        );
+
+        let result = TradeCapitalInMarket::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), dec!(75));
+    }
+
     #[test]
     #[should_panic(
         expected = "TradeCapitalInMarket: does not know how to calculate transaction with category: withdrawal_tax"
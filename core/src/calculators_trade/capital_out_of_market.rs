@@ -44,8 +44,17 @@ impl TradeCapitalOutOfMarket {
                     total = total.checked_add(tx.amount)
                         .ok_or_else(|| format!("Arithmetic overflow in addition: {} + {}", total, tx.amount))?
                 },
-                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_) => {
-                    // We ignore the fees because they are charged from the account and not from the trade.
+                TransactionCategory::ClosePartial(_) => {
+                    // This is money that we have used to exit part of the position.
+                    total = total.checked_add(tx.amount)
+                        .ok_or_else(|| format!("Arithmetic overflow in addition: {} + {}", total, tx.amount))?
+                },
+                TransactionCategory::ClosePartialCost(_) => {
+                    // The cost of a partial close only says what left the market.
+                }
+                TransactionCategory::FeeOpen(_) | TransactionCategory::FeeClose(_) | TransactionCategory::PaymentTax(_) | TransactionCategory::PaymentEarnings(_) |
+                TransactionCategory::Dividend(_) | TransactionCategory::BorrowFee(_) => {
+                    // We ignore the fees, dividends and borrow fees because they are paid to or charged from the account and not from the trade.
                 }
                 default => return Err(format!(
                     "TradeCapitalOutOfMarket: does not know how to calculate transaction with category: {default}"
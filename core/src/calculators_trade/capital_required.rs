@@ -1,4 +1,5 @@
-use model::{Trade, TradeCategory};
+use crate::calculators_trade::TradeFees;
+use model::{FeeSchedule, Trade, TradeCategory};
 use rust_decimal::Decimal;
 
 /// Calculates the maximum capital required to fund a trade.
@@ -61,6 +62,22 @@ impl TradeCapitalRequired {
             }
         }
     }
+
+    /// Calculates the capital required to fund a trade including the estimated
+    /// fees of opening and closing it with the fee schedule of the account.
+    pub fn calculate_with_fees(
+        trade: &Trade,
+        schedule: Option<&FeeSchedule>,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let required = TradeCapitalRequired::calculate(trade)?;
+        let Some(schedule) = schedule else {
+            return Ok(required);
+        };
+        let fees = TradeFees::estimated(trade, schedule)?;
+        required
+            .checked_add(fees)
+            .ok_or_else(|| format!("Arithmetic overflow in addition: {required} + {fees}").into())
+    }
 }
 
 #[cfg(test)]
@@ -138,4 +155,35 @@ mod tests {
         // Then: Should return $250 (stop price * stop quantity)
         assert_eq!(required, dec!(250));
     }
+
+    #[test]
+    fn test_calculate_required_capital_with_fees() {
+        // Given: Long trade with entry=$10, stop=$9, quantity=100
+        let trade = Trade {
+            category: TradeCategory::Long,
+            entry: Order {
+                unit_price: dec!(10),
+                quantity: 100,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(9),
+                quantity: 100,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let schedule = FeeSchedule {
+            per_order: dec!(1),
+            ..Default::default()
+        };
+
+        // When: Calculating required capital with and without a fee schedule
+        let without_fees = TradeCapitalRequired::calculate_with_fees(&trade, None).unwrap();
+        let with_fees = TradeCapitalRequired::calculate_with_fees(&trade, Some(&schedule)).unwrap();
+
+        // Then: Should add the fees of opening and closing the trade
+        assert_eq!(without_fees, dec!(1000));
+        assert_eq!(with_fees, dec!(1002));
+    }
 }
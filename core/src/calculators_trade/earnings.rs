@@ -0,0 +1,65 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+pub struct TradeEarnings;
+
+impl TradeEarnings {
+    /// Calculates the earnings to set aside from the performance of a closed trade.
+    /// Only profitable trades set aside earnings, so the result is zero for losses.
+    pub fn calculate(
+        performance: Decimal,
+        earnings_percentage: Decimal,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        if performance <= dec!(0) || earnings_percentage <= dec!(0) {
+            return Ok(dec!(0));
+        }
+
+        let earnings = performance
+            .checked_mul(earnings_percentage)
+            .and_then(|total| total.checked_div(dec!(100)))
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow calculating earnings: {performance} * {earnings_percentage}%"
+                )
+            })?;
+
+        Ok(earnings.round_dp(2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_profitable_trade() {
+        assert_eq!(
+            TradeEarnings::calculate(dec!(5000), dec!(10)).unwrap(),
+            dec!(500)
+        );
+        assert_eq!(
+            TradeEarnings::calculate(dec!(6497.26), dec!(10)).unwrap(),
+            dec!(649.73)
+        );
+    }
+
+    #[test]
+    fn test_calculate_losing_trade() {
+        assert_eq!(
+            TradeEarnings::calculate(dec!(-1000), dec!(10)).unwrap(),
+            dec!(0)
+        );
+        assert_eq!(
+            TradeEarnings::calculate(dec!(0), dec!(10)).unwrap(),
+            dec!(0)
+        );
+    }
+
+    #[test]
+    fn test_calculate_without_percentage() {
+        assert_eq!(
+            TradeEarnings::calculate(dec!(5000), dec!(0)).unwrap(),
+            dec!(0)
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::commands::order::filled_quantity;
+use model::{FeeSchedule, Order, OrderAction, Trade};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+
+/// Calculates the commissions and regulatory fees of the orders of a trade
+/// based on the fee schedule of the account.
+pub struct TradeFees;
+
+impl TradeFees {
+    /// Fees charged when the entry order is filled.
+    /// It uses the average filled price and the filled quantity if the order was filled,
+    /// otherwise the planned price and quantity.
+    pub fn opening(
+        trade: &Trade,
+        schedule: &FeeSchedule,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        TradeFees::order(&trade.entry, filled_quantity(&trade.entry), schedule)
+    }
+
+    /// Fees charged when the trade is closed by the given exit order.
+    /// Before the exit is filled it closes the quantity held by the entry.
+    pub fn closing(
+        trade: &Trade,
+        exit: &Order,
+        schedule: &FeeSchedule,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let quantity = match exit.filled_quantity {
+            0 => filled_quantity(&trade.entry),
+            filled => filled,
+        };
+        TradeFees::order(exit, quantity, schedule)
+    }
+
+    /// Fees that a trade is expected to pay if it is opened at the entry price
+    /// and closed at the stop price. This is the worst case used before funding.
+    pub fn estimated(
+        trade: &Trade,
+        schedule: &FeeSchedule,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let opening = TradeFees::opening(trade, schedule)?;
+        let closing = TradeFees::closing(trade, &trade.safety_stop, schedule)?;
+        opening
+            .checked_add(closing)
+            .ok_or_else(|| format!("Arithmetic overflow in addition: {opening} + {closing}").into())
+    }
+
+    fn order(
+        order: &Order,
+        quantity: u64,
+        schedule: &FeeSchedule,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let price = order.average_filled_price.unwrap_or(order.unit_price);
+        let quantity = Decimal::from(quantity);
+        let value = price.checked_mul(quantity).ok_or_else(|| {
+            format!("Arithmetic overflow in multiplication: {price} * {quantity}")
+        })?;
+
+        // 1. Commission of the broker
+        let commission = TradeFees::commission(value, quantity, schedule)?;
+
+        // 2. Regulatory fees are only charged when selling
+        let regulatory = match order.action {
+            OrderAction::Sell | OrderAction::Short => {
+                TradeFees::regulatory(value, quantity, schedule)?
+            }
+            OrderAction::Buy => dec!(0),
+        };
+
+        let total = commission.checked_add(regulatory).ok_or_else(|| {
+            format!("Arithmetic overflow in addition: {commission} + {regulatory}")
+        })?;
+
+        // Fees are charged in cents, rounding up.
+        Ok(total.round_dp_with_strategy(2, RoundingStrategy::AwayFromZero))
+    }
+
+    fn commission(
+        value: Decimal,
+        quantity: Decimal,
+        schedule: &FeeSchedule,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let per_share = schedule.per_share.checked_mul(quantity).ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in multiplication: {} * {quantity}",
+                schedule.per_share
+            )
+        })?;
+        let percentage = value
+            .checked_mul(schedule.percentage)
+            .and_then(|total| total.checked_div(dec!(100)))
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow calculating percentage: {value} * {}%",
+                    schedule.percentage
+                )
+            })?;
+        let commission = schedule
+            .per_order
+            .checked_add(per_share)
+            .and_then(|total| total.checked_add(percentage))
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow in addition: {} + {per_share} + {percentage}",
+                    schedule.per_order
+                )
+            })?;
+
+        let commission = commission.max(schedule.minimum);
+        Ok(match schedule.maximum {
+            Some(maximum) => commission.min(maximum),
+            None => commission,
+        })
+    }
+
+    fn regulatory(
+        value: Decimal,
+        quantity: Decimal,
+        schedule: &FeeSchedule,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let sec = value.checked_mul(schedule.sec_fee_rate).ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in multiplication: {value} * {}",
+                schedule.sec_fee_rate
+            )
+        })?;
+        let taf = schedule
+            .taf_per_share
+            .checked_mul(quantity)
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow in multiplication: {} * {quantity}",
+                    schedule.taf_per_share
+                )
+            })?;
+        let taf = if schedule.taf_maximum > dec!(0) {
+            taf.min(schedule.taf_maximum)
+        } else {
+            taf
+        };
+        sec.checked_add(taf)
+            .ok_or_else(|| format!("Arithmetic overflow in addition: {sec} + {taf}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(entry: Decimal, stop: Decimal, quantity: u64) -> Trade {
+        Trade {
+            entry: Order {
+                unit_price: entry,
+                quantity,
+                action: OrderAction::Buy,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: stop,
+                quantity,
+                action: OrderAction::Sell,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_opening_without_schedule_values() {
+        let trade = trade(dec!(40), dec!(38), 100);
+        let result = TradeFees::opening(&trade, &FeeSchedule::default());
+        assert_eq!(result.unwrap(), dec!(0));
+    }
+
+    #[test]
+    fn test_opening_per_share_with_minimum() {
+        let schedule = FeeSchedule {
+            per_share: dec!(0.005),
+            minimum: dec!(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            TradeFees::opening(&trade(dec!(40), dec!(38), 100), &schedule).unwrap(),
+            dec!(1)
+        );
+        assert_eq!(
+            TradeFees::opening(&trade(dec!(40), dec!(38), 1000), &schedule).unwrap(),
+            dec!(5)
+        );
+    }
+
+    #[test]
+    fn test_opening_percentage_with_maximum() {
+        let schedule = FeeSchedule {
+            per_order: dec!(1),
+            percentage: dec!(0.1),
+            maximum: Some(dec!(3)),
+            ..Default::default()
+        };
+        // 1 + 0.1% of 1000
+        assert_eq!(
+            TradeFees::opening(&trade(dec!(10), dec!(9), 100), &schedule).unwrap(),
+            dec!(2)
+        );
+        // 1 + 0.1% of 10000 is capped at 3
+        assert_eq!(
+            TradeFees::opening(&trade(dec!(100), dec!(90), 100), &schedule).unwrap(),
+            dec!(3)
+        );
+    }
+
+    #[test]
+    fn test_opening_uses_average_filled_price() {
+        let schedule = FeeSchedule {
+            percentage: dec!(1),
+            ..Default::default()
+        };
+        let mut trade = trade(dec!(10), dec!(9), 100);
+        trade.entry.average_filled_price = Some(dec!(12));
+        assert_eq!(TradeFees::opening(&trade, &schedule).unwrap(), dec!(12));
+    }
+
+    #[test]
+    fn test_opening_and_closing_use_filled_quantity() {
+        let schedule = FeeSchedule {
+            per_share: dec!(0.01),
+            ..Default::default()
+        };
+        let mut trade = trade(dec!(40), dec!(38), 100);
+        trade.entry.filled_quantity = 60;
+        assert_eq!(TradeFees::opening(&trade, &schedule).unwrap(), dec!(0.6));
+
+        // The exit closes what the entry filled until it is filled itself
+        assert_eq!(
+            TradeFees::closing(&trade, &trade.safety_stop, &schedule).unwrap(),
+            dec!(0.6)
+        );
+        trade.safety_stop.filled_quantity = 40;
+        assert_eq!(
+            TradeFees::closing(&trade, &trade.safety_stop, &schedule).unwrap(),
+            dec!(0.4)
+        );
+    }
+
+    #[test]
+    fn test_closing_regulatory_fees_on_sell() {
+        let schedule = FeeSchedule {
+            sec_fee_rate: dec!(0.0000278),
+            taf_per_share: dec!(0.000166),
+            taf_maximum: dec!(8.30),
+            ..Default::default()
+        };
+        let trade = trade(dec!(40), dec!(38), 1000);
+
+        // Buying does not pay regulatory fees
+        assert_eq!(TradeFees::opening(&trade, &schedule).unwrap(), dec!(0));
+
+        // SEC: 38000 * 0.0000278 = 1.0564, TAF: 1000 * 0.000166 = 0.166
+        assert_eq!(
+            TradeFees::closing(&trade, &trade.safety_stop, &schedule).unwrap(),
+            dec!(1.23)
+        );
+    }
+
+    #[test]
+    fn test_closing_taf_maximum() {
+        let schedule = FeeSchedule {
+            taf_per_share: dec!(0.000166),
+            taf_maximum: dec!(8.30),
+            ..Default::default()
+        };
+        let trade = trade(dec!(1), dec!(0.9), 100_000);
+        assert_eq!(
+            TradeFees::closing(&trade, &trade.safety_stop, &schedule).unwrap(),
+            dec!(8.30)
+        );
+    }
+
+    #[test]
+    fn test_estimated() {
+        let schedule = FeeSchedule {
+            per_order: dec!(1),
+            sec_fee_rate: dec!(0.0000278),
+            ..Default::default()
+        };
+        let trade = trade(dec!(40), dec!(38), 1000);
+        // Open: 1, Close: 1 + 1.0564
+        assert_eq!(TradeFees::estimated(&trade, &schedule).unwrap(), dec!(3.06));
+    }
+}
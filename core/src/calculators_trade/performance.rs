@@ -17,7 +17,8 @@ impl TradePerformance {
                 TransactionCategory::OpenTrade(_)
                 | TransactionCategory::FeeClose(_)
                 | TransactionCategory::FeeOpen(_)
-                | TransactionCategory::PaymentTax(_) => {
+                | TransactionCategory::PaymentTax(_)
+                | TransactionCategory::BorrowFee(_) => {
                     total = total.checked_sub(tx.amount).ok_or_else(|| {
                         format!(
                             "Arithmetic overflow in subtraction: {} - {}",
@@ -28,7 +29,9 @@ impl TradePerformance {
 
                 TransactionCategory::CloseTarget(_)
                 | TransactionCategory::CloseSafetyStop(_)
-                | TransactionCategory::CloseSafetyStopSlippage(_) => {
+                | TransactionCategory::CloseSafetyStopSlippage(_)
+                | TransactionCategory::ClosePartial(_)
+                | TransactionCategory::Dividend(_) => {
                     total = total.checked_add(tx.amount).ok_or_else(|| {
                         format!("Arithmetic overflow in addition: {} + {}", total, tx.amount)
                     })?
@@ -123,4 +126,18 @@ mod tests {
         let result = TradePerformance::calculate(Uuid::new_v4(), &mut database);
         assert_eq!(result.unwrap(), dec!(-52));
     }
+
+    #[test]
+    fn test_calculate_with_dividends_and_borrow_fees() {
+        let mut database = MockDatabase::new();
+
+        database.set_transaction(TransactionCategory::FundTrade(Uuid::new_v4()), dec!(100));
+        database.set_transaction(TransactionCategory::OpenTrade(Uuid::new_v4()), dec!(100));
+        database.set_transaction(TransactionCategory::Dividend(Uuid::new_v4()), dec!(3));
+        database.set_transaction(TransactionCategory::BorrowFee(Uuid::new_v4()), dec!(1.5));
+        database.set_transaction(TransactionCategory::CloseTarget(Uuid::new_v4()), dec!(120));
+
+        let result = TradePerformance::calculate(Uuid::new_v4(), &mut database);
+        assert_eq!(result.unwrap(), dec!(21.5));
+    }
 }
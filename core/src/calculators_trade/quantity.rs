@@ -1,30 +1,213 @@
-use model::{Currency, DatabaseFactory, RuleName};
+use model::{
+    Currency, DatabaseFactory, FeeSchedule, Order, OrderAction, PositionSizing, RuleName, Trade,
+};
 use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
 use uuid::Uuid;
 
-use crate::calculators_account::AccountCapitalAvailable;
-use crate::calculators_trade::RiskCalculator;
+use crate::calculators_account::{AccountCapitalAvailable, AccountKellyPercentage};
+use crate::calculators_trade::{RiskCalculator, TradeFees};
+
+/// Fraction of the capital that the Kelly model risks while the account has no
+/// closed trades to measure its edge, and no `RiskPerTrade` rule to size with.
+const KELLY_WITHOUT_HISTORY: Decimal = dec!(0.01);
+
+/// Fraction of the capital that the volatility model risks when the account has no
+/// `RiskPerTrade` rule to size with.
+const VOLATILITY_WITHOUT_RULES: Decimal = dec!(0.01);
 
 pub struct QuantityCalculator;
 
+/// The prices and the budgets that a quantity is sized with
+struct SizedTrade {
+    entry_price: Decimal,
+    stop_price: Decimal,
+    risk_per_share: Decimal,
+    risk_capital: Option<Decimal>,
+    available: Decimal,
+}
+
+impl SizedTrade {
+    /// Whether the quantity and its estimated fees fit in the capital and in the risk budget
+    fn fits(
+        &self,
+        quantity: i64,
+        schedule: &FeeSchedule,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let shares = u64::try_from(quantity)?;
+        let (entry, exit) = if self.entry_price >= self.stop_price {
+            (OrderAction::Buy, OrderAction::Sell)
+        } else {
+            (OrderAction::Short, OrderAction::Buy)
+        };
+        let trade = Trade {
+            entry: Order {
+                unit_price: self.entry_price,
+                quantity: shares,
+                action: entry,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: self.stop_price,
+                quantity: shares,
+                action: exit,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let fees = TradeFees::estimated(&trade, schedule)?;
+
+        let shares = Decimal::from(shares);
+        let cost = self
+            .entry_price
+            .checked_mul(shares)
+            .and_then(|cost| cost.checked_add(fees))
+            .ok_or_else(|| {
+                format!("Arithmetic overflow calculating the cost of {shares} shares")
+            })?;
+        let risk = self
+            .risk_per_share
+            .checked_mul(shares)
+            .and_then(|risk| risk.checked_add(fees))
+            .ok_or_else(|| {
+                format!("Arithmetic overflow calculating the risk of {shares} shares")
+            })?;
+        let within_budget = match self.risk_capital {
+            Some(capital) => risk <= capital,
+            None => true,
+        };
+        Ok(cost <= self.available && within_budget)
+    }
+}
+
 impl QuantityCalculator {
+    /// Returns the largest quantity that the position sizing model and the rules allow.
+    /// The estimated fees of opening the trade and closing it at the stop are paid
+    /// from the capital and the risk budget, as funding counts them.
     pub fn maximum_quantity(
         account_id: Uuid,
         entry_price: Decimal,
         stop_price: Decimal,
+        atr: Option<Decimal>,
         currency: &Currency,
         database: &mut dyn DatabaseFactory,
     ) -> Result<i64, Box<dyn std::error::Error>> {
+        let account = database.account_read().id(account_id)?;
         let total_available = AccountCapitalAvailable::calculate(
             account_id,
             currency,
             database.transaction_read().as_mut(),
         )?;
+        let risk = QuantityCalculator::risk_per_trade(account_id, currency, database)?;
+        let rules_capital = risk
+            .map(|risk| QuantityCalculator::risk_capital(total_available, risk))
+            .transpose()?;
+
+        // Fixed fractional risks everything that the rules allow.
+        let (quantity, risk_capital, risk_per_share) =
+            if account.position_sizing == PositionSizing::FixedFractional {
+                let quantity = match risk {
+                    Some(risk) => QuantityCalculator::max_quantity_per_trade(
+                        total_available,
+                        entry_price,
+                        stop_price,
+                        risk,
+                    ),
+                    None => QuantityCalculator::max_quantity(total_available, entry_price)?,
+                };
+                let risk_per_share = QuantityCalculator::risk_per_share(
+                    account.position_sizing,
+                    entry_price,
+                    stop_price,
+                    atr,
+                )?;
+                (quantity, rules_capital, risk_per_share)
+            } else {
+                // The rest of the models are capped by the capital that the rules allow to risk.
+                let model_capital = QuantityCalculator::model_risk_capital(
+                    account.position_sizing,
+                    account_id,
+                    total_available,
+                    rules_capital,
+                    database,
+                )?;
+                let risk_capital = match (model_capital, rules_capital) {
+                    (Some(model), Some(rules)) => model.min(rules),
+                    (Some(capital), None) | (None, Some(capital)) => capital,
+                    (None, None) => {
+                        return QuantityCalculator::max_quantity(total_available, entry_price)
+                    }
+                };
+                let risk_per_share = QuantityCalculator::risk_per_share(
+                    account.position_sizing,
+                    entry_price,
+                    stop_price,
+                    atr,
+                )?;
+                let quantity = QuantityCalculator::quantity_for_risk_capital(
+                    total_available,
+                    entry_price,
+                    risk_per_share,
+                    risk_capital,
+                );
+                (quantity, Some(risk_capital), risk_per_share)
+            };
+
+        match database
+            .fee_schedule_read()
+            .fee_schedule_for_account(account_id)?
+        {
+            Some(schedule) => QuantityCalculator::quantity_with_fees(
+                quantity,
+                &SizedTrade {
+                    entry_price,
+                    stop_price,
+                    risk_per_share,
+                    risk_capital,
+                    available: total_available,
+                },
+                &schedule,
+            ),
+            None => Ok(quantity),
+        }
+    }
 
+    /// Returns the largest quantity up to the given one whose estimated fees fit
+    /// in the capital and in the risk budget together with the trade.
+    fn quantity_with_fees(
+        quantity: i64,
+        trade: &SizedTrade,
+        schedule: &FeeSchedule,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        if quantity <= 0 || trade.fits(quantity, schedule)? {
+            return Ok(quantity.max(0));
+        }
+
+        // The fees grow with the quantity, so the largest quantity that fits is searched
+        // between none and the quantity sized without fees.
+        let (mut low, mut high) = (0_i64, quantity);
+        while high.saturating_sub(low) > 1 {
+            let middle = low.saturating_add(high.saturating_sub(low).checked_div(2).unwrap_or(0));
+            if trade.fits(middle, schedule)? {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Returns the percentage that can be risked in a trade according to the rules.
+    /// If the monthly risk is exhausted, the percentage is 0.
+    /// If the account does not have a `RiskPerTrade` rule, it returns None.
+    fn risk_per_trade(
+        account_id: Uuid,
+        currency: &Currency,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<Option<f32>, Box<dyn std::error::Error>> {
         // Get rules by priority
         let mut rules = database.rule_read().read_all_rules(account_id)?;
-        rules.sort_by(|a, b| a.priority.cmp(&b.priority));
+        rules.sort_by_key(|a| a.priority);
 
         let mut risk_per_month = dec!(100.0); // Default to 100% of the available capital
 
@@ -41,29 +224,135 @@ impl QuantityCalculator {
                     let risk_decimal = Decimal::from_f32_retain(risk)
                         .ok_or_else(|| format!("Failed to convert risk {risk} to Decimal"))?;
                     if risk_per_month < risk_decimal {
-                        return Ok(0); // No capital to risk this month, so quantity is 0. AKA: No trade.
-                    } else {
-                        let risk_per_trade = QuantityCalculator::max_quantity_per_trade(
-                            total_available,
-                            entry_price,
-                            stop_price,
-                            risk,
-                        );
-                        return Ok(risk_per_trade);
+                        return Ok(Some(0.0)); // No capital to risk this month, so quantity is 0. AKA: No trade.
                     }
+                    return Ok(Some(risk));
                 }
+                RuleName::MaxOpenRisk(_) => {} // Checked when the trade is funded.
             }
         }
 
-        // If there are no rules, return the maximum quantity based on available funds
-        let max_quantity = total_available.checked_div(entry_price).ok_or_else(|| {
-            format!("Division by zero or overflow: {total_available} / {entry_price}")
+        Ok(None)
+    }
+
+    /// Returns the capital that a position sizing model wants to risk.
+    /// Models that do not define their own budget return None.
+    fn model_risk_capital(
+        position_sizing: PositionSizing,
+        account_id: Uuid,
+        available: Decimal,
+        rules_capital: Option<Decimal>,
+        database: &mut dyn DatabaseFactory,
+    ) -> Result<Option<Decimal>, Box<dyn std::error::Error>> {
+        match position_sizing {
+            PositionSizing::FixedFractional => Ok(None),
+            PositionSizing::Volatility(_) => match rules_capital {
+                Some(_) => Ok(None),
+                None => QuantityCalculator::fraction_of(available, VOLATILITY_WITHOUT_RULES),
+            },
+            PositionSizing::FixedDollar(amount) => Ok(Some(amount)),
+            PositionSizing::Kelly(fraction) => {
+                let Some(kelly) =
+                    AccountKellyPercentage::calculate(account_id, database.trade_read().as_mut())?
+                else {
+                    // Without closed trades there is no win rate yet, so a new account
+                    // sizes with its rules, or with the minimum fraction without them.
+                    return match rules_capital {
+                        Some(_) => Ok(None),
+                        None => QuantityCalculator::fraction_of(available, KELLY_WITHOUT_HISTORY),
+                    };
+                };
+                let capital = available
+                    .checked_mul(kelly)
+                    .and_then(|capital| capital.checked_mul(fraction))
+                    .ok_or_else(|| {
+                        format!("Arithmetic overflow in multiplication: {available} * {kelly} * {fraction}")
+                    })?;
+                Ok(Some(capital))
+            }
+        }
+    }
+
+    /// Returns the capital that a model risks when there is no budget to size with
+    fn fraction_of(
+        available: Decimal,
+        fraction: Decimal,
+    ) -> Result<Option<Decimal>, Box<dyn std::error::Error>> {
+        available.checked_mul(fraction).map(Some).ok_or_else(|| {
+            format!("Arithmetic overflow in multiplication: {available} * {fraction}").into()
+        })
+    }
+
+    /// Returns the money at risk for each share of the trade.
+    /// Volatility sizing risks the ATR distance, or the distance to the stop when
+    /// it is wider, as the stop is where the trade is closed.
+    fn risk_per_share(
+        position_sizing: PositionSizing,
+        entry_price: Decimal,
+        stop_price: Decimal,
+        atr: Option<Decimal>,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let price_diff = entry_price.checked_sub(stop_price).ok_or_else(|| {
+            format!("Arithmetic overflow in subtraction: {entry_price} - {stop_price}")
         })?;
+
+        match position_sizing {
+            PositionSizing::Volatility(multiplier) if price_diff > dec!(0) => {
+                let atr = atr.ok_or("Volatility position sizing requires the ATR of the trade")?;
+                let atr_distance = atr.checked_mul(multiplier).ok_or_else(|| {
+                    format!("Arithmetic overflow in multiplication: {atr} * {multiplier}")
+                })?;
+                Ok(atr_distance.max(price_diff))
+            }
+            _ => Ok(price_diff),
+        }
+    }
+
+    fn risk_capital(available: Decimal, risk: f32) -> Result<Decimal, Box<dyn std::error::Error>> {
+        let risk_decimal = Decimal::from_f32_retain(risk)
+            .ok_or_else(|| format!("Failed to convert risk {risk} to Decimal"))?;
+        let risk_percent = risk_decimal
+            .checked_div(dec!(100.0))
+            .ok_or_else(|| format!("Division error: {risk_decimal} / 100"))?;
+        available.checked_mul(risk_percent).ok_or_else(|| {
+            format!("Arithmetic overflow in multiplication: {available} * {risk_percent}").into()
+        })
+    }
+
+    /// Returns the maximum quantity based on available funds
+    fn max_quantity(
+        available: Decimal,
+        entry_price: Decimal,
+    ) -> Result<i64, Box<dyn std::error::Error>> {
+        let max_quantity = available
+            .checked_div(entry_price)
+            .ok_or_else(|| format!("Division by zero or overflow: {available} / {entry_price}"))?;
         max_quantity
             .to_i64()
             .ok_or_else(|| format!("Cannot convert {max_quantity} to i64").into())
     }
 
+    fn quantity_for_risk_capital(
+        available: Decimal,
+        entry_price: Decimal,
+        risk_per_share: Decimal,
+        risk_capital: Decimal,
+    ) -> i64 {
+        if available <= dec!(0.0) || risk_per_share <= dec!(0.0) || risk_capital <= dec!(0.0) {
+            return 0;
+        }
+
+        let Some(max_quantity) = available.checked_div(entry_price) else {
+            return 0; // Division overflow
+        };
+
+        let Some(quantity) = risk_capital.checked_div(risk_per_share) else {
+            return 0; // Division overflow
+        };
+
+        quantity.min(max_quantity).to_i64().unwrap_or(0) // We round down to the nearest integer
+    }
+
     fn max_quantity_per_trade(
         available: Decimal,
         entry_price: Decimal,
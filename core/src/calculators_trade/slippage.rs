@@ -0,0 +1,153 @@
+use model::{Order, OrderAction, OrderSlippage, SlippageLeg, Trade};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Calculates the slippage of the filled orders of a trade.
+pub struct TradeSlippage;
+
+impl TradeSlippage {
+    /// Returns the slippage of every order of the trade that has been filled.
+    /// Orders that were not filled, like the stop of a trade closed at the target, are skipped.
+    pub fn calculate(trade: &Trade) -> Result<Vec<OrderSlippage>, Box<dyn std::error::Error>> {
+        let legs = [
+            (SlippageLeg::Entry, &trade.entry),
+            (SlippageLeg::Stop, &trade.safety_stop),
+            (SlippageLeg::Target, &trade.target),
+        ];
+
+        let mut slippages = Vec::new();
+        for (leg, order) in legs {
+            if let Some(slippage) = TradeSlippage::order(trade, leg, order)? {
+                slippages.push(slippage);
+            }
+        }
+        Ok(slippages)
+    }
+
+    fn order(
+        trade: &Trade,
+        leg: SlippageLeg,
+        order: &Order,
+    ) -> Result<Option<OrderSlippage>, Box<dyn std::error::Error>> {
+        let Some(filled_price) = order.average_filled_price else {
+            return Ok(None);
+        };
+        if order.unit_price.is_zero() {
+            return Ok(None);
+        }
+
+        // 1. Slippage per share, positive when the fill was worse than planned
+        let per_share = match order.action {
+            OrderAction::Buy => filled_price.checked_sub(order.unit_price),
+            OrderAction::Sell | OrderAction::Short => order.unit_price.checked_sub(filled_price),
+        }
+        .ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in subtraction: {} - {filled_price}",
+                order.unit_price
+            )
+        })?;
+
+        // 2. Slippage of the whole order
+        let quantity = if order.filled_quantity > 0 {
+            order.filled_quantity
+        } else {
+            order.quantity
+        };
+        let amount = per_share
+            .checked_mul(Decimal::from(quantity))
+            .ok_or_else(|| {
+                format!("Arithmetic overflow in multiplication: {per_share} * {quantity}")
+            })?;
+
+        // 3. Slippage in basis points of the planned price
+        let bps = per_share
+            .checked_mul(dec!(10000))
+            .and_then(|total| total.checked_div(order.unit_price))
+            .ok_or_else(|| {
+                format!(
+                    "Arithmetic overflow calculating basis points: {per_share} / {}",
+                    order.unit_price
+                )
+            })?
+            .round_dp(2);
+
+        Ok(Some(OrderSlippage {
+            trade_id: trade.id,
+            leg,
+            symbol: trade.trading_vehicle.symbol.clone(),
+            category: order.category,
+            currency: trade.currency,
+            filled_at: order.filled_at,
+            planned_price: order.unit_price,
+            filled_price,
+            quantity,
+            amount,
+            bps,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::OrderCategory;
+
+    fn order(action: OrderAction, planned: Decimal, filled: Option<Decimal>) -> Order {
+        Order {
+            unit_price: planned,
+            average_filled_price: filled,
+            quantity: 100,
+            filled_quantity: if filled.is_some() { 100 } else { 0 },
+            action,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_calculate_long_trade_closed_at_stop() {
+        let trade = Trade {
+            entry: order(OrderAction::Buy, dec!(40), Some(dec!(40.1))),
+            safety_stop: Order {
+                category: OrderCategory::Stop,
+                ..order(OrderAction::Sell, dec!(38), Some(dec!(37.62)))
+            },
+            target: order(OrderAction::Sell, dec!(50), None),
+            ..Default::default()
+        };
+
+        let result = TradeSlippage::calculate(&trade).unwrap();
+
+        assert_eq!(result.len(), 2);
+        let entry = result.first().unwrap();
+        assert_eq!(entry.leg, SlippageLeg::Entry);
+        assert_eq!(entry.amount, dec!(10));
+        assert_eq!(entry.bps, dec!(25));
+
+        let stop = result.get(1).unwrap();
+        assert_eq!(stop.leg, SlippageLeg::Stop);
+        assert_eq!(stop.category, OrderCategory::Stop);
+        assert_eq!(stop.amount, dec!(38));
+        assert_eq!(stop.bps, dec!(100));
+    }
+
+    #[test]
+    fn test_calculate_favorable_fill_is_negative() {
+        let trade = Trade {
+            entry: order(OrderAction::Short, dec!(20), Some(dec!(20.5))),
+            ..Default::default()
+        };
+
+        let result = TradeSlippage::calculate(&trade).unwrap();
+
+        let entry = result.first().unwrap();
+        assert_eq!(entry.amount, dec!(-50));
+        assert_eq!(entry.bps, dec!(-250));
+    }
+
+    #[test]
+    fn test_calculate_without_fills() {
+        let trade = Trade::default();
+        assert!(TradeSlippage::calculate(&trade).unwrap().is_empty());
+    }
+}
@@ -1,5 +1,10 @@
+pub mod account;
+pub mod activity;
 pub mod balance;
+pub mod doctor;
+pub mod fee;
 pub mod order;
+pub mod price;
 pub mod rule;
 pub mod trade;
 pub mod transaction;
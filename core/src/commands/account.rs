@@ -0,0 +1,25 @@
+use model::{Account, DatabaseFactory, PositionSizing};
+
+pub fn update_position_sizing(
+    account: &Account,
+    position_sizing: PositionSizing,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Account, Box<dyn std::error::Error>> {
+    crate::validators::account::can_use_position_sizing(&position_sizing)?;
+    database
+        .account_write()
+        .update_position_sizing(account, position_sizing)
+}
+
+pub fn update_benchmark(
+    account: &Account,
+    benchmark: Option<&str>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Account, Box<dyn std::error::Error>> {
+    if let Some(symbol) = benchmark {
+        crate::validators::account::can_use_benchmark(symbol)?;
+    }
+    database
+        .account_write()
+        .update_benchmark(account, benchmark)
+}
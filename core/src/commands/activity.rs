@@ -0,0 +1,513 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use model::{
+    Account, ActivityCategory, ActivityDiscrepancy, ActivityImport, BrokerActivity, Currency,
+    DatabaseFactory, Order, OrderStatus, Status, Trade, TradeCategory, TransactionCategory,
+};
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::error::Error;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::commands;
+
+/// Days between the date of an activity and a transaction recorded by hand for it
+const RECORDED_WITHIN_DAYS: u64 = 7;
+
+/// How an activity that was not imported before is reconciled with what Trust recorded
+enum Outcome {
+    /// A new transaction was created for the activity
+    Created(model::Transaction),
+    /// Trust had already recorded the activity, in a transaction or in a filled order
+    Matched(Option<Uuid>),
+}
+
+/// Imports the activities of the broker into an account, oldest first.
+///
+/// Activities that were imported before are skipped by their ID. Deposits, withdrawals,
+/// interest, dividends and borrow fees become transactions, unless a transaction of the
+/// same category and amount that no activity is linked to was already recorded by hand
+/// within a week of the activity.
+/// Fills are compared with the orders of the trades. The activities that differ from
+/// what Trust recorded are returned as discrepancies and are not marked as imported.
+pub fn import(
+    account: &Account,
+    mut activities: Vec<BrokerActivity>,
+    database: &mut dyn DatabaseFactory,
+) -> Result<ActivityImport, Box<dyn Error>> {
+    activities.sort_by_key(|activity| activity.date);
+    let imported = database.activity_read().imported_activities(account.id)?;
+    let mut seen: HashSet<String> = imported.iter().map(|a| a.activity_id.clone()).collect();
+    let mut linked: Vec<Uuid> = imported.iter().filter_map(|a| a.transaction_id).collect();
+
+    let mut result = ActivityImport::default();
+    for activity in activities {
+        if !seen.insert(activity.id.clone()) {
+            result.duplicates = result.duplicates.saturating_add(1);
+            continue;
+        }
+        match reconcile(&activity, account, &linked, database) {
+            Ok(Outcome::Created(transaction)) => {
+                database.activity_write().create_imported_activity(
+                    account,
+                    &activity.id,
+                    Some(transaction.id),
+                )?;
+                linked.push(transaction.id);
+                result.transactions.push(transaction);
+            }
+            Ok(Outcome::Matched(transaction_id)) => {
+                database.activity_write().create_imported_activity(
+                    account,
+                    &activity.id,
+                    transaction_id,
+                )?;
+                linked.extend(transaction_id);
+                result.matched.push(activity);
+            }
+            Err(error) => result.discrepancies.push(ActivityDiscrepancy {
+                activity,
+                reason: error.to_string(),
+            }),
+        }
+    }
+    Ok(result)
+}
+
+fn reconcile(
+    activity: &BrokerActivity,
+    account: &Account,
+    linked: &[Uuid],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Outcome, Box<dyn Error>> {
+    match activity.category {
+        ActivityCategory::Deposit => cash(
+            activity,
+            TransactionCategory::Deposit,
+            activity.amount,
+            account,
+            linked,
+            database,
+        ),
+        ActivityCategory::Withdrawal => cash(
+            activity,
+            TransactionCategory::Withdrawal,
+            activity.amount.abs(),
+            account,
+            linked,
+            database,
+        ),
+        ActivityCategory::Interest => cash(
+            activity,
+            TransactionCategory::Interest,
+            activity.amount,
+            account,
+            linked,
+            database,
+        ),
+        ActivityCategory::Dividend => {
+            let trade = find_trade(activity, account, database)?;
+            let category = TransactionCategory::Dividend(trade.id);
+            for_trade(
+                activity,
+                &trade,
+                category,
+                activity.amount,
+                linked,
+                database,
+            )
+        }
+        ActivityCategory::Fee => fee(activity, account, linked, database),
+        ActivityCategory::Fill => fill(activity, account, database),
+        ActivityCategory::Other => {
+            Err(format!("Trust does not import {} activities", activity.description).into())
+        }
+    }
+}
+
+fn cash(
+    activity: &BrokerActivity,
+    category: TransactionCategory,
+    amount: Decimal,
+    account: &Account,
+    linked: &[Uuid],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Outcome, Box<dyn Error>> {
+    if let Some(id) = find_recorded(
+        account.id,
+        &activity.currency,
+        category,
+        amount,
+        activity.date,
+        linked,
+        database,
+    )? {
+        return Ok(Outcome::Matched(Some(id)));
+    }
+    let (transaction, _) =
+        commands::transaction::create(database, &category, amount, &activity.currency, account.id)?;
+    Ok(Outcome::Created(transaction))
+}
+
+/// Only the borrow fees of short trades are imported.
+/// The commissions are recorded when the orders are filled, from the fee schedule of the account.
+fn fee(
+    activity: &BrokerActivity,
+    account: &Account,
+    linked: &[Uuid],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Outcome, Box<dyn Error>> {
+    let trade = find_trade(activity, account, database)?;
+    if trade.category != TradeCategory::Short {
+        return Err(format!(
+            "The broker charged a fee of {} for the long trade {}. Trust records the fees of long trades from the fee schedule of the account",
+            activity.amount.abs(),
+            trade.id
+        )
+        .into());
+    }
+    let category = TransactionCategory::BorrowFee(trade.id);
+    for_trade(
+        activity,
+        &trade,
+        category,
+        activity.amount.abs(),
+        linked,
+        database,
+    )
+}
+
+fn for_trade(
+    activity: &BrokerActivity,
+    trade: &Trade,
+    category: TransactionCategory,
+    amount: Decimal,
+    linked: &[Uuid],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Outcome, Box<dyn Error>> {
+    if let Some(id) = find_recorded(
+        trade.account_id,
+        &trade.currency,
+        category,
+        amount,
+        activity.date,
+        linked,
+        database,
+    )? {
+        return Ok(Outcome::Matched(Some(id)));
+    }
+    let (transaction, _, _) =
+        commands::transaction::create_for_trade(trade, &category, amount, database)?;
+    Ok(Outcome::Created(transaction))
+}
+
+/// A transaction of the same category and amount, recorded within a week of the
+/// activity, that is not linked to an activity yet
+fn find_recorded(
+    account_id: Uuid,
+    currency: &Currency,
+    category: TransactionCategory,
+    amount: Decimal,
+    date: NaiveDateTime,
+    linked: &[Uuid],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Option<Uuid>, Box<dyn Error>> {
+    Ok(database
+        .transaction_read()
+        .all_transactions(account_id, currency)?
+        .into_iter()
+        .find(|t| {
+            t.category == category
+                && t.amount == amount
+                && is_near(t.created_at, date)
+                && !linked.contains(&t.id)
+        })
+        .map(|t| t.id))
+}
+
+fn is_near(recorded: NaiveDateTime, date: NaiveDateTime) -> bool {
+    recorded
+        .signed_duration_since(date)
+        .num_days()
+        .unsigned_abs()
+        <= RECORDED_WITHIN_DAYS
+}
+
+/// The trade of the symbol with the latest entry filled before the activity.
+/// Trades that are open or were closed, by the broker or by hand, held a position.
+fn find_trade(
+    activity: &BrokerActivity,
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Trade, Box<dyn Error>> {
+    let symbol = activity
+        .symbol
+        .as_deref()
+        .ok_or_else(|| format!("The {} has no symbol", activity.category))?;
+    let mut trades = Vec::new();
+    for status in [
+        Status::PartiallyFilled,
+        Status::Filled,
+        Status::ClosedTarget,
+        Status::ClosedStopLoss,
+        Status::Canceled,
+    ] {
+        trades.extend(
+            database
+                .trade_read()
+                .read_trades_with_status(account.id, status)?,
+        );
+    }
+    trades
+        .into_iter()
+        .filter(|trade| trade.trading_vehicle.symbol.eq_ignore_ascii_case(symbol))
+        .filter(|trade| matches!(trade.entry.filled_at, Some(filled) if filled <= activity.date))
+        .max_by_key(|trade| trade.entry.filled_at)
+        .ok_or_else(|| {
+            format!(
+                "No trade of {symbol} had a position on {}",
+                activity.date.date()
+            )
+            .into()
+        })
+}
+
+fn fill(
+    activity: &BrokerActivity,
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Outcome, Box<dyn Error>> {
+    let order_id = activity.order_id.ok_or("The fill has no order ID")?;
+    let order = find_order(order_id, account, database)?
+        .ok_or_else(|| format!("The order {order_id} of the fill is not part of any trade"))?;
+
+    if !matches!(
+        order.status,
+        OrderStatus::Filled | OrderStatus::PartiallyFilled
+    ) {
+        return Err(format!(
+            "The broker filled {} at {}, but Trust has the order {order_id} as {:?}. Sync its trade to record the fill",
+            activity.quantity, activity.amount, order.status
+        )
+        .into());
+    }
+    // A fill of the whole order must have the average price that Trust recorded
+    let whole = Decimal::from(order.filled_quantity) == activity.quantity;
+    match order.average_filled_price {
+        Some(price) if whole && price != activity.amount => Err(format!(
+            "The broker filled the order {order_id} at {}, but Trust has it filled at {price}",
+            activity.amount
+        )
+        .into()),
+        _ => Ok(Outcome::Matched(None)),
+    }
+}
+
+fn find_order(
+    broker_order_id: Uuid,
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Option<Order>, Box<dyn Error>> {
+    for status in Status::all() {
+        for trade in database
+            .trade_read()
+            .read_trades_with_status(account.id, status)?
+        {
+            if let Some(order) = [trade.entry, trade.safety_stop, trade.target]
+                .into_iter()
+                .find(|order| order.broker_order_id == Some(broker_order_id))
+            {
+                return Ok(Some(order));
+            }
+        }
+    }
+    Ok(None)
+}
+
+struct Columns {
+    id: usize,
+    date: usize,
+    category: usize,
+    amount: usize,
+    symbol: Option<usize>,
+    order_id: Option<usize>,
+    quantity: Option<usize>,
+    description: Option<usize>,
+}
+
+/// Reads the activities of a CSV with a header row, for brokers that can not list them.
+/// The columns are found by name: ID, Date, Type, Amount and the optional Symbol,
+/// Order ID, Quantity and Description. The type is deposit, withdrawal, dividend,
+/// interest, fee or fill; other types are read so they are reported as not imported.
+/// Amounts are positive when paid into the account. The amount of a fill is its price.
+pub fn parse(csv: &str, currency: &Currency) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+    let mut lines = csv.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines.next().ok_or("The CSV is empty")?;
+    let columns = parse_header(header)?;
+
+    (2..)
+        .zip(lines)
+        .map(|(line_number, line)| {
+            parse_line(&columns, line, currency)
+                .map_err(|error| format!("Line {line_number} of the CSV: {error}").into())
+        })
+        .collect()
+}
+
+fn parse_header(header: &str) -> Result<Columns, Box<dyn Error>> {
+    let names: Vec<String> = header
+        .split(',')
+        .map(|name| name.trim().trim_matches('"').to_lowercase())
+        .collect();
+    let position = |name: &str| -> Result<usize, Box<dyn Error>> {
+        names
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| format!("The CSV does not have a {name} column").into())
+    };
+
+    Ok(Columns {
+        id: position("id")?,
+        date: position("date")?,
+        category: position("type")?,
+        amount: position("amount")?,
+        symbol: position("symbol").ok(),
+        order_id: position("order id").ok(),
+        quantity: position("quantity").ok(),
+        description: position("description").ok(),
+    })
+}
+
+fn parse_line(
+    columns: &Columns,
+    line: &str,
+    currency: &Currency,
+) -> Result<BrokerActivity, Box<dyn Error>> {
+    let values: Vec<&str> = line
+        .split(',')
+        .map(|value| value.trim().trim_matches('"'))
+        .collect();
+    let value = |column: usize| -> Result<&str, Box<dyn Error>> {
+        values
+            .get(column)
+            .copied()
+            .ok_or_else(|| "missing columns".into())
+    };
+    let optional = |column: Option<usize>| -> Option<&str> {
+        column
+            .and_then(|column| values.get(column).copied())
+            .filter(|value| !value.is_empty())
+    };
+    let decimal = |value: &str| -> Result<Decimal, Box<dyn Error>> {
+        Decimal::from_str(value).map_err(|_| format!("'{value}' is not a number").into())
+    };
+
+    let category = value(columns.category)?.to_lowercase();
+    Ok(BrokerActivity {
+        id: value(columns.id)?.to_string(),
+        date: parse_date(value(columns.date)?)?,
+        category: ActivityCategory::from_str(&category).unwrap_or(ActivityCategory::Other),
+        description: optional(columns.description)
+            .unwrap_or(&category)
+            .to_string(),
+        symbol: optional(columns.symbol).map(str::to_uppercase),
+        amount: decimal(value(columns.amount)?)?,
+        currency: *currency,
+        order_id: optional(columns.order_id)
+            .map(|id| Uuid::parse_str(id).map_err(|_| format!("'{id}' is not an order ID")))
+            .transpose()?,
+        quantity: optional(columns.quantity)
+            .map(decimal)
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn parse_date(date: &str) -> Result<NaiveDateTime, Box<dyn Error>> {
+    NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| format!("'{date}' is not a date like 2024-01-31").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_activities() {
+        let csv = "ID,Date,Type,Amount,Symbol,Order ID,Quantity\n\
+                   1,2024-01-02,Deposit,1000,,,\n\
+                   2,2024-01-03T15:30:00,fill,185.5,aapl,904837e3-3b76-47ec-b432-046db621571b,10\n\
+                   3,2024-01-04,split,0,AAPL,,\n";
+
+        let activities = parse(csv, &Currency::USD).unwrap();
+
+        assert_eq!(activities.len(), 3);
+        let deposit = activities.first().unwrap();
+        assert_eq!(deposit.id, "1");
+        assert_eq!(deposit.category, ActivityCategory::Deposit);
+        assert_eq!(deposit.amount, dec!(1000));
+        assert_eq!(deposit.symbol, None);
+        assert_eq!(deposit.currency, Currency::USD);
+        let fill = activities.get(1).unwrap();
+        assert_eq!(fill.category, ActivityCategory::Fill);
+        assert_eq!(fill.symbol, Some("AAPL".to_string()));
+        assert_eq!(fill.quantity, dec!(10));
+        assert_eq!(
+            fill.order_id,
+            Some(Uuid::parse_str("904837e3-3b76-47ec-b432-046db621571b").unwrap())
+        );
+        assert_eq!(
+            fill.date,
+            NaiveDate::from_ymd_opt(2024, 1, 3)
+                .unwrap()
+                .and_hms_opt(15, 30, 0)
+                .unwrap()
+        );
+        let split = activities.last().unwrap();
+        assert_eq!(split.category, ActivityCategory::Other);
+        assert_eq!(split.description, "split");
+    }
+
+    #[test]
+    fn test_is_near() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 10)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let day = |day: u32| {
+            NaiveDate::from_ymd_opt(2024, 1, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+        };
+
+        assert!(is_near(day(10), date));
+        assert!(is_near(day(3), date));
+        assert!(is_near(day(17), date));
+        assert!(!is_near(day(2), date));
+        assert!(!is_near(day(18), date));
+    }
+
+    #[test]
+    fn test_parse_activities_errors() {
+        let error = parse("ID,Date,Amount\n1,2024-01-02,10", &Currency::USD)
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "The CSV does not have a type column");
+
+        let csv = "ID,Date,Type,Amount\n1,02/01/2024,deposit,10";
+        let error = parse(csv, &Currency::USD).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Line 2 of the CSV: '02/01/2024' is not a date like 2024-01-31"
+        );
+    }
+}
@@ -3,8 +3,8 @@ use std::error::Error;
 
 use crate::{
     calculators_account::{
-        AccountCapitalAvailable, AccountCapitalBalance, AccountCapitalInApprovedTrades,
-        AccountCapitalTaxable,
+        AccountCapitalAvailable, AccountCapitalBalance, AccountCapitalEarnings,
+        AccountCapitalInApprovedTrades, AccountCapitalTaxable,
     },
     calculators_trade::{TradeCapitalFunded, TradeCapitalInMarket},
     calculators_trade::{TradeCapitalOutOfMarket, TradeCapitalTaxable, TradePerformance},
@@ -14,6 +14,25 @@ pub fn calculate_account(
     database: &mut dyn DatabaseFactory,
     account: &Account,
     currency: &Currency,
+) -> Result<AccountBalance, Box<dyn Error>> {
+    let balance = recalculate_account(database, account, currency)?;
+
+    database.account_balance_write().update(
+        &balance,
+        balance.total_balance,
+        balance.total_in_trade,
+        balance.total_available,
+        balance.taxed,
+        balance.total_earnings,
+    )
+}
+
+/// Calculates the balance of an account from its transactions without saving it.
+/// The returned balance is the stored one with the recalculated amounts.
+pub fn recalculate_account(
+    database: &mut dyn DatabaseFactory,
+    account: &Account,
+    currency: &Currency,
 ) -> Result<AccountBalance, Box<dyn Error>> {
     let total_available = AccountCapitalAvailable::calculate(
         account.id,
@@ -35,23 +54,47 @@ pub fn calculate_account(
         currency,
         database.transaction_read().as_mut(),
     )?;
+    let total_earnings = AccountCapitalEarnings::calculate(
+        account.id,
+        currency,
+        database.transaction_read().as_mut(),
+    )?;
 
     let balance = database
         .account_balance_read()
         .for_currency(account.id, currency)?;
 
-    database.account_balance_write().update(
-        &balance,
+    Ok(AccountBalance {
         total_balance,
         total_in_trade,
         total_available,
         taxed,
-    )
+        total_earnings,
+        ..balance
+    })
 }
 
 pub fn calculate_trade(
     database: &mut dyn DatabaseFactory,
     trade: &Trade,
+) -> Result<TradeBalance, Box<dyn Error>> {
+    let balance = recalculate_trade(database, trade)?;
+
+    database.trade_balance_write().update_trade_balance(
+        trade,
+        balance.funding,
+        balance.capital_in_market,
+        balance.capital_out_market,
+        balance.taxed,
+        balance.total_performance,
+    )
+}
+
+/// Calculates the balance of a trade from its transactions without saving it.
+/// The returned balance is the stored one with the recalculated amounts.
+pub fn recalculate_trade(
+    database: &mut dyn DatabaseFactory,
+    trade: &Trade,
 ) -> Result<TradeBalance, Box<dyn Error>> {
     let funding = TradeCapitalFunded::calculate(trade.id, database.transaction_read().as_mut())?;
     let capital_in_market =
@@ -62,12 +105,12 @@ pub fn calculate_trade(
     let total_performance =
         TradePerformance::calculate(trade.id, database.transaction_read().as_mut())?;
 
-    database.trade_balance_write().update_trade_balance(
-        trade,
+    Ok(TradeBalance {
         funding,
         capital_in_market,
         capital_out_market,
         taxed,
         total_performance,
-    )
+        ..trade.balance.clone()
+    })
 }
@@ -0,0 +1,377 @@
+use crate::commands::balance;
+use model::{
+    Account, Currency, DatabaseFactory, Inconsistency, InconsistencyCategory, Order, OrderStatus,
+    Status, Trade,
+};
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Checks the data of all the accounts and returns every inconsistency found.
+/// Nothing is written to the database.
+pub fn diagnose(database: &mut dyn DatabaseFactory) -> Result<Vec<Inconsistency>, Box<dyn Error>> {
+    let mut inconsistencies = Vec::new();
+    let mut trades = Vec::new();
+
+    for account in database.account_read().all()? {
+        // 1. Compare the cached account balances with the transactions
+        inconsistencies.extend(account_balances(&account, database)?);
+
+        // 2. Compare the cached trade balances and the status of the trades with their orders
+        let account_trades = all_trades(&account, database)?;
+        for trade in &account_trades {
+            inconsistencies.extend(trade_balance(trade, database)?);
+            inconsistencies.extend(trade_status(trade));
+        }
+
+        // 3. Find transactions of trades that do not belong to the account
+        inconsistencies.extend(orphan_transactions(&account, &account_trades, database)?);
+        trades.extend(account_trades);
+    }
+
+    // 4. Find orders that do not belong to any trade
+    inconsistencies.extend(orphan_orders(&trades, database)?);
+
+    Ok(inconsistencies)
+}
+
+/// Repairs the inconsistencies that can be fixed by recalculating a cache.
+/// Returns the number of inconsistencies that were repaired.
+pub fn repair(
+    inconsistencies: &[Inconsistency],
+    database: &mut dyn DatabaseFactory,
+) -> Result<usize, Box<dyn Error>> {
+    let mut repaired: usize = 0;
+    for inconsistency in inconsistencies {
+        match inconsistency.category {
+            InconsistencyCategory::TradeBalance => {
+                let trade = database.trade_read().read_trade(inconsistency.entity_id)?;
+                balance::calculate_trade(database, &trade)?;
+            }
+            InconsistencyCategory::AccountBalance => {
+                let account = database.account_read().id(inconsistency.entity_id)?;
+                let currency = inconsistency
+                    .currency
+                    .ok_or("Account balance inconsistency without currency")?;
+                balance::calculate_account(database, &account, &currency)?;
+            }
+            _ => continue,
+        }
+        repaired = repaired.saturating_add(1);
+    }
+    Ok(repaired)
+}
+
+fn all_trades(
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Trade>, Box<dyn Error>> {
+    let mut trades = Vec::new();
+    for status in Status::all() {
+        trades.extend(
+            database
+                .trade_read()
+                .read_trades_with_status(account.id, status)?,
+        );
+    }
+    Ok(trades)
+}
+
+fn account_balances(
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Inconsistency>, Box<dyn Error>> {
+    let mut inconsistencies = Vec::new();
+    for cached in database.account_balance_read().for_account(account.id)? {
+        let expected = balance::recalculate_account(database, account, &cached.currency)?;
+        let drift = drift(&[
+            (
+                "total_balance",
+                cached.total_balance,
+                expected.total_balance,
+            ),
+            (
+                "total_in_trade",
+                cached.total_in_trade,
+                expected.total_in_trade,
+            ),
+            (
+                "total_available",
+                cached.total_available,
+                expected.total_available,
+            ),
+            ("taxed", cached.taxed, expected.taxed),
+            (
+                "total_earnings",
+                cached.total_earnings,
+                expected.total_earnings,
+            ),
+        ]);
+        if !drift.is_empty() {
+            inconsistencies.push(Inconsistency {
+                category: InconsistencyCategory::AccountBalance,
+                account_id: Some(account.id),
+                entity_id: account.id,
+                currency: Some(cached.currency),
+                message: format!(
+                    "Balance of account {} in {} is out of sync: {drift}",
+                    account.name, cached.currency
+                ),
+            });
+        }
+    }
+    Ok(inconsistencies)
+}
+
+fn trade_balance(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Option<Inconsistency>, Box<dyn Error>> {
+    let cached = &trade.balance;
+    let expected = balance::recalculate_trade(database, trade)?;
+    let drift = drift(&[
+        ("funding", cached.funding, expected.funding),
+        (
+            "capital_in_market",
+            cached.capital_in_market,
+            expected.capital_in_market,
+        ),
+        (
+            "capital_out_market",
+            cached.capital_out_market,
+            expected.capital_out_market,
+        ),
+        ("taxed", cached.taxed, expected.taxed),
+        (
+            "total_performance",
+            cached.total_performance,
+            expected.total_performance,
+        ),
+    ]);
+    if drift.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Inconsistency {
+        category: InconsistencyCategory::TradeBalance,
+        account_id: Some(trade.account_id),
+        entity_id: trade.id,
+        currency: Some(trade.currency),
+        message: format!(
+            "Balance of trade {} ({}) is out of sync: {drift}",
+            trade.id, trade.trading_vehicle.symbol
+        ),
+    }))
+}
+
+// Returns a description of the fields whose cached value differs from the expected one.
+fn drift(fields: &[(&str, Decimal, Decimal)]) -> String {
+    fields
+        .iter()
+        .filter(|(_, cached, expected)| cached != expected)
+        .map(|(name, cached, expected)| format!("{name} is {cached}, expected {expected}"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn trade_status(trade: &Trade) -> Option<Inconsistency> {
+    let entry_filled = is_filled(&trade.entry);
+    let stop_filled = is_filled(&trade.safety_stop);
+    let target_filled = is_filled(&trade.target);
+
+    let consistent = match trade.status {
+        Status::New | Status::Funded | Status::Submitted => !entry_filled,
+        Status::Filled => entry_filled && !stop_filled && !target_filled,
+        Status::ClosedStopLoss => entry_filled && stop_filled,
+        Status::ClosedTarget => entry_filled && target_filled,
+        // Canceled trades can be closed manually with a market order, other statuses
+        // are set by the broker and can not be verified with the orders.
+        _ => true,
+    };
+    if consistent {
+        return None;
+    }
+
+    Some(Inconsistency {
+        category: InconsistencyCategory::TradeStatus,
+        account_id: Some(trade.account_id),
+        entity_id: trade.id,
+        currency: Some(trade.currency),
+        message: format!(
+            "Trade {} ({}) is {} but its entry is {}, its stop is {} and its target is {}",
+            trade.id,
+            trade.trading_vehicle.symbol,
+            trade.status,
+            trade.entry.status,
+            trade.safety_stop.status,
+            trade.target.status
+        ),
+    })
+}
+
+// An order is filled when the broker says so or when it was filled manually.
+// An entry whose rest was canceled after a partial fill is filled with what it got.
+fn is_filled(order: &Order) -> bool {
+    match order.status {
+        OrderStatus::Filled => true,
+        OrderStatus::Canceled | OrderStatus::Expired => order.filled_quantity > 0,
+        OrderStatus::Rejected => false,
+        _ => order.filled_at.is_some() || order.closed_at.is_some(),
+    }
+}
+
+fn orphan_transactions(
+    account: &Account,
+    trades: &[Trade],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Inconsistency>, Box<dyn Error>> {
+    let trade_ids: HashSet<Uuid> = trades.iter().map(|trade| trade.id).collect();
+    let mut inconsistencies = Vec::new();
+
+    for currency in Currency::all() {
+        let transactions = database
+            .transaction_read()
+            .all_transactions(account.id, &currency)?;
+        for transaction in transactions {
+            let Some(trade_id) = transaction.category.trade_id() else {
+                continue;
+            };
+            if trade_ids.contains(&trade_id) {
+                continue;
+            }
+            inconsistencies.push(Inconsistency {
+                category: InconsistencyCategory::OrphanTransaction,
+                account_id: Some(account.id),
+                entity_id: transaction.id,
+                currency: Some(transaction.currency),
+                message: format!(
+                    "Transaction {} ({} of {} {}) references trade {trade_id} that does not belong to account {}",
+                    transaction.id,
+                    transaction.category,
+                    transaction.amount,
+                    transaction.currency,
+                    account.name
+                ),
+            });
+        }
+    }
+    Ok(inconsistencies)
+}
+
+fn orphan_orders(
+    trades: &[Trade],
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<Inconsistency>, Box<dyn Error>> {
+    let order_ids: HashSet<Uuid> = trades
+        .iter()
+        .flat_map(|trade| [trade.entry.id, trade.safety_stop.id, trade.target.id])
+        .collect();
+
+    Ok(database
+        .order_read()
+        .all()?
+        .into_iter()
+        .filter(|order| !order_ids.contains(&order.id))
+        .map(|order| Inconsistency {
+            category: InconsistencyCategory::OrphanOrder,
+            account_id: None,
+            entity_id: order.id,
+            currency: Some(order.currency),
+            message: format!(
+                "Order {} ({} {} at {}) does not belong to any trade",
+                order.id, order.action, order.quantity, order.unit_price
+            ),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_trade_status_consistent() {
+        let trade = Trade {
+            status: Status::Submitted,
+            ..Default::default()
+        };
+        assert!(trade_status(&trade).is_none());
+    }
+
+    #[test]
+    fn test_trade_status_filled_without_entry() {
+        let trade = Trade {
+            status: Status::Filled,
+            ..Default::default()
+        };
+        let inconsistency = trade_status(&trade).unwrap();
+        assert_eq!(inconsistency.category, InconsistencyCategory::TradeStatus);
+        assert_eq!(inconsistency.entity_id, trade.id);
+    }
+
+    #[test]
+    fn test_trade_status_closed_manually() {
+        let now = Utc::now().naive_utc();
+        let trade = Trade {
+            status: Status::ClosedStopLoss,
+            entry: Order {
+                filled_at: Some(now),
+                ..Default::default()
+            },
+            safety_stop: Order {
+                status: OrderStatus::Held,
+                closed_at: Some(now),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(trade_status(&trade).is_none());
+    }
+
+    #[test]
+    fn test_trade_status_canceled_exit_is_not_filled() {
+        let now = Utc::now().naive_utc();
+        let trade = Trade {
+            status: Status::ClosedTarget,
+            entry: Order {
+                status: OrderStatus::Filled,
+                ..Default::default()
+            },
+            target: Order {
+                status: OrderStatus::Canceled,
+                closed_at: Some(now),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(trade_status(&trade).is_some());
+    }
+
+    #[test]
+    fn test_trade_status_filled_with_the_rest_of_the_entry_canceled() {
+        let trade = Trade {
+            status: Status::Filled,
+            entry: Order {
+                status: OrderStatus::Expired,
+                quantity: 100,
+                filled_quantity: 40,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(trade_status(&trade).is_none());
+    }
+
+    #[test]
+    fn test_drift() {
+        assert_eq!(drift(&[("funding", Decimal::ONE, Decimal::ONE)]), "");
+        assert_eq!(
+            drift(&[
+                ("funding", Decimal::ONE, Decimal::ONE),
+                ("taxed", Decimal::ONE, Decimal::TWO)
+            ]),
+            "taxed is 1, expected 2"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::calculators_trade::TradeFees;
+use model::{Account, DatabaseFactory, FeeSchedule, Order, Trade};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::error::Error;
+
+pub fn set_schedule(
+    account: &Account,
+    schedule: &FeeSchedule,
+    database: &mut dyn DatabaseFactory,
+) -> Result<FeeSchedule, Box<dyn Error>> {
+    crate::validators::account::can_use_fee_schedule(schedule)?;
+    database
+        .fee_schedule_write()
+        .set_fee_schedule(account, schedule)
+}
+
+/// Returns the fee of filling the entry of a trade based on the fee schedule of its account.
+/// If the account does not have a fee schedule, the fee is zero.
+pub fn opening(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Decimal, Box<dyn Error>> {
+    match schedule_for(trade, database)? {
+        Some(schedule) => TradeFees::opening(trade, &schedule),
+        None => Ok(dec!(0)),
+    }
+}
+
+/// Returns the fee of closing a trade with the given exit order based on the fee schedule of its account.
+/// If the account does not have a fee schedule, the fee is zero.
+pub fn closing(
+    trade: &Trade,
+    exit: &Order,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Decimal, Box<dyn Error>> {
+    match schedule_for(trade, database)? {
+        Some(schedule) => TradeFees::closing(trade, exit, &schedule),
+        None => Ok(dec!(0)),
+    }
+}
+
+fn schedule_for(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Option<FeeSchedule>, Box<dyn Error>> {
+    database
+        .fee_schedule_read()
+        .fee_schedule_for_account(trade.account_id)
+}
@@ -105,14 +105,45 @@ pub fn record_timestamp_target(
     read_database.read_trade(trade.id)
 }
 
+/// Changes the price of an order, with the broker ID of the modified order.
+/// An order without a broker ID is not placed at the broker yet, so only its price changes.
 pub fn modify(
     order: &Order,
     new_price: Decimal,
-    broker_id: Uuid,
+    broker_id: Option<Uuid>,
     write_database: &mut dyn OrderWrite,
 ) -> Result<Order, Box<dyn std::error::Error>> {
-    let stop = write_database.update_price(order, new_price, broker_id)?;
-    Ok(stop)
+    match broker_id {
+        Some(broker_id) => write_database.update_price(order, new_price, broker_id),
+        None => write_database.update(&Order {
+            unit_price: new_price,
+            ..order.clone()
+        }),
+    }
+}
+
+/// The quantity of an order that was filled. Orders filled by hand, or before the
+/// broker reported the filled quantity, are filled with their whole quantity.
+pub fn filled_quantity(order: &Order) -> u64 {
+    if order.filled_quantity > 0 {
+        order.filled_quantity
+    } else {
+        order.quantity
+    }
+}
+
+/// Changes the quantity of the stop and the target to the quantity filled by the entry.
+pub fn resize_exits(
+    trade: &Trade,
+    write_database: &mut dyn OrderWrite,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let quantity = filled_quantity(&trade.entry);
+    for order in [&trade.safety_stop, &trade.target] {
+        if order.quantity != quantity {
+            write_database.update_quantity(order, quantity)?;
+        }
+    }
+    Ok(())
 }
 
 fn action_for_stop(category: &TradeCategory) -> OrderAction {
@@ -140,6 +171,21 @@ fn action_for_target(category: &TradeCategory) -> OrderAction {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_filled_quantity() {
+        let order = Order {
+            quantity: 100,
+            ..Default::default()
+        };
+        assert_eq!(filled_quantity(&order), 100);
+
+        let order = Order {
+            filled_quantity: 40,
+            ..order
+        };
+        assert_eq!(filled_quantity(&order), 40);
+    }
+
     #[test]
     fn test_action_for_stop_long() {
         let category = TradeCategory::Long;
@@ -0,0 +1,166 @@
+use chrono::NaiveDate;
+use model::{DatabaseFactory, PriceBar};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+
+/// Imports the daily bars of a symbol from a CSV with a header row.
+/// The columns are found by name: Date, Open, High, Low, Close and an optional Volume.
+/// Other columns, like the Adj Close of Yahoo Finance exports, are ignored.
+/// Bars of days that were imported before are replaced.
+pub fn import(
+    symbol: &str,
+    csv: &str,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Vec<PriceBar>, Box<dyn Error>> {
+    crate::validators::account::can_use_benchmark(symbol)?;
+
+    let bars = parse(symbol, csv)?;
+    if bars.is_empty() {
+        return Err(format!("No prices of {symbol} found in the CSV").into());
+    }
+    for bar in &bars {
+        crate::validators::price::can_import_bar(bar)?;
+    }
+
+    database.price_write().save_bars(&bars)
+}
+
+struct Columns {
+    date: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+}
+
+fn parse(symbol: &str, csv: &str) -> Result<Vec<PriceBar>, Box<dyn Error>> {
+    let mut lines = csv.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines.next().ok_or("The CSV is empty")?;
+    let columns = parse_header(header)?;
+
+    (2..)
+        .zip(lines)
+        .map(|(line_number, line)| {
+            parse_line(symbol, &columns, line)
+                .map_err(|error| format!("Line {line_number} of the CSV: {error}").into())
+        })
+        .collect()
+}
+
+fn parse_header(header: &str) -> Result<Columns, Box<dyn Error>> {
+    let names: Vec<String> = header
+        .split(',')
+        .map(|name| name.trim().trim_matches('"').to_lowercase())
+        .collect();
+    let position = |name: &str| -> Result<usize, Box<dyn Error>> {
+        names
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| format!("The CSV does not have a {name} column").into())
+    };
+
+    Ok(Columns {
+        date: position("date")?,
+        open: position("open")?,
+        high: position("high")?,
+        low: position("low")?,
+        close: position("close")?,
+        volume: position("volume").ok(),
+    })
+}
+
+fn parse_line(symbol: &str, columns: &Columns, line: &str) -> Result<PriceBar, Box<dyn Error>> {
+    let values: Vec<&str> = line
+        .split(',')
+        .map(|value| value.trim().trim_matches('"'))
+        .collect();
+    let value = |column: usize| -> Result<&str, Box<dyn Error>> {
+        values
+            .get(column)
+            .copied()
+            .ok_or_else(|| "missing columns".into())
+    };
+    let decimal = |column: usize| -> Result<Decimal, Box<dyn Error>> {
+        let value = value(column)?;
+        Decimal::from_str(value)
+            .or_else(|_| Decimal::from_scientific(value))
+            .map_err(|_| format!("'{value}' is not a number").into())
+    };
+
+    let date = value(columns.date)?;
+    Ok(PriceBar {
+        symbol: symbol.to_uppercase(),
+        date: NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("'{date}' is not a date like 2024-01-31"))?,
+        open: decimal(columns.open)?,
+        high: decimal(columns.high)?,
+        low: decimal(columns.low)?,
+        close: decimal(columns.close)?,
+        volume: columns.volume.map(decimal).transpose()?.unwrap_or_default(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_yahoo_export() {
+        let csv = "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                   2024-01-02,472.16,473.67,470.49,472.65,465.24,123623700\n\
+                   2024-01-03,470.43,471.19,468.17,468.79,461.44,103585900\n";
+
+        let bars = parse("spy", csv).unwrap();
+
+        assert_eq!(bars.len(), 2);
+        let first = bars.first().unwrap();
+        assert_eq!(first.symbol, "SPY");
+        assert_eq!(first.date, NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert_eq!(first.open, dec!(472.16));
+        assert_eq!(first.high, dec!(473.67));
+        assert_eq!(first.low, dec!(470.49));
+        assert_eq!(first.close, dec!(472.65));
+        assert_eq!(first.volume, dec!(123623700));
+    }
+
+    #[test]
+    fn test_parse_without_volume_and_other_column_order() {
+        let csv = "close,low,high,open,date\n\"101.5\",99,102,100,2024-02-01";
+
+        let bars = parse("QQQ", csv).unwrap();
+
+        let bar = bars.first().unwrap();
+        assert_eq!(bar.close, dec!(101.5));
+        assert_eq!(bar.open, dec!(100));
+        assert_eq!(bar.volume, dec!(0));
+    }
+
+    #[test]
+    fn test_parse_without_required_column() {
+        let error = parse("SPY", "Date,Open,High,Low\n2024-01-02,1,1,1")
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "The CSV does not have a close column");
+    }
+
+    #[test]
+    fn test_parse_with_invalid_values() {
+        let csv = "Date,Open,High,Low,Close\n2024-01-02,1,1,1,1\n02/01/2024,1,1,1,1";
+        let error = parse("SPY", csv).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Line 3 of the CSV: '02/01/2024' is not a date like 2024-01-31"
+        );
+
+        let csv = "Date,Open,High,Low,Close\n2024-01-02,1,1,null,1";
+        let error = parse("SPY", csv).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Line 2 of the CSV: 'null' is not a number"
+        );
+    }
+}
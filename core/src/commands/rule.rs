@@ -33,5 +33,6 @@ fn priority_for(name: &RuleName) -> u32 {
     match name {
         RuleName::RiskPerMonth(_) => 1,
         RuleName::RiskPerTrade(_) => 2,
+        RuleName::MaxOpenRisk(_) => 3,
     }
 }
@@ -1,19 +1,26 @@
+use crate::calculators_trade::TradeCapitalOutOfMarket;
 use crate::commands;
 use model::{
-    Account, AccountBalance, Broker, BrokerLog, DatabaseFactory, DraftTrade, Order, OrderStatus,
-    Status, Trade, TradeBalance, Transaction,
+    Account, AccountBalance, Broker, BrokerCapabilities, BrokerError, BrokerLog, BrokerPosition,
+    DatabaseFactory, DraftTrade, Order, OrderCategory, OrderStatus, Status, Trade, TradeBalance,
+    TradingVehicle, Transaction, TransactionCategory,
 };
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
+use std::time::Instant;
 
 pub fn create_trade(
     trade: DraftTrade,
     stop_price: Decimal,
     entry_price: Decimal,
     target_price: Decimal,
+    capabilities: &BrokerCapabilities,
     database: &mut dyn DatabaseFactory,
 ) -> Result<Trade, Box<dyn std::error::Error>> {
+    // 0. Verify that the broker supports the trade
+    crate::validators::broker::can_create(&trade, capabilities)?;
+
     // 1. Create Stop-loss Order
     let stop = commands::order::create_stop(
         trade.trading_vehicle.id,
@@ -64,9 +71,15 @@ pub fn update_status(
     database: &mut dyn DatabaseFactory,
 ) -> Result<(Trade, Option<Transaction>), Box<dyn Error>> {
     match status {
-        Status::Filled if trade.status == Status::Submitted => {
-            let (trade, tx) = fill_trade(trade, dec!(0), database)?;
-            return Ok((trade, Some(tx)));
+        Status::PartiallyFilled
+            if matches!(trade.status, Status::Submitted | Status::PartiallyFilled) =>
+        {
+            let fee = opening_fee(trade, database)?;
+            return partially_fill_trade(trade, fee, database);
+        }
+        Status::Filled if matches!(trade.status, Status::Submitted | Status::PartiallyFilled) => {
+            let fee = opening_fee(trade, database)?;
+            return fill_trade(trade, fee, database);
         }
         Status::Filled if trade.status == Status::Filled => {
             return Ok((trade.clone(), None)); // Nothing to update.
@@ -75,16 +88,18 @@ pub fn update_status(
             return Ok((trade.clone(), None)); // Nothing to update.
         }
         Status::ClosedStopLoss => {
-            if trade.status == Status::Submitted {
+            if matches!(trade.status, Status::Submitted | Status::PartiallyFilled) {
                 // We also update the trade entry
-                fill_trade(trade, dec!(0), database)?;
+                let fee = opening_fee(trade, database)?;
+                fill_trade(trade, fee, database)?;
             }
 
             // We only update the trade target once
             let trade = database.trade_read().read_trade(trade.id)?;
             if trade.status == Status::Filled {
                 // We also update the trade stop loss
-                let (trade, _) = stop_executed(&trade, dec!(0), database)?;
+                let fee = commands::fee::closing(&trade, &trade.safety_stop, database)?;
+                let (trade, _) = stop_executed(&trade, fee, database)?;
                 let (tx, _, _) = commands::transaction::transfer_to_account_from(&trade, database)?;
 
                 return Ok((trade, Some(tx)));
@@ -94,9 +109,10 @@ pub fn update_status(
             return Ok((trade.clone(), None)); // Nothing to update.
         }
         Status::ClosedTarget => {
-            if trade.status == Status::Submitted {
+            if matches!(trade.status, Status::Submitted | Status::PartiallyFilled) {
                 // We also update the trade entry
-                fill_trade(trade, dec!(0), database)?;
+                let fee = opening_fee(trade, database)?;
+                fill_trade(trade, fee, database)?;
             }
 
             // We only update the trade target once
@@ -104,7 +120,8 @@ pub fn update_status(
             if trade.status == Status::Filled || trade.status == Status::Canceled {
                 // It can be canceled if the target was updated.
                 // We also update the trade stop loss
-                let (trade, _) = target_executed(&trade, dec!(0), database)?;
+                let fee = commands::fee::closing(&trade, &trade.target, database)?;
+                let (trade, _) = target_executed(&trade, fee, database)?;
                 let (tx, _, _) = commands::transaction::transfer_to_account_from(&trade, database)?;
 
                 return Ok((trade, Some(tx)));
@@ -120,11 +137,61 @@ pub fn update_status(
     unimplemented!()
 }
 
+/// The opening fee of the fills since the last charge: the fee of the quantity filled
+/// so far less the opening fees already charged, so the fee per order is charged once.
+fn opening_fee(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Decimal, Box<dyn Error>> {
+    let fee = commands::fee::opening(trade, database)?;
+    let charged = database
+        .transaction_read()
+        .all_trade_transactions(trade.id)?
+        .into_iter()
+        .filter(|tx| matches!(tx.category, TransactionCategory::FeeOpen(_)))
+        .try_fold(dec!(0), |acc, tx| {
+            acc.checked_add(tx.amount)
+                .ok_or_else(|| format!("Arithmetic overflow in addition: {acc} + {}", tx.amount))
+        })?;
+    let fee = fee
+        .checked_sub(charged)
+        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {fee} - {charged}"))?;
+    Ok(fee.max(dec!(0)))
+}
+
+/// Moves the capital of a partially filled entry into the market and resizes the stop
+/// and the target to the quantity filled so far. The rest of the entry is still open.
+pub fn partially_fill_trade(
+    trade: &Trade,
+    fee: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Trade, Option<Transaction>), Box<dyn Error>> {
+    // 1. Create Transaction to pay for fees
+    if fee > dec!(0) {
+        commands::transaction::transfer_opening_fee(fee, trade, database)?;
+    }
+
+    // 2. Create Transaction to transfer the new fills to the market
+    let (tx, _) = commands::transaction::transfer_to_partially_fill_trade(trade, database)?;
+
+    // 3. Protect only the quantity that is in the market
+    commands::order::resize_exits(trade, database.order_write().as_mut())?;
+
+    // 4. Update Trade Status
+    let trade = database
+        .trade_write()
+        .update_trade_status(Status::PartiallyFilled, trade)?;
+
+    Ok((trade, tx))
+}
+
+/// Fills the entry of a trade. When the rest of a partially filled entry was canceled,
+/// the trade is filled with the quantity it got and the unused funding is paid back.
 pub fn fill_trade(
     trade: &Trade,
     fee: Decimal,
     database: &mut dyn DatabaseFactory,
-) -> Result<(Trade, Transaction), Box<dyn Error>> {
+) -> Result<(Trade, Option<Transaction>), Box<dyn Error>> {
     // Create Transaction to pay for fees
     if fee > dec!(0) {
         commands::transaction::transfer_opening_fee(fee, trade, database)?;
@@ -133,6 +200,9 @@ pub fn fill_trade(
     // Create Transaction to transfer funds to the market
     let (tx, _) = commands::transaction::transfer_to_fill_trade(trade, database)?;
 
+    // Protect only the quantity that is in the market
+    commands::order::resize_exits(trade, database.order_write().as_mut())?;
+
     // Record timestamp when the order was opened
     commands::order::record_timestamp_filled(
         trade,
@@ -255,7 +325,9 @@ pub fn cancel_submitted(
 
     // 2. Cancel trade with broker
     let account = database.account_read().id(trade.account_id)?;
-    broker.cancel_trade(trade, &account)?;
+    call_broker("cancel_trade", trade, database, || {
+        broker.cancel_trade(trade, &account).map(|log| ((), log))
+    })?;
 
     // 3. Update Trade Status
     database
@@ -269,6 +341,66 @@ pub fn cancel_submitted(
     Ok((trade_o, account_o, tx))
 }
 
+pub fn modify_entry(
+    trade: &Trade,
+    account: &Account,
+    new_price: Decimal,
+    new_quantity: u64,
+    broker: &mut dyn Broker,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Trade, Option<Transaction>), Box<dyn std::error::Error>> {
+    // 1. Verify trade can be modified
+    crate::validators::trade::can_modify_entry(trade, new_price, new_quantity)?;
+    crate::validators::broker::can_modify_entry(&broker.capabilities())?;
+
+    // 2. Verify that the account can fund the trade with the new entry
+    let modified = with_entry(trade, new_price, new_quantity);
+    let funded =
+        TradeCapitalOutOfMarket::calculate(trade.id, database.transaction_read().as_mut())?;
+    crate::validators::funding::can_modify_funding(&modified, funded, database)?;
+
+    // 3. Replace the entry on the broker
+    let (new_broker_id, _) = call_broker("modify_entry", trade, database, || {
+        broker.modify_entry(trade, account, new_price, new_quantity)
+    })?;
+
+    // 4. Modify the entry, and resize the stop and the target
+    commands::order::modify(
+        &trade.entry,
+        new_price,
+        Some(new_broker_id),
+        &mut *database.order_write(),
+    )?;
+    for order in [&trade.entry, &trade.safety_stop, &trade.target] {
+        database
+            .order_write()
+            .update_quantity(order, new_quantity)?;
+    }
+
+    // 5. Fund the extra capital that the trade needs, or pay back what it does not need
+    let transaction =
+        commands::transaction::transfer_to_modify_funding(&modified, funded, database)?;
+
+    // 6. Refresh Trade
+    let trade = database.trade_read().read_trade(trade.id)?;
+
+    Ok((trade, transaction))
+}
+
+/// The trade with a new entry price and quantity, and its stop and target resized
+fn with_entry(trade: &Trade, price: Decimal, quantity: u64) -> Trade {
+    let mut modified = trade.clone();
+    modified.entry.unit_price = price;
+    for order in [
+        &mut modified.entry,
+        &mut modified.safety_stop,
+        &mut modified.target,
+    ] {
+        order.quantity = quantity;
+    }
+    modified
+}
+
 pub fn modify_stop(
     trade: &Trade,
     account: &Account,
@@ -278,9 +410,19 @@ pub fn modify_stop(
 ) -> Result<Trade, Box<dyn std::error::Error>> {
     // 1. Verify trade can be modified
     crate::validators::trade::can_modify_stop(trade, new_stop_price)?;
-
-    // 2. Update Trade on the broker
-    let new_broker_id = broker.modify_stop(trade, account, new_stop_price)?;
+    crate::validators::broker::can_modify_stop(&broker.capabilities())?;
+
+    // 2. Update Trade on the broker. A stop that the broker did not place yet is
+    // placed with the new price.
+    let new_broker_id = match trade.safety_stop.broker_order_id {
+        Some(_) => Some(
+            call_broker("modify_stop", trade, database, || {
+                broker.modify_stop(trade, account, new_stop_price)
+            })?
+            .0,
+        ),
+        None => None,
+    };
 
     // 3. Modify stop order
     commands::order::modify(
@@ -305,9 +447,19 @@ pub fn modify_target(
 ) -> Result<Trade, Box<dyn std::error::Error>> {
     // 1. Verify trade can be modified
     crate::validators::trade::can_modify_target(trade)?;
-
-    // 2. Update Trade on the broker
-    let new_broker_id = broker.modify_target(trade, account, new_price)?;
+    crate::validators::broker::can_modify_target(&broker.capabilities())?;
+
+    // 2. Update Trade on the broker. A target that the broker did not place yet is
+    // placed with the new price.
+    let new_broker_id = match trade.target.broker_order_id {
+        Some(_) => Some(
+            call_broker("modify_target", trade, database, || {
+                broker.modify_target(trade, account, new_price)
+            })?
+            .0,
+        ),
+        None => None,
+    };
 
     // 3. Modify stop order
     commands::order::modify(
@@ -350,34 +502,37 @@ pub fn submit(
 ) -> Result<(Trade, BrokerLog), Box<dyn std::error::Error>> {
     // 1. Validate that Trade can be submitted
     crate::validators::trade::can_submit(trade)?;
+    crate::validators::broker::can_submit(trade, &broker.capabilities())?;
 
     // 2. Submit trade to broker
     let account = database.account_read().id(trade.account_id)?;
-    let (log, order_id) = broker.submit_trade(trade, &account)?;
+    let (order_id, log) = call_broker("submit_trade", trade, database, || {
+        broker
+            .submit_trade(trade, &account)
+            .map(|(log, order_id)| (order_id, log))
+    })?;
 
-    // 3. Save log in the DB
-    database.log_write().create_log(log.log.as_str(), trade)?;
-
-    // 4. Update Trade status to submitted
+    // 3. Update Trade status to submitted
     let trade = database
         .trade_write()
         .update_trade_status(Status::Submitted, trade)?;
 
-    // 5. Update internal orders orders to submitted
-    database
-        .order_write()
-        .submit_of(&trade.safety_stop, order_id.stop)?;
+    // 4. Update internal orders orders to submitted. An exit that the broker places
+    // later keeps no broker ID until then.
+    if let Some(stop) = order_id.stop {
+        database.order_write().submit_of(&trade.safety_stop, stop)?;
+    }
     database
         .order_write()
         .submit_of(&trade.entry, order_id.entry)?;
-    database
-        .order_write()
-        .submit_of(&trade.target, order_id.target)?;
+    if let Some(target) = order_id.target {
+        database.order_write().submit_of(&trade.target, target)?;
+    }
 
-    // 6. Read Trade with updated values
+    // 5. Read Trade with updated values
     let trade = database.trade_read().read_trade(trade.id)?;
 
-    // 7. Return Trade and Log
+    // 6. Return Trade and Log
     Ok((trade, log))
 }
 
@@ -388,21 +543,22 @@ pub fn sync_with_broker(
     broker: &mut dyn Broker,
 ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn std::error::Error>> {
     // 1. Sync Trade with Broker
-    let (status, orders, log) = broker.sync_trade(trade, account)?;
-
-    // 2. Save log in the DB
-    database.log_write().create_log(log.log.as_str(), trade)?;
+    let ((status, orders), log) = call_broker("sync_trade", trade, database, || {
+        broker
+            .sync_trade(trade, account)
+            .map(|(status, orders, log)| ((status, orders), log))
+    })?;
 
-    // 3. Update Orders
+    // 2. Update Orders
     for order in orders.clone() {
         commands::order::update_order(&order, database)?;
     }
 
-    // 4. Update Trade Status
+    // 3. Update Trade Status
     let trade = database.trade_read().read_trade(trade.id)?; // We need to read the trade again to get the updated orders
     update_status(&trade, status, database)?;
 
-    // 5. Update Account Overview
+    // 4. Update Account Overview
     commands::balance::calculate_account(database, account, &trade.currency)?;
 
     Ok((status, orders, log))
@@ -418,23 +574,280 @@ pub fn close(
 
     // 2. Submit a market order to close the trade
     let account = database.account_read().id(trade.account_id)?;
-    let (target_order, log) = broker.close_trade(trade, &account)?;
-
-    // 3. Save log in the database
-    database.log_write().create_log(log.log.as_str(), trade)?;
+    let (target_order, log) = call_broker("close_trade", trade, database, || {
+        broker.close_trade(trade, &account)
+    })?;
 
-    // 4. Update Order Target with the filled price and new ID
+    // 3. Update Order Target with the filled price and new ID
     commands::order::update_order(&target_order, database)?;
 
-    // 5. Update Trade Status
+    // 4. Update Trade Status
     database
         .trade_write()
         .update_trade_status(Status::Canceled, trade)?;
 
-    // 6. Cancel Stop-loss Order
+    // 5. Cancel Stop-loss Order
     let mut stop_order = trade.safety_stop.clone();
     stop_order.status = OrderStatus::Canceled;
     database.order_write().update(&stop_order)?;
 
     Ok((trade.balance.clone(), log))
 }
+
+pub fn close_partial(
+    trade: &Trade,
+    quantity: u64,
+    database: &mut dyn DatabaseFactory,
+    broker: &mut dyn Broker,
+) -> Result<(Trade, Transaction, BrokerLog), Box<dyn std::error::Error>> {
+    // 1. Verify that part of the trade can be closed
+    crate::validators::trade::can_close_partial(trade, quantity)?;
+    crate::validators::broker::can_close_partial(&broker.capabilities())?;
+
+    // 2. Submit a market order for the quantity, and resize the stop and the target
+    let account = database.account_read().id(trade.account_id)?;
+    let (closed, log) = call_broker("close_partial", trade, database, || {
+        broker.close_partial(trade, &account, quantity)
+    })?;
+
+    // 3. Update the stop and the target with the open quantity and their new IDs
+    let open = trade
+        .safety_stop
+        .quantity
+        .checked_sub(closed.quantity)
+        .ok_or("The broker closed more than the open quantity")?;
+    for (order, broker_id) in [
+        (&trade.safety_stop, closed.stop),
+        (&trade.target, closed.target),
+    ] {
+        let order = database.order_write().update_quantity(order, open)?;
+        database.order_write().update(&Order {
+            broker_order_id: broker_id,
+            ..order
+        })?;
+    }
+
+    // 4. Pay the fee of the market order that closed the quantity
+    let trade = database.trade_read().read_trade(trade.id)?;
+    let exit = Order {
+        category: OrderCategory::Market,
+        quantity: closed.quantity,
+        filled_quantity: closed.quantity,
+        average_filled_price: Some(closed.price),
+        ..trade.target.clone()
+    };
+    let fee = commands::fee::closing(&trade, &exit, database)?;
+    if fee > dec!(0) {
+        commands::transaction::transfer_closing_fee(fee, &trade, database)?;
+    }
+
+    // 5. Transfer the money of the closed quantity into the trade
+    let held = open
+        .checked_add(closed.quantity)
+        .ok_or("Arithmetic overflow in the quantity held")?;
+    let (transaction, _) =
+        commands::transaction::transfer_to_close_partial(&trade, &closed, held, database)?;
+
+    // 6. Refresh Trade
+    let trade = database.trade_read().read_trade(trade.id)?;
+
+    Ok((trade, transaction, log))
+}
+
+/// Makes a call to the broker for a trade and saves its log with the latency,
+/// so every request and answer of the broker can be inspected later.
+/// A failed call is saved with its error, and with what it sent when the broker
+/// returns a `BrokerError`, before the error is returned.
+/// The calls for the whole account, listing its positions and its activities, are
+/// not made through here, as every log belongs to a trade.
+fn call_broker<T>(
+    action: &str,
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+    call: impl FnOnce() -> Result<(T, BrokerLog), Box<dyn Error>>,
+) -> Result<(T, BrokerLog), Box<dyn Error>> {
+    let started = Instant::now();
+    let result = call();
+    let latency_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    match result {
+        Ok((value, log)) => {
+            let log = BrokerLog {
+                action: action.to_string(),
+                latency_ms,
+                ..log
+            };
+            let log = database.log_write().create_log(&log, trade)?;
+            Ok((value, log))
+        }
+        Err(error) => {
+            let sent = error
+                .downcast_ref::<BrokerError>()
+                .map(|error| error.log.clone())
+                .unwrap_or_default();
+            let log = BrokerLog {
+                trade_id: trade.id,
+                action: action.to_string(),
+                latency_ms,
+                error: Some(error.to_string()),
+                ..sent
+            };
+            database
+                .log_write()
+                .create_log(&log, trade)
+                .map_err(|e| format!("{error}. The log of the call could not be saved: {e}"))?;
+            Err(error)
+        }
+    }
+}
+
+/// Creates a trade for a position or a working entry order held at the broker.
+///
+/// The trade is funded and recorded as submitted with the orders of the broker,
+/// and a filled entry opens it, so the position counts in the risk of the account.
+pub fn import(
+    position: &BrokerPosition,
+    account: &Account,
+    capabilities: &BrokerCapabilities,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Trade, Box<dyn Error>> {
+    // 1. Verify that the position can be imported, is protected and was not imported before
+    if let Some(reason) = &position.skip_reason {
+        return Err(reason.clone().into());
+    }
+    position
+        .entry
+        .broker_order_id
+        .ok_or("The order that opened the position was not found at the broker")?;
+    let stop = position
+        .safety_stop
+        .as_ref()
+        .ok_or("There is no stop order at the broker")?;
+    let target = position
+        .target
+        .as_ref()
+        .ok_or("There is no target order at the broker")?;
+    if is_imported(&position.symbol, account, database)? {
+        return Err(format!("{} is already held by an open trade", position.symbol).into());
+    }
+
+    // 2. Validate that the account can fund the trade before creating it
+    let trading_vehicle = find_trading_vehicle(position, database)?;
+    let planned = Trade {
+        account_id: account.id,
+        trading_vehicle: trading_vehicle.clone(),
+        category: position.category,
+        currency: position.currency,
+        entry: position.entry.clone(),
+        safety_stop: stop.clone(),
+        target: target.clone(),
+        ..Default::default()
+    };
+    crate::validators::funding::can_fund(&planned, database)?;
+
+    // 3. Create and fund the trade
+    let draft = DraftTrade {
+        account: account.clone(),
+        trading_vehicle,
+        quantity: i64::try_from(position.entry.quantity)?,
+        currency: position.currency,
+        category: position.category,
+    };
+    let trade = create_trade(
+        draft,
+        stop.unit_price,
+        position.entry.unit_price,
+        target.unit_price,
+        capabilities,
+        database,
+    )?;
+    fund(&trade, database)?;
+
+    // 4. Record the orders of the broker
+    let trade = database.trade_read().read_trade(trade.id)?;
+    let trade = database
+        .trade_write()
+        .update_trade_status(Status::Submitted, &trade)?;
+    for (order, imported) in [
+        (&trade.entry, &position.entry),
+        (&trade.safety_stop, stop),
+        (&trade.target, target),
+    ] {
+        let broker_order_id = imported
+            .broker_order_id
+            .ok_or("An order of the position does not have a broker ID")?;
+        let submitted = database.order_write().submit_of(order, broker_order_id)?;
+        database.order_write().update(&Order {
+            status: imported.status,
+            filled_quantity: imported.filled_quantity,
+            average_filled_price: imported.average_filled_price,
+            submitted_at: imported.submitted_at.or(submitted.submitted_at),
+            filled_at: imported.filled_at,
+            ..submitted
+        })?;
+    }
+
+    // 5. Open the trade if the entry is filled
+    let trade = database.trade_read().read_trade(trade.id)?;
+    if position.entry.status == OrderStatus::Filled {
+        open_imported(&trade, &position.entry, database)?;
+    }
+    database.trade_read().read_trade(trade.id)
+}
+
+fn open_imported(
+    trade: &Trade,
+    entry: &Order,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(), Box<dyn Error>> {
+    update_status(trade, Status::Filled, database)?;
+    // Filling the trade dates the entry now, so it gets back the fill time of the broker
+    if entry.filled_at.is_some() {
+        let filled = database.order_read().for_id(trade.entry.id)?;
+        database.order_write().update(&Order {
+            filled_at: entry.filled_at,
+            ..filled
+        })?;
+    }
+    Ok(())
+}
+
+/// Whether an open trade of the account holds the symbol. The order that opened a
+/// position changes when the position is added to, so the symbol is compared.
+fn is_imported(
+    symbol: &str,
+    account: &Account,
+    database: &mut dyn DatabaseFactory,
+) -> Result<bool, Box<dyn Error>> {
+    for status in [Status::Submitted, Status::PartiallyFilled, Status::Filled] {
+        let trades = database
+            .trade_read()
+            .read_trades_with_status(account.id, status)?;
+        if trades
+            .iter()
+            .any(|trade| trade.trading_vehicle.symbol.eq_ignore_ascii_case(symbol))
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn find_trading_vehicle(
+    position: &BrokerPosition,
+    database: &mut dyn DatabaseFactory,
+) -> Result<TradingVehicle, Box<dyn Error>> {
+    let existing = database
+        .trading_vehicle_read()
+        .read_all_trading_vehicles()?
+        .into_iter()
+        .find(|vehicle| vehicle.symbol.eq_ignore_ascii_case(&position.symbol));
+    match existing {
+        Some(vehicle) => Ok(vehicle),
+        None => database.trading_vehicle_write().create_trading_vehicle(
+            &position.symbol,
+            &position.symbol,
+            &position.vehicle_category,
+            &position.broker,
+        ),
+    }
+}
@@ -1,6 +1,6 @@
 use model::{
-    AccountBalance, Currency, DatabaseFactory, Trade, TradeBalance, Transaction,
-    TransactionCategory,
+    Account, AccountBalance, Currency, DatabaseFactory, PartialClose, Trade, TradeBalance,
+    Transaction, TransactionCategory, Transfer,
 };
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -8,14 +8,17 @@ use std::error::Error;
 use uuid::Uuid;
 
 use crate::{
-    calculators_trade::{TradeCapitalOutOfMarket, TradeCapitalRequired},
+    calculators_account::AccountCapitalEarnings,
+    calculators_trade::{
+        TradeCapitalInMarket, TradeCapitalOutOfMarket, TradeCapitalRequired, TradeEarnings,
+    },
     validators::{
         transaction::{self, can_transfer_deposit},
         TransactionValidationErrorCode,
     },
 };
 
-use super::balance;
+use super::{balance, order};
 
 pub fn create(
     database: &mut dyn DatabaseFactory,
@@ -31,8 +34,9 @@ pub fn create(
             unimplemented!("WithdrawalTax is not implemented yet")
         }
         TransactionCategory::WithdrawalEarnings => {
-            unimplemented!("WithdrawalEarnings is not implemented yet")
+            withdraw_earnings(database, amount, currency, account_id)
         }
+        TransactionCategory::Interest => interest(database, amount, currency, account_id),
         default => {
             let message = format!("Manually creating transaction category {default:?} is not allowed. Only Withdrawals and deposits are allowed");
             Err(message.into())
@@ -114,6 +118,190 @@ fn withdraw(
     Ok((transaction, updated_balance))
 }
 
+fn withdraw_earnings(
+    database: &mut dyn DatabaseFactory,
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+
+    // Validate that account has enough earnings set aside to withdraw
+    let earnings = AccountCapitalEarnings::calculate(
+        account_id,
+        currency,
+        database.transaction_read().as_mut(),
+    )?;
+    transaction::can_transfer_withdraw_earnings(amount, earnings)?;
+
+    // Create transaction
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        currency,
+        TransactionCategory::WithdrawalEarnings,
+    )?;
+
+    // Update account balance
+    let updated_balance = balance::calculate_account(database, &account, currency)?;
+
+    Ok((transaction, updated_balance))
+}
+
+fn interest(
+    database: &mut dyn DatabaseFactory,
+    amount: Decimal,
+    currency: &Currency,
+    account_id: Uuid,
+) -> Result<(Transaction, AccountBalance), Box<dyn Error>> {
+    let account = database.account_read().id(account_id)?;
+
+    // Interest is paid on the cash of an existing balance
+    can_transfer_deposit(
+        amount,
+        currency,
+        account_id,
+        database.account_balance_read().as_mut(),
+    )?;
+
+    // Create transaction
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        currency,
+        TransactionCategory::Interest,
+    )?;
+
+    // Set aside the taxes of the interest
+    let taxes = taxes_of(&account, amount)?;
+    if taxes > dec!(0) {
+        database.transaction_write().create_transaction(
+            &account,
+            taxes,
+            currency,
+            TransactionCategory::InterestTax,
+        )?;
+    }
+
+    // Update account balance
+    let updated_balance = balance::calculate_account(database, &account, currency)?;
+
+    Ok((transaction, updated_balance))
+}
+
+/// Moves money from one account to another.
+/// Both sides are recorded as a pair of transactions linked by the transfer.
+/// When the currencies differ, the amount is converted with the given exchange rate.
+pub fn transfer(
+    database: &mut dyn DatabaseFactory,
+    from_account_id: Uuid,
+    to_account_id: Uuid,
+    amount: Decimal,
+    currency: &Currency,
+    to_currency: &Currency,
+    exchange_rate: Option<Decimal>,
+) -> Result<(Transfer, AccountBalance, AccountBalance), Box<dyn Error>> {
+    let from_account = database.account_read().id(from_account_id)?;
+    let to_account = database.account_read().id(to_account_id)?;
+
+    // 1. Validate that the source account has enough funds to transfer
+    let from_balance = database
+        .account_balance_read()
+        .for_currency(from_account_id, currency)?;
+    transaction::can_transfer_between_accounts(
+        from_account_id,
+        to_account_id,
+        &from_balance,
+        amount,
+        to_currency,
+        exchange_rate,
+    )?;
+
+    // 2. Convert the amount to the currency of the destination
+    let converted_amount = match exchange_rate {
+        Some(rate) if currency != to_currency => amount
+            .checked_mul(rate)
+            .ok_or_else(|| {
+                format!("Arithmetic overflow in transfer conversion: {amount} * {rate}")
+            })?
+            .round_dp(2),
+        _ => amount,
+    };
+
+    // 3. Create the destination balance if it never received money in this currency
+    if database
+        .account_balance_read()
+        .for_currency(to_account_id, to_currency)
+        .is_err()
+    {
+        database
+            .account_balance_write()
+            .create(&to_account, to_currency)?;
+    }
+
+    // 4. Create both transactions and the transfer atomically
+    let transfer = database.transfer_write().create_transfer(
+        &from_account,
+        amount,
+        currency,
+        &to_account,
+        converted_amount,
+        to_currency,
+    )?;
+
+    // 5. Update both account balances
+    let from_balance = balance::calculate_account(database, &from_account, currency)?;
+    let to_balance = balance::calculate_account(database, &to_account, to_currency)?;
+
+    Ok((transfer, from_balance, to_balance))
+}
+
+/// Records a dividend or a borrow fee of a trade.
+/// The money is paid to or charged from the account, and it is part of the trade performance.
+/// The taxes of a dividend are set aside as a PaymentTax of the trade.
+pub fn create_for_trade(
+    trade: &Trade,
+    category: &TransactionCategory,
+    amount: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Transaction, AccountBalance, TradeBalance), Box<dyn Error>> {
+    // 1. Validate the transaction
+    transaction::can_transfer_trade_transaction(trade, category, amount)?;
+    if let TransactionCategory::BorrowFee(_) = category {
+        let account_balance = database
+            .account_balance_read()
+            .for_currency(trade.account_id, &trade.currency)?;
+        transaction::can_transfer_fee(&account_balance, amount)?;
+    }
+
+    // 2. Create transaction
+    let account = database.account_read().id(trade.account_id)?;
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        amount,
+        &trade.currency,
+        *category,
+    )?;
+
+    if let TransactionCategory::Dividend(_) = category {
+        let taxes = taxes_of(&account, amount)?;
+        if taxes > dec!(0) {
+            database.transaction_write().create_transaction(
+                &account,
+                taxes,
+                &trade.currency,
+                TransactionCategory::PaymentTax(trade.id),
+            )?;
+        }
+    }
+
+    // 3. Update trade balance and account balance
+    let trade_balance = balance::calculate_trade(database, trade)?;
+    let account_balance = balance::calculate_account(database, &account, &trade.currency)?;
+
+    Ok((transaction, account_balance, trade_balance))
+}
+
 pub fn transfer_to_fund_trade(
     trade: &Trade,
     database: &mut dyn DatabaseFactory,
@@ -143,65 +331,134 @@ pub fn transfer_to_fund_trade(
     Ok((transaction, account_balance, trade_balance))
 }
 
-pub fn transfer_to_fill_trade(
+/// Moves the capital that a modified trade needs now: the extra capital is funded from
+/// the account, and the capital that is not needed anymore is paid back to it.
+/// `funded` is the capital that the trade held before the modification.
+pub fn transfer_to_modify_funding(
     trade: &Trade,
+    funded: Decimal,
     database: &mut dyn DatabaseFactory,
-) -> Result<(Transaction, TradeBalance), Box<dyn Error>> {
+) -> Result<Option<Transaction>, Box<dyn Error>> {
+    let required = TradeCapitalRequired::calculate(trade)?;
+    let difference = required
+        .checked_sub(funded)
+        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {required} - {funded}"))?;
+    let category = match difference {
+        d if d > dec!(0) => TransactionCategory::FundTrade(trade.id),
+        d if d < dec!(0) => TransactionCategory::PaymentFromTrade(trade.id),
+        _ => return Ok(None),
+    };
+
     let account = database.account_read().id(trade.account_id)?;
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        difference.abs(),
+        &trade.currency,
+        category,
+    )?;
 
-    // 1. Calculate the total amount of the trade
+    balance::calculate_account(database, &account, &trade.currency)?;
+    balance::calculate_trade(database, trade)?;
+
+    Ok(Some(transaction))
+}
+
+/// Moves the capital of the entry quantity filled since the last transfer into the
+/// market, so a partially filled entry is transferred a little on every sync.
+pub fn transfer_to_partially_fill_trade(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Option<Transaction>, TradeBalance), Box<dyn Error>> {
+    let account = database.account_read().id(trade.account_id)?;
+
+    // 1. Calculate the total amount of the quantity filled so far
+    let quantity = order::filled_quantity(&trade.entry);
     let average_price = trade
         .entry
         .average_filled_price
         .ok_or("Entry order has no average filled price")?;
     let total = average_price
-        .checked_mul(Decimal::from(trade.entry.quantity))
+        .checked_mul(Decimal::from(quantity))
         .ok_or_else(|| {
-            format!(
-                "Arithmetic overflow in multiplication: {} * {}",
-                average_price, trade.entry.quantity
-            )
+            format!("Arithmetic overflow in multiplication: {average_price} * {quantity}")
         })?;
 
     // 2. Validate that the trade has enough funds to fill the trade
     transaction::can_transfer_fill(trade, total)?;
 
-    // 3. Create transaction
-    let transaction = database.transaction_write().create_transaction(
-        &account,
-        total,
-        &trade.currency,
-        TransactionCategory::OpenTrade(trade.id),
-    )?;
+    // 3. Create a transaction for what is not in the market yet
+    let in_market =
+        TradeCapitalInMarket::calculate(trade.id, database.transaction_read().as_mut())?;
+    let remaining = total
+        .checked_sub(in_market)
+        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {total} - {in_market}"))?;
+    let transaction = if remaining > dec!(0) {
+        Some(database.transaction_write().create_transaction(
+            &account,
+            remaining,
+            &trade.currency,
+            TransactionCategory::OpenTrade(trade.id),
+        )?)
+    } else {
+        None
+    };
 
-    // 4. If there is a difference between the unit_price and the average_filled_price
+    // 4. Update trade balance
+    let trade_balance: TradeBalance = balance::calculate_trade(database, trade)?;
+    Ok((transaction, trade_balance))
+}
+
+pub fn transfer_to_fill_trade(
+    trade: &Trade,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Option<Transaction>, TradeBalance), Box<dyn Error>> {
+    let account = database.account_read().id(trade.account_id)?;
+
+    // 1. Transfer what was filled since the last partial fill
+    let (transaction, _) = transfer_to_partially_fill_trade(trade, database)?;
+
+    // 2. If there is a difference between the unit_price and the average_filled_price
     // then we should create a transaction to transfer the difference to the account.
-    let entry_total = trade
+    let quantity = order::filled_quantity(&trade.entry);
+    let average_price = trade
         .entry
-        .unit_price
-        .checked_mul(Decimal::from(trade.entry.quantity))
+        .average_filled_price
+        .ok_or("Entry order has no average filled price")?;
+    let difference = average_price
+        .checked_sub(trade.entry.unit_price)
+        .and_then(|difference| difference.checked_mul(Decimal::from(quantity)))
         .ok_or_else(|| {
             format!(
-                "Arithmetic overflow in multiplication: {} * {}",
-                trade.entry.unit_price, trade.entry.quantity
+                "Arithmetic overflow calculating the difference: ({} - {}) * {}",
+                average_price, trade.entry.unit_price, quantity
             )
-        })?;
+        })?
+        .abs();
 
-    let mut total_difference = total
-        .checked_sub(entry_total)
-        .ok_or_else(|| format!("Arithmetic overflow in subtraction: {total} - {entry_total}"))?;
-    total_difference.set_sign_positive(true);
+    if difference > dec!(0) {
+        database.transaction_write().create_transaction(
+            &account,
+            difference,
+            &trade.currency,
+            TransactionCategory::PaymentFromTrade(trade.id),
+        )?;
+    }
 
-    if total_difference > dec!(0) {
+    // 3. The capital of the quantity that was never filled goes back to the account
+    let unfilled = trade.entry.quantity.saturating_sub(quantity);
+    if unfilled > 0 {
+        let mut unfilled_trade = trade.clone();
+        unfilled_trade.entry.quantity = unfilled;
+        unfilled_trade.safety_stop.quantity = unfilled;
         database.transaction_write().create_transaction(
             &account,
-            total_difference,
+            TradeCapitalRequired::calculate(&unfilled_trade)?,
             &trade.currency,
             TransactionCategory::PaymentFromTrade(trade.id),
         )?;
     }
 
-    // 5. Update trade balance
+    // 4. Update trade balance
     let trade_balance: TradeBalance = balance::calculate_trade(database, trade)?;
     Ok((transaction, trade_balance))
 }
@@ -264,17 +521,15 @@ pub fn transfer_to_close_target(
 ) -> Result<(Transaction, TradeBalance), Box<dyn Error>> {
     let account = database.account_read().id(trade.account_id)?;
 
+    let quantity = order::filled_quantity(&trade.target);
     let average_price = trade
         .target
         .average_filled_price
         .ok_or("Target order has no average filled price")?;
     let total = average_price
-        .checked_mul(Decimal::from(trade.entry.quantity))
+        .checked_mul(Decimal::from(quantity))
         .ok_or_else(|| {
-            format!(
-                "Arithmetic overflow in multiplication: {} * {}",
-                average_price, trade.entry.quantity
-            )
+            format!("Arithmetic overflow in multiplication: {average_price} * {quantity}")
         })?;
 
     // 1. Validate that the closing is possible
@@ -302,17 +557,15 @@ pub fn transfer_to_close_stop(
     let account = database.account_read().id(trade.account_id)?;
 
     // 1. Calculate the total amount of the trade
+    let quantity = order::filled_quantity(&trade.safety_stop);
     let average_price = trade
         .safety_stop
         .average_filled_price
         .ok_or("Safety stop order has no average filled price")?;
     let total = average_price
-        .checked_mul(Decimal::from(trade.entry.quantity))
+        .checked_mul(Decimal::from(quantity))
         .ok_or_else(|| {
-            format!(
-                "Arithmetic overflow in multiplication: {} * {}",
-                average_price, trade.entry.quantity
-            )
+            format!("Arithmetic overflow in multiplication: {average_price} * {quantity}")
         })?;
 
     // 2. Validate that the closing is possible
@@ -323,11 +576,11 @@ pub fn transfer_to_close_stop(
     let planned_total = trade
         .safety_stop
         .unit_price
-        .checked_mul(Decimal::from(trade.entry.quantity))
+        .checked_mul(Decimal::from(quantity))
         .ok_or_else(|| {
             format!(
-                "Arithmetic overflow in multiplication: {} * {}",
-                trade.safety_stop.unit_price, trade.entry.quantity
+                "Arithmetic overflow in multiplication: {} * {quantity}",
+                trade.safety_stop.unit_price
             )
         })?;
 
@@ -352,6 +605,68 @@ pub fn transfer_to_close_stop(
     Ok((transaction, trade_balance))
 }
 
+/// Moves the money of the closed quantity from the market into the trade, and records
+/// what that quantity cost out of the `held` quantity, so it leaves the capital in the market.
+/// The rest of the trade stays open, so nothing is paid back to the account yet.
+pub fn transfer_to_close_partial(
+    trade: &Trade,
+    closed: &PartialClose,
+    held: u64,
+    database: &mut dyn DatabaseFactory,
+) -> Result<(Transaction, TradeBalance), Box<dyn Error>> {
+    let account = database.account_read().id(trade.account_id)?;
+
+    // 1. Calculate the total amount of the closed quantity
+    let total = closed
+        .price
+        .checked_mul(Decimal::from(closed.quantity))
+        .ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in multiplication: {} * {}",
+                closed.price, closed.quantity
+            )
+        })?;
+
+    // 2. Validate that the closing is possible
+    transaction::can_transfer_close(total)?;
+
+    // 3. Create transaction
+    let transaction = database.transaction_write().create_transaction(
+        &account,
+        total,
+        &trade.currency,
+        TransactionCategory::ClosePartial(trade.id),
+    )?;
+
+    // 4. Record the cost of the closed quantity
+    let in_market =
+        TradeCapitalInMarket::calculate(trade.id, database.transaction_read().as_mut())?;
+    let cost = in_market
+        .checked_mul(Decimal::from(closed.quantity))
+        .and_then(|cost| cost.checked_div(Decimal::from(held)))
+        .ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in the cost of {} of {held}: {in_market}",
+                closed.quantity
+            )
+        })?
+        .round_dp(2);
+    if cost > dec!(0) {
+        database.transaction_write().create_transaction(
+            &account,
+            cost,
+            &trade.currency,
+            TransactionCategory::ClosePartialCost(trade.id),
+        )?;
+    }
+
+    // 5. Update trade balance and account balance
+    let trade_balance: TradeBalance = balance::calculate_trade(database, trade)?;
+    balance::calculate_account(database, &account, &trade.currency)?;
+
+    Ok((transaction, trade_balance))
+}
+
 pub fn transfer_to_account_from(
     trade: &Trade,
     database: &mut dyn DatabaseFactory,
@@ -368,10 +683,58 @@ pub fn transfer_to_account_from(
         TransactionCategory::PaymentFromTrade(trade.id),
     )?;
 
-    // Update account balance and trade balance.
+    // Update trade balance, set aside the earnings and update account balance.
+    let trade_balance: TradeBalance = balance::calculate_trade(database, trade)?;
+    transfer_earnings(&account, trade, trade_balance.total_performance, database)?;
     let account_balance: AccountBalance =
         balance::calculate_account(database, &account, &trade.currency)?;
-    let trade_balance: TradeBalance = balance::calculate_trade(database, trade)?;
 
     Ok((transaction, account_balance, trade_balance))
 }
+
+fn transfer_earnings(
+    account: &Account,
+    trade: &Trade,
+    performance: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> Result<Option<Transaction>, Box<dyn Error>> {
+    // 1. Only profitable trades set aside earnings
+    let earnings = TradeEarnings::calculate(performance, account.earnings_percentage)?;
+    if earnings <= dec!(0) {
+        return Ok(None);
+    }
+
+    // 2. Earnings are set aside once per trade
+    let already_paid = database
+        .transaction_read()
+        .all_trade_transactions(trade.id)?
+        .iter()
+        .any(|tx| matches!(tx.category, TransactionCategory::PaymentEarnings(_)));
+    if already_paid {
+        return Ok(None);
+    }
+
+    // 3. Create transaction
+    let transaction = database.transaction_write().create_transaction(
+        account,
+        earnings,
+        &trade.currency,
+        TransactionCategory::PaymentEarnings(trade.id),
+    )?;
+
+    Ok(Some(transaction))
+}
+
+/// The part of an income that goes to the tax authorities, by the taxes percentage of the account.
+fn taxes_of(account: &Account, amount: Decimal) -> Result<Decimal, Box<dyn Error>> {
+    let taxes = amount
+        .checked_mul(account.taxes_percentage)
+        .and_then(|taxes| taxes.checked_div(dec!(100)))
+        .ok_or_else(|| {
+            format!(
+                "Arithmetic overflow in taxes: {amount} * {}%",
+                account.taxes_percentage
+            )
+        })?;
+    Ok(taxes.round_dp(2))
+}
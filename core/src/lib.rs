@@ -32,9 +32,12 @@
 
 use calculators_trade::QuantityCalculator;
 use model::{
-    Account, AccountBalance, Broker, BrokerLog, Currency, DatabaseFactory, DraftTrade, Environment,
-    Order, Rule, RuleLevel, RuleName, Status, Trade, TradeBalance, TradingVehicle,
-    TradingVehicleCategory, Transaction, TransactionCategory,
+    Account, AccountBalance, ActivityImport, BacktestParameters, BacktestReport,
+    BenchmarkComparison, Broker, BrokerCapabilities, BrokerLog, Currency, DatabaseFactory,
+    DraftTrade, Environment, FeeSchedule, Inconsistency, OpenRisk, Order, OrderSlippage,
+    PositionSizing, PriceBar, Rule, RuleLevel, RuleName, Simulation, SimulationParameters,
+    SkippedPosition, SlippageGrouping, SlippageSummary, Status, Trade, TradeBalance, TradePlan,
+    TradingVehicle, TradingVehicleCategory, Transaction, TransactionCategory, Transfer,
 };
 use rust_decimal::Decimal;
 use uuid::Uuid;
@@ -85,6 +88,98 @@ impl TrustFacade {
         )
     }
 
+    /// Select the position sizing model used to calculate the quantity of new trades.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account to update
+    /// * `position_sizing` - The position sizing model and its parameter
+    ///
+    /// # Returns
+    ///
+    /// Returns the updated account, or an error if the parameter is not valid.
+    pub fn update_position_sizing(
+        &mut self,
+        account: &Account,
+        position_sizing: PositionSizing,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        commands::account::update_position_sizing(account, position_sizing, &mut *self.factory)
+    }
+
+    /// Select the index that the returns of an account are compared with, like SPY.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account to update
+    /// * `benchmark` - The symbol of the benchmark, or `None` to remove it
+    ///
+    /// # Returns
+    ///
+    /// Returns the updated account, or an error if the symbol is not valid.
+    pub fn update_benchmark(
+        &mut self,
+        account: &Account,
+        benchmark: Option<&str>,
+    ) -> Result<Account, Box<dyn std::error::Error>> {
+        commands::account::update_benchmark(account, benchmark, &mut *self.factory)
+    }
+
+    /// Import the daily prices of a symbol from a CSV with Date, Open, High, Low, Close
+    /// and optionally Volume columns. Prices of days imported before are replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - The symbol of the prices, like SPY
+    /// * `csv` - The content of the CSV file
+    ///
+    /// # Returns
+    ///
+    /// Returns the imported price bars.
+    pub fn import_prices(
+        &mut self,
+        symbol: &str,
+        csv: &str,
+    ) -> Result<Vec<PriceBar>, Box<dyn std::error::Error>> {
+        commands::price::import(symbol, csv, &mut *self.factory)
+    }
+
+    /// Set the commissions and fees that the broker charges to an account.
+    /// The previous fee schedule of the account, if any, is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account charged by the fee schedule
+    /// * `schedule` - The fee schedule to apply to new fills and closes
+    ///
+    /// # Returns
+    ///
+    /// Returns the created fee schedule, or an error if any value is not valid.
+    pub fn set_fee_schedule(
+        &mut self,
+        account: &Account,
+        schedule: &FeeSchedule,
+    ) -> Result<FeeSchedule, Box<dyn std::error::Error>> {
+        commands::fee::set_schedule(account, schedule, &mut *self.factory)
+    }
+
+    /// Search for the fee schedule of an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The ID of the account
+    ///
+    /// # Returns
+    ///
+    /// Returns the fee schedule, or None if the account does not have one.
+    pub fn search_fee_schedule(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<FeeSchedule>, Box<dyn std::error::Error>> {
+        self.factory
+            .fee_schedule_read()
+            .fee_schedule_for_account(account_id)
+    }
+
     /// Search for an account by name.
     ///
     /// # Arguments
@@ -145,6 +240,98 @@ impl TrustFacade {
         commands::transaction::create(&mut *self.factory, category, amount, currency, account.id)
     }
 
+    /// Record a dividend or a borrow fee of a trade.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade` - The trade that received the dividend or paid the borrow fee
+    /// * `category` - Either `TransactionCategory::Dividend` or `TransactionCategory::BorrowFee`
+    /// * `amount` - The amount paid or charged
+    ///
+    /// # Returns
+    ///
+    /// Returns the created transaction with the updated account and trade balances.
+    pub fn create_trade_transaction(
+        &mut self,
+        trade: &Trade,
+        category: &TransactionCategory,
+        amount: Decimal,
+    ) -> Result<(Transaction, AccountBalance, TradeBalance), Box<dyn std::error::Error>> {
+        commands::transaction::create_for_trade(trade, category, amount, &mut *self.factory)
+    }
+
+    /// Transfer money from one account to another.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The account the money is taken from
+    /// * `to` - The account receiving the money
+    /// * `amount` - The amount to transfer in the source currency
+    /// * `currency` - The currency of the source account balance
+    /// * `to_currency` - The currency of the destination account balance
+    /// * `exchange_rate` - The rate used to convert the amount when the currencies differ
+    ///
+    /// # Returns
+    ///
+    /// Returns the transfer with the updated balances of the source and destination accounts.
+    pub fn transfer(
+        &mut self,
+        from: &Account,
+        to: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        to_currency: &Currency,
+        exchange_rate: Option<Decimal>,
+    ) -> Result<(Transfer, AccountBalance, AccountBalance), Box<dyn std::error::Error>> {
+        commands::transaction::transfer(
+            &mut *self.factory,
+            from.id,
+            to.id,
+            amount,
+            currency,
+            to_currency,
+            exchange_rate,
+        )
+    }
+
+    /// Search all transfers sent or received by an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The UUID of the account
+    ///
+    /// # Returns
+    ///
+    /// Returns the transfers ordered by creation date.
+    pub fn search_transfers(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<Transfer>, Box<dyn std::error::Error>> {
+        self.factory.transfer_read().all_transfers(account_id)
+    }
+
+    /// Calculate the earnings set aside from profitable trades that were not withdrawn yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The UUID of the account
+    /// * `currency` - The currency of the earnings
+    ///
+    /// # Returns
+    ///
+    /// Returns the earnings available to withdraw.
+    pub fn calculate_earnings(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        calculators_account::AccountCapitalEarnings::calculate(
+            account_id,
+            currency,
+            self.factory.transaction_read().as_mut(),
+        )
+    }
+
     /// Search for the account balance in a specific currency.
     ///
     /// # Arguments
@@ -276,22 +463,26 @@ impl TrustFacade {
     /// * `account_id` - The UUID of the account
     /// * `entry_price` - The planned entry price
     /// * `stop_price` - The stop loss price
+    /// * `atr` - The average true range, required by volatility position sizing
     /// * `currency` - The currency of the trade
     ///
     /// # Returns
     ///
-    /// Returns the maximum quantity allowed by risk management rules.
+    /// Returns the maximum quantity allowed by the position sizing model of the
+    /// account, capped by the risk management rules.
     pub fn calculate_maximum_quantity(
         &mut self,
         account_id: Uuid,
         entry_price: Decimal,
         stop_price: Decimal,
+        atr: Option<Decimal>,
         currency: &Currency,
     ) -> Result<i64, Box<dyn std::error::Error>> {
         QuantityCalculator::maximum_quantity(
             account_id,
             entry_price,
             stop_price,
+            atr,
             currency,
             &mut *self.factory,
         )
@@ -321,10 +512,22 @@ impl TrustFacade {
             stop_price,
             entry_price,
             target_price,
+            &self.broker.capabilities(),
             &mut *self.factory,
         )
     }
 
+    /// What the broker supports, like short selling or modifying the stop.
+    /// Trades that the broker does not support are rejected when they are
+    /// created or submitted.
+    ///
+    /// # Returns
+    ///
+    /// Returns the capabilities declared by the broker.
+    pub fn broker_capabilities(&self) -> BrokerCapabilities {
+        self.broker.capabilities()
+    }
+
     /// Search for trades by account and status.
     ///
     /// # Arguments
@@ -345,6 +548,235 @@ impl TrustFacade {
             .read_trades_with_status(account_id, status)
     }
 
+    /// Read the calls made to the broker for a trade, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade_id` - The UUID of the trade
+    ///
+    /// # Returns
+    ///
+    /// Returns the log of every call, with its request, response, latency and error.
+    pub fn read_trade_logs(
+        &mut self,
+        trade_id: Uuid,
+    ) -> Result<Vec<BrokerLog>, Box<dyn std::error::Error>> {
+        self.factory.log_read().read_all_logs_for_trade(trade_id)
+    }
+
+    /// Check the data of all the accounts for inconsistencies.
+    ///
+    /// Balances are recalculated from the transactions and compared with the cached ones,
+    /// the status of every trade is compared with its orders, and orders and transactions
+    /// that do not belong to a trade of the account are reported. Nothing is modified.
+    ///
+    /// # Returns
+    ///
+    /// Returns all the inconsistencies found, or an empty vector if the data is consistent.
+    pub fn diagnose(&mut self) -> Result<Vec<Inconsistency>, Box<dyn std::error::Error>> {
+        commands::doctor::diagnose(&mut *self.factory)
+    }
+
+    /// Repair the inconsistencies that can be fixed by recalculating the cached balances.
+    /// Other inconsistencies are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `inconsistencies` - The inconsistencies returned by `diagnose`
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of inconsistencies that were repaired.
+    pub fn repair(
+        &mut self,
+        inconsistencies: &[Inconsistency],
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        commands::doctor::repair(inconsistencies, &mut *self.factory)
+    }
+
+    /// Calculate the money that would be lost if the stops of all the open trades
+    /// of an account were hit. Stops moved past breakeven do not add any risk.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The UUID of the account
+    /// * `currency` - The currency of the open trades
+    ///
+    /// # Returns
+    ///
+    /// Returns the open risk and its percentage of the total balance of the account.
+    pub fn calculate_open_risk(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<OpenRisk, Box<dyn std::error::Error>> {
+        let amount = calculators_account::AccountOpenRisk::calculate(
+            account_id,
+            currency,
+            self.factory.trade_read().as_mut(),
+        )?;
+        let balance = self
+            .factory
+            .account_balance_read()
+            .for_currency(account_id, currency)?;
+        let in_market = calculators_account::AccountOpenRisk::in_market(
+            account_id,
+            currency,
+            self.factory.trade_read().as_mut(),
+            self.factory.transaction_read().as_mut(),
+        )?;
+        let capital = calculators_account::AccountOpenRisk::capital(&balance, in_market)?;
+        Ok(OpenRisk {
+            currency: *currency,
+            amount,
+            percentage: calculators_account::AccountOpenRisk::percentage(amount, capital)?,
+        })
+    }
+
+    /// Calculate the slippage of every filled order of the trades of an account.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The UUID of the account
+    /// * `currency` - The currency of the trades
+    ///
+    /// # Returns
+    ///
+    /// Returns the slippage of the entries, stops and targets that have been filled.
+    pub fn calculate_slippage(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+    ) -> Result<Vec<OrderSlippage>, Box<dyn std::error::Error>> {
+        calculators_account::AccountSlippage::calculate(
+            account_id,
+            currency,
+            self.factory.trade_read().as_mut(),
+        )
+    }
+
+    /// Aggregate the slippage of orders by leg, symbol, order type or hour of the day.
+    ///
+    /// # Arguments
+    ///
+    /// * `slippages` - The slippage of the orders to aggregate
+    /// * `grouping` - How to group the orders
+    ///
+    /// # Returns
+    ///
+    /// Returns one summary for each group, sorted by the name of the group.
+    pub fn summarize_slippage(
+        &mut self,
+        slippages: &[OrderSlippage],
+        grouping: SlippageGrouping,
+    ) -> Result<Vec<SlippageSummary>, Box<dyn std::error::Error>> {
+        calculators_account::AccountSlippage::summarize(slippages, grouping)
+    }
+
+    /// Compare the returns of an account with the returns of its benchmark,
+    /// from the first transaction of the account until today.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account to compare, it must have a benchmark
+    /// * `currency` - The currency of the account balance to compare
+    ///
+    /// # Returns
+    ///
+    /// Returns the equity curve next to the benchmark with the alpha, beta and correlation.
+    pub fn compare_with_benchmark(
+        &mut self,
+        account: &Account,
+        currency: &Currency,
+    ) -> Result<BenchmarkComparison, Box<dyn std::error::Error>> {
+        calculators_account::AccountBenchmark::calculate(
+            account,
+            currency,
+            chrono::Utc::now().date_naive(),
+            self.factory.transaction_read().as_mut(),
+            self.factory.price_read().as_mut(),
+        )
+    }
+
+    /// Simulate random sequences of future trades of an account, drawn from the
+    /// R-multiples of its closed trades and sized with its risk rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `account_id` - The account to simulate, it must have a `RiskPerTrade` rule
+    /// * `currency` - The currency of the closed trades and the starting equity
+    /// * `parameters` - The number of paths and trades and the thresholds to report
+    ///
+    /// # Returns
+    ///
+    /// Returns the percentiles of the final equity with the probabilities of
+    /// hitting the drawdown and the ruin thresholds.
+    pub fn simulate(
+        &mut self,
+        account_id: Uuid,
+        currency: &Currency,
+        parameters: &SimulationParameters,
+    ) -> Result<Simulation, Box<dyn std::error::Error>> {
+        let rules = self.factory.rule_read().read_all_rules(account_id)?;
+        let balance = self
+            .factory
+            .account_balance_read()
+            .for_currency(account_id, currency)?;
+        calculators_account::AccountMonteCarlo::simulate(
+            account_id,
+            currency,
+            parameters,
+            &rules,
+            balance.total_balance,
+            self.factory.trade_read().as_mut(),
+        )
+    }
+
+    /// Backtest trade plans against the daily prices imported with `import_prices`.
+    ///
+    /// The plans go through a throwaway account with the position sizing, rules and
+    /// fee schedule of `account`, and are sized, funded and submitted like live trades
+    /// after the close of their signal date. The bars of the next days fill the orders:
+    /// gaps fill at the open, and a bar that reaches the stop and the target fills the stop.
+    ///
+    /// The transactions are dated today, so the `RiskPerMonth` rule sees the whole
+    /// backtest as the current month.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account whose settings are copied, it is not modified
+    /// * `plans` - The trade plans, with the entry, stop and target of every signal
+    /// * `parameters` - The starting capital and the currency of the trades
+    /// * `database` - An empty database for the throwaway account, like an in-memory one
+    ///
+    /// # Returns
+    ///
+    /// Returns the report of the backtest, with a facade over `database` to build
+    /// the same reports as a live account.
+    pub fn backtest(
+        &mut self,
+        account: &Account,
+        plans: &[TradePlan],
+        parameters: &BacktestParameters,
+        database: Box<dyn DatabaseFactory>,
+    ) -> Result<(BacktestReport, TrustFacade), Box<dyn std::error::Error>> {
+        backtest::Backtest::run(account, plans, parameters, &mut *self.factory, database)
+    }
+
+    /// Read trade plans from a CSV with Date, Symbol, Category, Entry, Stop, Target
+    /// and optionally ATR columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `csv` - The content of the CSV file
+    ///
+    /// # Returns
+    ///
+    /// Returns the trade plans in the order of the rows.
+    pub fn parse_trade_plans(csv: &str) -> Result<Vec<TradePlan>, Box<dyn std::error::Error>> {
+        backtest::parse(csv)
+    }
+
     // Trade Steps
 
     /// Fund a trade by transferring capital from the account.
@@ -380,6 +812,86 @@ impl TrustFacade {
         commands::trade::submit(trade, &mut *self.factory, &mut *self.broker)
     }
 
+    /// Import the open positions and the working entry orders of the broker as trades.
+    ///
+    /// Every position becomes a funded trade with the orders of the broker, and a
+    /// filled entry opens it. Positions without a stop or a target, that the account
+    /// can not fund, or that were imported before are skipped.
+    ///
+    /// Broker logs belong to a trade, so listing the positions of the account is the
+    /// one call to the broker that is not logged. Its failure is returned instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account that holds the positions at the broker
+    ///
+    /// # Returns
+    ///
+    /// Returns the imported trades and the skipped positions with the reason.
+    pub fn import_positions(
+        &mut self,
+        account: &Account,
+    ) -> Result<(Vec<Trade>, Vec<SkippedPosition>), Box<dyn std::error::Error>> {
+        let capabilities = self.broker.capabilities();
+        let mut trades = Vec::new();
+        let mut skipped = Vec::new();
+        for position in self.broker.positions(account)? {
+            match commands::trade::import(&position, account, &capabilities, &mut *self.factory) {
+                Ok(trade) => trades.push(trade),
+                Err(error) => skipped.push(SkippedPosition {
+                    position,
+                    reason: error.to_string(),
+                }),
+            }
+        }
+        Ok((trades, skipped))
+    }
+
+    /// Import the deposits, withdrawals, interest, dividends, fees and fills of the broker.
+    ///
+    /// Activities are imported once by their broker ID. Cash activities become
+    /// transactions unless they were already recorded by hand, and fills are compared
+    /// with the orders of the trades.
+    ///
+    /// Like listing the positions, listing the activities of the account is not part
+    /// of a trade, so it is not saved as a broker log.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account of the activities at the broker
+    ///
+    /// # Returns
+    ///
+    /// Returns the created transactions, the matched activities and the discrepancies.
+    pub fn import_activities(
+        &mut self,
+        account: &Account,
+    ) -> Result<ActivityImport, Box<dyn std::error::Error>> {
+        let activities = self.broker.activities(account)?;
+        commands::activity::import(account, activities, &mut *self.factory)
+    }
+
+    /// Import the activities of a CSV, for brokers that can not list them.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account of the activities
+    /// * `csv` - The content of the CSV file, with ID, Date, Type and Amount columns
+    /// * `currency` - The currency of the amounts
+    ///
+    /// # Returns
+    ///
+    /// Returns the created transactions, the matched activities and the discrepancies.
+    pub fn import_activities_csv(
+        &mut self,
+        account: &Account,
+        csv: &str,
+        currency: &Currency,
+    ) -> Result<ActivityImport, Box<dyn std::error::Error>> {
+        let activities = commands::activity::parse(csv, currency)?;
+        commands::activity::import(account, activities, &mut *self.factory)
+    }
+
     /// Synchronize trade status with the broker.
     ///
     /// # Arguments
@@ -407,15 +919,50 @@ impl TrustFacade {
     ///
     /// # Returns
     ///
-    /// Returns a tuple of the updated trade and transaction.
+    /// Returns a tuple of the updated trade and the transaction that moves the capital
+    /// into the market, if the fills were not moved already while partially filled.
     pub fn fill_trade(
         &mut self,
         trade: &Trade,
         fee: Decimal,
-    ) -> Result<(Trade, Transaction), Box<dyn std::error::Error>> {
+    ) -> Result<(Trade, Option<Transaction>), Box<dyn std::error::Error>> {
         commands::trade::fill_trade(trade, fee, self.factory.as_mut())
     }
 
+    /// Calculate the fee of filling the entry of a trade with the fee schedule of its account.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade` - The trade to be filled
+    ///
+    /// # Returns
+    ///
+    /// Returns the fee, or zero if the account does not have a fee schedule.
+    pub fn calculate_opening_fee(
+        &mut self,
+        trade: &Trade,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        commands::fee::opening(trade, &mut *self.factory)
+    }
+
+    /// Calculate the fee of closing a trade with the fee schedule of its account.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade` - The trade to be closed
+    /// * `exit` - The order that closes the trade, either the stop or the target
+    ///
+    /// # Returns
+    ///
+    /// Returns the fee, or zero if the account does not have a fee schedule.
+    pub fn calculate_closing_fee(
+        &mut self,
+        trade: &Trade,
+        exit: &Order,
+    ) -> Result<Decimal, Box<dyn std::error::Error>> {
+        commands::fee::closing(trade, exit, &mut *self.factory)
+    }
+
     /// Handle a trade that hit its stop loss.
     ///
     /// # Arguments
@@ -451,6 +998,24 @@ impl TrustFacade {
         commands::trade::close(trade, &mut *self.factory, &mut *self.broker)
     }
 
+    /// Close part of an open trade at market price. The rest of the trade stays open.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade` - The trade to close partially
+    /// * `quantity` - The quantity to close, less than the open quantity
+    ///
+    /// # Returns
+    ///
+    /// Returns a tuple of the updated trade, the transaction of the closed quantity and broker log.
+    pub fn close_partial(
+        &mut self,
+        trade: &Trade,
+        quantity: u64,
+    ) -> Result<(Trade, Transaction, BrokerLog), Box<dyn std::error::Error>> {
+        commands::trade::close_partial(trade, quantity, &mut *self.factory, &mut *self.broker)
+    }
+
     /// Cancel a funded trade and return capital to the account.
     ///
     /// # Arguments
@@ -502,6 +1067,39 @@ impl TrustFacade {
         commands::trade::target_acquired(trade, fee, &mut *self.factory)
     }
 
+    /// Modify the entry price and quantity of a submitted trade that is not filled.
+    ///
+    /// The trade is validated again against the available capital and the rules,
+    /// the entry is replaced at the broker, and the stop and the target are resized.
+    ///
+    /// # Arguments
+    ///
+    /// * `trade` - The trade to modify
+    /// * `account` - The account associated with the trade
+    /// * `new_price` - The new entry price
+    /// * `new_quantity` - The new quantity of the trade
+    ///
+    /// # Returns
+    ///
+    /// Returns the updated trade, and the transaction that funds the extra capital
+    /// or pays back the capital that is not needed, if the capital changed.
+    pub fn modify_entry(
+        &mut self,
+        trade: &Trade,
+        account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<(Trade, Option<Transaction>), Box<dyn std::error::Error>> {
+        commands::trade::modify_entry(
+            trade,
+            account,
+            new_price,
+            new_quantity,
+            &mut *self.broker,
+            &mut *self.factory,
+        )
+    }
+
     /// Modify the stop loss price of an active trade.
     ///
     /// # Arguments
@@ -555,6 +1153,7 @@ impl TrustFacade {
     }
 }
 
+mod backtest;
 mod calculators_account;
 mod calculators_trade;
 mod commands;
@@ -73,6 +73,69 @@ pub mod read_transaction_db_mocks {
             self.trades.push(trade);
         }
 
+        pub fn set_open_trade(
+            &mut self,
+            category: TradeCategory,
+            entry: Decimal,
+            stop: Decimal,
+            quantity: u64,
+        ) {
+            let trade = Trade {
+                status: Status::Filled,
+                currency: Currency::USD,
+                category,
+                account_id: self.account_id,
+                entry: MockDatabase::order(entry, OrderCategory::Limit, OrderAction::Buy, quantity),
+                safety_stop: MockDatabase::order(
+                    stop,
+                    OrderCategory::Stop,
+                    OrderAction::Sell,
+                    quantity,
+                ),
+                ..Default::default()
+            };
+            self.trades.push(trade);
+        }
+
+        pub fn set_closed_trade(&mut self, performance: Decimal) {
+            let trade = Trade {
+                status: Status::ClosedTarget,
+                account_id: self.account_id,
+                balance: TradeBalance {
+                    total_performance: performance,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            self.trades.push(trade);
+        }
+
+        pub fn set_closed_trade_with_risk(
+            &mut self,
+            performance: Decimal,
+            entry: Decimal,
+            stop: Decimal,
+            quantity: u64,
+        ) {
+            let trade = Trade {
+                status: Status::ClosedTarget,
+                account_id: self.account_id,
+                entry: MockDatabase::order(entry, OrderCategory::Limit, OrderAction::Buy, quantity),
+                safety_stop: MockDatabase::order(
+                    stop,
+                    OrderCategory::Stop,
+                    OrderAction::Sell,
+                    quantity,
+                ),
+                balance: TradeBalance {
+                    total_performance: performance,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            self.trades.push(trade);
+        }
+
         fn order(
             amount: Decimal,
             category: OrderCategory,
@@ -166,9 +229,14 @@ pub mod read_transaction_db_mocks {
         fn read_trades_with_status(
             &mut self,
             _account_id: Uuid,
-            _status: Status,
+            status: Status,
         ) -> Result<Vec<Trade>, Box<dyn Error>> {
-            Ok(self.trades.clone())
+            Ok(self
+                .trades
+                .iter()
+                .filter(|trade| trade.status == status)
+                .cloned()
+                .collect())
         }
 
         fn read_trade(&mut self, _id: Uuid) -> Result<Trade, Box<dyn Error>> {
@@ -1,4 +1,7 @@
+pub mod account;
+pub mod broker;
 pub mod funding;
+pub mod price;
 pub mod rule;
 pub mod trade;
 pub mod transaction;
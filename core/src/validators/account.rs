@@ -0,0 +1,177 @@
+use model::{FeeSchedule, PositionSizing};
+use rust_decimal_macros::dec;
+use std::error::Error;
+
+type AccountValidationResult = Result<(), Box<AccountValidationError>>;
+
+pub fn can_use_position_sizing(position_sizing: &PositionSizing) -> AccountValidationResult {
+    match position_sizing {
+        PositionSizing::FixedFractional => Ok(()),
+        PositionSizing::FixedDollar(value) | PositionSizing::Volatility(value)
+            if *value <= dec!(0) =>
+        {
+            Err(Box::new(AccountValidationError {
+                code: AccountValidationErrorCode::PositionSizingValueNotValid,
+                message: format!("Position sizing {position_sizing} requires a value above 0"),
+            }))
+        }
+        PositionSizing::Kelly(value) if *value <= dec!(0) || *value > dec!(1) => {
+            Err(Box::new(AccountValidationError {
+                code: AccountValidationErrorCode::PositionSizingValueNotValid,
+                message: format!(
+                    "Position sizing {position_sizing} requires a fraction above 0 and up to 1"
+                ),
+            }))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn can_use_fee_schedule(schedule: &FeeSchedule) -> AccountValidationResult {
+    let values = [
+        schedule.per_share,
+        schedule.per_order,
+        schedule.percentage,
+        schedule.minimum,
+        schedule.maximum.unwrap_or_default(),
+        schedule.sec_fee_rate,
+        schedule.taf_per_share,
+        schedule.taf_maximum,
+    ];
+    if values
+        .iter()
+        .any(|value| value.is_sign_negative() && !value.is_zero())
+    {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::FeeScheduleNotValid,
+            message: "Fee schedule values can not be negative".to_string(),
+        }));
+    }
+
+    match schedule.maximum {
+        Some(maximum) if maximum < schedule.minimum => Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::FeeScheduleNotValid,
+            message: format!(
+                "Fee schedule maximum {maximum} can not be lower than the minimum {}",
+                schedule.minimum
+            ),
+        })),
+        _ => Ok(()),
+    }
+}
+
+pub fn can_use_benchmark(symbol: &str) -> AccountValidationResult {
+    let valid = !symbol.is_empty()
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '^'));
+    if !valid {
+        return Err(Box::new(AccountValidationError {
+            code: AccountValidationErrorCode::InvalidBenchmark,
+            message: format!("Benchmark symbol '{symbol}' is not valid"),
+        }));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+
+pub enum AccountValidationErrorCode {
+    PositionSizingValueNotValid,
+    FeeScheduleNotValid,
+    InvalidBenchmark,
+}
+
+#[derive(Debug)]
+pub struct AccountValidationError {
+    pub code: AccountValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for AccountValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "AccountValidationError: {}, code: {:?}",
+            self.message, self.code
+        )
+    }
+}
+
+impl Error for AccountValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_use_position_sizing() {
+        assert!(can_use_position_sizing(&PositionSizing::FixedFractional).is_ok());
+        assert!(can_use_position_sizing(&PositionSizing::FixedDollar(dec!(200))).is_ok());
+        assert!(can_use_position_sizing(&PositionSizing::Volatility(dec!(2))).is_ok());
+        assert!(can_use_position_sizing(&PositionSizing::Kelly(dec!(0.5))).is_ok());
+    }
+
+    #[test]
+    fn test_can_not_use_position_sizing_without_value() {
+        let result = can_use_position_sizing(&PositionSizing::FixedDollar(dec!(0)));
+        assert_eq!(
+            result.unwrap_err().code,
+            AccountValidationErrorCode::PositionSizingValueNotValid
+        );
+        assert!(can_use_position_sizing(&PositionSizing::Volatility(dec!(-1))).is_err());
+    }
+
+    #[test]
+    fn test_can_not_use_more_than_full_kelly() {
+        assert!(can_use_position_sizing(&PositionSizing::Kelly(dec!(1.5))).is_err());
+        assert!(can_use_position_sizing(&PositionSizing::Kelly(dec!(0))).is_err());
+    }
+
+    #[test]
+    fn test_can_use_fee_schedule() {
+        let schedule = FeeSchedule {
+            per_share: dec!(0.005),
+            minimum: dec!(1),
+            maximum: Some(dec!(5)),
+            ..Default::default()
+        };
+        assert!(can_use_fee_schedule(&schedule).is_ok());
+        assert!(can_use_fee_schedule(&FeeSchedule::default()).is_ok());
+    }
+
+    #[test]
+    fn test_can_not_use_fee_schedule_with_invalid_values() {
+        let negative = FeeSchedule {
+            per_order: dec!(-1),
+            ..Default::default()
+        };
+        assert_eq!(
+            can_use_fee_schedule(&negative).unwrap_err().code,
+            AccountValidationErrorCode::FeeScheduleNotValid
+        );
+
+        let maximum_below_minimum = FeeSchedule {
+            minimum: dec!(2),
+            maximum: Some(dec!(1)),
+            ..Default::default()
+        };
+        assert!(can_use_fee_schedule(&maximum_below_minimum).is_err());
+    }
+
+    #[test]
+    fn test_can_use_benchmark() {
+        assert!(can_use_benchmark("SPY").is_ok());
+        assert!(can_use_benchmark("^GSPC").is_ok());
+        assert!(can_use_benchmark("BRK.B").is_ok());
+        assert_eq!(
+            can_use_benchmark("").unwrap_err().code,
+            AccountValidationErrorCode::InvalidBenchmark
+        );
+        assert!(can_use_benchmark("S P Y").is_err());
+    }
+}
@@ -0,0 +1,321 @@
+use model::{
+    BrokerCapabilities, DraftTrade, Order, OrderCategory, Trade, TradeCategory, TradingVehicle,
+    TradingVehicleCategory,
+};
+use std::error::Error;
+
+type BrokerValidationResult = Result<(), Box<BrokerValidationError>>;
+
+pub fn can_create(trade: &DraftTrade, capabilities: &BrokerCapabilities) -> BrokerValidationResult {
+    can_trade(trade.category, &trade.trading_vehicle, capabilities)?;
+
+    // Trades are sized in whole units, so less than one coin needs a fractional quantity
+    if trade.trading_vehicle.category == TradingVehicleCategory::Crypto
+        && !capabilities.fractional
+        && trade.quantity < 1
+    {
+        return Err(Box::new(BrokerValidationError {
+            code: BrokerValidationErrorCode::Fractional,
+            message: format!(
+                "The broker only trades whole units of {}, fractional quantities are not supported",
+                trade.trading_vehicle.symbol
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+fn can_trade(
+    category: TradeCategory,
+    trading_vehicle: &TradingVehicle,
+    capabilities: &BrokerCapabilities,
+) -> BrokerValidationResult {
+    if category == TradeCategory::Short && !capabilities.short_selling {
+        return Err(Box::new(BrokerValidationError {
+            code: BrokerValidationErrorCode::ShortSelling,
+            message: "The broker does not support short selling".to_string(),
+        }));
+    }
+
+    if trading_vehicle.category == TradingVehicleCategory::Crypto && !capabilities.crypto {
+        return Err(Box::new(BrokerValidationError {
+            code: BrokerValidationErrorCode::Crypto,
+            message: format!(
+                "The broker does not support cryptocurrencies like {}",
+                trading_vehicle.symbol
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
+pub fn can_submit(trade: &Trade, capabilities: &BrokerCapabilities) -> BrokerValidationResult {
+    can_trade(trade.category, &trade.trading_vehicle, capabilities)?;
+
+    // Trust saves the stop as a market order that the broker triggers at its price
+    let orders = [
+        ("entry", &trade.entry, trade.entry.category),
+        ("stop", &trade.safety_stop, OrderCategory::Stop),
+        ("target", &trade.target, trade.target.category),
+    ];
+    for (leg, order, category) in orders {
+        can_place(leg, order, category, capabilities)?;
+    }
+    Ok(())
+}
+
+fn can_place(
+    leg: &str,
+    order: &Order,
+    category: OrderCategory,
+    capabilities: &BrokerCapabilities,
+) -> BrokerValidationResult {
+    if !capabilities.order_categories.contains(&category) {
+        return Err(Box::new(BrokerValidationError {
+            code: BrokerValidationErrorCode::OrderCategory,
+            message: format!("The broker does not support {category} orders for the {leg}"),
+        }));
+    }
+
+    if !capabilities.time_in_force.contains(&order.time_in_force) {
+        return Err(Box::new(BrokerValidationError {
+            code: BrokerValidationErrorCode::TimeInForce,
+            message: format!(
+                "The broker does not support the time in force {} for the {leg}",
+                order.time_in_force
+            ),
+        }));
+    }
+
+    if order.extended_hours && !capabilities.extended_hours {
+        return Err(Box::new(BrokerValidationError {
+            code: BrokerValidationErrorCode::ExtendedHours,
+            message: format!("The broker does not support extended hours for the {leg}"),
+        }));
+    }
+
+    Ok(())
+}
+
+pub fn can_modify_entry(capabilities: &BrokerCapabilities) -> BrokerValidationResult {
+    if capabilities.modify_entry {
+        return Ok(());
+    }
+    Err(Box::new(BrokerValidationError {
+        code: BrokerValidationErrorCode::Modification,
+        message: "The broker does not support modifying the entry".to_string(),
+    }))
+}
+
+pub fn can_modify_stop(capabilities: &BrokerCapabilities) -> BrokerValidationResult {
+    if capabilities.modify_stop {
+        return Ok(());
+    }
+    Err(Box::new(BrokerValidationError {
+        code: BrokerValidationErrorCode::Modification,
+        message: "The broker does not support modifying the stop".to_string(),
+    }))
+}
+
+pub fn can_modify_target(capabilities: &BrokerCapabilities) -> BrokerValidationResult {
+    if capabilities.modify_target {
+        return Ok(());
+    }
+    Err(Box::new(BrokerValidationError {
+        code: BrokerValidationErrorCode::Modification,
+        message: "The broker does not support modifying the target".to_string(),
+    }))
+}
+
+pub fn can_close_partial(capabilities: &BrokerCapabilities) -> BrokerValidationResult {
+    if capabilities.partial_close {
+        return Ok(());
+    }
+    Err(Box::new(BrokerValidationError {
+        code: BrokerValidationErrorCode::Modification,
+        message: "The broker does not support closing part of a trade".to_string(),
+    }))
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BrokerValidationErrorCode {
+    OrderCategory,
+    TimeInForce,
+    ExtendedHours,
+    ShortSelling,
+    Crypto,
+    Fractional,
+    Modification,
+}
+
+#[derive(Debug)]
+pub struct BrokerValidationError {
+    pub code: BrokerValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for BrokerValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "BrokerValidationError: {}, code: {:?}",
+            self.message, self.code
+        )
+    }
+}
+
+impl Error for BrokerValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{Account, Currency, TimeInForce};
+
+    fn trade(category: TradeCategory, vehicle: TradingVehicleCategory) -> Trade {
+        Trade {
+            category,
+            trading_vehicle: TradingVehicle {
+                category: vehicle,
+                ..Default::default()
+            },
+            entry: Order {
+                category: OrderCategory::Limit,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                category: OrderCategory::Market,
+                ..Default::default()
+            },
+            target: Order {
+                category: OrderCategory::Limit,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_submit_supported() {
+        let trade = trade(TradeCategory::Long, TradingVehicleCategory::Stock);
+        assert!(can_submit(&trade, &BrokerCapabilities::default()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_submit_short_selling_not_supported() {
+        let trade = trade(TradeCategory::Short, TradingVehicleCategory::Stock);
+        let capabilities = BrokerCapabilities {
+            short_selling: false,
+            ..Default::default()
+        };
+        let error = can_submit(&trade, &capabilities).unwrap_err();
+        assert_eq!(error.code, BrokerValidationErrorCode::ShortSelling);
+    }
+
+    #[test]
+    fn test_validate_submit_crypto_not_supported() {
+        let trade = trade(TradeCategory::Long, TradingVehicleCategory::Crypto);
+        let error = can_submit(&trade, &BrokerCapabilities::default()).unwrap_err();
+        assert_eq!(error.code, BrokerValidationErrorCode::Crypto);
+
+        let capabilities = BrokerCapabilities {
+            crypto: true,
+            ..Default::default()
+        };
+        assert!(can_submit(&trade, &capabilities).is_ok());
+    }
+
+    #[test]
+    fn test_validate_create_fractional_crypto_not_supported() {
+        let draft = |quantity| DraftTrade {
+            account: Account::default(),
+            trading_vehicle: TradingVehicle {
+                symbol: "BTC/USD".to_string(),
+                category: TradingVehicleCategory::Crypto,
+                ..Default::default()
+            },
+            quantity,
+            currency: Currency::USD,
+            category: TradeCategory::Long,
+        };
+        let capabilities = BrokerCapabilities {
+            crypto: true,
+            ..Default::default()
+        };
+
+        let error = can_create(&draft(0), &capabilities).unwrap_err();
+        assert_eq!(error.code, BrokerValidationErrorCode::Fractional);
+        assert_eq!(
+            error.message,
+            "The broker only trades whole units of BTC/USD, fractional quantities are not supported"
+        );
+        assert!(can_create(&draft(1), &capabilities).is_ok());
+    }
+
+    #[test]
+    fn test_validate_submit_stop_orders_not_supported() {
+        let trade = trade(TradeCategory::Long, TradingVehicleCategory::Stock);
+        let capabilities = BrokerCapabilities {
+            order_categories: vec![OrderCategory::Market, OrderCategory::Limit],
+            ..Default::default()
+        };
+        let error = can_submit(&trade, &capabilities).unwrap_err();
+        assert_eq!(error.code, BrokerValidationErrorCode::OrderCategory);
+        assert_eq!(
+            error.message,
+            "The broker does not support stop orders for the stop"
+        );
+    }
+
+    #[test]
+    fn test_validate_submit_time_in_force_not_supported() {
+        let trade = trade(TradeCategory::Long, TradingVehicleCategory::Stock);
+        let capabilities = BrokerCapabilities {
+            time_in_force: vec![TimeInForce::Day],
+            ..Default::default()
+        };
+        let error = can_submit(&trade, &capabilities).unwrap_err();
+        assert_eq!(error.code, BrokerValidationErrorCode::TimeInForce);
+    }
+
+    #[test]
+    fn test_validate_submit_extended_hours_not_supported() {
+        let mut trade = trade(TradeCategory::Long, TradingVehicleCategory::Stock);
+        trade.entry.extended_hours = true;
+        let error = can_submit(&trade, &BrokerCapabilities::default()).unwrap_err();
+        assert_eq!(error.code, BrokerValidationErrorCode::ExtendedHours);
+    }
+
+    #[test]
+    fn test_validate_modify() {
+        let capabilities = BrokerCapabilities {
+            modify_entry: false,
+            modify_target: false,
+            ..Default::default()
+        };
+        assert!(can_modify_stop(&capabilities).is_ok());
+        assert_eq!(
+            can_modify_target(&capabilities).unwrap_err().code,
+            BrokerValidationErrorCode::Modification
+        );
+        assert_eq!(
+            can_modify_entry(&capabilities).unwrap_err().code,
+            BrokerValidationErrorCode::Modification
+        );
+    }
+
+    #[test]
+    fn test_validate_close_partial() {
+        assert!(can_close_partial(&BrokerCapabilities::default()).is_err());
+        let capabilities = BrokerCapabilities {
+            partial_close: true,
+            ..Default::default()
+        };
+        assert!(can_close_partial(&capabilities).is_ok());
+    }
+}
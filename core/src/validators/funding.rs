@@ -1,5 +1,6 @@
-use crate::calculators_trade::{RiskCalculator, TradeCapitalRequired};
-use model::{AccountBalance, DatabaseFactory, Rule, RuleName, Trade, TradeCategory};
+use crate::calculators_account::AccountOpenRisk;
+use crate::calculators_trade::{RiskCalculator, TradeCapitalRequired, TradeFees};
+use model::{AccountBalance, DatabaseFactory, FeeSchedule, Rule, RuleName, Trade, TradeCategory};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
@@ -9,6 +10,24 @@ type FundingValidationResult = Result<(), Box<FundValidationError>>;
 
 // Validate if trade can be funded by checking account balance, available capital and rules
 pub fn can_fund(trade: &Trade, database: &mut dyn DatabaseFactory) -> FundingValidationResult {
+    validate(trade, dec!(0), database)
+}
+
+// Validate a funded trade that is being modified, as if it was funded again
+// with the capital that it already holds returned to the account
+pub fn can_modify_funding(
+    trade: &Trade,
+    funded: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> FundingValidationResult {
+    validate(trade, funded, database)
+}
+
+fn validate(
+    trade: &Trade,
+    funded: Decimal,
+    database: &mut dyn DatabaseFactory,
+) -> FundingValidationResult {
     // 1.  Get account balance
     let account = database.account_read().id(trade.account_id).map_err(|e| {
         Box::new(FundValidationError {
@@ -17,14 +36,39 @@ pub fn can_fund(trade: &Trade, database: &mut dyn DatabaseFactory) -> FundingVal
         })
     })?;
 
-    // 2. Calculate account balance based on the given trade currency
+    // 2. Read the fee schedule of the account, if any, to include the estimated fees
+    let schedule = database
+        .fee_schedule_read()
+        .fee_schedule_for_account(trade.account_id)
+        .map_err(|e| {
+            Box::new(FundValidationError {
+                code: FundValidationErrorCode::NotEnoughFunds,
+                message: format!(
+                    "Fee schedule of account {} not read: {}",
+                    trade.account_id, e
+                ),
+            })
+        })?;
+
+    // 3. Calculate account balance based on the given trade currency
     // This calculators uses all the transactions to ensure that the account balance is the latest one
     match crate::commands::balance::calculate_account(database, &account, &trade.currency) {
         Ok(balance) => {
-            // 3. Validate that there is enough capital available to fund the trade
-            validate_enough_capital(trade, &balance)?;
-            // 4. Validate the trade against all the applicable rules
-            validate_rules(trade, &balance, database)
+            // The capital that the trade already holds is available to fund it again
+            let total_available = balance.total_available.checked_add(funded).ok_or_else(|| {
+                Box::new(FundValidationError {
+                    code: FundValidationErrorCode::NotEnoughFunds,
+                    message: "Addition overflow calculating available capital".to_string(),
+                })
+            })?;
+            let balance = AccountBalance {
+                total_available,
+                ..balance
+            };
+            // 4. Validate that there is enough capital available to fund the trade
+            validate_enough_capital(trade, &balance, schedule.as_ref())?;
+            // 5. Validate the trade against all the applicable rules
+            validate_rules(trade, &balance, schedule.as_ref(), database)
         }
         Err(e) => {
             // If there is not enough funds in the account for the given currency, return an error
@@ -39,13 +83,18 @@ pub fn can_fund(trade: &Trade, database: &mut dyn DatabaseFactory) -> FundingVal
     }
 }
 
-fn validate_enough_capital(trade: &Trade, balance: &AccountBalance) -> FundingValidationResult {
-    let required_capital = TradeCapitalRequired::calculate(trade).map_err(|e| {
-        Box::new(FundValidationError {
-            code: FundValidationErrorCode::NotEnoughFunds,
-            message: format!("Error calculating required capital: {e}"),
-        })
-    })?;
+fn validate_enough_capital(
+    trade: &Trade,
+    balance: &AccountBalance,
+    schedule: Option<&FeeSchedule>,
+) -> FundingValidationResult {
+    let required_capital =
+        TradeCapitalRequired::calculate_with_fees(trade, schedule).map_err(|e| {
+            Box::new(FundValidationError {
+                code: FundValidationErrorCode::NotEnoughFunds,
+                message: format!("Error calculating required capital: {e}"),
+            })
+        })?;
 
     if balance.total_available >= required_capital {
         Ok(())
@@ -54,7 +103,7 @@ fn validate_enough_capital(trade: &Trade, balance: &AccountBalance) -> FundingVa
             code: FundValidationErrorCode::NotEnoughFunds,
             message: format!(
                 "Not enough funds in account {} for {} trade in {}. \
-                Required: {} (based on {}), Available: {}",
+                Required: {} (based on {}{}), Available: {}",
                 trade.account_id,
                 trade.category,
                 trade.currency,
@@ -63,6 +112,11 @@ fn validate_enough_capital(trade: &Trade, balance: &AccountBalance) -> FundingVa
                     TradeCategory::Long => "entry price",
                     TradeCategory::Short => "stop price (full amount needed to close)",
                 },
+                if schedule.is_some() {
+                    " and estimated fees"
+                } else {
+                    ""
+                },
                 balance.total_available
             ),
         }))
@@ -74,13 +128,14 @@ fn sorted_rules(account_id: Uuid, database: &mut dyn DatabaseFactory) -> Vec<Rul
         .rule_read()
         .read_all_rules(account_id)
         .unwrap_or_else(|_| vec![]);
-    rules.sort_by(|a, b| a.priority.cmp(&b.priority));
+    rules.sort_by_key(|a| a.priority);
     rules
 }
 
 fn validate_rules(
     trade: &Trade,
     account_balance: &AccountBalance,
+    schedule: Option<&FeeSchedule>,
     database: &mut dyn DatabaseFactory,
 ) -> FundingValidationResult {
     // Get rules by priority
@@ -111,7 +166,37 @@ fn validate_rules(
                         message: format!("Failed to convert risk {risk} to decimal"),
                     })
                 })?;
-                validate_risk_per_trade(trade, account_balance, risk_decimal, risk_per_month)?;
+                validate_risk_per_trade(
+                    trade,
+                    account_balance,
+                    risk_decimal,
+                    risk_per_month,
+                    schedule,
+                )?;
+            }
+            RuleName::MaxOpenRisk(risk) => {
+                let open_risk =
+                    AccountOpenRisk::calculate_excluding(trade, database.trade_read().as_mut())
+                        .map_err(|e| {
+                            Box::new(FundValidationError {
+                                code: FundValidationErrorCode::NotEnoughFunds,
+                                message: format!("Error calculating open risk: {e}"),
+                            })
+                        })?;
+                let capital = AccountOpenRisk::in_market(
+                    trade.account_id,
+                    &trade.currency,
+                    database.trade_read().as_mut(),
+                    database.transaction_read().as_mut(),
+                )
+                .and_then(|in_market| AccountOpenRisk::capital(account_balance, in_market))
+                .map_err(|e| {
+                    Box::new(FundValidationError {
+                        code: FundValidationErrorCode::NotEnoughFunds,
+                        message: format!("Error calculating capital: {e}"),
+                    })
+                })?;
+                validate_max_open_risk(trade, capital, risk, open_risk)?;
             }
         }
     }
@@ -127,6 +212,7 @@ fn validate_risk_per_trade(
     account_balance: &AccountBalance,
     risk: Decimal,
     risk_per_month: Decimal,
+    schedule: Option<&FeeSchedule>,
 ) -> FundingValidationResult {
     // Check if the risk per month limit has been exceeded.
     if risk_per_month < risk {
@@ -155,7 +241,17 @@ fn validate_risk_per_trade(
             })
         })?;
 
-    // Calculate the total amount that will be risked in this trade.
+    // Calculate the total amount that will be risked in this trade, including the fees
+    // of opening it and closing it at the stop.
+    let fees = match schedule {
+        Some(schedule) => TradeFees::estimated(trade, schedule).map_err(|e| {
+            Box::new(FundValidationError {
+                code: FundValidationErrorCode::NotEnoughFunds,
+                message: format!("Error calculating estimated fees: {e}"),
+            })
+        })?,
+        None => dec!(0),
+    };
     let price_diff = trade
         .entry
         .unit_price
@@ -168,6 +264,7 @@ fn validate_risk_per_trade(
         })?;
     let total_risk = price_diff
         .checked_mul(Decimal::from(trade.entry.quantity))
+        .and_then(|risk| risk.checked_add(fees))
         .ok_or_else(|| {
             Box::new(FundValidationError {
                 code: FundValidationErrorCode::NotEnoughFunds,
@@ -180,7 +277,8 @@ fn validate_risk_per_trade(
         return Err(Box::new(FundValidationError {
             code: FundValidationErrorCode::RiskPerTradeExceeded,
             message: format!(
-                "Risk per trade exceeded for risk per trade rule, maximum that can be at risk is {maximum_risk}, trade is attempting to risk {total_risk}",
+                "Risk per trade exceeded for risk per trade rule, maximum that can be at risk is {maximum_risk}, trade is attempting to risk {total_risk}{}",
+                if fees.is_zero() { String::new() } else { format!(" (including {fees} in fees)") },
             ),
         }));
     }
@@ -189,6 +287,45 @@ fn validate_risk_per_trade(
     Ok(())
 }
 
+// This function validates that funding the trade does not put more than the given
+// percentage of the capital of the account at risk, counting the trades already open.
+fn validate_max_open_risk(
+    trade: &Trade,
+    capital: Decimal,
+    risk: f32,
+    open_risk: Decimal,
+) -> FundingValidationResult {
+    let arithmetic_error = |message: &str| {
+        Box::new(FundValidationError {
+            code: FundValidationErrorCode::NotEnoughFunds,
+            message: message.to_string(),
+        })
+    };
+
+    let risk_decimal = Decimal::from_f32_retain(risk)
+        .ok_or_else(|| arithmetic_error(&format!("Failed to convert risk {risk} to decimal")))?;
+    let maximum_risk = capital
+        .checked_mul(risk_decimal)
+        .and_then(|risk| risk.checked_div(dec!(100.0)))
+        .ok_or_else(|| arithmetic_error("Arithmetic overflow calculating maximum open risk"))?;
+    let trade_risk = AccountOpenRisk::trade_risk(trade)
+        .map_err(|e| arithmetic_error(&format!("Error calculating trade risk: {e}")))?;
+    let total_risk = open_risk
+        .checked_add(trade_risk)
+        .ok_or_else(|| arithmetic_error("Addition overflow calculating total open risk"))?;
+
+    if total_risk > maximum_risk {
+        return Err(Box::new(FundValidationError {
+            code: FundValidationErrorCode::MaxOpenRiskExceeded,
+            message: format!(
+                "Max open risk exceeded, maximum that can be at risk in open trades is {maximum_risk}, {open_risk} is already at risk and the trade is attempting to risk {trade_risk}",
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, PartialEq)]
 pub struct FundValidationError {
     pub code: FundValidationErrorCode,
@@ -210,6 +347,7 @@ impl Error for FundValidationError {
 pub enum FundValidationErrorCode {
     RiskPerTradeExceeded,
     RiskPerMonthExceeded,
+    MaxOpenRiskExceeded,
     NotEnoughFunds,
 }
 
@@ -235,7 +373,7 @@ mod tests {
             ..Default::default()
         };
 
-        assert!(validate_enough_capital(&trade, &balance).is_ok());
+        assert!(validate_enough_capital(&trade, &balance, None).is_ok());
     }
 
     #[test]
@@ -256,7 +394,7 @@ mod tests {
             ..Default::default()
         };
 
-        let result = validate_enough_capital(&trade, &balance);
+        let result = validate_enough_capital(&trade, &balance, None);
 
         assert!(result.is_err());
         let err_msg = result.unwrap_err().message;
@@ -289,7 +427,7 @@ mod tests {
         };
 
         // Then: Should pass validation
-        assert!(validate_enough_capital(&trade, &balance).is_ok());
+        assert!(validate_enough_capital(&trade, &balance, None).is_ok());
     }
 
     #[test]
@@ -319,7 +457,7 @@ mod tests {
         };
 
         // Then: Should fail with clear error message
-        let result = validate_enough_capital(&trade, &balance);
+        let result = validate_enough_capital(&trade, &balance, None);
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.message.contains("stop price"));
@@ -347,7 +485,9 @@ mod tests {
         };
         let risk = dec!(5);
         let risk_per_month = dec!(6.2);
-        assert!(validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month).is_ok());
+        assert!(
+            validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month, None).is_ok()
+        );
     }
 
     #[test]
@@ -371,7 +511,7 @@ mod tests {
         let risk = dec!(5);
         let risk_per_month = dec!(4.9);
         assert_eq!(
-            validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month),
+            validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month, None),
             Err(Box::new(FundValidationError {
                 code: FundValidationErrorCode::RiskPerMonthExceeded,
                 message: "Risk per month exceeded for risk per trade rule, maximum that can be at risk is 4.9, trade is attempting to risk 5".to_string(),
@@ -400,11 +540,109 @@ mod tests {
         let risk = dec!(3);
         let risk_per_month = dec!(5.1);
         assert_eq!(
-            validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month),
+            validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month, None),
             Err(Box::new(FundValidationError {
                 code: FundValidationErrorCode::RiskPerTradeExceeded,
                 message: "Risk per trade exceeded for risk per trade rule, maximum that can be at risk is 3.00, trade is attempting to risk 5".to_string(),
             }))
         );
     }
+
+    #[test]
+    fn test_validate_enough_capital_includes_fees() {
+        let trade = Trade {
+            entry: Order {
+                unit_price: dec!(10),
+                quantity: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let balance = AccountBalance {
+            total_available: dec!(100),
+            ..Default::default()
+        };
+        let schedule = FeeSchedule {
+            per_order: dec!(1),
+            ..Default::default()
+        };
+
+        assert!(validate_enough_capital(&trade, &balance, None).is_ok());
+        let result = validate_enough_capital(&trade, &balance, Some(&schedule));
+        assert!(result.unwrap_err().message.contains("estimated fees"));
+    }
+
+    #[test]
+    fn test_risk_per_trade_exceeded_by_fees() {
+        let trade = Trade {
+            entry: Order {
+                unit_price: dec!(10),
+                quantity: 5,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(9),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let account_balance = AccountBalance {
+            total_available: dec!(100),
+            ..Default::default()
+        };
+        let schedule = FeeSchedule {
+            per_order: dec!(1),
+            ..Default::default()
+        };
+        let risk = dec!(5);
+        let risk_per_month = dec!(6.2);
+        assert_eq!(
+            validate_risk_per_trade(&trade, &account_balance, risk, risk_per_month, Some(&schedule)),
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::RiskPerTradeExceeded,
+                message: "Risk per trade exceeded for risk per trade rule, maximum that can be at risk is 5.00, trade is attempting to risk 7 (including 2 in fees)".to_string(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_max_open_risk_success() {
+        let trade = Trade {
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 500,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(38),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // 6% of 50000 is 3000, 2000 is already at risk and the trade risks 1000
+        assert!(validate_max_open_risk(&trade, dec!(50000), 6.0, dec!(2000)).is_ok());
+    }
+
+    #[test]
+    fn test_max_open_risk_exceeded() {
+        let trade = Trade {
+            entry: Order {
+                unit_price: dec!(40),
+                quantity: 500,
+                ..Default::default()
+            },
+            safety_stop: Order {
+                unit_price: dec!(38),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            validate_max_open_risk(&trade, dec!(50000), 6.0, dec!(2500)),
+            Err(Box::new(FundValidationError {
+                code: FundValidationErrorCode::MaxOpenRiskExceeded,
+                message: "Max open risk exceeded, maximum that can be at risk in open trades is 3000, 2500 is already at risk and the trade is attempting to risk 1000".to_string(),
+            }))
+        );
+    }
 }
@@ -0,0 +1,120 @@
+use model::PriceBar;
+use rust_decimal_macros::dec;
+use std::error::Error;
+
+type PriceValidationResult = Result<(), Box<PriceValidationError>>;
+
+pub fn can_import_bar(bar: &PriceBar) -> PriceValidationResult {
+    if [bar.open, bar.high, bar.low, bar.close]
+        .iter()
+        .any(|price| *price <= dec!(0))
+    {
+        return Err(Box::new(PriceValidationError {
+            code: PriceValidationErrorCode::PriceMustBePositive,
+            message: format!("Prices of {} on {} must be positive", bar.symbol, bar.date),
+        }));
+    }
+
+    if bar.low > bar.high
+        || [bar.open, bar.close]
+            .iter()
+            .any(|price| *price < bar.low || *price > bar.high)
+    {
+        return Err(Box::new(PriceValidationError {
+            code: PriceValidationErrorCode::PriceOutsideOfRange,
+            message: format!(
+                "Open and close of {} on {} must be between the low {} and the high {}",
+                bar.symbol, bar.date, bar.low, bar.high
+            ),
+        }));
+    }
+
+    if bar.volume.is_sign_negative() && !bar.volume.is_zero() {
+        return Err(Box::new(PriceValidationError {
+            code: PriceValidationErrorCode::VolumeMustNotBeNegative,
+            message: format!(
+                "Volume of {} on {} can not be negative",
+                bar.symbol, bar.date
+            ),
+        }));
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PriceValidationErrorCode {
+    PriceMustBePositive,
+    PriceOutsideOfRange,
+    VolumeMustNotBeNegative,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PriceValidationError {
+    pub code: PriceValidationErrorCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for PriceValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PriceValidationError: {}", self.message)
+    }
+}
+
+impl Error for PriceValidationError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn bar(open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> PriceBar {
+        PriceBar {
+            symbol: "SPY".to_string(),
+            open,
+            high,
+            low,
+            close,
+            volume: dec!(100),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_can_import_bar() {
+        assert!(can_import_bar(&bar(dec!(10), dec!(12), dec!(9), dec!(11))).is_ok());
+        assert!(can_import_bar(&bar(dec!(10), dec!(10), dec!(10), dec!(10))).is_ok());
+    }
+
+    #[test]
+    fn test_can_not_import_bar_without_positive_prices() {
+        let result = can_import_bar(&bar(dec!(0), dec!(12), dec!(0), dec!(11)));
+        assert_eq!(
+            result.unwrap_err().code,
+            PriceValidationErrorCode::PriceMustBePositive
+        );
+    }
+
+    #[test]
+    fn test_can_not_import_bar_outside_of_range() {
+        let result = can_import_bar(&bar(dec!(10), dec!(12), dec!(9), dec!(13)));
+        assert_eq!(
+            result.unwrap_err().code,
+            PriceValidationErrorCode::PriceOutsideOfRange
+        );
+        assert!(can_import_bar(&bar(dec!(10), dec!(9), dec!(12), dec!(10))).is_err());
+    }
+
+    #[test]
+    fn test_can_not_import_bar_with_negative_volume() {
+        let mut bar = bar(dec!(10), dec!(12), dec!(9), dec!(11));
+        bar.volume = dec!(-1);
+        assert_eq!(
+            can_import_bar(&bar).unwrap_err().code,
+            PriceValidationErrorCode::VolumeMustNotBeNegative
+        );
+    }
+}
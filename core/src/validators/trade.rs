@@ -27,6 +27,24 @@ pub fn can_close(trade: &Trade) -> TradeValidationResult {
     }
 }
 
+/// The stop and the target are resized to the open quantity, so the stop has it.
+pub fn can_close_partial(trade: &Trade, quantity: u64) -> TradeValidationResult {
+    can_close(trade)?;
+
+    let open = trade.safety_stop.quantity;
+    if quantity == 0 || quantity >= open {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::QuantityNotValid,
+            message: format!(
+                "The quantity to close must be between 1 and {}, the trade has {open} open. Close the trade to close all of it",
+                open.saturating_sub(1)
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
 pub fn can_cancel_funded(trade: &Trade) -> TradeValidationResult {
     match trade.status {
         Status::Funded => Ok(()),
@@ -91,11 +109,54 @@ pub fn can_modify_target(trade: &Trade) -> TradeValidationResult {
     }
 }
 
+pub fn can_modify_entry(
+    trade: &Trade,
+    new_price: Decimal,
+    new_quantity: u64,
+) -> TradeValidationResult {
+    if trade.status != Status::Submitted || trade.entry.filled_quantity > 0 {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::TradeNotSubmitted,
+            message: format!(
+                "Trade with id {} is not submitted or its entry is already filled, the entry cannot be modified",
+                trade.id
+            ),
+        }));
+    }
+
+    if new_quantity == 0 {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::EntryNotValid,
+            message: "The quantity of the entry must be positive".to_string(),
+        }));
+    }
+
+    // The entry must stay between the stop and the target
+    let (low, high) = match trade.category {
+        TradeCategory::Long => (trade.safety_stop.unit_price, trade.target.unit_price),
+        TradeCategory::Short => (trade.target.unit_price, trade.safety_stop.unit_price),
+    };
+    if new_price <= low || new_price >= high {
+        return Err(Box::new(TradeValidationError {
+            code: TradeValidationErrorCode::EntryNotValid,
+            message: format!(
+                "The entry of a {} trade must be between {low} and {high}, new entry: {new_price}",
+                trade.category
+            ),
+        }));
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, PartialEq)]
 pub enum TradeValidationErrorCode {
     TradeNotFunded,
     TradeNotFilled,
+    TradeNotSubmitted,
     StopPriceNotValid,
+    EntryNotValid,
+    QuantityNotValid,
 }
 
 #[derive(Debug)]
@@ -330,4 +391,65 @@ mod tests {
         let result = can_modify_target(&trade);
         assert!(result.is_err());
     }
+
+    fn submitted(category: TradeCategory, stop: Decimal, target: Decimal) -> Trade {
+        Trade {
+            status: Status::Submitted,
+            category,
+            safety_stop: model::Order {
+                unit_price: stop,
+                ..Default::default()
+            },
+            target: model::Order {
+                unit_price: target,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_modify_entry() {
+        let long = submitted(TradeCategory::Long, dec!(38), dec!(50));
+        assert!(can_modify_entry(&long, dec!(39), 200).is_ok());
+        let short = submitted(TradeCategory::Short, dec!(50), dec!(38));
+        assert!(can_modify_entry(&short, dec!(45), 200).is_ok());
+    }
+
+    #[test]
+    fn test_validate_modify_entry_outside_stop_and_target() {
+        let trade = submitted(TradeCategory::Long, dec!(38), dec!(50));
+        let result = can_modify_entry(&trade, dec!(37), 200).unwrap_err();
+        assert_eq!(result.code, TradeValidationErrorCode::EntryNotValid);
+        assert!(can_modify_entry(&trade, dec!(50), 200).is_err());
+        assert!(can_modify_entry(&trade, dec!(40), 0).is_err());
+    }
+
+    #[test]
+    fn test_validate_modify_entry_filled() {
+        let mut trade = submitted(TradeCategory::Long, dec!(38), dec!(50));
+        trade.entry.filled_quantity = 10;
+        let result = can_modify_entry(&trade, dec!(40), 200).unwrap_err();
+        assert_eq!(result.code, TradeValidationErrorCode::TradeNotSubmitted);
+        trade.entry.filled_quantity = 0;
+        trade.status = Status::Funded;
+        assert!(can_modify_entry(&trade, dec!(40), 200).is_err());
+    }
+
+    #[test]
+    fn test_validate_close_partial() {
+        let mut trade = Trade {
+            status: Status::Filled,
+            ..Default::default()
+        };
+        trade.safety_stop.quantity = 100;
+        assert!(can_close_partial(&trade, 1).is_ok());
+        assert!(can_close_partial(&trade, 99).is_ok());
+        let result = can_close_partial(&trade, 100).unwrap_err();
+        assert_eq!(result.code, TradeValidationErrorCode::QuantityNotValid);
+        assert!(can_close_partial(&trade, 0).is_err());
+        trade.status = Status::Submitted;
+        let result = can_close_partial(&trade, 50).unwrap_err();
+        assert_eq!(result.code, TradeValidationErrorCode::TradeNotFilled);
+    }
 }
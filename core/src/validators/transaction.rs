@@ -1,4 +1,6 @@
-use model::{AccountBalance, AccountBalanceRead, Currency, Status, Trade, TradeCategory};
+use model::{
+    AccountBalance, AccountBalanceRead, Currency, Status, Trade, TradeCategory, TransactionCategory,
+};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::error::Error;
@@ -7,7 +9,7 @@ type TransactionValidationResult = Result<(), Box<TransactionValidationError>>;
 
 pub fn can_transfer_fill(trade: &Trade, total: Decimal) -> TransactionValidationResult {
     match trade.status {
-        Status::Submitted | Status::Funded => (),
+        Status::Submitted | Status::PartiallyFilled | Status::Funded => (),
         _ => {
             return Err(Box::new(TransactionValidationError {
                 code: TransactionValidationErrorCode::WrongTradeStatus,
@@ -128,11 +130,131 @@ pub fn can_transfer_withdraw(
     }
 }
 
+pub fn can_transfer_withdraw_earnings(
+    amount: Decimal,
+    earnings: Decimal,
+) -> TransactionValidationResult {
+    if amount.is_sign_negative() | amount.is_zero() {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountOfWithdrawalMustBePositive,
+            message: "Amount of withdrawal must be positive".to_string(),
+        }));
+    }
+
+    if amount > earnings {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::WithdrawalAmountIsGreaterThanEarnings,
+            message: format!(
+                "Withdrawal amount {amount} is greater than the earnings set aside {earnings}"
+            ),
+        }));
+    }
+    Ok(())
+}
+
+pub fn can_transfer_trade_transaction(
+    trade: &Trade,
+    category: &TransactionCategory,
+    amount: Decimal,
+) -> TransactionValidationResult {
+    if amount <= dec!(0) {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountMustBePositive,
+            message: format!("Amount of {category} must be positive"),
+        }));
+    }
+
+    // Dividends and borrow fees are only paid while the position is open or after it was closed.
+    let entered_market = match trade.status {
+        Status::Filled | Status::ClosedStopLoss | Status::ClosedTarget => true,
+        Status::Canceled => trade.entry.filled_at.is_some(),
+        _ => false,
+    };
+    if !entered_market {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::WrongTradeStatus,
+            message: format!(
+                "Trade with status {} has no position to pay a {category}",
+                trade.status
+            ),
+        }));
+    }
+
+    let expected_category = match category {
+        TransactionCategory::Dividend(_) => TradeCategory::Long,
+        TransactionCategory::BorrowFee(_) => TradeCategory::Short,
+        _ => {
+            return Err(Box::new(TransactionValidationError {
+                code: TransactionValidationErrorCode::WrongTransactionCategory,
+                message: format!("Category {category} can not be added to a trade"),
+            }))
+        }
+    };
+    if trade.category != expected_category {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::WrongTradeCategory,
+            message: format!("A {category} can only be added to a {expected_category} trade"),
+        }));
+    }
+    Ok(())
+}
+
+pub fn can_transfer_between_accounts(
+    from_account_id: Uuid,
+    to_account_id: Uuid,
+    balance: &AccountBalance,
+    amount: Decimal,
+    to_currency: &Currency,
+    exchange_rate: Option<Decimal>,
+) -> TransactionValidationResult {
+    if from_account_id == to_account_id {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::TransferToSameAccount,
+            message: "Source and destination accounts must be different".to_string(),
+        }));
+    }
+
+    if amount <= dec!(0) {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::AmountMustBePositive,
+            message: "Amount of transfer must be positive".to_string(),
+        }));
+    }
+
+    if amount > balance.total_available {
+        return Err(Box::new(TransactionValidationError {
+            code: TransactionValidationErrorCode::TransferAmountIsGreaterThanAvailableAmount,
+            message: format!(
+                "Transfer amount {amount} is greater than available amount {} {}",
+                balance.total_available, balance.currency
+            ),
+        }));
+    }
+
+    // Converting between currencies needs a rate, otherwise the amount is moved as is.
+    if balance.currency != *to_currency {
+        match exchange_rate {
+            Some(rate) if rate > dec!(0) => (),
+            _ => {
+                return Err(Box::new(TransactionValidationError {
+                    code: TransactionValidationErrorCode::ExchangeRateNotValid,
+                    message: format!(
+                        "A positive exchange rate is required to transfer from {} to {to_currency}",
+                        balance.currency
+                    ),
+                }))
+            }
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, PartialEq)]
 pub enum TransactionValidationErrorCode {
     AmountOfWithdrawalMustBePositive,
     AmountOfDepositMustBePositive,
     WithdrawalAmountIsGreaterThanAvailableAmount,
+    WithdrawalAmountIsGreaterThanEarnings,
     OverviewNotFound,
     OverviewForWithdrawNotFound,
     NotEnoughFunds,
@@ -140,6 +262,12 @@ pub enum TransactionValidationErrorCode {
     FillingMustBePositive,
     FeeMustBePositive,
     ClosingMustBePositive,
+    AmountMustBePositive,
+    WrongTransactionCategory,
+    WrongTradeCategory,
+    TransferToSameAccount,
+    TransferAmountIsGreaterThanAvailableAmount,
+    ExchangeRateNotValid,
 }
 
 #[derive(Debug, PartialEq)]
@@ -338,4 +466,166 @@ mod tests {
         );
         assert_eq!(err.message, "Closing must be positive");
     }
+
+    #[test]
+    fn test_validate_withdraw_earnings_success() {
+        assert!(can_transfer_withdraw_earnings(dec!(100), dec!(100)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_withdraw_earnings_failure() {
+        let err = can_transfer_withdraw_earnings(dec!(100.01), dec!(100)).unwrap_err();
+        assert_eq!(
+            err.code,
+            TransactionValidationErrorCode::WithdrawalAmountIsGreaterThanEarnings
+        );
+
+        let err = can_transfer_withdraw_earnings(dec!(0), dec!(100)).unwrap_err();
+        assert_eq!(
+            err.code,
+            TransactionValidationErrorCode::AmountOfWithdrawalMustBePositive
+        );
+    }
+
+    #[test]
+    fn test_validate_trade_transaction_success() {
+        let trade = Trade {
+            status: Status::Filled,
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        let category = TransactionCategory::Dividend(trade.id);
+        assert!(can_transfer_trade_transaction(&trade, &category, dec!(10)).is_ok());
+    }
+
+    #[test]
+    fn test_validate_trade_transaction_wrong_trade_category() {
+        let trade = Trade {
+            status: Status::ClosedTarget,
+            category: TradeCategory::Long,
+            ..Default::default()
+        };
+        let category = TransactionCategory::BorrowFee(trade.id);
+        let err = can_transfer_trade_transaction(&trade, &category, dec!(10)).unwrap_err();
+        assert_eq!(err.code, TransactionValidationErrorCode::WrongTradeCategory);
+    }
+
+    #[test]
+    fn test_validate_trade_transaction_without_position() {
+        let trade = Trade {
+            status: Status::Canceled,
+            category: TradeCategory::Short,
+            ..Default::default()
+        };
+        let category = TransactionCategory::BorrowFee(trade.id);
+        let err = can_transfer_trade_transaction(&trade, &category, dec!(10)).unwrap_err();
+        assert_eq!(err.code, TransactionValidationErrorCode::WrongTradeStatus);
+
+        let err = can_transfer_trade_transaction(&trade, &category, dec!(0)).unwrap_err();
+        assert_eq!(
+            err.code,
+            TransactionValidationErrorCode::AmountMustBePositive
+        );
+    }
+
+    #[test]
+    fn test_validate_transfer_between_accounts_success() {
+        let balance = AccountBalance {
+            total_available: dec!(1000),
+            currency: Currency::USD,
+            ..Default::default()
+        };
+        let result = can_transfer_between_accounts(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            &balance,
+            dec!(1000),
+            &Currency::USD,
+            None,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_transfer_between_accounts_same_account() {
+        let balance = AccountBalance {
+            total_available: dec!(1000),
+            ..Default::default()
+        };
+        let id = Uuid::new_v4();
+        let result =
+            can_transfer_between_accounts(id, id, &balance, dec!(10), &Currency::USD, None);
+        assert_eq!(
+            result.unwrap_err().code,
+            TransactionValidationErrorCode::TransferToSameAccount
+        );
+    }
+
+    #[test]
+    fn test_validate_transfer_between_accounts_not_positive() {
+        let balance = AccountBalance {
+            total_available: dec!(1000),
+            ..Default::default()
+        };
+        let result = can_transfer_between_accounts(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            &balance,
+            dec!(0),
+            &Currency::USD,
+            None,
+        );
+        assert_eq!(
+            result.unwrap_err().code,
+            TransactionValidationErrorCode::AmountMustBePositive
+        );
+    }
+
+    #[test]
+    fn test_validate_transfer_between_accounts_greater_than_available() {
+        let balance = AccountBalance {
+            total_available: dec!(1000),
+            ..Default::default()
+        };
+        let result = can_transfer_between_accounts(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            &balance,
+            dec!(1000.01),
+            &Currency::USD,
+            None,
+        );
+        assert_eq!(
+            result.unwrap_err().code,
+            TransactionValidationErrorCode::TransferAmountIsGreaterThanAvailableAmount
+        );
+    }
+
+    #[test]
+    fn test_validate_transfer_between_accounts_exchange_rate() {
+        let balance = AccountBalance {
+            total_available: dec!(1000),
+            currency: Currency::EUR,
+            ..Default::default()
+        };
+        let from = Uuid::new_v4();
+        let to = Uuid::new_v4();
+        for rate in [None, Some(dec!(0)), Some(dec!(-1.1))] {
+            let result =
+                can_transfer_between_accounts(from, to, &balance, dec!(10), &Currency::USD, rate);
+            assert_eq!(
+                result.unwrap_err().code,
+                TransactionValidationErrorCode::ExchangeRateNotValid
+            );
+        }
+        let result = can_transfer_between_accounts(
+            from,
+            to,
+            &balance,
+            dec!(10),
+            &Currency::USD,
+            Some(dec!(1.085)),
+        );
+        assert!(result.is_ok());
+    }
 }
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE accounts DROP COLUMN position_sizing_value;
+ALTER TABLE accounts DROP COLUMN position_sizing;
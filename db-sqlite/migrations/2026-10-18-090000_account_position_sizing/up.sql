@@ -0,0 +1,2 @@
+ALTER TABLE accounts ADD COLUMN position_sizing TEXT CHECK(position_sizing IN ('fixed_fractional', 'fixed_dollar', 'volatility', 'kelly')) NOT NULL DEFAULT 'fixed_fractional';
+ALTER TABLE accounts ADD COLUMN position_sizing_value TEXT NOT NULL DEFAULT '0';
@@ -0,0 +1,15 @@
+CREATE TABLE fee_schedules (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	per_share		TEXT NOT NULL,
+	per_order		TEXT NOT NULL,
+	percentage		TEXT NOT NULL,
+	minimum			TEXT NOT NULL,
+	maximum			TEXT,
+	sec_fee_rate		TEXT NOT NULL,
+	taf_per_share		TEXT NOT NULL,
+	taf_maximum		TEXT NOT NULL
+);
@@ -0,0 +1,16 @@
+-- This file should undo anything in `up.sql`
+CREATE TABLE transactions_old (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	category 		TEXT CHECK(category IN ('deposit', 'withdrawal', 'payment_from_trade', 'fund_trade', 'open_trade', 'close_target', "close_safety_stop", "close_safety_stop_slippage", "fee_open", "fee_close", "payment_earnings", "withdrawal_earnings", "payment_tax", "withdrawal_tax")) NOT NULL,
+	amount			TEXT NOT NULL,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	trade_id		TEXT REFERENCES trades (uuid)
+);
+
+INSERT INTO transactions_old SELECT * FROM transactions WHERE category NOT IN ('dividend', 'borrow_fee', 'interest');
+DROP TABLE transactions;
+ALTER TABLE transactions_old RENAME TO transactions;
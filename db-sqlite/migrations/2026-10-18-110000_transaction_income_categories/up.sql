@@ -0,0 +1,16 @@
+-- SQLite can not alter a CHECK constraint, so the table is copied into a new one.
+CREATE TABLE transactions_new (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	category 		TEXT CHECK(category IN ('deposit', 'withdrawal', 'payment_from_trade', 'fund_trade', 'open_trade', 'close_target', "close_safety_stop", "close_safety_stop_slippage", "fee_open", "fee_close", "payment_earnings", "withdrawal_earnings", "payment_tax", "withdrawal_tax", "dividend", "borrow_fee", "interest")) NOT NULL,
+	amount			TEXT NOT NULL,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	trade_id		TEXT REFERENCES trades (uuid)
+);
+
+INSERT INTO transactions_new SELECT * FROM transactions;
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
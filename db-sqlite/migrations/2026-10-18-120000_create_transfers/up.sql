@@ -0,0 +1,31 @@
+-- SQLite can not alter a CHECK constraint, so the table is copied into a new one.
+CREATE TABLE transactions_new (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	category 		TEXT CHECK(category IN ('deposit', 'withdrawal', 'payment_from_trade', 'fund_trade', 'open_trade', 'close_target', "close_safety_stop", "close_safety_stop_slippage", "fee_open", "fee_close", "payment_earnings", "withdrawal_earnings", "payment_tax", "withdrawal_tax", "dividend", "borrow_fee", "interest", "transfer_out", "transfer_in")) NOT NULL,
+	amount			TEXT NOT NULL,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	trade_id		TEXT REFERENCES trades (uuid)
+);
+
+INSERT INTO transactions_new SELECT * FROM transactions;
+DROP TABLE transactions;
+ALTER TABLE transactions_new RENAME TO transactions;
+
+CREATE TABLE transfers (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	from_account_id		TEXT NOT NULL REFERENCES accounts(id),
+	from_transaction_id	TEXT NOT NULL REFERENCES transactions(id),
+	amount			TEXT NOT NULL,
+	currency 		TEXT CHECK(currency IN ('EUR', 'USD', 'BTC')) NOT NULL,
+	to_account_id		TEXT NOT NULL REFERENCES accounts(id),
+	to_transaction_id	TEXT NOT NULL REFERENCES transactions(id),
+	converted_amount	TEXT NOT NULL,
+	converted_currency 	TEXT CHECK(converted_currency IN ('EUR', 'USD', 'BTC')) NOT NULL
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE accounts DROP COLUMN benchmark;
+DROP INDEX price_bars_symbol_date;
+DROP TABLE price_bars;
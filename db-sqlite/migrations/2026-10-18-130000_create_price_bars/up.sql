@@ -0,0 +1,17 @@
+CREATE TABLE price_bars (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	symbol			TEXT NOT NULL,
+	date			DATE NOT NULL,
+	open			TEXT NOT NULL,
+	high			TEXT NOT NULL,
+	low			TEXT NOT NULL,
+	close			TEXT NOT NULL,
+	volume			TEXT NOT NULL
+);
+
+CREATE INDEX price_bars_symbol_date ON price_bars(symbol, date);
+
+ALTER TABLE accounts ADD COLUMN benchmark TEXT;
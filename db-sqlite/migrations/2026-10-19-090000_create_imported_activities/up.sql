@@ -0,0 +1,10 @@
+CREATE TABLE imported_activities (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	deleted_at		DATETIME,
+	account_id 		TEXT NOT NULL REFERENCES accounts(id),
+	activity_id		TEXT NOT NULL,
+	transaction_id		TEXT REFERENCES transactions(id),
+	UNIQUE(account_id, activity_id)
+);
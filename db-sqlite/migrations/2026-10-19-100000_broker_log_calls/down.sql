@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE logs DROP COLUMN error;
+ALTER TABLE logs DROP COLUMN latency_ms;
+ALTER TABLE logs DROP COLUMN request;
+ALTER TABLE logs DROP COLUMN action;
+ALTER TABLE logs RENAME COLUMN response TO log;
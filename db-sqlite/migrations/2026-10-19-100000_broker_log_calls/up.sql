@@ -0,0 +1,5 @@
+ALTER TABLE logs RENAME COLUMN log TO response;
+ALTER TABLE logs ADD COLUMN action TEXT NOT NULL DEFAULT '';
+ALTER TABLE logs ADD COLUMN request TEXT NOT NULL DEFAULT '';
+ALTER TABLE logs ADD COLUMN latency_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE logs ADD COLUMN error TEXT;
@@ -1,17 +1,20 @@
 use crate::workers::{
-    AccountBalanceDB, AccountDB, BrokerLogDB, WorkerOrder, WorkerRule, WorkerTrade,
-    WorkerTradingVehicle, WorkerTransaction,
+    AccountBalanceDB, AccountDB, BrokerLogDB, WorkerActivity, WorkerFeeSchedule, WorkerOrder,
+    WorkerPrice, WorkerRule, WorkerTrade, WorkerTradingVehicle, WorkerTransaction, WorkerTransfer,
 };
+use chrono::NaiveDate;
 use diesel::prelude::*;
 use model::DraftTrade;
 use model::Status;
 use model::{
     database::{AccountWrite, WriteAccountBalanceDB},
     Account, AccountBalanceRead, AccountBalanceWrite, AccountRead, Currency, DatabaseFactory,
-    Order, OrderAction, OrderCategory, OrderRead, OrderWrite, ReadRuleDB, ReadTradeDB,
-    ReadTradingVehicleDB, ReadTransactionDB, Rule, RuleName, Trade, TradeBalance, TradingVehicle,
-    TradingVehicleCategory, Transaction, TransactionCategory, WriteRuleDB, WriteTradeDB,
-    WriteTradingVehicleDB, WriteTransactionDB,
+    FeeSchedule, ImportedActivity, Order, OrderAction, OrderCategory, OrderRead, OrderWrite,
+    PriceBar, ReadActivityDB, ReadFeeScheduleDB, ReadPriceDB, ReadRuleDB, ReadTradeDB,
+    ReadTradingVehicleDB, ReadTransactionDB, ReadTransferDB, Rule, RuleName, Trade, TradeBalance,
+    TradingVehicle, TradingVehicleCategory, Transaction, TransactionCategory, Transfer,
+    WriteActivityDB, WriteFeeScheduleDB, WritePriceDB, WriteRuleDB, WriteTradeDB,
+    WriteTradingVehicleDB, WriteTransactionDB, WriteTransferDB,
 };
 use rust_decimal::Decimal;
 use std::error::Error;
@@ -57,6 +60,30 @@ impl DatabaseFactory for SqliteDatabase {
         })
     }
 
+    fn fee_schedule_read(&self) -> Box<dyn ReadFeeScheduleDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
+    fn fee_schedule_write(&self) -> Box<dyn WriteFeeScheduleDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
+    fn transfer_read(&self) -> Box<dyn ReadTransferDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
+    fn transfer_write(&self) -> Box<dyn WriteTransferDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
+    fn price_read(&self) -> Box<dyn ReadPriceDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
+    fn price_write(&self) -> Box<dyn WritePriceDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
     fn account_balance_read(&self) -> Box<dyn AccountBalanceRead> {
         Box::new(AccountBalanceDB {
             connection: self.connection.clone(),
@@ -103,6 +130,14 @@ impl DatabaseFactory for SqliteDatabase {
     fn trading_vehicle_write(&self) -> Box<dyn WriteTradingVehicleDB> {
         Box::new(SqliteDatabase::new_from(self.connection.clone()))
     }
+
+    fn activity_read(&self) -> Box<dyn ReadActivityDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
+
+    fn activity_write(&self) -> Box<dyn WriteActivityDB> {
+        Box::new(SqliteDatabase::new_from(self.connection.clone()))
+    }
 }
 
 impl SqliteDatabase {
@@ -145,24 +180,22 @@ impl SqliteDatabase {
 
     /// Establish a connection to the SQLite database.
     fn establish_connection(database_url: &str) -> SqliteConnection {
-        let db_exists = std::path::Path::new(database_url).exists();
         // Use the database URL to establish a connection to the SQLite database
         let mut connection = SqliteConnection::establish(database_url).unwrap_or_else(|e| {
             eprintln!("Error connecting to {database_url}: {e}");
             std::process::exit(1);
         });
 
-        // Run migrations only if it is a new DB
-        if !db_exists {
-            use diesel_migrations::*;
-            pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
-            connection
-                .run_pending_migrations(MIGRATIONS)
-                .unwrap_or_else(|e| {
-                    eprintln!("Failed to run migrations on new database: {e}");
-                    std::process::exit(1);
-                });
-        }
+        // Run pending migrations. New databases get the full schema and
+        // existing databases are upgraded to the latest version.
+        use diesel_migrations::*;
+        pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to run migrations on database: {e}");
+                std::process::exit(1);
+            });
 
         connection
     }
@@ -248,6 +281,17 @@ impl OrderWrite for SqliteDatabase {
             new_broker_id,
         )
     }
+
+    fn update_quantity(&mut self, order: &Order, quantity: u64) -> Result<Order, Box<dyn Error>> {
+        WorkerOrder::update_quantity(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            order,
+            quantity,
+        )
+    }
 }
 
 impl WriteTransactionDB for SqliteDatabase {
@@ -416,6 +460,140 @@ impl ReadRuleDB for SqliteDatabase {
     }
 }
 
+impl ReadFeeScheduleDB for SqliteDatabase {
+    fn fee_schedule_for_account(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<FeeSchedule>, Box<dyn Error>> {
+        WorkerFeeSchedule::read_for_account(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            account_id,
+        )
+    }
+}
+
+impl WriteFeeScheduleDB for SqliteDatabase {
+    fn set_fee_schedule(
+        &mut self,
+        account: &Account,
+        schedule: &FeeSchedule,
+    ) -> Result<FeeSchedule, Box<dyn Error>> {
+        WorkerFeeSchedule::set(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            account,
+            schedule,
+        )
+    }
+}
+
+impl ReadTransferDB for SqliteDatabase {
+    fn all_transfers(&mut self, account_id: Uuid) -> Result<Vec<Transfer>, Box<dyn Error>> {
+        WorkerTransfer::read_all(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            account_id,
+        )
+    }
+}
+
+impl WriteTransferDB for SqliteDatabase {
+    fn create_transfer(
+        &mut self,
+        from: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        to: &Account,
+        converted_amount: Decimal,
+        converted_currency: &Currency,
+    ) -> Result<Transfer, Box<dyn Error>> {
+        WorkerTransfer::create(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            from,
+            amount,
+            currency,
+            to,
+            converted_amount,
+            converted_currency,
+        )
+    }
+}
+
+impl ReadPriceDB for SqliteDatabase {
+    fn bars(
+        &mut self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PriceBar>, Box<dyn Error>> {
+        WorkerPrice::read_all(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            symbol,
+            from,
+            to,
+        )
+    }
+}
+
+impl WritePriceDB for SqliteDatabase {
+    fn save_bars(&mut self, bars: &[PriceBar]) -> Result<Vec<PriceBar>, Box<dyn Error>> {
+        WorkerPrice::save_all(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            bars,
+        )
+    }
+}
+
+impl ReadActivityDB for SqliteDatabase {
+    fn imported_activities(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<ImportedActivity>, Box<dyn Error>> {
+        WorkerActivity::read_all(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            account_id,
+        )
+    }
+}
+
+impl WriteActivityDB for SqliteDatabase {
+    fn create_imported_activity(
+        &mut self,
+        account: &Account,
+        activity_id: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<ImportedActivity, Box<dyn Error>> {
+        WorkerActivity::create(
+            &mut self.connection.lock().unwrap_or_else(|e| {
+                eprintln!("Failed to acquire connection lock: {e}");
+                std::process::exit(1);
+            }),
+            account,
+            activity_id,
+            transaction_id,
+        )
+    }
+}
+
 impl WriteRuleDB for SqliteDatabase {
     fn create_rule(
         &mut self,
@@ -602,4 +780,11 @@ impl OrderRead for SqliteDatabase {
             id,
         )
     }
+
+    fn all(&mut self) -> Result<Vec<Order>, Box<dyn Error>> {
+        WorkerOrder::read_all(&mut self.connection.lock().unwrap_or_else(|e| {
+            eprintln!("Failed to acquire connection lock: {e}");
+            std::process::exit(1);
+        }))
+    }
 }
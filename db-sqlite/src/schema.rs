@@ -9,6 +9,9 @@ diesel::table! {
         environment -> Text,
         taxes_percentage -> Text,
         earnings_percentage -> Text,
+        position_sizing -> Text,
+        position_sizing_value -> Text,
+        benchmark -> Nullable<Text>,
     }
 }
 
@@ -138,12 +141,80 @@ diesel::table! {
         created_at -> Timestamp,
         updated_at -> Timestamp,
         deleted_at -> Nullable<Timestamp>,
-        log -> Text,
+        response -> Text,
         trade_id -> Text,
+        action -> Text,
+        request -> Text,
+        latency_ms -> BigInt,
+        error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    fee_schedules (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        per_share -> Text,
+        per_order -> Text,
+        percentage -> Text,
+        minimum -> Text,
+        maximum -> Nullable<Text>,
+        sec_fee_rate -> Text,
+        taf_per_share -> Text,
+        taf_maximum -> Text,
+    }
+}
+
+diesel::table! {
+    transfers (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        from_account_id -> Text,
+        from_transaction_id -> Text,
+        amount -> Text,
+        currency -> Text,
+        to_account_id -> Text,
+        to_transaction_id -> Text,
+        converted_amount -> Text,
+        converted_currency -> Text,
+    }
+}
+
+diesel::table! {
+    price_bars (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        symbol -> Text,
+        date -> Date,
+        open -> Text,
+        high -> Text,
+        low -> Text,
+        close -> Text,
+        volume -> Text,
+    }
+}
+
+diesel::table! {
+    imported_activities (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        deleted_at -> Nullable<Timestamp>,
+        account_id -> Text,
+        activity_id -> Text,
+        transaction_id -> Nullable<Text>,
     }
 }
 
 diesel::joinable!(transactions -> accounts (account_id));
+diesel::joinable!(fee_schedules -> accounts (account_id));
 diesel::joinable!(accounts_balances -> accounts (account_id));
 diesel::joinable!(orders -> trading_vehicles (trading_vehicle_id));
 diesel::joinable!(trades -> accounts (account_id));
@@ -1,17 +1,25 @@
 mod account_balance;
 mod accounts;
 mod broker_logs;
+mod worker_activity;
+mod worker_fee_schedule;
 mod worker_order;
+mod worker_price;
 mod worker_rule;
 mod worker_trade;
 mod worker_trading_vehicle;
 mod worker_transaction;
+mod worker_transfer;
 
 pub use account_balance::AccountBalanceDB;
 pub use accounts::AccountDB;
 pub use broker_logs::BrokerLogDB;
+pub use worker_activity::WorkerActivity;
+pub use worker_fee_schedule::WorkerFeeSchedule;
 pub use worker_order::WorkerOrder;
+pub use worker_price::WorkerPrice;
 pub use worker_rule::WorkerRule;
 pub use worker_trade::WorkerTrade;
 pub use worker_trading_vehicle::WorkerTradingVehicle;
 pub use worker_transaction::WorkerTransaction;
+pub use worker_transfer::WorkerTransfer;
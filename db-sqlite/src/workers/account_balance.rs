@@ -58,6 +58,7 @@ impl AccountBalanceWrite for AccountBalanceDB {
         total_in_trade: Decimal,
         total_available: Decimal,
         total_taxed: Decimal,
+        total_earnings: Decimal,
     ) -> Result<AccountBalance, Box<dyn Error>> {
         let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap_or_else(|e| {
             eprintln!("Failed to acquire connection lock: {e}");
@@ -71,6 +72,7 @@ impl AccountBalanceWrite for AccountBalanceDB {
                 accounts_balances::total_available.eq(total_available.to_string()),
                 accounts_balances::total_in_trade.eq(total_in_trade.to_string()),
                 accounts_balances::taxed.eq(total_taxed.to_string()),
+                accounts_balances::total_earnings.eq(total_earnings.to_string()),
             ))
             .get_result::<AccountBalanceSQLite>(connection)
             .map_err(|error| {
@@ -322,12 +324,20 @@ mod tests {
             .expect("Failed to create balance");
 
         let updated_balance = db
-            .update(&balance, dec!(200), dec!(1), dec!(203), dec!(44.2))
+            .update(
+                &balance,
+                dec!(200),
+                dec!(1),
+                dec!(203),
+                dec!(44.2),
+                dec!(12.5),
+            )
             .expect("Failed to update balance");
 
         assert_eq!(updated_balance.total_balance, dec!(200));
         assert_eq!(updated_balance.total_available, dec!(203));
         assert_eq!(updated_balance.total_in_trade, dec!(1));
         assert_eq!(updated_balance.taxed, dec!(44.2));
+        assert_eq!(updated_balance.total_earnings, dec!(12.5));
     }
 }
@@ -3,7 +3,7 @@ use crate::schema::accounts;
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use model::AccountRead;
-use model::{Account, AccountWrite, Environment};
+use model::{Account, AccountWrite, Environment, PositionSizing};
 use rust_decimal::Decimal;
 use std::error::Error;
 use std::str::FromStr;
@@ -47,6 +47,9 @@ impl AccountWrite for AccountDB {
             environment: environment.to_string(),
             taxes_percentage: taxes_percentage.to_string(),
             earnings_percentage: earnings_percentage.to_string(),
+            position_sizing: PositionSizing::default().to_string(),
+            position_sizing_value: PositionSizing::default().value().to_string(),
+            benchmark: None,
         };
 
         let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap_or_else(|e| {
@@ -63,6 +66,55 @@ impl AccountWrite for AccountDB {
             })?
             .into_domain_model()
     }
+
+    fn update_position_sizing(
+        &mut self,
+        account: &Account,
+        position_sizing: PositionSizing,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap_or_else(|e| {
+            eprintln!("Failed to acquire connection lock: {e}");
+            std::process::exit(1);
+        });
+
+        diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(Utc::now().naive_utc()),
+                accounts::position_sizing.eq(position_sizing.to_string()),
+                accounts::position_sizing_value.eq(position_sizing.value().to_string()),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map_err(|error| {
+                error!("Error updating account position sizing: {:?}", error);
+                error
+            })?
+            .into_domain_model()
+    }
+
+    fn update_benchmark(
+        &mut self,
+        account: &Account,
+        benchmark: Option<&str>,
+    ) -> Result<Account, Box<dyn Error>> {
+        let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap_or_else(|e| {
+            eprintln!("Failed to acquire connection lock: {e}");
+            std::process::exit(1);
+        });
+
+        diesel::update(accounts::table)
+            .filter(accounts::id.eq(account.id.to_string()))
+            .set((
+                accounts::updated_at.eq(Utc::now().naive_utc()),
+                accounts::benchmark.eq(benchmark.map(|symbol| symbol.to_uppercase())),
+            ))
+            .get_result::<AccountSQLite>(connection)
+            .map_err(|error| {
+                error!("Error updating account benchmark: {:?}", error);
+                error
+            })?
+            .into_domain_model()
+    }
 }
 
 impl AccountRead for AccountDB {
@@ -128,6 +180,9 @@ pub struct AccountSQLite {
     pub environment: String,
     pub taxes_percentage: String,
     pub earnings_percentage: String,
+    pub position_sizing: String,
+    pub position_sizing_value: String,
+    pub benchmark: Option<String>,
 }
 
 impl TryFrom<AccountSQLite> for Account {
@@ -150,6 +205,15 @@ impl TryFrom<AccountSQLite> for Account {
             earnings_percentage: Decimal::from_str(&value.earnings_percentage).map_err(|_| {
                 ConversionError::new("earnings_percentage", "Failed to parse earnings percentage")
             })?,
+            position_sizing: Decimal::from_str(&value.position_sizing_value)
+                .ok()
+                .and_then(|sizing_value| {
+                    PositionSizing::parse(&value.position_sizing, sizing_value).ok()
+                })
+                .ok_or_else(|| {
+                    ConversionError::new("position_sizing", "Failed to parse position sizing")
+                })?,
+            benchmark: value.benchmark,
         })
     }
 }
@@ -173,6 +237,9 @@ struct NewAccount {
     environment: String,
     taxes_percentage: String,
     earnings_percentage: String,
+    position_sizing: String,
+    position_sizing_value: String,
+    benchmark: Option<String>,
 }
 
 #[cfg(test)]
@@ -284,6 +351,60 @@ mod tests {
         )
         .expect_err("Error creating account with same name");
     }
+    #[test]
+    fn test_update_position_sizing() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+        };
+        let account = db
+            .create(
+                "Test Account",
+                "This is a test account",
+                Environment::Paper,
+                dec!(20),
+                dec!(80),
+            )
+            .expect("Error creating account");
+        assert_eq!(account.position_sizing, PositionSizing::FixedFractional);
+
+        let updated = db
+            .update_position_sizing(&account, PositionSizing::Kelly(dec!(0.5)))
+            .expect("Error updating position sizing");
+        assert_eq!(updated.position_sizing, PositionSizing::Kelly(dec!(0.5)));
+
+        let read_account = db.id(account.id).expect("Account should be found");
+        assert_eq!(read_account, updated);
+    }
+
+    #[test]
+    fn test_update_benchmark() {
+        let conn = establish_connection();
+        let mut db = AccountDB {
+            connection: Arc::new(Mutex::new(conn)),
+        };
+        let account = db
+            .create(
+                "Test Account",
+                "This is a test account",
+                Environment::Paper,
+                dec!(20),
+                dec!(80),
+            )
+            .expect("Error creating account");
+        assert_eq!(account.benchmark, None);
+
+        let updated = db
+            .update_benchmark(&account, Some("spy"))
+            .expect("Error updating benchmark");
+        assert_eq!(updated.benchmark, Some("SPY".to_string()));
+
+        let removed = db
+            .update_benchmark(&updated, None)
+            .expect("Error removing benchmark");
+        assert_eq!(removed.benchmark, None);
+    }
+
     #[test]
     fn test_read_account_not_found() {
         let conn = establish_connection();
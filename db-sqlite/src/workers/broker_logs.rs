@@ -23,17 +23,21 @@ impl std::fmt::Debug for BrokerLogDB {
 }
 
 impl WriteBrokerLogsDB for BrokerLogDB {
-    fn create_log(&mut self, log: &str, trade: &Trade) -> Result<BrokerLog, Box<dyn Error>> {
+    fn create_log(&mut self, log: &BrokerLog, trade: &Trade) -> Result<BrokerLog, Box<dyn Error>> {
         let uuid = Uuid::new_v4().to_string();
         let now = Utc::now().naive_utc();
 
-        let new_account = NewBrokerLogs {
+        let new_log = NewBrokerLogs {
             id: uuid,
             created_at: now,
             updated_at: now,
             deleted_at: None,
-            log: log.to_lowercase(),
+            response: log.response.clone(),
             trade_id: trade.id.to_string(),
+            action: log.action.clone(),
+            request: log.request.clone(),
+            latency_ms: i64::try_from(log.latency_ms).unwrap_or(i64::MAX),
+            error: log.error.clone(),
         };
 
         let connection: &mut SqliteConnection = &mut self.connection.lock().unwrap_or_else(|e| {
@@ -42,7 +46,7 @@ impl WriteBrokerLogsDB for BrokerLogDB {
         });
 
         diesel::insert_into(logs::table)
-            .values(&new_account)
+            .values(&new_log)
             .get_result::<BrokerLogSQLite>(connection)
             .map_err(|error| {
                 error!("Error creating broker log: {:?}", error);
@@ -64,6 +68,7 @@ impl ReadBrokerLogsDB for BrokerLogDB {
 
         logs::table
             .filter(logs::trade_id.eq(trade_id.to_string()))
+            .order(logs::created_at.asc())
             .load::<BrokerLogSQLite>(connection)
             .map_err(|error| {
                 error!("Error reading broker logs for trade: {:?}", error);
@@ -80,8 +85,12 @@ pub struct BrokerLogSQLite {
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub deleted_at: Option<NaiveDateTime>,
-    pub log: String,
+    pub response: String,
     pub trade_id: String,
+    pub action: String,
+    pub request: String,
+    pub latency_ms: i64,
+    pub error: Option<String>,
 }
 
 impl TryFrom<BrokerLogSQLite> for BrokerLog {
@@ -94,9 +103,14 @@ impl TryFrom<BrokerLogSQLite> for BrokerLog {
             created_at: value.created_at,
             updated_at: value.updated_at,
             deleted_at: value.deleted_at,
-            log: value.log,
             trade_id: Uuid::parse_str(&value.trade_id)
                 .map_err(|_| ConversionError::new("trade_id", "Failed to parse trade ID"))?,
+            action: value.action,
+            request: value.request,
+            response: value.response,
+            latency_ms: u64::try_from(value.latency_ms)
+                .map_err(|_| ConversionError::new("latency_ms", "Failed to parse latency"))?,
+            error: value.error,
         })
     }
 }
@@ -115,8 +129,12 @@ struct NewBrokerLogs {
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
     deleted_at: Option<NaiveDateTime>,
-    log: String,
+    response: String,
     trade_id: String,
+    action: String,
+    request: String,
+    latency_ms: i64,
+    error: Option<String>,
 }
 
 #[cfg(test)]
@@ -133,6 +151,16 @@ mod tests {
         connection
     }
 
+    fn log() -> BrokerLog {
+        BrokerLog {
+            action: "submit_trade".to_string(),
+            request: "{\"symbol\":\"AAPL\"}".to_string(),
+            response: "{\"status\":\"Accepted\"}".to_string(),
+            latency_ms: 120,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_create_log() {
         let conn: SqliteConnection = establish_connection();
@@ -142,15 +170,37 @@ mod tests {
 
         let trade = Trade::default();
 
-        let log = db
-            .create_log("Test Account", &trade)
-            .expect("Error creating log");
+        let log = db.create_log(&log(), &trade).expect("Error creating log");
 
-        assert_eq!(log.log, "test account");
+        assert_eq!(log.action, "submit_trade");
+        assert_eq!(log.request, "{\"symbol\":\"AAPL\"}");
+        assert_eq!(log.response, "{\"status\":\"Accepted\"}");
+        assert_eq!(log.latency_ms, 120);
+        assert_eq!(log.error, None);
         assert_eq!(log.trade_id, trade.id);
         assert_eq!(log.deleted_at, None);
     }
 
+    #[test]
+    fn test_create_failed_log() {
+        let conn: SqliteConnection = establish_connection();
+        let mut db = BrokerLogDB {
+            connection: Arc::new(Mutex::new(conn)),
+        };
+        let failed = BrokerLog {
+            action: "cancel_trade".to_string(),
+            error: Some("order is not cancelable".to_string()),
+            ..Default::default()
+        };
+
+        let log = db
+            .create_log(&failed, &Trade::default())
+            .expect("Error creating log");
+
+        assert_eq!(log.error, Some("order is not cancelable".to_string()));
+        assert!(log.response.is_empty());
+    }
+
     #[test]
     fn test_read_log() {
         let conn: SqliteConnection = establish_connection();
@@ -160,26 +210,14 @@ mod tests {
 
         let trade = Trade::default();
 
-        let log = db
-            .create_log("Test Account", &trade)
-            .expect("Error creating log");
+        let log = db.create_log(&log(), &trade).expect("Error creating log");
 
         let read_log = db
             .read_all_logs_for_trade(trade.id)
             .expect("Error reading log");
 
         assert_eq!(read_log.len(), 1);
-        assert_eq!(
-            log.log,
-            read_log.first().expect("Expected at least one log").log
-        );
-        assert_eq!(
-            read_log
-                .first()
-                .expect("Expected at least one log")
-                .trade_id,
-            trade.id
-        );
+        assert_eq!(&log, read_log.first().expect("Expected at least one log"));
         assert_eq!(log.deleted_at, None);
     }
 }
@@ -0,0 +1,174 @@
+use crate::error::{ConversionError, IntoDomainModel, IntoDomainModels};
+use crate::schema::imported_activities;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use model::{Account, ImportedActivity};
+use std::error::Error;
+use tracing::error;
+use uuid::Uuid;
+
+/// Worker for handling imported broker activity database operations
+#[derive(Debug)]
+pub struct WorkerActivity;
+
+impl WorkerActivity {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        account: &Account,
+        activity_id: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<ImportedActivity, Box<dyn Error>> {
+        let now = Utc::now().naive_utc();
+        let new_activity = NewImportedActivity {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account.id.to_string(),
+            activity_id: activity_id.to_string(),
+            transaction_id: transaction_id.map(|id| id.to_string()),
+        };
+
+        diesel::insert_into(imported_activities::table)
+            .values(&new_activity)
+            .get_result::<ImportedActivitySQLite>(connection)
+            .map_err(|error| {
+                error!("Error creating imported activity: {:?}", error);
+                error
+            })?
+            .into_domain_model()
+    }
+
+    pub fn read_all(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Vec<ImportedActivity>, Box<dyn Error>> {
+        imported_activities::table
+            .filter(imported_activities::account_id.eq(account_id.to_string()))
+            .filter(imported_activities::deleted_at.is_null())
+            .order(imported_activities::created_at.asc())
+            .load::<ImportedActivitySQLite>(connection)
+            .map_err(|error| {
+                error!("Error reading imported activities: {:?}", error);
+                error
+            })?
+            .into_domain_models()
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = imported_activities)]
+struct ImportedActivitySQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    activity_id: String,
+    transaction_id: Option<String>,
+}
+
+fn parse_uuid(value: &str, field: &'static str) -> Result<Uuid, ConversionError> {
+    Uuid::parse_str(value).map_err(|_| ConversionError::new(field, "Failed to parse ID"))
+}
+
+impl TryFrom<ImportedActivitySQLite> for ImportedActivity {
+    type Error = ConversionError;
+
+    fn try_from(value: ImportedActivitySQLite) -> Result<Self, Self::Error> {
+        Ok(ImportedActivity {
+            id: parse_uuid(&value.id, "id")?,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            deleted_at: value.deleted_at,
+            account_id: parse_uuid(&value.account_id, "account_id")?,
+            activity_id: value.activity_id,
+            transaction_id: value
+                .transaction_id
+                .as_deref()
+                .map(|id| parse_uuid(id, "transaction_id"))
+                .transpose()?,
+        })
+    }
+}
+
+impl IntoDomainModel<ImportedActivity> for ImportedActivitySQLite {
+    fn into_domain_model(self) -> Result<ImportedActivity, Box<dyn Error>> {
+        self.try_into().map_err(Into::into)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = imported_activities)]
+#[diesel(treat_none_as_null = true)]
+struct NewImportedActivity {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    activity_id: String,
+    transaction_id: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SqliteDatabase;
+    use diesel_migrations::*;
+    use model::{DatabaseFactory, Environment};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    fn create_factory() -> Box<dyn DatabaseFactory> {
+        Box::new(SqliteDatabase::new_from(Arc::new(Mutex::new(
+            establish_connection(),
+        ))))
+    }
+
+    #[test]
+    fn test_create_and_read_imported_activities() {
+        let db = create_factory();
+        let account = db
+            .account_write()
+            .create("Paper", "paper", Environment::Paper, dec!(20), dec!(10))
+            .unwrap();
+        let other = db
+            .account_write()
+            .create("Live", "live", Environment::Live, dec!(20), dec!(10))
+            .unwrap();
+
+        let deposit = db
+            .activity_write()
+            .create_imported_activity(&account, "20240102::csd", None)
+            .unwrap();
+
+        assert_eq!(deposit.account_id, account.id);
+        assert_eq!(deposit.activity_id, "20240102::csd");
+        assert_eq!(deposit.transaction_id, None);
+        assert_eq!(
+            db.activity_read().imported_activities(account.id).unwrap(),
+            vec![deposit]
+        );
+        assert!(db
+            .activity_read()
+            .imported_activities(other.id)
+            .unwrap()
+            .is_empty());
+
+        // An activity can only be imported once into an account
+        assert!(db
+            .activity_write()
+            .create_imported_activity(&account, "20240102::csd", None)
+            .is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use crate::error::{ConversionError, IntoDomainModel};
+use crate::schema::fee_schedules;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use model::{Account, FeeSchedule};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+/// Worker for handling fee schedule database operations
+#[derive(Debug)]
+pub struct WorkerFeeSchedule;
+
+impl WorkerFeeSchedule {
+    pub fn set(
+        connection: &mut SqliteConnection,
+        account: &Account,
+        schedule: &FeeSchedule,
+    ) -> Result<FeeSchedule, Box<dyn Error>> {
+        let now = Utc::now().naive_utc();
+
+        let new_schedule = NewFeeSchedule {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: account.id.to_string(),
+            per_share: schedule.per_share.to_string(),
+            per_order: schedule.per_order.to_string(),
+            percentage: schedule.percentage.to_string(),
+            minimum: schedule.minimum.to_string(),
+            maximum: schedule.maximum.map(|maximum| maximum.to_string()),
+            sec_fee_rate: schedule.sec_fee_rate.to_string(),
+            taf_per_share: schedule.taf_per_share.to_string(),
+            taf_maximum: schedule.taf_maximum.to_string(),
+        };
+
+        connection.transaction(|connection| {
+            // 1. Delete the previous schedule of the account
+            diesel::update(fee_schedules::table)
+                .filter(fee_schedules::account_id.eq(account.id.to_string()))
+                .filter(fee_schedules::deleted_at.is_null())
+                .set(fee_schedules::deleted_at.eq(Some(now)))
+                .execute(connection)
+                .map_err(|error| {
+                    error!("Error deleting fee schedule: {:?}", error);
+                    error
+                })?;
+
+            // 2. Create the new schedule
+            diesel::insert_into(fee_schedules::table)
+                .values(&new_schedule)
+                .get_result::<FeeScheduleSQLite>(connection)
+                .map_err(|error| {
+                    error!("Error creating fee schedule: {:?}", error);
+                    error
+                })?
+                .into_domain_model()
+        })
+    }
+
+    pub fn read_for_account(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Option<FeeSchedule>, Box<dyn Error>> {
+        fee_schedules::table
+            .filter(fee_schedules::account_id.eq(account_id.to_string()))
+            .filter(fee_schedules::deleted_at.is_null())
+            .first::<FeeScheduleSQLite>(connection)
+            .optional()
+            .map_err(|error| {
+                error!("Error reading fee schedule: {:?}", error);
+                error
+            })?
+            .map(IntoDomainModel::into_domain_model)
+            .transpose()
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = fee_schedules)]
+struct FeeScheduleSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    per_share: String,
+    per_order: String,
+    percentage: String,
+    minimum: String,
+    maximum: Option<String>,
+    sec_fee_rate: String,
+    taf_per_share: String,
+    taf_maximum: String,
+}
+
+fn parse_decimal(value: &str, field: &'static str) -> Result<Decimal, ConversionError> {
+    Decimal::from_str(value).map_err(|_| ConversionError::new(field, "Failed to parse decimal"))
+}
+
+impl TryFrom<FeeScheduleSQLite> for FeeSchedule {
+    type Error = ConversionError;
+
+    fn try_from(value: FeeScheduleSQLite) -> Result<Self, Self::Error> {
+        Ok(FeeSchedule {
+            id: Uuid::parse_str(&value.id)
+                .map_err(|_| ConversionError::new("id", "Failed to parse fee schedule ID"))?,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            deleted_at: value.deleted_at,
+            account_id: Uuid::parse_str(&value.account_id)
+                .map_err(|_| ConversionError::new("account_id", "Failed to parse account ID"))?,
+            per_share: parse_decimal(&value.per_share, "per_share")?,
+            per_order: parse_decimal(&value.per_order, "per_order")?,
+            percentage: parse_decimal(&value.percentage, "percentage")?,
+            minimum: parse_decimal(&value.minimum, "minimum")?,
+            maximum: value
+                .maximum
+                .map(|maximum| parse_decimal(&maximum, "maximum"))
+                .transpose()?,
+            sec_fee_rate: parse_decimal(&value.sec_fee_rate, "sec_fee_rate")?,
+            taf_per_share: parse_decimal(&value.taf_per_share, "taf_per_share")?,
+            taf_maximum: parse_decimal(&value.taf_maximum, "taf_maximum")?,
+        })
+    }
+}
+
+impl IntoDomainModel<FeeSchedule> for FeeScheduleSQLite {
+    fn into_domain_model(self) -> Result<FeeSchedule, Box<dyn Error>> {
+        self.try_into().map_err(Into::into)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = fee_schedules)]
+#[diesel(treat_none_as_null = true)]
+struct NewFeeSchedule {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    account_id: String,
+    per_share: String,
+    per_order: String,
+    percentage: String,
+    minimum: String,
+    maximum: Option<String>,
+    sec_fee_rate: String,
+    taf_per_share: String,
+    taf_maximum: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SqliteDatabase;
+    use diesel_migrations::*;
+    use model::{DatabaseFactory, Environment};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    fn create_factory() -> Box<dyn DatabaseFactory> {
+        Box::new(SqliteDatabase::new_from(Arc::new(Mutex::new(
+            establish_connection(),
+        ))))
+    }
+
+    #[test]
+    fn test_set_and_read_fee_schedule() {
+        let db = create_factory();
+        let account = db
+            .account_write()
+            .create(
+                "Test",
+                "description",
+                Environment::Paper,
+                dec!(20),
+                dec!(10),
+            )
+            .unwrap();
+
+        let schedule = FeeSchedule {
+            per_share: dec!(0.005),
+            minimum: dec!(1),
+            maximum: Some(dec!(10)),
+            sec_fee_rate: dec!(0.0000278),
+            taf_per_share: dec!(0.000166),
+            taf_maximum: dec!(8.30),
+            ..Default::default()
+        };
+
+        let created = db
+            .fee_schedule_write()
+            .set_fee_schedule(&account, &schedule)
+            .unwrap();
+        let read = db
+            .fee_schedule_read()
+            .fee_schedule_for_account(account.id)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(created, read);
+        assert_eq!(read.account_id, account.id);
+        assert_eq!(read.per_share, dec!(0.005));
+        assert_eq!(read.maximum, Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_set_fee_schedule_replaces_previous() {
+        let db = create_factory();
+        let account = db
+            .account_write()
+            .create(
+                "Test",
+                "description",
+                Environment::Paper,
+                dec!(20),
+                dec!(10),
+            )
+            .unwrap();
+
+        db.fee_schedule_write()
+            .set_fee_schedule(
+                &account,
+                &FeeSchedule {
+                    per_order: dec!(5),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        db.fee_schedule_write()
+            .set_fee_schedule(
+                &account,
+                &FeeSchedule {
+                    per_order: dec!(1),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        let read = db
+            .fee_schedule_read()
+            .fee_schedule_for_account(account.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(read.per_order, dec!(1));
+    }
+
+    #[test]
+    fn test_read_fee_schedule_not_found() {
+        let db = create_factory();
+        assert_eq!(
+            db.fee_schedule_read()
+                .fee_schedule_for_account(Uuid::new_v4())
+                .unwrap(),
+            None
+        );
+    }
+}
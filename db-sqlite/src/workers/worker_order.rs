@@ -1,4 +1,4 @@
-use crate::error::{ConversionError, IntoDomainModel};
+use crate::error::{ConversionError, IntoDomainModel, IntoDomainModels};
 use crate::schema::orders::{self};
 use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
@@ -46,6 +46,17 @@ impl WorkerOrder {
         Ok(order)
     }
 
+    pub fn read_all(connection: &mut SqliteConnection) -> Result<Vec<Order>, Box<dyn Error>> {
+        orders::table
+            .filter(orders::deleted_at.is_null())
+            .load::<OrderSQLite>(connection)
+            .map_err(|error| {
+                error!("Error reading all orders: {:?}", error);
+                error
+            })?
+            .into_domain_models()
+    }
+
     pub fn read(connection: &mut SqliteConnection, id: Uuid) -> Result<Order, Box<dyn Error>> {
         let order = orders::table
             .filter(orders::id.eq(id.to_string()))
@@ -68,6 +79,8 @@ impl WorkerOrder {
             .set((
                 orders::updated_at.eq(now),
                 orders::broker_order_id.eq(order.broker_order_id.map(|id| id.to_string())),
+                // A replaced stop or target is synced with the price of its replacement
+                orders::unit_price.eq(order.unit_price.to_string()),
                 orders::status.eq(order.status.to_string()),
                 orders::filled_quantity.eq(order.filled_quantity as i64),
                 orders::average_filled_price
@@ -103,6 +116,23 @@ impl WorkerOrder {
         WorkerOrder::read(connection, order.id)
     }
 
+    pub fn update_quantity(
+        connection: &mut SqliteConnection,
+        order: &Order,
+        quantity: u64,
+    ) -> Result<Order, Box<dyn Error>> {
+        let now: NaiveDateTime = Utc::now().naive_utc();
+        diesel::update(orders::table)
+            .filter(orders::id.eq(&order.id.to_string()))
+            .set((
+                orders::updated_at.eq(now),
+                orders::quantity.eq(i64::try_from(quantity)?),
+            ))
+            .execute(connection)?;
+
+        WorkerOrder::read(connection, order.id)
+    }
+
     pub fn update_submitted_at(
         connection: &mut SqliteConnection,
         order: &Order,
@@ -349,4 +379,101 @@ mod tests {
         assert_eq!(order.created_at, order.updated_at);
         assert_eq!(order.deleted_at, None);
     }
+
+    #[test]
+    fn test_read_all_orders() {
+        let mut conn = establish_connection();
+
+        let trading_vehicle = WorkerTradingVehicle::create(
+            &mut conn,
+            "AAPL",
+            "isin",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+
+        for price in [dec!(150), dec!(140)] {
+            WorkerOrder::create(
+                &mut conn,
+                price,
+                &Currency::USD,
+                100,
+                &OrderAction::Buy,
+                &OrderCategory::Limit,
+                &trading_vehicle,
+            )
+            .unwrap();
+        }
+
+        let orders = WorkerOrder::read_all(&mut conn).unwrap();
+        assert_eq!(orders.len(), 2);
+    }
+    #[test]
+    fn test_update_quantity() {
+        let mut conn = establish_connection();
+
+        let trading_vehicle = WorkerTradingVehicle::create(
+            &mut conn,
+            "AAPL",
+            "isin",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+        let order = WorkerOrder::create(
+            &mut conn,
+            dec!(150),
+            &Currency::USD,
+            100,
+            &OrderAction::Buy,
+            &OrderCategory::Limit,
+            &trading_vehicle,
+        )
+        .unwrap();
+
+        let order = WorkerOrder::update_quantity(&mut conn, &order, 80).unwrap();
+
+        assert_eq!(order.quantity, 80);
+        assert_eq!(order.unit_price, dec!(150));
+    }
+
+    #[test]
+    fn test_update_saves_the_price_of_a_replaced_order() {
+        let mut conn = establish_connection();
+
+        let trading_vehicle = WorkerTradingVehicle::create(
+            &mut conn,
+            "AAPL",
+            "isin",
+            &TradingVehicleCategory::Stock,
+            "NASDAQ",
+        )
+        .unwrap();
+        let order = WorkerOrder::create(
+            &mut conn,
+            dec!(140),
+            &Currency::USD,
+            100,
+            &OrderAction::Sell,
+            &OrderCategory::Stop,
+            &trading_vehicle,
+        )
+        .unwrap();
+
+        let replacement = Uuid::new_v4();
+        WorkerOrder::update(
+            &mut conn,
+            &Order {
+                unit_price: dec!(145),
+                broker_order_id: Some(replacement),
+                ..order.clone()
+            },
+        )
+        .unwrap();
+
+        let read = WorkerOrder::read(&mut conn, order.id).unwrap();
+        assert_eq!(read.unit_price, dec!(145));
+        assert_eq!(read.broker_order_id, Some(replacement));
+    }
 }
@@ -0,0 +1,217 @@
+use crate::error::{ConversionError, IntoDomainModel, IntoDomainModels};
+use crate::schema::price_bars;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use model::PriceBar;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+/// Worker for handling price series database operations
+#[derive(Debug)]
+pub struct WorkerPrice;
+
+impl WorkerPrice {
+    pub fn save_all(
+        connection: &mut SqliteConnection,
+        bars: &[PriceBar],
+    ) -> Result<Vec<PriceBar>, Box<dyn Error>> {
+        connection.transaction(|connection| {
+            bars.iter()
+                .map(|bar| WorkerPrice::save(connection, bar))
+                .collect()
+        })
+    }
+
+    fn save(connection: &mut SqliteConnection, bar: &PriceBar) -> Result<PriceBar, Box<dyn Error>> {
+        let now = Utc::now().naive_utc();
+        let symbol = bar.symbol.to_uppercase();
+
+        // 1. Delete the previous bar of the same day
+        diesel::update(price_bars::table)
+            .filter(price_bars::symbol.eq(&symbol))
+            .filter(price_bars::date.eq(bar.date))
+            .filter(price_bars::deleted_at.is_null())
+            .set(price_bars::deleted_at.eq(Some(now)))
+            .execute(connection)
+            .map_err(|error| {
+                error!("Error deleting price bar: {:?}", error);
+                error
+            })?;
+
+        // 2. Create the new bar
+        let new_bar = NewPriceBar {
+            id: Uuid::new_v4().to_string(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            symbol,
+            date: bar.date,
+            open: bar.open.to_string(),
+            high: bar.high.to_string(),
+            low: bar.low.to_string(),
+            close: bar.close.to_string(),
+            volume: bar.volume.to_string(),
+        };
+
+        diesel::insert_into(price_bars::table)
+            .values(&new_bar)
+            .get_result::<PriceBarSQLite>(connection)
+            .map_err(|error| {
+                error!("Error creating price bar: {:?}", error);
+                error
+            })?
+            .into_domain_model()
+    }
+
+    pub fn read_all(
+        connection: &mut SqliteConnection,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PriceBar>, Box<dyn Error>> {
+        price_bars::table
+            .filter(price_bars::symbol.eq(symbol.to_uppercase()))
+            .filter(price_bars::date.ge(from))
+            .filter(price_bars::date.le(to))
+            .filter(price_bars::deleted_at.is_null())
+            .order(price_bars::date.asc())
+            .load::<PriceBarSQLite>(connection)
+            .map_err(|error| {
+                error!("Error reading price bars: {:?}", error);
+                error
+            })?
+            .into_domain_models()
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = price_bars)]
+struct PriceBarSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    symbol: String,
+    date: NaiveDate,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    volume: String,
+}
+
+fn parse_decimal(value: &str, field: &'static str) -> Result<Decimal, ConversionError> {
+    Decimal::from_str(value).map_err(|_| ConversionError::new(field, "Failed to parse decimal"))
+}
+
+impl TryFrom<PriceBarSQLite> for PriceBar {
+    type Error = ConversionError;
+
+    fn try_from(value: PriceBarSQLite) -> Result<Self, Self::Error> {
+        Ok(PriceBar {
+            id: Uuid::parse_str(&value.id)
+                .map_err(|_| ConversionError::new("id", "Failed to parse price bar ID"))?,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            deleted_at: value.deleted_at,
+            symbol: value.symbol,
+            date: value.date,
+            open: parse_decimal(&value.open, "open")?,
+            high: parse_decimal(&value.high, "high")?,
+            low: parse_decimal(&value.low, "low")?,
+            close: parse_decimal(&value.close, "close")?,
+            volume: parse_decimal(&value.volume, "volume")?,
+        })
+    }
+}
+
+impl IntoDomainModel<PriceBar> for PriceBarSQLite {
+    fn into_domain_model(self) -> Result<PriceBar, Box<dyn Error>> {
+        self.try_into().map_err(Into::into)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = price_bars)]
+#[diesel(treat_none_as_null = true)]
+struct NewPriceBar {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    symbol: String,
+    date: NaiveDate,
+    open: String,
+    high: String,
+    low: String,
+    close: String,
+    volume: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SqliteDatabase;
+    use diesel_migrations::*;
+    use model::DatabaseFactory;
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    fn create_factory() -> Box<dyn DatabaseFactory> {
+        Box::new(SqliteDatabase::new_from(Arc::new(Mutex::new(
+            establish_connection(),
+        ))))
+    }
+
+    fn bar(date: NaiveDate, close: Decimal) -> PriceBar {
+        PriceBar {
+            symbol: "spy".to_string(),
+            date,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: dec!(1000),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_save_and_read_bars() {
+        let db = create_factory();
+        let first = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let second = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let third = NaiveDate::from_ymd_opt(2024, 1, 4).unwrap();
+
+        let saved = db
+            .price_write()
+            .save_bars(&[bar(second, dec!(101)), bar(first, dec!(100))])
+            .unwrap();
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved.first().unwrap().symbol, "SPY");
+
+        // Saving a bar of the same day replaces the previous one
+        db.price_write()
+            .save_bars(&[bar(second, dec!(102)), bar(third, dec!(103))])
+            .unwrap();
+
+        let bars = db.price_read().bars("SPY", first, second).unwrap();
+        let closes: Vec<Decimal> = bars.iter().map(|bar| bar.close).collect();
+        assert_eq!(closes, vec![dec!(100), dec!(102)]);
+
+        let bars = db.price_read().bars("qqq", first, third).unwrap();
+        assert!(bars.is_empty());
+    }
+}
@@ -72,54 +72,31 @@ impl WorkerTransaction {
         currency: &Currency,
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
         // REFACTOR: Query all transactions for an account and filer taxes out in memory.
-        let tx_deposit = WorkerTransaction::read_all_account_transactions_for_category(
-            connection,
-            account_id,
-            currency,
+        let categories = [
             TransactionCategory::Deposit,
-        )?;
-        let tx_withdrawal = WorkerTransaction::read_all_account_transactions_for_category(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::Withdrawal,
-        )?;
-
-        let tx_fee_open = WorkerTransaction::read_all_account_transactions_for_category(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::FeeOpen(Uuid::new_v4()),
-        )?;
-
-        let tx_fee_close = WorkerTransaction::read_all_account_transactions_for_category(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::FeeClose(Uuid::new_v4()),
-        )?;
-
-        let tx_output = WorkerTransaction::read_all_account_transactions_for_category(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::FundTrade(Uuid::new_v4()),
-        )?;
-
-        let tx_input = WorkerTransaction::read_all_account_transactions_for_category(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::PaymentFromTrade(Uuid::new_v4()),
-        )?;
-        Ok(tx_deposit
-            .into_iter()
-            .chain(tx_withdrawal)
-            .chain(tx_fee_open)
-            .chain(tx_fee_close)
-            .chain(tx_output)
-            .chain(tx_input)
-            .collect())
+            // Earnings are set aside from the account, so they are not available to trade.
+            TransactionCategory::PaymentEarnings(Uuid::new_v4()),
+            TransactionCategory::Dividend(Uuid::new_v4()),
+            TransactionCategory::BorrowFee(Uuid::new_v4()),
+            TransactionCategory::Interest,
+            TransactionCategory::TransferOut,
+            TransactionCategory::TransferIn,
+        ];
+
+        let mut transactions = Vec::new();
+        for category in categories {
+            transactions.extend(
+                WorkerTransaction::read_all_account_transactions_for_category(
+                    connection, account_id, currency, category,
+                )?,
+            );
+        }
+        Ok(transactions)
     }
 
     pub fn all_account_transactions_in_trade(
@@ -129,7 +106,7 @@ impl WorkerTransaction {
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
         // Here we are getting all the transactions for a given account and currency
         // and then filtering them in memory to only include transactions that are
-        // part of a trade that is either Funded, Submitted, or Filled.
+        // part of a trade that is either Funded, Submitted, PartiallyFilled, or Filled.
         // All this transactions are part of a trade that is using the money
         // Either in the market or in the process of being filled or submitted.
         let funded_trades = WorkerTrade::read_all_trades_with_status_currency(
@@ -158,6 +135,13 @@ impl WorkerTransaction {
             currency,
         )?;
 
+        let partially_filled_trades = WorkerTrade::read_all_trades_with_status_currency(
+            connection,
+            account_id,
+            Status::PartiallyFilled,
+            currency,
+        )?;
+
         let filled_trades = WorkerTrade::read_all_trades_with_status_currency(
             connection,
             account_id,
@@ -165,7 +149,10 @@ impl WorkerTransaction {
             currency,
         )?;
 
-        let in_market_trades = submitted_trades.into_iter().chain(filled_trades);
+        let in_market_trades = submitted_trades
+            .into_iter()
+            .chain(partially_filled_trades)
+            .chain(filled_trades);
 
         let submitted_trades: Vec<Transaction> = in_market_trades
             .into_iter()
@@ -199,10 +186,17 @@ impl WorkerTransaction {
             currency,
             TransactionCategory::WithdrawalTax,
         )?;
+        let tx_interest_tax = WorkerTransaction::read_all_account_transactions_for_category(
+            connection,
+            account_id,
+            currency,
+            TransactionCategory::InterestTax,
+        )?;
 
         Ok(tx_payments_tax
             .into_iter()
             .chain(tx_withdrawal_tax)
+            .chain(tx_interest_tax)
             .collect())
     }
 
@@ -265,37 +259,33 @@ impl WorkerTransaction {
         account_id: Uuid,
         currency: &Currency,
     ) -> Result<Vec<Transaction>, Box<dyn Error>> {
-        let tx_deposits = WorkerTransaction::read_all_transaction_beginning_of_the_month(
-            connection,
-            account_id,
-            currency,
+        // The same categories that count towards the capital available to trade.
+        // Earnings withdrawn from the account are read too, as they were set aside before.
+        let categories = [
             TransactionCategory::Deposit,
-        )?;
-        let tx_withdrawals = WorkerTransaction::read_all_transaction_beginning_of_the_month(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::Withdrawal,
-        )?;
-        let tx_outputs = WorkerTransaction::read_all_transaction_beginning_of_the_month(
-            connection,
-            account_id,
-            currency,
+            TransactionCategory::FeeOpen(Uuid::new_v4()),
+            TransactionCategory::FeeClose(Uuid::new_v4()),
             TransactionCategory::FundTrade(Uuid::new_v4()),
-        )?;
-        let tx_inputs = WorkerTransaction::read_all_transaction_beginning_of_the_month(
-            connection,
-            account_id,
-            currency,
             TransactionCategory::PaymentFromTrade(Uuid::new_v4()),
-        )?;
-
-        Ok(tx_deposits
-            .into_iter()
-            .chain(tx_withdrawals)
-            .chain(tx_outputs)
-            .chain(tx_inputs)
-            .collect())
+            TransactionCategory::PaymentEarnings(Uuid::new_v4()),
+            TransactionCategory::WithdrawalEarnings,
+            TransactionCategory::Dividend(Uuid::new_v4()),
+            TransactionCategory::BorrowFee(Uuid::new_v4()),
+            TransactionCategory::Interest,
+            TransactionCategory::TransferOut,
+            TransactionCategory::TransferIn,
+        ];
+
+        let mut transactions = Vec::new();
+        for category in categories {
+            transactions.extend(
+                WorkerTransaction::read_all_transaction_beginning_of_the_month(
+                    connection, account_id, currency, category,
+                )?,
+            );
+        }
+        Ok(transactions)
     }
 
     fn read_all_transaction_beginning_of_the_month(
@@ -485,4 +475,75 @@ mod tests {
         assert_eq!(tx.category, TransactionCategory::FundTrade(trade_id));
         assert_eq!(tx.deleted_at, None);
     }
+
+    #[test]
+    fn test_read_all_transaction_excluding_current_month_and_taxes() {
+        let connection = Arc::new(Mutex::new(establish_connection()));
+        let db = SqliteDatabase::new_from(connection.clone());
+        let account = db
+            .account_write()
+            .create(
+                "Test Account",
+                "This is a test account",
+                Environment::Paper,
+                dec!(0.0),
+                dec!(0.0),
+            )
+            .unwrap();
+        let mut connection = connection.lock().unwrap();
+
+        let trade_id = Uuid::new_v4();
+        let last_month = [
+            TransactionCategory::Deposit,
+            TransactionCategory::FeeOpen(trade_id),
+            TransactionCategory::PaymentEarnings(trade_id),
+            TransactionCategory::WithdrawalEarnings,
+            TransactionCategory::Dividend(trade_id),
+            TransactionCategory::BorrowFee(trade_id),
+            TransactionCategory::Interest,
+            TransactionCategory::PaymentTax(trade_id),
+        ];
+        let now = Utc::now().naive_utc();
+        let first_day_of_month = NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let before_month = first_day_of_month - chrono::Duration::seconds(1);
+        for category in last_month {
+            let tx = WorkerTransaction::create_transaction(
+                &mut connection,
+                account.id,
+                dec!(10),
+                &Currency::USD,
+                category,
+            )
+            .unwrap();
+            diesel::update(transactions::table.filter(transactions::id.eq(tx.id.to_string())))
+                .set(transactions::created_at.eq(before_month))
+                .execute(&mut *connection)
+                .unwrap();
+        }
+        WorkerTransaction::create_transaction(
+            &mut connection,
+            account.id,
+            dec!(10),
+            &Currency::USD,
+            TransactionCategory::Interest,
+        )
+        .unwrap();
+
+        let transactions =
+            WorkerTransaction::read_all_transaction_excluding_current_month_and_taxes(
+                &mut connection,
+                account.id,
+                &Currency::USD,
+            )
+            .unwrap();
+
+        // Everything before the month but the taxes
+        assert_eq!(transactions.len(), 7);
+        assert!(transactions
+            .iter()
+            .all(|tx| tx.created_at < first_day_of_month));
+    }
 }
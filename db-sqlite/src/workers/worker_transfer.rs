@@ -0,0 +1,252 @@
+use crate::error::{ConversionError, IntoDomainModel, IntoDomainModels};
+use crate::schema::transfers;
+use crate::workers::WorkerTransaction;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use model::{Account, Currency, TransactionCategory, Transfer};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use tracing::error;
+use uuid::Uuid;
+
+/// Worker for handling transfer database operations
+#[derive(Debug)]
+pub struct WorkerTransfer;
+
+impl WorkerTransfer {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        from: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        to: &Account,
+        converted_amount: Decimal,
+        converted_currency: &Currency,
+    ) -> Result<Transfer, Box<dyn Error>> {
+        connection.transaction(|connection| {
+            // 1. Take the money out of the account that sends it
+            let withdrawal = WorkerTransaction::create_transaction(
+                connection,
+                from.id,
+                amount,
+                currency,
+                TransactionCategory::TransferOut,
+            )?;
+
+            // 2. Put the money into the account that receives it
+            let deposit = WorkerTransaction::create_transaction(
+                connection,
+                to.id,
+                converted_amount,
+                converted_currency,
+                TransactionCategory::TransferIn,
+            )?;
+
+            // 3. Link both transactions
+            let now = Utc::now().naive_utc();
+            let new_transfer = NewTransfer {
+                id: Uuid::new_v4().to_string(),
+                created_at: now,
+                updated_at: now,
+                deleted_at: None,
+                from_account_id: from.id.to_string(),
+                from_transaction_id: withdrawal.id.to_string(),
+                amount: amount.to_string(),
+                currency: currency.to_string(),
+                to_account_id: to.id.to_string(),
+                to_transaction_id: deposit.id.to_string(),
+                converted_amount: converted_amount.to_string(),
+                converted_currency: converted_currency.to_string(),
+            };
+
+            diesel::insert_into(transfers::table)
+                .values(&new_transfer)
+                .get_result::<TransferSQLite>(connection)
+                .map_err(|error| {
+                    error!("Error creating transfer: {:?}", error);
+                    error
+                })?
+                .into_domain_model()
+        })
+    }
+
+    pub fn read_all(
+        connection: &mut SqliteConnection,
+        account_id: Uuid,
+    ) -> Result<Vec<Transfer>, Box<dyn Error>> {
+        transfers::table
+            .filter(
+                transfers::from_account_id
+                    .eq(account_id.to_string())
+                    .or(transfers::to_account_id.eq(account_id.to_string())),
+            )
+            .filter(transfers::deleted_at.is_null())
+            .order(transfers::created_at.asc())
+            .load::<TransferSQLite>(connection)
+            .map_err(|error| {
+                error!("Error reading transfers: {:?}", error);
+                error
+            })?
+            .into_domain_models()
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = transfers)]
+struct TransferSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    from_account_id: String,
+    from_transaction_id: String,
+    amount: String,
+    currency: String,
+    to_account_id: String,
+    to_transaction_id: String,
+    converted_amount: String,
+    converted_currency: String,
+}
+
+fn parse_uuid(value: &str, field: &'static str) -> Result<Uuid, ConversionError> {
+    Uuid::parse_str(value).map_err(|_| ConversionError::new(field, "Failed to parse ID"))
+}
+
+impl TryFrom<TransferSQLite> for Transfer {
+    type Error = ConversionError;
+
+    fn try_from(value: TransferSQLite) -> Result<Self, Self::Error> {
+        Ok(Transfer {
+            id: parse_uuid(&value.id, "id")?,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            deleted_at: value.deleted_at,
+            from_account_id: parse_uuid(&value.from_account_id, "from_account_id")?,
+            from_transaction_id: parse_uuid(&value.from_transaction_id, "from_transaction_id")?,
+            amount: Decimal::from_str(&value.amount)
+                .map_err(|_| ConversionError::new("amount", "Failed to parse amount"))?,
+            currency: Currency::from_str(&value.currency)
+                .map_err(|_| ConversionError::new("currency", "Failed to parse currency"))?,
+            to_account_id: parse_uuid(&value.to_account_id, "to_account_id")?,
+            to_transaction_id: parse_uuid(&value.to_transaction_id, "to_transaction_id")?,
+            converted_amount: Decimal::from_str(&value.converted_amount).map_err(|_| {
+                ConversionError::new("converted_amount", "Failed to parse converted amount")
+            })?,
+            converted_currency: Currency::from_str(&value.converted_currency).map_err(|_| {
+                ConversionError::new("converted_currency", "Failed to parse currency")
+            })?,
+        })
+    }
+}
+
+impl IntoDomainModel<Transfer> for TransferSQLite {
+    fn into_domain_model(self) -> Result<Transfer, Box<dyn Error>> {
+        self.try_into().map_err(Into::into)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = transfers)]
+#[diesel(treat_none_as_null = true)]
+struct NewTransfer {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    deleted_at: Option<NaiveDateTime>,
+    from_account_id: String,
+    from_transaction_id: String,
+    amount: String,
+    currency: String,
+    to_account_id: String,
+    to_transaction_id: String,
+    converted_amount: String,
+    converted_currency: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SqliteDatabase;
+    use diesel_migrations::*;
+    use model::{DatabaseFactory, Environment};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+    fn establish_connection() -> SqliteConnection {
+        let mut connection = SqliteConnection::establish(":memory:").unwrap();
+        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        connection.begin_test_transaction().unwrap();
+        connection
+    }
+
+    fn create_factory() -> Box<dyn DatabaseFactory> {
+        Box::new(SqliteDatabase::new_from(Arc::new(Mutex::new(
+            establish_connection(),
+        ))))
+    }
+
+    #[test]
+    fn test_create_and_read_transfer() {
+        let db = create_factory();
+        let paper = db
+            .account_write()
+            .create("Paper", "paper", Environment::Paper, dec!(20), dec!(10))
+            .unwrap();
+        let live = db
+            .account_write()
+            .create("Live", "live", Environment::Live, dec!(20), dec!(10))
+            .unwrap();
+
+        let transfer = db
+            .transfer_write()
+            .create_transfer(
+                &paper,
+                dec!(100),
+                &Currency::EUR,
+                &live,
+                dec!(108.5),
+                &Currency::USD,
+            )
+            .unwrap();
+
+        assert_eq!(transfer.from_account_id, paper.id);
+        assert_eq!(transfer.to_account_id, live.id);
+        assert_eq!(transfer.rate(), Some(dec!(1.085)));
+
+        // Both transactions are created in their accounts
+        let withdrawals = db
+            .transaction_read()
+            .all_transactions(paper.id, &Currency::EUR)
+            .unwrap();
+        assert_eq!(withdrawals.len(), 1);
+        assert_eq!(
+            withdrawals.first().unwrap().id,
+            transfer.from_transaction_id
+        );
+        assert_eq!(
+            withdrawals.first().unwrap().category,
+            TransactionCategory::TransferOut
+        );
+
+        let deposits = db
+            .transaction_read()
+            .all_transactions(live.id, &Currency::USD)
+            .unwrap();
+        assert_eq!(deposits.len(), 1);
+        assert_eq!(deposits.first().unwrap().id, transfer.to_transaction_id);
+        assert_eq!(deposits.first().unwrap().amount, dec!(108.5));
+
+        // The transfer is found from both accounts
+        assert_eq!(
+            db.transfer_read().all_transfers(paper.id).unwrap(),
+            vec![transfer.clone()]
+        );
+        assert_eq!(
+            db.transfer_read().all_transfers(live.id).unwrap(),
+            vec![transfer]
+        );
+    }
+}
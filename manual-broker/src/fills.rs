@@ -0,0 +1,159 @@
+use chrono::{NaiveDateTime, Utc};
+use model::Trade;
+use rust_decimal::Decimal;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::str::FromStr;
+
+/// Execution of an order reported by the trader
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fill {
+    /// Average price of the execution
+    pub price: Decimal,
+    /// When the order was executed
+    pub filled_at: NaiveDateTime,
+}
+
+/// Orders of a trade that were executed at the broker.
+///
+/// The fill file of a trade is a JSON object with an optional `entry`, `stop` and
+/// `target`, each with a `price` and an optional `filled_at`, like
+/// `{"entry": {"price": "40.05", "filled_at": "2024-01-02 15:30:00"}}`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Fills {
+    /// Fill of the entry
+    pub entry: Option<Fill>,
+    /// Fill of the safety stop
+    pub stop: Option<Fill>,
+    /// Fill of the target, or of the market order that closed the trade
+    pub target: Option<Fill>,
+}
+
+/// Asks the trader for the fills of a trade that does not have a fill file yet.
+pub trait FillPrompt {
+    /// Returns the orders of the trade that were executed since it was submitted.
+    fn fills(&self, trade: &Trade) -> Result<Fills, Box<dyn Error>>;
+}
+
+impl Fills {
+    /// True when no order was executed
+    pub fn is_empty(&self) -> bool {
+        self.entry.is_none() && self.stop.is_none() && self.target.is_none()
+    }
+
+    /// Reads the fills of a fill file.
+    pub fn parse(content: &str) -> Result<Fills, Box<dyn Error>> {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|error| format!("The fill file is not valid JSON: {error}"))?;
+        let object = value
+            .as_object()
+            .ok_or("The fill file must be a JSON object with entry, stop or target")?;
+
+        let fills = Fills {
+            entry: parse_fill(object, "entry")?,
+            stop: parse_fill(object, "stop")?,
+            target: parse_fill(object, "target")?,
+        };
+        if fills.entry.is_none() && (fills.stop.is_some() || fills.target.is_some()) {
+            return Err("The fill file has an exit without the fill of the entry".into());
+        }
+        if fills.stop.is_some() && fills.target.is_some() {
+            return Err("The fill file has a fill of the stop and of the target, only one of them can be executed".into());
+        }
+        Ok(fills)
+    }
+
+    /// The content of the fill file of these fills.
+    pub fn to_json(&self) -> Value {
+        let fill = |fill: Option<Fill>| {
+            fill.map(|fill| {
+                json!({
+                    "price": fill.price.to_string(),
+                    "filled_at": fill.filled_at.format(DATE_FORMAT).to_string(),
+                })
+            })
+        };
+        json!({
+            "entry": fill(self.entry),
+            "stop": fill(self.stop),
+            "target": fill(self.target),
+        })
+    }
+}
+
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn parse_fill(object: &Map<String, Value>, leg: &str) -> Result<Option<Fill>, Box<dyn Error>> {
+    let fill = match object.get(leg) {
+        None | Some(Value::Null) => return Ok(None),
+        Some(fill) => fill,
+    };
+
+    let price = match fill.get("price") {
+        Some(Value::String(price)) => Decimal::from_str(price).ok(),
+        Some(Value::Number(price)) => Decimal::from_str(&price.to_string()).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("The {leg} of the fill file does not have a valid price"))?;
+    if price <= Decimal::ZERO {
+        return Err(format!("The {leg} of the fill file must have a positive price").into());
+    }
+
+    let filled_at = match fill.get("filled_at") {
+        None | Some(Value::Null) => Utc::now().naive_utc(),
+        Some(Value::String(date)) => NaiveDateTime::parse_from_str(date, DATE_FORMAT)
+            .or_else(|_| NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S"))
+            .map_err(|_| format!("The {leg} of the fill file has '{date}' instead of a date like 2024-01-31 15:30:00"))?,
+        Some(date) => {
+            return Err(format!("The {leg} of the fill file has {date} instead of a date").into())
+        }
+    };
+
+    Ok(Some(Fill { price, filled_at }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_fills() {
+        let fills = Fills::parse(
+            r#"{"entry": {"price": "40.05", "filled_at": "2024-01-02 15:30:00"}, "target": {"price": 50}}"#,
+        )
+        .unwrap();
+
+        let entry = fills.entry.unwrap();
+        assert_eq!(entry.price, dec!(40.05));
+        assert_eq!(entry.filled_at.to_string(), "2024-01-02 15:30:00");
+        assert_eq!(fills.stop, None);
+        assert_eq!(fills.target.unwrap().price, dec!(50));
+        let written = Fills::parse(&fills.to_json().to_string()).unwrap();
+        assert_eq!(written.entry, fills.entry);
+        assert_eq!(written.target.unwrap().price, dec!(50));
+    }
+
+    #[test]
+    fn test_parse_invalid_fills() {
+        let error = Fills::parse(r#"{"stop": {"price": "38"}}"#).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The fill file has an exit without the fill of the entry"
+        );
+
+        let error = Fills::parse(r#"{"entry": {"price": "-1"}}"#).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The entry of the fill file must have a positive price"
+        );
+
+        let error = Fills::parse(r#"{"entry": {"price": "40", "filled_at": "yesterday"}}"#)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The entry of the fill file has 'yesterday' instead of a date like 2024-01-31 15:30:00"
+        );
+    }
+}
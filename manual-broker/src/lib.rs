@@ -0,0 +1,522 @@
+//! Trust Manual Broker
+//!
+//! This crate provides a broker for trading at brokers without an API. Every
+//! call writes an order ticket for the trader to place by hand, and the fills
+//! are read back from a fill file or asked to the trader.
+
+// === FINANCIAL APPLICATION SAFETY LINTS ===
+// These lint rules are critical for financial applications where precision,
+// safety, and reliability are paramount. Violations can lead to financial losses.
+
+#![deny(
+    // Error handling safety - force proper error handling
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+
+    // Financial precision safety - prevent calculation errors
+    clippy::float_arithmetic,
+    clippy::arithmetic_side_effects,
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+
+    // Code quality enforcement
+    clippy::cognitive_complexity,
+    clippy::too_many_lines,
+)]
+// Allow unwrap and expect in test code only
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+// Standard Rust lints for code quality
+#![warn(missing_docs, rust_2018_idioms, missing_debug_implementations)]
+
+use model::{
+    Account, Broker, BrokerCapabilities, BrokerLog, Order, OrderCategory, OrderIds, OrderStatus,
+    Status, Trade,
+};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+mod fills;
+mod ticket;
+
+pub use fills::{Fill, FillPrompt, Fills};
+pub use ticket::Ticket;
+
+/// Broker that writes order tickets for the trader to place at any broker
+///
+/// The tickets of a trade are written as text and JSON files to a directory,
+/// named after the trade ID and the action, like `<trade-id>-submit.txt`. The
+/// fills are read from `<trade-id>-fills.json` in the same directory. When a
+/// trade does not have a fill file yet, the prompt asks for the fills and
+/// saves them in the fill file.
+pub struct ManualBroker {
+    directory: PathBuf,
+    prompt: Option<Box<dyn FillPrompt>>,
+}
+
+impl std::fmt::Debug for ManualBroker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManualBroker")
+            .field("directory", &self.directory)
+            .field(
+                "prompt",
+                &self.prompt.as_ref().map(|_| "Box<dyn FillPrompt>"),
+            )
+            .finish()
+    }
+}
+
+impl ManualBroker {
+    /// Creates a broker that writes the tickets and reads the fills of the directory
+    pub fn new(directory: &Path) -> Self {
+        ManualBroker {
+            directory: directory.to_path_buf(),
+            prompt: None,
+        }
+    }
+
+    /// Asks the prompt for the fills of the trades without a fill file
+    pub fn with_prompt(mut self, prompt: Box<dyn FillPrompt>) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    /// The path of the fill file of a trade
+    pub fn fills_path(&self, trade: &Trade) -> PathBuf {
+        self.directory.join(format!("{}-fills.json", trade.id))
+    }
+
+    /// Reads the fills of a trade from its fill file, or from the prompt.
+    /// The answers of the prompt are saved in the fill file.
+    pub fn read_fills(&self, trade: &Trade) -> Result<Fills, Box<dyn Error>> {
+        let path = self.fills_path(trade);
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|error| format!("Failed to read {}: {error}", path.display()))?;
+            return Fills::parse(&content)
+                .map_err(|error| format!("{}: {error}", path.display()).into());
+        }
+
+        let fills = match &self.prompt {
+            Some(prompt) => prompt.fills(trade)?,
+            None => Fills::default(),
+        };
+        if !fills.is_empty() {
+            self.write_fills(trade, &fills)?;
+        }
+        Ok(fills)
+    }
+
+    /// Replaces the fill file of a trade
+    pub fn write_fills(&self, trade: &Trade, fills: &Fills) -> Result<(), Box<dyn Error>> {
+        // Validate the fills like the ones written by hand
+        let content = serde_json::to_string_pretty(&fills.to_json())?;
+        Fills::parse(&content)?;
+
+        fs::create_dir_all(&self.directory).map_err(|error| {
+            format!(
+                "Failed to create the directory {}: {error}",
+                self.directory.display()
+            )
+        })?;
+        let path = self.fills_path(trade);
+        fs::write(&path, content)
+            .map_err(|error| format!("Failed to write {}: {error}", path.display()).into())
+    }
+
+    fn issue(&self, ticket: &Ticket, trade: &Trade) -> Result<BrokerLog, Box<dyn Error>> {
+        let (text, json) = ticket.write(&self.directory, trade)?;
+        Ok(BrokerLog {
+            trade_id: trade.id,
+            request: ticket.text().to_string(),
+            response: format!("Ticket saved to {} and {}", text.display(), json.display()),
+            ..Default::default()
+        })
+    }
+}
+
+impl Broker for ManualBroker {
+    fn submit_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+        let ticket = Ticket::submit(trade, account, &self.fills_path(trade));
+        let log = self.issue(&ticket, trade)?;
+
+        // The orders are identified at the broker by their client order IDs
+        let ids = OrderIds {
+            stop: Some(trade.safety_stop.id),
+            entry: trade.entry.id,
+            target: Some(trade.target.id),
+        };
+        Ok((log, ids))
+    }
+
+    fn sync_trade(
+        &self,
+        trade: &Trade,
+        _account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+        let fills = self.read_fills(trade)?;
+
+        let status = if fills.stop.is_some() {
+            Status::ClosedStopLoss
+        } else if fills.target.is_some() {
+            Status::ClosedTarget
+        } else if fills.entry.is_some() {
+            Status::Filled
+        } else {
+            trade.status
+        };
+        let orders: Vec<Order> = [
+            (&trade.entry, fills.entry),
+            (&trade.safety_stop, fills.stop),
+            (&trade.target, fills.target),
+        ]
+        .into_iter()
+        .filter_map(|(order, fill)| fill.map(|fill| filled(order, fill)))
+        .chain(canceled_exit(trade, &fills))
+        .collect();
+
+        let log = BrokerLog {
+            trade_id: trade.id,
+            request: format!("Read the fills of {}", self.fills_path(trade).display()),
+            response: fills.to_json().to_string(),
+            ..Default::default()
+        };
+        Ok((status, orders, log))
+    }
+
+    fn close_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        let mut target = trade.target.clone();
+        target.category = OrderCategory::Market;
+        target.status = OrderStatus::New;
+        target.broker_order_id = Some(target.id);
+
+        let log = self.issue(&Ticket::close(trade, account, &target), trade)?;
+        Ok((target, log))
+    }
+
+    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<BrokerLog, Box<dyn Error>> {
+        self.issue(&Ticket::cancel(trade, account), trade)
+    }
+
+    fn modify_stop(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_stop_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        let stop = &trade.safety_stop;
+        let ticket = Ticket::modify(trade, account, "stop", stop, new_stop_price);
+        let log = self.issue(&ticket, trade)?;
+        Ok((stop.broker_order_id.unwrap_or(stop.id), log))
+    }
+
+    fn modify_target(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        let target = &trade.target;
+        let ticket = Ticket::modify(trade, account, "target", target, new_price);
+        let log = self.issue(&ticket, trade)?;
+        Ok((target.broker_order_id.unwrap_or(target.id), log))
+    }
+
+    fn modify_entry(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        let entry = &trade.entry;
+        let ticket = Ticket::modify_entry(trade, account, new_price, new_quantity);
+        let log = self.issue(&ticket, trade)?;
+        Ok((entry.broker_order_id.unwrap_or(entry.id), log))
+    }
+
+    /// The trader places the tickets, so it depends on the broker they use.
+    /// Trust quantities are whole numbers, so fractional orders are not supported.
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities {
+            extended_hours: true,
+            crypto: true,
+            modify_entry: true,
+            ..Default::default()
+        }
+    }
+}
+
+fn filled(order: &Order, fill: Fill) -> Order {
+    Order {
+        broker_order_id: order.broker_order_id.or(Some(order.id)),
+        status: OrderStatus::Filled,
+        filled_quantity: order.quantity,
+        average_filled_price: Some(fill.price),
+        filled_at: Some(fill.filled_at),
+        ..order.clone()
+    }
+}
+
+/// The stop and the target cancel each other, so the one not filled is canceled.
+fn canceled_exit(trade: &Trade, fills: &Fills) -> Option<Order> {
+    let (order, fill) = match (fills.stop, fills.target) {
+        (Some(fill), None) => (&trade.target, fill),
+        (None, Some(fill)) => (&trade.safety_stop, fill),
+        _ => return None,
+    };
+    if order.status == OrderStatus::Canceled {
+        return None;
+    }
+    Some(Order {
+        broker_order_id: order.broker_order_id.or(Some(order.id)),
+        status: OrderStatus::Canceled,
+        cancelled_at: Some(fill.filled_at),
+        ..order.clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use model::{OrderAction, TradeCategory, TradingVehicle};
+    use rust_decimal_macros::dec;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn directory() -> PathBuf {
+        std::env::temp_dir().join(format!("trust-manual-broker-{}", Uuid::new_v4()))
+    }
+
+    fn order(category: OrderCategory, action: OrderAction, price: Decimal) -> Order {
+        Order {
+            id: Uuid::new_v4(),
+            category,
+            action,
+            unit_price: price,
+            quantity: 10,
+            ..Default::default()
+        }
+    }
+
+    fn trade() -> Trade {
+        Trade {
+            category: TradeCategory::Long,
+            status: Status::Submitted,
+            trading_vehicle: TradingVehicle {
+                symbol: "AAPL".to_string(),
+                ..Default::default()
+            },
+            entry: order(OrderCategory::Limit, OrderAction::Buy, dec!(40)),
+            safety_stop: order(OrderCategory::Market, OrderAction::Sell, dec!(38)),
+            target: order(OrderCategory::Limit, OrderAction::Sell, dec!(50)),
+            ..Default::default()
+        }
+    }
+
+    fn account() -> Account {
+        Account {
+            name: "paper".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_submit_writes_ticket() {
+        let directory = directory();
+        let broker = ManualBroker::new(&directory);
+        let trade = trade();
+
+        let (log, ids) = broker.submit_trade(&trade, &account()).unwrap();
+
+        assert_eq!(ids.entry, trade.entry.id);
+        assert_eq!(ids.stop, Some(trade.safety_stop.id));
+        assert_eq!(ids.target, Some(trade.target.id));
+        assert!(log
+            .request
+            .contains("Entry: buy 10 AAPL limit 40, until_canceled"));
+        assert!(log.request.contains(
+            "Stop: sell 10 AAPL stop 38, until_canceled, one-cancels-other with the target"
+        ));
+
+        let text = fs::read_to_string(directory.join(format!("{}-submit.txt", trade.id))).unwrap();
+        assert!(text.starts_with(&format!("Order ticket: submit trade {}", trade.id)));
+        let json = fs::read_to_string(directory.join(format!("{}-submit.json", trade.id))).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let stop = json.get("orders").unwrap().get(1).unwrap();
+        assert_eq!(stop["type"], "stop");
+        assert_eq!(stop["price"], "38");
+        assert_eq!(stop["one_cancels_other_with"], "target");
+    }
+
+    #[test]
+    fn test_sync_without_fills() {
+        let broker = ManualBroker::new(&directory());
+        let trade = trade();
+
+        let (status, orders, _) = broker.sync_trade(&trade, &account()).unwrap();
+
+        assert_eq!(status, Status::Submitted);
+        assert!(orders.is_empty());
+    }
+
+    #[test]
+    fn test_sync_reads_fill_file() {
+        let directory = directory();
+        let broker = ManualBroker::new(&directory);
+        let trade = trade();
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            broker.fills_path(&trade),
+            r#"{"entry": {"price": "39.9", "filled_at": "2024-01-02 15:30:00"}}"#,
+        )
+        .unwrap();
+
+        let (status, orders, _) = broker.sync_trade(&trade, &account()).unwrap();
+
+        assert_eq!(status, Status::Filled);
+        let entry = orders.first().unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(entry.id, trade.entry.id);
+        assert_eq!(entry.status, OrderStatus::Filled);
+        assert_eq!(entry.filled_quantity, 10);
+        assert_eq!(entry.average_filled_price, Some(dec!(39.9)));
+
+        fs::write(
+            broker.fills_path(&trade),
+            r#"{"entry": {"price": "39.9"}, "stop": {"price": "37.5"}}"#,
+        )
+        .unwrap();
+
+        let (status, orders, _) = broker.sync_trade(&trade, &account()).unwrap();
+
+        assert_eq!(status, Status::ClosedStopLoss);
+        let stop = orders.get(1).unwrap();
+        assert_eq!(stop.average_filled_price, Some(dec!(37.5)));
+        let target = orders.get(2).unwrap();
+        assert_eq!(target.id, trade.target.id);
+        assert_eq!(target.status, OrderStatus::Canceled);
+    }
+
+    #[test]
+    fn test_sync_rejects_invalid_fill_file() {
+        let directory = directory();
+        let broker = ManualBroker::new(&directory);
+        let trade = trade();
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(broker.fills_path(&trade), r#"{"entry": {"price": "x"}}"#).unwrap();
+
+        let error = broker.sync_trade(&trade, &account()).err().unwrap();
+
+        assert!(error
+            .to_string()
+            .ends_with("The entry of the fill file does not have a valid price"));
+    }
+
+    struct CountingPrompt {
+        calls: Rc<Cell<u32>>,
+    }
+
+    impl FillPrompt for CountingPrompt {
+        fn fills(&self, _trade: &Trade) -> Result<Fills, Box<dyn Error>> {
+            self.calls.set(self.calls.get().saturating_add(1));
+            Ok(Fills {
+                entry: Some(Fill {
+                    price: dec!(40),
+                    filled_at: NaiveDateTime::default(),
+                }),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_sync_saves_the_answers_of_the_prompt() {
+        let calls = Rc::new(Cell::new(0));
+        let broker = ManualBroker::new(&directory()).with_prompt(Box::new(CountingPrompt {
+            calls: calls.clone(),
+        }));
+        let trade = trade();
+
+        let (status, _, _) = broker.sync_trade(&trade, &account()).unwrap();
+        assert_eq!(status, Status::Filled);
+        assert!(broker.fills_path(&trade).exists());
+
+        // The second sync reads the fill file instead of asking again
+        let (status, _, _) = broker.sync_trade(&trade, &account()).unwrap();
+        assert_eq!(status, Status::Filled);
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_close_writes_market_ticket() {
+        let directory = directory();
+        let broker = ManualBroker::new(&directory);
+        let trade = trade();
+
+        let (target, log) = broker.close_trade(&trade, &account()).unwrap();
+
+        assert_eq!(target.id, trade.target.id);
+        assert_eq!(target.category, OrderCategory::Market);
+        assert_eq!(target.status, OrderStatus::New);
+        assert!(log
+            .request
+            .contains("Close: sell 10 AAPL market, until_canceled"));
+        assert!(directory.join(format!("{}-close.json", trade.id)).exists());
+    }
+
+    #[test]
+    fn test_modify_stop_writes_ticket() {
+        let directory = directory();
+        let broker = ManualBroker::new(&directory);
+        let trade = trade();
+
+        let (id, _) = broker.modify_stop(&trade, &account(), dec!(39)).unwrap();
+
+        assert_eq!(id, trade.safety_stop.id);
+        let json =
+            fs::read_to_string(directory.join(format!("{}-modify-stop.json", trade.id))).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let stop = json.get("orders").unwrap().get(0).unwrap();
+        assert_eq!(stop["price"], "39");
+    }
+
+    #[test]
+    fn test_modify_entry_writes_ticket() {
+        let directory = directory();
+        let broker = ManualBroker::new(&directory);
+        let trade = trade();
+
+        let (id, log) = broker
+            .modify_entry(&trade, &account(), dec!(41), 5)
+            .unwrap();
+
+        assert_eq!(id, trade.entry.id);
+        assert!(log
+            .request
+            .contains("Change the quantity of the stop and the target to 5"));
+        let json =
+            fs::read_to_string(directory.join(format!("{}-modify-entry.json", trade.id))).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let orders = json.get("orders").unwrap();
+        let entry = orders.get(0).unwrap();
+        assert_eq!(entry.get("price").unwrap(), "41");
+        assert_eq!(entry.get("quantity").unwrap(), 5);
+        let target = orders.get(2).unwrap();
+        assert_eq!(target.get("quantity").unwrap(), 5);
+    }
+}
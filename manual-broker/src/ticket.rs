@@ -0,0 +1,224 @@
+use model::{Account, Order, OrderCategory, Trade};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Instructions for the trader to place at the broker, in text and in JSON
+#[derive(Debug)]
+pub struct Ticket {
+    name: &'static str,
+    text: String,
+    json: Value,
+}
+
+impl Ticket {
+    /// Places the entry, with the stop and the target as one-cancels-other orders.
+    pub fn submit(trade: &Trade, account: &Account, fills: &Path) -> Ticket {
+        let lines = [
+            line("Entry", trade, &trade.entry, trade.entry.category),
+            format!(
+                "{}, one-cancels-other with the target",
+                line("Stop", trade, &trade.safety_stop, OrderCategory::Stop)
+            ),
+            format!(
+                "{}, one-cancels-other with the stop",
+                line("Target", trade, &trade.target, trade.target.category)
+            ),
+            format!(
+                "Place the stop and the target when the entry is filled, and record the fills in {}",
+                fills.display()
+            ),
+        ];
+        let orders = vec![
+            order_json("entry", trade, &trade.entry, trade.entry.category, None),
+            order_json(
+                "stop",
+                trade,
+                &trade.safety_stop,
+                OrderCategory::Stop,
+                Some("target"),
+            ),
+            order_json(
+                "target",
+                trade,
+                &trade.target,
+                trade.target.category,
+                Some("stop"),
+            ),
+        ];
+        Ticket::new("submit", trade, account, &lines, orders)
+    }
+
+    /// Cancels the stop and the target and closes the position at the market.
+    pub fn close(trade: &Trade, account: &Account, target: &Order) -> Ticket {
+        let lines = [
+            "Cancel the stop and the target".to_string(),
+            line("Close", trade, target, OrderCategory::Market),
+            "Record the fill of the close as the target in the fill file".to_string(),
+        ];
+        let orders = vec![order_json(
+            "target",
+            trade,
+            target,
+            OrderCategory::Market,
+            None,
+        )];
+        Ticket::new("close", trade, account, &lines, orders)
+    }
+
+    /// Cancels the orders of a trade whose entry was not filled.
+    pub fn cancel(trade: &Trade, account: &Account) -> Ticket {
+        let lines = ["Cancel the entry, the stop and the target".to_string()];
+        Ticket::new("cancel", trade, account, &lines, Vec::new())
+    }
+
+    /// Changes the price of the stop or the target.
+    pub fn modify(
+        trade: &Trade,
+        account: &Account,
+        leg: &'static str,
+        order: &Order,
+        price: Decimal,
+    ) -> Ticket {
+        let lines = [format!(
+            "Change the price of the {leg} from {} to {price}",
+            order.unit_price
+        )];
+        let category = match leg {
+            "stop" => OrderCategory::Stop,
+            _ => order.category,
+        };
+        let modified = Order {
+            unit_price: price,
+            ..order.clone()
+        };
+        let json = order_json(leg, trade, &modified, category, None);
+        let name = match leg {
+            "stop" => "modify-stop",
+            _ => "modify-target",
+        };
+        Ticket::new(name, trade, account, &lines, vec![json])
+    }
+
+    /// Changes the price and the quantity of the entry, and the quantity of the stop and the target.
+    pub fn modify_entry(trade: &Trade, account: &Account, price: Decimal, quantity: u64) -> Ticket {
+        let lines = [
+            format!(
+                "Change the entry from {} at {} to {quantity} at {price}",
+                trade.entry.quantity, trade.entry.unit_price
+            ),
+            format!("Change the quantity of the stop and the target to {quantity}"),
+        ];
+        let entry = Order {
+            unit_price: price,
+            quantity,
+            ..trade.entry.clone()
+        };
+        let stop = Order {
+            quantity,
+            ..trade.safety_stop.clone()
+        };
+        let target = Order {
+            quantity,
+            ..trade.target.clone()
+        };
+        let orders = vec![
+            order_json("entry", trade, &entry, entry.category, None),
+            order_json("stop", trade, &stop, OrderCategory::Stop, Some("target")),
+            order_json("target", trade, &target, target.category, Some("stop")),
+        ];
+        Ticket::new("modify-entry", trade, account, &lines, orders)
+    }
+
+    fn new(
+        name: &'static str,
+        trade: &Trade,
+        account: &Account,
+        lines: &[String],
+        orders: Vec<Value>,
+    ) -> Ticket {
+        let header = [
+            format!("Order ticket: {name} trade {}", trade.id),
+            format!("Account: {}", account.name),
+            format!("Symbol: {}", trade.trading_vehicle.symbol),
+        ];
+        let text = header
+            .iter()
+            .chain(lines.iter())
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("\n");
+        let json = json!({
+            "ticket": name,
+            "trade_id": trade.id.to_string(),
+            "account": account.name,
+            "symbol": trade.trading_vehicle.symbol,
+            "orders": orders,
+        });
+        Ticket { name, text, json }
+    }
+
+    /// The ticket as text, for the trader to read
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Writes the ticket to a text and a JSON file of the directory and returns their paths.
+    pub fn write(
+        &self,
+        directory: &Path,
+        trade: &Trade,
+    ) -> Result<(PathBuf, PathBuf), Box<dyn Error>> {
+        fs::create_dir_all(directory).map_err(|error| {
+            format!(
+                "Failed to create the directory {}: {error}",
+                directory.display()
+            )
+        })?;
+
+        let stem = format!("{}-{}", trade.id, self.name);
+        let text = directory.join(format!("{stem}.txt"));
+        let json = directory.join(format!("{stem}.json"));
+        fs::write(&text, &self.text)
+            .map_err(|error| format!("Failed to write {}: {error}", text.display()))?;
+        fs::write(&json, serde_json::to_string_pretty(&self.json)?)
+            .map_err(|error| format!("Failed to write {}: {error}", json.display()))?;
+        Ok((text, json))
+    }
+}
+
+fn line(leg: &str, trade: &Trade, order: &Order, category: OrderCategory) -> String {
+    let price = match category {
+        OrderCategory::Market => String::new(),
+        _ => format!(" {}", order.unit_price),
+    };
+    format!(
+        "{leg}: {} {} {} {category}{price}, {}",
+        order.action, order.quantity, trade.trading_vehicle.symbol, order.time_in_force
+    )
+}
+
+fn order_json(
+    leg: &str,
+    trade: &Trade,
+    order: &Order,
+    category: OrderCategory,
+    oco_with: Option<&str>,
+) -> Value {
+    json!({
+        "leg": leg,
+        "client_order_id": order.id.to_string(),
+        "symbol": trade.trading_vehicle.symbol,
+        "side": order.action.to_string(),
+        "type": category.to_string(),
+        "quantity": order.quantity,
+        "price": match category {
+            OrderCategory::Market => None,
+            _ => Some(order.unit_price.to_string()),
+        },
+        "time_in_force": order.time_in_force.to_string(),
+        "one_cancels_other_with": oco_with,
+    })
+}
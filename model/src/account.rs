@@ -32,6 +32,10 @@ pub struct Account {
     pub taxes_percentage: Decimal,
     /// Percentage of earnings to set aside
     pub earnings_percentage: Decimal,
+    /// Model used to calculate the size of new trades
+    pub position_sizing: PositionSizing,
+    /// Symbol of the index that the returns of the account are compared with, like SPY
+    pub benchmark: Option<String>,
 }
 
 /// AccountBalance entity (read-only)
@@ -77,6 +81,23 @@ pub struct AccountBalance {
     pub currency: Currency,
 }
 
+/// OpenRisk entity (read-only)
+/// The money that an account would lose if the stops of all its open trades were hit,
+/// also known as portfolio heat.
+///
+/// Stops moved past breakeven do not add any risk.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct OpenRisk {
+    /// The currency of the open trades
+    pub currency: Currency,
+
+    /// Total amount of money at risk in the open trades
+    pub amount: Decimal,
+
+    /// The amount at risk as a percentage of the capital of the account, including the capital in trades
+    pub percentage: Decimal,
+}
+
 // Implementations
 
 impl std::fmt::Display for Account {
@@ -102,6 +123,8 @@ impl Default for Account {
             environment: Environment::Paper,
             taxes_percentage: Decimal::default(),
             earnings_percentage: Decimal::default(),
+            position_sizing: PositionSizing::default(),
+            benchmark: None,
         }
     }
 }
@@ -163,3 +186,105 @@ impl std::str::FromStr for Environment {
         }
     }
 }
+
+/// Position sizing model used to calculate the quantity of a trade.
+///
+/// Every model is still capped by the `RiskPerTrade` and `RiskPerMonth` rules
+/// of the account. The models only decide how much of that budget is used.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PositionSizing {
+    /// Risk a percentage of the available capital defined by the `RiskPerTrade` rule.
+    #[default]
+    FixedFractional,
+
+    /// Risk a fixed amount of money per trade.
+    /// For example: risking 200 with an entry at 40 and a stop at 38 buys 100 shares.
+    FixedDollar(Decimal),
+
+    /// Size by volatility. The risk per share is the ATR supplied when the trade
+    /// is created multiplied by this value, or the distance to the stop when it is wider.
+    /// Without a `RiskPerTrade` rule it risks 1% of the available capital.
+    /// For example: risking 1000 with an ATR of 2.5 and a multiplier of 2 buys 200 shares.
+    Volatility(Decimal),
+
+    /// Risk a fraction of the Kelly percentage calculated from the win rate and
+    /// payoff ratio of the closed trades of the account.
+    /// For example: 0.5 is half Kelly.
+    Kelly(Decimal),
+}
+
+impl PositionSizing {
+    /// Returns all possible position sizing models with default values
+    pub fn all() -> Vec<PositionSizing> {
+        vec![
+            PositionSizing::FixedFractional,
+            PositionSizing::FixedDollar(Decimal::default()),
+            PositionSizing::Volatility(Decimal::default()),
+            PositionSizing::Kelly(Decimal::default()),
+        ]
+    }
+
+    /// Returns the parameter of the model. Fixed fractional does not have any.
+    pub fn value(&self) -> Decimal {
+        match self {
+            PositionSizing::FixedFractional => Decimal::default(),
+            PositionSizing::FixedDollar(value)
+            | PositionSizing::Volatility(value)
+            | PositionSizing::Kelly(value) => *value,
+        }
+    }
+
+    /// Parse a position sizing model from string with its parameter
+    pub fn parse(s: &str, value: Decimal) -> Result<Self, PositionSizingParseError> {
+        match s {
+            "fixed_fractional" => Ok(PositionSizing::FixedFractional),
+            "fixed_dollar" => Ok(PositionSizing::FixedDollar(value)),
+            "volatility" => Ok(PositionSizing::Volatility(value)),
+            "kelly" => Ok(PositionSizing::Kelly(value)),
+            _ => Err(PositionSizingParseError),
+        }
+    }
+}
+
+impl Display for PositionSizing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            PositionSizing::FixedFractional => write!(f, "fixed_fractional"),
+            PositionSizing::FixedDollar(_) => write!(f, "fixed_dollar"),
+            PositionSizing::Volatility(_) => write!(f, "volatility"),
+            PositionSizing::Kelly(_) => write!(f, "kelly"),
+        }
+    }
+}
+
+/// Error when parsing position sizing from string fails
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionSizingParseError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_position_sizing_parse() {
+        for sizing in PositionSizing::all() {
+            let parsed = PositionSizing::parse(&sizing.to_string(), sizing.value());
+            assert_eq!(parsed, Ok(sizing));
+        }
+        assert_eq!(
+            PositionSizing::parse("kelly", dec!(0.5)),
+            Ok(PositionSizing::Kelly(dec!(0.5)))
+        );
+        assert_eq!(
+            PositionSizing::parse("martingale", dec!(1)),
+            Err(PositionSizingParseError)
+        );
+    }
+
+    #[test]
+    fn test_position_sizing_value() {
+        assert_eq!(PositionSizing::FixedFractional.value(), dec!(0));
+        assert_eq!(PositionSizing::FixedDollar(dec!(200)).value(), dec!(200));
+    }
+}
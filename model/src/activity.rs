@@ -0,0 +1,176 @@
+use crate::{Currency, Transaction};
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// An account activity reported by a broker: a cash movement, a fee or a fill.
+///
+/// Activities are read from the broker, or from a CSV when the broker can not list them,
+/// and are imported as transactions. The ID is the one of the broker, so an activity is
+/// only imported once.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BrokerActivity {
+    /// The ID of the activity at the broker
+    pub id: String,
+
+    /// When the activity happened at the broker
+    pub date: NaiveDateTime,
+
+    /// The kind of activity
+    pub category: ActivityCategory,
+
+    /// The description of the broker, for example its own activity type
+    pub description: String,
+
+    /// The symbol of a dividend, a fee or a fill
+    pub symbol: Option<String>,
+
+    /// The money paid into the account if positive, or charged from it if negative.
+    /// For fills it is the price of the fill.
+    pub amount: Decimal,
+
+    /// The currency of the amount
+    pub currency: Currency,
+
+    /// The broker ID of the order of a fill
+    pub order_id: Option<Uuid>,
+
+    /// The quantity of a fill
+    pub quantity: Decimal,
+}
+
+/// ActivityCategory enum - the kinds of broker activities that Trust understands
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum ActivityCategory {
+    /// Money deposited into the account
+    Deposit,
+    /// Money withdrawn from the account
+    Withdrawal,
+    /// Dividend paid for a position
+    Dividend,
+    /// Interest paid on the cash
+    Interest,
+    /// Fee charged by the broker
+    Fee,
+    /// An order executed at the broker
+    Fill,
+    /// Any other activity, like splits or journal entries
+    Other,
+}
+
+impl ActivityCategory {
+    /// Returns all the activity categories
+    pub fn all() -> Vec<ActivityCategory> {
+        vec![
+            ActivityCategory::Deposit,
+            ActivityCategory::Withdrawal,
+            ActivityCategory::Dividend,
+            ActivityCategory::Interest,
+            ActivityCategory::Fee,
+            ActivityCategory::Fill,
+            ActivityCategory::Other,
+        ]
+    }
+}
+
+impl fmt::Display for ActivityCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActivityCategory::Deposit => write!(f, "deposit"),
+            ActivityCategory::Withdrawal => write!(f, "withdrawal"),
+            ActivityCategory::Dividend => write!(f, "dividend"),
+            ActivityCategory::Interest => write!(f, "interest"),
+            ActivityCategory::Fee => write!(f, "fee"),
+            ActivityCategory::Fill => write!(f, "fill"),
+            ActivityCategory::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// Error when parsing an activity category from a string
+#[derive(PartialEq, Debug)]
+pub struct ActivityCategoryParseError;
+
+impl FromStr for ActivityCategory {
+    type Err = ActivityCategoryParseError;
+
+    fn from_str(category: &str) -> Result<Self, Self::Err> {
+        ActivityCategory::all()
+            .into_iter()
+            .find(|candidate| candidate.to_string() == category)
+            .ok_or(ActivityCategoryParseError)
+    }
+}
+
+/// ImportedActivity entity - a broker activity that was already imported into an account.
+///
+/// The transaction is the one that was created for the activity, or the one that
+/// was already recorded for it. Fills that match their trade have no transaction.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ImportedActivity {
+    /// Unique identifier for the imported activity
+    pub id: Uuid,
+
+    /// When the activity was imported
+    pub created_at: NaiveDateTime,
+    /// When the imported activity was last updated
+    pub updated_at: NaiveDateTime,
+    /// When the imported activity was deleted, if applicable
+    pub deleted_at: Option<NaiveDateTime>,
+
+    /// The account that the activity was imported into
+    pub account_id: Uuid,
+
+    /// The ID of the activity at the broker
+    pub activity_id: String,
+
+    /// The transaction of the activity, if any
+    pub transaction_id: Option<Uuid>,
+}
+
+/// The result of importing the activities of an account
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct ActivityImport {
+    /// Transactions created for the new activities
+    pub transactions: Vec<Transaction>,
+
+    /// Activities that matched what Trust had already recorded
+    pub matched: Vec<BrokerActivity>,
+
+    /// Activities that were skipped because they had been imported before
+    pub duplicates: usize,
+
+    /// Activities that differ from what Trust recorded, or that Trust can not import.
+    /// They are not marked as imported, so they are checked again by the next import.
+    pub discrepancies: Vec<ActivityDiscrepancy>,
+}
+
+/// A broker activity that differs from what Trust recorded, with the difference
+#[derive(PartialEq, Debug, Clone)]
+pub struct ActivityDiscrepancy {
+    /// The activity at the broker
+    pub activity: BrokerActivity,
+    /// What is different
+    pub reason: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_category_from_str() {
+        for category in ActivityCategory::all() {
+            assert_eq!(
+                ActivityCategory::from_str(&category.to_string()),
+                Ok(category)
+            );
+        }
+        assert_eq!(
+            ActivityCategory::from_str("split"),
+            Err(ActivityCategoryParseError)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{Account, AccountBalance, Currency, Trade, TradeCategory};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+/// A trade planned by a signal, to be tested against the historical prices of its symbol.
+#[derive(PartialEq, Debug, Clone)]
+pub struct TradePlan {
+    /// Day of the signal. The orders are placed after its close.
+    pub signal_date: NaiveDate,
+
+    /// Symbol to trade, like AAPL
+    pub symbol: String,
+
+    /// Whether the trade is long or short
+    pub category: TradeCategory,
+
+    /// Limit price of the entry
+    pub entry: Decimal,
+
+    /// Price of the safety stop
+    pub stop: Decimal,
+
+    /// Limit price of the target
+    pub target: Decimal,
+
+    /// Average true range at the signal, required by volatility position sizing
+    pub atr: Option<Decimal>,
+}
+
+/// Parameters of a backtest of trade plans.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct BacktestParameters {
+    /// Capital deposited in the backtest account before the first signal
+    pub starting_capital: Decimal,
+
+    /// Currency of the capital and the trades
+    pub currency: Currency,
+}
+
+/// A trade plan that did not become a trade in the backtest.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SkippedPlan {
+    /// The trade plan
+    pub plan: TradePlan,
+
+    /// Why the plan was skipped, like the rule that rejected its funding
+    pub reason: String,
+}
+
+/// Result of a backtest of trade plans.
+///
+/// The trades went through the same sizing, funding and transactions as live trades,
+/// so the backtest account supports the same reports as a real one.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BacktestReport {
+    /// The throwaway account the trades were placed in
+    pub account: Account,
+
+    /// Balance of the account after the last bar
+    pub balance: AccountBalance,
+
+    /// Trades placed by the plans. Entries not filled by the last bar are canceled.
+    pub trades: Vec<Trade>,
+
+    /// Plans that did not become a trade
+    pub skipped: Vec<SkippedPlan>,
+
+    /// Day of the first bar
+    pub from: NaiveDate,
+
+    /// Day of the last bar
+    pub to: NaiveDate,
+}
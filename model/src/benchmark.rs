@@ -0,0 +1,64 @@
+use crate::Currency;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+/// Comparison of the returns of an account with the returns of a benchmark, like SPY,
+/// over the same period.
+///
+/// The returns of the account are time weighted: deposits, withdrawals and transfers
+/// are not counted as returns. All returns are fractions, 0.05 is a 5% return.
+#[derive(PartialEq, Debug, Clone)]
+pub struct BenchmarkComparison {
+    /// The symbol of the benchmark
+    pub symbol: String,
+
+    /// The currency of the account balance that was compared
+    pub currency: Currency,
+
+    /// The first day of the comparison
+    pub from: NaiveDate,
+
+    /// The last day of the comparison
+    pub to: NaiveDate,
+
+    /// Return of the account over the period
+    pub account_return: Decimal,
+
+    /// Return of the benchmark over the period
+    pub benchmark_return: Decimal,
+
+    /// Sensitivity of the daily returns of the account to the daily returns of the benchmark.
+    /// It is not available when the benchmark price did not change.
+    pub beta: Option<Decimal>,
+
+    /// Return of the account that is not explained by the benchmark:
+    /// account return - beta * benchmark return
+    pub alpha: Option<Decimal>,
+
+    /// Correlation between the daily returns of the account and the benchmark, from -1 to 1.
+    /// It is not available when the account or the benchmark did not change.
+    pub correlation: Option<Decimal>,
+
+    /// The equity curve of the account next to the benchmark, one point per price bar
+    pub points: Vec<EquityPoint>,
+}
+
+/// A day of the equity curve of an account compared with a benchmark
+#[derive(PartialEq, Debug, Clone)]
+pub struct EquityPoint {
+    /// The day of the point
+    pub date: NaiveDate,
+
+    /// The equity of the account at the end of the day.
+    /// Open trades are valued at the price they were opened.
+    pub equity: Decimal,
+
+    /// Return of the account from the first day until this day
+    pub account_return: Decimal,
+
+    /// The close price of the benchmark
+    pub benchmark_close: Decimal,
+
+    /// Return of the benchmark from the first day until this day
+    pub benchmark_return: Decimal,
+}
@@ -1,11 +1,18 @@
-use crate::{Account, Order, Status, Trade};
+use crate::{
+    Account, BrokerActivity, Currency, Order, OrderCategory, Status, TimeInForce, Trade,
+    TradeCategory, TradingVehicleCategory,
+};
 use chrono::NaiveDateTime;
 use rust_decimal::Decimal;
 use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
 use uuid::Uuid;
 
-/// Log entry for broker operations
-#[derive(Debug)]
+/// Log of a call to a broker: what was asked, what the broker answered,
+/// how long it took and why it failed, if it did.
+#[derive(Debug, Clone, PartialEq)]
 pub struct BrokerLog {
     /// Unique identifier for the log entry
     pub id: Uuid,
@@ -21,8 +28,16 @@ pub struct BrokerLog {
     // Entity fields
     /// ID of the trade associated with this log
     pub trade_id: Uuid,
-    /// Log message content
-    pub log: String,
+    /// Broker call that was made, like `submit_trade`
+    pub action: String,
+    /// What was sent to the broker
+    pub request: String,
+    /// What the broker answered
+    pub response: String,
+    /// Time the broker took to answer, in milliseconds
+    pub latency_ms: u64,
+    /// Error of the call, if it failed
+    pub error: Option<String>,
 }
 
 impl Default for BrokerLog {
@@ -34,23 +49,167 @@ impl Default for BrokerLog {
             updated_at: now,
             deleted_at: None,
             trade_id: Uuid::new_v4(),
-            log: String::new(),
+            action: String::new(),
+            request: String::new(),
+            response: String::new(),
+            latency_ms: 0,
+            error: None,
+        }
+    }
+}
+
+/// Error of a broker call that sent requests before it failed.
+/// It carries the log of what was sent, so the failed call can be inspected later.
+#[derive(Debug)]
+pub struct BrokerError {
+    /// What was sent to the broker, and what it answered, before the call failed
+    pub log: BrokerLog,
+    /// Why the call failed
+    pub error: AsyncBrokerError,
+}
+
+impl BrokerError {
+    /// The error of a call that sent the request for a trade
+    pub fn sent(trade_id: Uuid, request: &str, error: impl Into<AsyncBrokerError>) -> Self {
+        BrokerError {
+            log: BrokerLog {
+                trade_id,
+                request: request.to_string(),
+                ..Default::default()
+            },
+            error: error.into(),
         }
     }
 }
 
+impl fmt::Display for BrokerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl Error for BrokerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
 /// Container for order IDs associated with a trade
 #[derive(Debug)]
 pub struct OrderIds {
-    /// ID of the stop loss order
-    pub stop: Uuid,
+    /// ID of the stop loss order, None when the broker places it later
+    pub stop: Option<Uuid>,
     /// ID of the entry order
     pub entry: Uuid,
-    /// ID of the target/take profit order
-    pub target: Uuid,
+    /// ID of the target/take profit order, None when the broker places it later
+    pub target: Option<Uuid>,
+}
+
+/// Part of a filled position that was closed at the market while the rest stays open
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialClose {
+    /// Quantity that was closed
+    pub quantity: u64,
+    /// Average price at which the quantity was closed
+    pub price: Decimal,
+    /// Broker ID of the stop, resized to the quantity that stays open. None when it is not placed yet
+    pub stop: Option<Uuid>,
+    /// Broker ID of the target, resized to the quantity that stays open. None when it is not placed yet
+    pub target: Option<Uuid>,
+}
+
+/// What a broker supports, so trades that it can not execute are rejected before submission
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokerCapabilities {
+    /// Order types that the broker accepts
+    pub order_categories: Vec<OrderCategory>,
+    /// Time in force values that the broker accepts
+    pub time_in_force: Vec<TimeInForce>,
+    /// Whether the entry, the stop and the target are placed as one bracket order
+    pub bracket_orders: bool,
+    /// Whether the price and the quantity of an unfilled entry can be changed after submission
+    pub modify_entry: bool,
+    /// Whether the price of the stop can be changed after submission
+    pub modify_stop: bool,
+    /// Whether part of a filled position can be closed while the rest stays open
+    pub partial_close: bool,
+    /// Whether the price of the target can be changed after submission
+    pub modify_target: bool,
+    /// Whether quantities can have decimals
+    pub fractional: bool,
+    /// Whether short trades are accepted
+    pub short_selling: bool,
+    /// Whether orders can execute outside regular trading hours
+    pub extended_hours: bool,
+    /// Whether cryptocurrencies can be traded
+    pub crypto: bool,
+}
+
+/// The capabilities that Trust assumes of a broker that does not declare them
+impl Default for BrokerCapabilities {
+    fn default() -> Self {
+        BrokerCapabilities {
+            order_categories: vec![
+                OrderCategory::Market,
+                OrderCategory::Limit,
+                OrderCategory::Stop,
+            ],
+            time_in_force: vec![
+                TimeInForce::Day,
+                TimeInForce::UntilCanceled,
+                TimeInForce::UntilMarketOpen,
+                TimeInForce::UntilMarketClose,
+            ],
+            bracket_orders: true,
+            modify_entry: false,
+            modify_stop: true,
+            modify_target: true,
+            partial_close: false,
+            fractional: false,
+            short_selling: true,
+            extended_hours: false,
+            crypto: false,
+        }
+    }
+}
+
+/// A position or a working entry order held at a broker, with the orders that protect it.
+/// The orders have the prices, the statuses and the IDs of the broker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokerPosition {
+    /// Name of the broker that holds the position
+    pub broker: String,
+    /// Symbol of the position
+    pub symbol: String,
+    /// Kind of asset of the position
+    pub vehicle_category: TradingVehicleCategory,
+    /// Whether the position is long or short
+    pub category: TradeCategory,
+    /// Currency of the prices
+    pub currency: Currency,
+    /// Order that opened the position, or the entry order that is still working
+    pub entry: Order,
+    /// Stop order that protects the position, if any
+    pub safety_stop: Option<Order>,
+    /// Target order of the position, if any
+    pub target: Option<Order>,
+    /// Why the position can not be imported, like a fractional quantity
+    pub skip_reason: Option<String>,
+}
+
+/// A broker position that was not imported, with the reason
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedPosition {
+    /// The position at the broker
+    pub position: BrokerPosition,
+    /// Why it was not imported
+    pub reason: String,
 }
 
 /// Trait for implementing broker integrations
+///
+/// The calls block until the broker answers. Services that drive many trades at the
+/// same time should use the [`AsyncBroker`] companion trait instead.
 pub trait Broker {
     /// Submit a new trade to the broker
     fn submit_trade(
@@ -78,21 +237,140 @@ pub trait Broker {
 
     /// Cancel a trade that has been submitted
     /// The order should not be filled
-    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>>;
+    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<BrokerLog, Box<dyn Error>>;
+
+    /// Close part of a filled trade at the market.
+    /// The stop and the target are resized to the quantity that stays open.
+    fn close_partial(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _quantity: u64,
+    ) -> Result<(PartialClose, BrokerLog), Box<dyn Error>> {
+        Err("The broker can not close part of a trade".into())
+    }
+
+    /// Replace the entry of a trade that is not filled with a new price and quantity.
+    /// The stop and the target are resized to the new quantity.
+    /// The return value is the broker ID of the new entry.
+    fn modify_entry(
+        &self,
+        _trade: &Trade,
+        _account: &Account,
+        _new_price: Decimal,
+        _new_quantity: u64,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        Err("The broker can not modify the entry of a trade".into())
+    }
 
     /// Modify the stop loss price of an existing trade
+    /// The return value is the broker ID of the modified stop.
     fn modify_stop(
         &self,
         trade: &Trade,
         account: &Account,
         new_stop_price: Decimal,
-    ) -> Result<Uuid, Box<dyn Error>>;
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>>;
 
     /// Modify the target price of an existing trade
+    /// The return value is the broker ID of the modified target.
     fn modify_target(
         &self,
         trade: &Trade,
         account: &Account,
         new_price: Decimal,
-    ) -> Result<Uuid, Box<dyn Error>>;
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>>;
+
+    /// What the broker supports. Trades are validated against it before they are submitted.
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities::default()
+    }
+
+    /// Open positions and working entry orders of the account, to import them as trades
+    fn positions(&self, _account: &Account) -> Result<Vec<BrokerPosition>, Box<dyn Error>> {
+        Err("The broker can not list its positions".into())
+    }
+
+    /// Cash movements, fees and fills of the account, to import them as transactions
+    fn activities(&self, _account: &Account) -> Result<Vec<BrokerActivity>, Box<dyn Error>> {
+        Err("The broker can not list its account activities".into())
+    }
+}
+
+/// Error returned by the calls of an [`AsyncBroker`].
+/// It can be sent between threads, so the calls can run in tasks of a multi-threaded runtime.
+pub type AsyncBrokerError = Box<dyn Error + Send + Sync>;
+
+/// Future returned by the calls of an [`AsyncBroker`]
+pub type BrokerFuture<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, AsyncBrokerError>> + Send + 'a>>;
+
+/// Asynchronous companion of the [`Broker`] trait
+///
+/// The calls return futures instead of blocking, so a long-running service can
+/// drive many trades concurrently from its own runtime.
+pub trait AsyncBroker: Send + Sync {
+    /// Submit a new trade to the broker
+    fn submit_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, (BrokerLog, OrderIds)>;
+
+    /// Synchronize trade status with the broker
+    fn sync_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, (Status, Vec<Order>, BrokerLog)>;
+
+    /// Manually Close a trade
+    /// The target will be cancelled and a new target will be created
+    /// with the market price. The return value is the new target order.
+    fn close_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, (Order, BrokerLog)>;
+
+    /// Cancel a trade that has been submitted
+    /// The order should not be filled
+    fn cancel_trade<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+    ) -> BrokerFuture<'a, BrokerLog>;
+
+    /// Close part of a filled trade at the market and resize the stop and the target
+    fn close_partial<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        quantity: u64,
+    ) -> BrokerFuture<'a, (PartialClose, BrokerLog)>;
+
+    /// Replace the entry of a trade that is not filled with a new price and quantity
+    fn modify_entry<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> BrokerFuture<'a, (Uuid, BrokerLog)>;
+
+    /// Modify the stop loss price of an existing trade
+    fn modify_stop<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        new_stop_price: Decimal,
+    ) -> BrokerFuture<'a, (Uuid, BrokerLog)>;
+
+    /// Modify the target price of an existing trade
+    fn modify_target<'a>(
+        &'a self,
+        trade: &'a Trade,
+        account: &'a Account,
+        new_price: Decimal,
+    ) -> BrokerFuture<'a, (Uuid, BrokerLog)>;
 }
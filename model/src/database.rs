@@ -1,8 +1,10 @@
 use crate::{
-    Account, AccountBalance, BrokerLog, Currency, Environment, Order, OrderAction, OrderCategory,
-    Rule, RuleLevel, RuleName, Status, Trade, TradeBalance, TradeCategory, TradingVehicle,
-    TradingVehicleCategory, Transaction, TransactionCategory,
+    Account, AccountBalance, BrokerLog, Currency, Environment, FeeSchedule, ImportedActivity,
+    Order, OrderAction, OrderCategory, PositionSizing, PriceBar, Rule, RuleLevel, RuleName, Status,
+    Trade, TradeBalance, TradeCategory, TradingVehicle, TradingVehicleCategory, Transaction,
+    TransactionCategory, Transfer,
 };
+use chrono::NaiveDate;
 use rust_decimal::Decimal;
 use uuid::Uuid;
 
@@ -55,6 +57,22 @@ pub trait DatabaseFactory {
     fn log_read(&self) -> Box<dyn ReadBrokerLogsDB>;
     /// Returns a writer for broker log data operations
     fn log_write(&self) -> Box<dyn WriteBrokerLogsDB>;
+    /// Returns a reader for fee schedule data
+    fn fee_schedule_read(&self) -> Box<dyn ReadFeeScheduleDB>;
+    /// Returns a writer for fee schedule data
+    fn fee_schedule_write(&self) -> Box<dyn WriteFeeScheduleDB>;
+    /// Returns a reader for transfers between accounts
+    fn transfer_read(&self) -> Box<dyn ReadTransferDB>;
+    /// Returns a writer for transfers between accounts
+    fn transfer_write(&self) -> Box<dyn WriteTransferDB>;
+    /// Returns a reader for price series
+    fn price_read(&self) -> Box<dyn ReadPriceDB>;
+    /// Returns a writer for price series
+    fn price_write(&self) -> Box<dyn WritePriceDB>;
+    /// Returns a reader for the broker activities imported into the accounts
+    fn activity_read(&self) -> Box<dyn ReadActivityDB>;
+    /// Returns a writer for the broker activities imported into the accounts
+    fn activity_write(&self) -> Box<dyn WriteActivityDB>;
 }
 // TODO: Rename
 /// Trait for reading account data from the database
@@ -78,6 +96,20 @@ pub trait AccountWrite {
         taxes_percentage: Decimal,
         earnings_percentage: Decimal,
     ) -> Result<Account, Box<dyn Error>>;
+
+    /// Updates the position sizing model used by the account
+    fn update_position_sizing(
+        &mut self,
+        account: &Account,
+        position_sizing: PositionSizing,
+    ) -> Result<Account, Box<dyn Error>>;
+
+    /// Updates the symbol of the benchmark the account is compared with
+    fn update_benchmark(
+        &mut self,
+        account: &Account,
+        benchmark: Option<&str>,
+    ) -> Result<Account, Box<dyn Error>>;
 }
 
 /// Trait for reading account balance data from the database
@@ -110,6 +142,7 @@ pub trait AccountBalanceWrite {
         in_trade: Decimal,
         available: Decimal,
         taxed: Decimal,
+        earnings: Decimal,
     ) -> Result<AccountBalance, Box<dyn Error>>;
 }
 
@@ -117,6 +150,8 @@ pub trait AccountBalanceWrite {
 pub trait OrderRead {
     /// Retrieves an order by its ID
     fn for_id(&mut self, id: Uuid) -> Result<Order, Box<dyn Error>>;
+    /// Retrieves all orders that are not deleted
+    fn all(&mut self) -> Result<Vec<Order>, Box<dyn Error>>;
 }
 
 /// Trait for writing order data to the database
@@ -146,6 +181,8 @@ pub trait OrderWrite {
         price: Decimal,
         broker_id: Uuid,
     ) -> Result<Order, Box<dyn Error>>;
+    /// Updates the quantity of an order
+    fn update_quantity(&mut self, order: &Order, quantity: u64) -> Result<Order, Box<dyn Error>>;
 }
 
 /// Trait for reading transaction data from the database
@@ -338,8 +375,8 @@ pub trait WriteTradingVehicleDB {
 
 /// Trait for writing broker log data to the database
 pub trait WriteBrokerLogsDB {
-    /// Creates a new log entry for a trade
-    fn create_log(&mut self, log: &str, trade: &Trade) -> Result<BrokerLog, Box<dyn Error>>;
+    /// Saves the log of a broker call for a trade
+    fn create_log(&mut self, log: &BrokerLog, trade: &Trade) -> Result<BrokerLog, Box<dyn Error>>;
 }
 
 /// Trait for reading broker log data from the database
@@ -348,3 +385,81 @@ pub trait ReadBrokerLogsDB {
     fn read_all_logs_for_trade(&mut self, trade_id: Uuid)
         -> Result<Vec<BrokerLog>, Box<dyn Error>>;
 }
+
+/// Trait for reading fee schedules from the database
+pub trait ReadFeeScheduleDB {
+    /// Retrieves the active fee schedule of an account, or None if it does not have one
+    fn fee_schedule_for_account(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Option<FeeSchedule>, Box<dyn Error>>;
+}
+
+/// Trait for writing fee schedules to the database
+pub trait WriteFeeScheduleDB {
+    /// Replaces the fee schedule of an account with the given values
+    fn set_fee_schedule(
+        &mut self,
+        account: &Account,
+        schedule: &FeeSchedule,
+    ) -> Result<FeeSchedule, Box<dyn Error>>;
+}
+
+/// Trait for reading transfers between accounts from the database
+pub trait ReadTransferDB {
+    /// Retrieves all the transfers sent or received by an account
+    fn all_transfers(&mut self, account_id: Uuid) -> Result<Vec<Transfer>, Box<dyn Error>>;
+}
+
+/// Trait for writing transfers between accounts to the database
+pub trait WriteTransferDB {
+    /// Creates the `TransferOut` and `TransferIn` transactions and the transfer that links them.
+    /// Either everything is created or nothing is.
+    fn create_transfer(
+        &mut self,
+        from: &Account,
+        amount: Decimal,
+        currency: &Currency,
+        to: &Account,
+        converted_amount: Decimal,
+        converted_currency: &Currency,
+    ) -> Result<Transfer, Box<dyn Error>>;
+}
+
+/// Trait for reading price series from the database
+pub trait ReadPriceDB {
+    /// Retrieves the bars of a symbol between two days, both included, ordered by date
+    fn bars(
+        &mut self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<PriceBar>, Box<dyn Error>>;
+}
+
+/// Trait for writing price series to the database
+pub trait WritePriceDB {
+    /// Saves the bars, replacing any existing bar of the same symbol and day.
+    /// Either all the bars are saved or none is.
+    fn save_bars(&mut self, bars: &[PriceBar]) -> Result<Vec<PriceBar>, Box<dyn Error>>;
+}
+
+/// Trait for reading the broker activities imported into an account
+pub trait ReadActivityDB {
+    /// Retrieves all the activities imported into an account
+    fn imported_activities(
+        &mut self,
+        account_id: Uuid,
+    ) -> Result<Vec<ImportedActivity>, Box<dyn Error>>;
+}
+
+/// Trait for recording the broker activities imported into an account
+pub trait WriteActivityDB {
+    /// Records that an activity was imported, with the transaction created or matched for it
+    fn create_imported_activity(
+        &mut self,
+        account: &Account,
+        activity_id: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<ImportedActivity, Box<dyn Error>>;
+}
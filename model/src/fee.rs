@@ -0,0 +1,95 @@
+use chrono::NaiveDateTime;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::fmt::{self, Display, Formatter};
+use uuid::Uuid;
+
+/// FeeSchedule entity - represents the commissions and fees that a broker charges to an account.
+///
+/// The schedule is used to calculate the fees of an order automatically when a trade
+/// is filled or closed, and to estimate the fees of a trade before it is funded.
+///
+/// For example, a broker charging 0.005 per share with a minimum of 1 per order:
+///
+/// 1. Buying 100 shares costs max(100 * 0.005, 1) = 1.
+/// 2. Buying 1000 shares costs max(1000 * 0.005, 1) = 5.
+///
+/// Regulatory fees (SEC and FINRA TAF) are only charged on sell orders.
+#[derive(PartialEq, Debug, Clone)]
+pub struct FeeSchedule {
+    /// Unique identifier for the fee schedule
+    pub id: Uuid,
+
+    /// When the fee schedule was created
+    pub created_at: NaiveDateTime,
+    /// When the fee schedule was last updated
+    pub updated_at: NaiveDateTime,
+    /// When the fee schedule was deleted, if applicable
+    pub deleted_at: Option<NaiveDateTime>,
+
+    /// The account that the fee schedule is associated with
+    pub account_id: Uuid,
+
+    /// Commission charged for each share of an order
+    pub per_share: Decimal,
+
+    /// Commission charged for each order
+    pub per_order: Decimal,
+
+    /// Commission charged as a percentage of the value of an order
+    pub percentage: Decimal,
+
+    /// Minimum commission charged for an order
+    pub minimum: Decimal,
+
+    /// Maximum commission charged for an order, if any
+    pub maximum: Option<Decimal>,
+
+    /// SEC fee rate applied to the value of sell orders. For example: 0.0000278
+    pub sec_fee_rate: Decimal,
+
+    /// FINRA trading activity fee charged for each share sold. For example: 0.000166
+    pub taf_per_share: Decimal,
+
+    /// Maximum FINRA trading activity fee charged for an order. For example: 8.30
+    pub taf_maximum: Decimal,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        FeeSchedule {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            account_id: Uuid::new_v4(),
+            per_share: Decimal::default(),
+            per_order: Decimal::default(),
+            percentage: Decimal::default(),
+            minimum: Decimal::default(),
+            maximum: None,
+            sec_fee_rate: Decimal::default(),
+            taf_per_share: Decimal::default(),
+            taf_maximum: Decimal::default(),
+        }
+    }
+}
+
+impl Display for FeeSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "per share: {}, per order: {}, percentage: {}%, minimum: {}",
+            self.per_share, self.per_order, self.percentage, self.minimum
+        )?;
+        if let Some(maximum) = self.maximum {
+            write!(f, ", maximum: {maximum}")?;
+        }
+        write!(
+            f,
+            ", SEC rate: {}, TAF per share: {} (max {})",
+            self.sec_fee_rate, self.taf_per_share, self.taf_maximum
+        )
+    }
+}
@@ -0,0 +1,77 @@
+use crate::Currency;
+use std::fmt::{self, Display, Formatter};
+use uuid::Uuid;
+
+/// Inconsistency entity - a problem found when checking the data of an account.
+///
+/// Account and trade balances are caches of the transactions, so they can be
+/// recalculated and repaired. Other inconsistencies are only reported because
+/// they require a decision from the user.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Inconsistency {
+    /// The kind of inconsistency
+    pub category: InconsistencyCategory,
+
+    /// The account where the inconsistency was found, if it is known
+    pub account_id: Option<Uuid>,
+
+    /// The entity that is inconsistent - an account, trade, order or transaction
+    pub entity_id: Uuid,
+
+    /// The currency of the inconsistent balance, if any
+    pub currency: Option<Currency>,
+
+    /// A description of the problem
+    pub message: String,
+}
+
+/// The kind of problem found in an account
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum InconsistencyCategory {
+    /// The cached account balance is different from the one calculated from transactions
+    AccountBalance,
+    /// The cached trade balance is different from the one calculated from transactions
+    TradeBalance,
+    /// The status of a trade does not agree with the status of its orders
+    TradeStatus,
+    /// An order that does not belong to any trade
+    OrphanOrder,
+    /// A transaction that references a trade that does not exist or belongs to another account
+    OrphanTransaction,
+}
+
+impl InconsistencyCategory {
+    /// Returns true if the inconsistency can be repaired by recalculating a cache
+    pub fn is_repairable(&self) -> bool {
+        matches!(
+            self,
+            InconsistencyCategory::AccountBalance | InconsistencyCategory::TradeBalance
+        )
+    }
+}
+
+impl Display for InconsistencyCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InconsistencyCategory::AccountBalance => write!(f, "account_balance"),
+            InconsistencyCategory::TradeBalance => write!(f, "trade_balance"),
+            InconsistencyCategory::TradeStatus => write!(f, "trade_status"),
+            InconsistencyCategory::OrphanOrder => write!(f, "orphan_order"),
+            InconsistencyCategory::OrphanTransaction => write!(f, "orphan_transaction"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_repairable() {
+        assert!(InconsistencyCategory::AccountBalance.is_repairable());
+        assert!(InconsistencyCategory::TradeBalance.is_repairable());
+        assert!(!InconsistencyCategory::TradeStatus.is_repairable());
+        assert!(!InconsistencyCategory::OrphanOrder.is_repairable());
+        assert!(!InconsistencyCategory::OrphanTransaction.is_repairable());
+    }
+}
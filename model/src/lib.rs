@@ -32,16 +32,32 @@
 
 /// Account management types and functionality
 pub mod account;
+/// Account activities reported by brokers and their import
+pub mod activity;
+/// Backtests of trade plans against historical prices
+pub mod backtest;
+/// Comparison of account returns with a benchmark
+pub mod benchmark;
 /// Broker integration traits and types
 pub mod broker;
 /// Currency definitions and operations
 pub mod currency;
 /// Database abstraction layer
 pub mod database;
+/// Commission and fee schedules
+pub mod fee;
+/// Inconsistencies found when checking the data of an account
+pub mod inconsistency;
 /// Order types and order management
 pub mod order;
+/// Daily price series of instruments
+pub mod price;
 /// Risk management rules and enforcement
 pub mod rule;
+/// Monte Carlo simulations of the future equity of an account
+pub mod simulation;
+/// Slippage of filled orders and its aggregation
+pub mod slippage;
 /// Trading strategy definitions
 pub mod strategy;
 /// Trade lifecycle and management
@@ -50,20 +66,37 @@ pub mod trade;
 pub mod trading_vehicle;
 /// Transaction tracking and accounting
 pub mod transaction;
+/// Transfers of money between accounts
+pub mod transfer;
 
 // Re-export the types from the model crate.
-pub use account::{Account, AccountBalance, Environment};
-pub use broker::{Broker, BrokerLog, OrderIds};
+pub use account::{Account, AccountBalance, Environment, OpenRisk, PositionSizing};
+pub use activity::{
+    ActivityCategory, ActivityDiscrepancy, ActivityImport, BrokerActivity, ImportedActivity,
+};
+pub use backtest::{BacktestParameters, BacktestReport, SkippedPlan, TradePlan};
+pub use benchmark::{BenchmarkComparison, EquityPoint};
+pub use broker::{
+    AsyncBroker, AsyncBrokerError, Broker, BrokerCapabilities, BrokerError, BrokerFuture,
+    BrokerLog, BrokerPosition, OrderIds, PartialClose, SkippedPosition,
+};
 pub use currency::Currency;
 pub use database::{
     AccountBalanceRead, AccountBalanceWrite, AccountRead, AccountWrite, DatabaseFactory,
-    DraftTrade, OrderRead, OrderWrite, ReadBrokerLogsDB, ReadRuleDB, ReadTradeDB,
-    ReadTradingVehicleDB, ReadTransactionDB, WriteBrokerLogsDB, WriteRuleDB, WriteTradeDB,
-    WriteTradingVehicleDB, WriteTransactionDB,
+    DraftTrade, OrderRead, OrderWrite, ReadActivityDB, ReadBrokerLogsDB, ReadFeeScheduleDB,
+    ReadPriceDB, ReadRuleDB, ReadTradeDB, ReadTradingVehicleDB, ReadTransactionDB, ReadTransferDB,
+    WriteActivityDB, WriteBrokerLogsDB, WriteFeeScheduleDB, WritePriceDB, WriteRuleDB,
+    WriteTradeDB, WriteTradingVehicleDB, WriteTransactionDB, WriteTransferDB,
 };
+pub use fee::FeeSchedule;
+pub use inconsistency::{Inconsistency, InconsistencyCategory};
 pub use order::{Order, OrderAction, OrderCategory, OrderStatus, TimeInForce};
+pub use price::PriceBar;
 pub use rule::{Rule, RuleLevel, RuleName};
+pub use simulation::{EquityPercentile, Simulation, SimulationParameters};
+pub use slippage::{OrderSlippage, SlippageGrouping, SlippageLeg, SlippageSummary};
 pub use strategy::Strategy;
 pub use trade::{Status, Trade, TradeBalance, TradeCategory};
 pub use trading_vehicle::{TradingVehicle, TradingVehicleCategory};
 pub use transaction::{Transaction, TransactionCategory};
+pub use transfer::Transfer;
@@ -0,0 +1,60 @@
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// PriceBar entity - the open, high, low and close prices of a symbol for one day.
+///
+/// Price bars are imported from CSV files and are used as the price series of the
+/// benchmarks that the accounts are compared with. There is one bar per symbol and date.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PriceBar {
+    /// Unique identifier for the price bar
+    pub id: Uuid,
+
+    /// When the price bar was created
+    pub created_at: NaiveDateTime,
+    /// When the price bar was last updated
+    pub updated_at: NaiveDateTime,
+    /// When the price bar was deleted, if applicable
+    pub deleted_at: Option<NaiveDateTime>,
+
+    /// The symbol of the instrument, for example SPY
+    pub symbol: String,
+
+    /// The trading day of the bar
+    pub date: NaiveDate,
+
+    /// The first price of the day
+    pub open: Decimal,
+
+    /// The highest price of the day
+    pub high: Decimal,
+
+    /// The lowest price of the day
+    pub low: Decimal,
+
+    /// The last price of the day
+    pub close: Decimal,
+
+    /// The traded volume of the day
+    pub volume: Decimal,
+}
+
+impl Default for PriceBar {
+    fn default() -> Self {
+        let now = Utc::now().naive_utc();
+        PriceBar {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            symbol: "".to_string(),
+            date: now.date(),
+            open: Decimal::default(),
+            high: Decimal::default(),
+            low: Decimal::default(),
+            close: Decimal::default(),
+            volume: Decimal::default(),
+        }
+    }
+}
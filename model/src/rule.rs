@@ -65,6 +65,17 @@ pub enum RuleName {
     ///
     /// It is recommended not to set this rule to more than 6% of the account.
     RiskPerMonth(f32),
+
+    /// The maximum open risk defined in percentage
+    /// This rule is used to limit the money at risk in all the open trades at the same time
+    /// If funding a trade would put more than this percentage of the account at risk, the trade will not be funded.
+    /// The open risk is calculated as the amount of money that would be lost if every open stop was hit.
+    /// For example:
+    ///
+    /// 1. If your account is 50_000, and the maximum open risk is 6% of the account, then the maximum open risk is 3000.
+    /// 2. If you have two open trades risking 1000 each, a new trade can risk up to 1000.
+    /// 3. Trades whose stop was moved past breakeven do not count.
+    MaxOpenRisk(f32),
 }
 
 // Implementations
@@ -80,6 +91,7 @@ impl fmt::Display for RuleName {
         match self {
             RuleName::RiskPerTrade(_) => write!(f, "risk_per_trade"),
             RuleName::RiskPerMonth(_) => write!(f, "risk_per_month"),
+            RuleName::MaxOpenRisk(_) => write!(f, "max_open_risk"),
         }
     }
 }
@@ -87,7 +99,11 @@ impl fmt::Display for RuleName {
 impl RuleName {
     /// Returns all possible rule name types with default values
     pub fn all() -> Vec<RuleName> {
-        vec![RuleName::RiskPerTrade(0.0), RuleName::RiskPerMonth(0.0)]
+        vec![
+            RuleName::RiskPerTrade(0.0),
+            RuleName::RiskPerMonth(0.0),
+            RuleName::MaxOpenRisk(0.0),
+        ]
     }
 }
 
@@ -97,6 +113,7 @@ impl RuleName {
         match self {
             RuleName::RiskPerTrade(value) => *value,
             RuleName::RiskPerMonth(value) => *value,
+            RuleName::MaxOpenRisk(value) => *value,
         }
     }
 }
@@ -111,6 +128,7 @@ impl RuleName {
         match s {
             "risk_per_trade" => Ok(RuleName::RiskPerTrade(risk)),
             "risk_per_month" => Ok(RuleName::RiskPerMonth(risk)),
+            "max_open_risk" => Ok(RuleName::MaxOpenRisk(risk)),
             _ => Err(RuleNameParseError),
         }
     }
@@ -172,6 +190,8 @@ mod tests {
         assert_eq!(result, Ok(RuleName::RiskPerTrade(2.0)));
         let result = RuleName::parse("risk_per_month", 2.0);
         assert_eq!(result, Ok(RuleName::RiskPerMonth(2.0)));
+        let result = RuleName::parse("max_open_risk", 6.0);
+        assert_eq!(result, Ok(RuleName::MaxOpenRisk(6.0)));
         let result = RuleName::parse("invalid", 0.0);
         assert_eq!(result, Err(RuleNameParseError));
     }
@@ -0,0 +1,86 @@
+use crate::Currency;
+use rust_decimal::Decimal;
+
+/// Parameters of a Monte Carlo simulation of the future equity of an account.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct SimulationParameters {
+    /// Number of randomized trade sequences to run
+    pub paths: u32,
+
+    /// Number of trades in each sequence
+    pub trades: u32,
+
+    /// Drawdown from the highest equity, in percentage, whose probability is reported
+    pub drawdown_percentage: Decimal,
+
+    /// Loss of the starting equity, in percentage, that is considered ruin
+    pub ruin_percentage: Decimal,
+
+    /// Seed of the random sequences. The same seed returns the same simulation.
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulationParameters {
+    fn default() -> Self {
+        SimulationParameters {
+            paths: 10_000,
+            trades: 100,
+            drawdown_percentage: Decimal::from(20),
+            ruin_percentage: Decimal::from(50),
+            seed: None,
+        }
+    }
+}
+
+/// Result of a Monte Carlo simulation of the future equity of an account.
+///
+/// Each path draws trades at random from the R-multiples of the closed trades of the
+/// account, and sizes them with the `RiskPerTrade` and `RiskPerMonth` rules of the account.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Simulation {
+    /// The currency of the simulated equity
+    pub currency: Currency,
+
+    /// The equity that every path starts with
+    pub starting_equity: Decimal,
+
+    /// Percentage of the equity risked in each trade
+    pub risk_per_trade: Decimal,
+
+    /// Percentage of the equity at the beginning of a month that can be lost in that month
+    pub risk_per_month: Option<Decimal>,
+
+    /// Number of trades in a month, from the history of the account
+    pub trades_per_month: u32,
+
+    /// Number of closed trades the R-multiples come from
+    pub sample_size: usize,
+
+    /// Average R-multiple of the closed trades
+    pub expectancy: Decimal,
+
+    /// The parameters of the simulation
+    pub parameters: SimulationParameters,
+
+    /// Final equity at the 5th, 25th, 50th, 75th and 95th percentiles
+    pub percentiles: Vec<EquityPercentile>,
+
+    /// Median of the largest drawdown of each path, in percentage
+    pub median_max_drawdown: Decimal,
+
+    /// Probability, from 0 to 1, of a drawdown of at least `drawdown_percentage`
+    pub drawdown_probability: Decimal,
+
+    /// Probability, from 0 to 1, of losing at least `ruin_percentage` of the starting equity
+    pub ruin_probability: Decimal,
+}
+
+/// The final equity of the simulated paths at a percentile
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct EquityPercentile {
+    /// The percentile, from 0 to 100
+    pub percentile: u32,
+
+    /// The final equity at the percentile
+    pub equity: Decimal,
+}
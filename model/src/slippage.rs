@@ -0,0 +1,122 @@
+use crate::{Currency, OrderCategory};
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use std::fmt::{self, Display, Formatter};
+use uuid::Uuid;
+
+/// Slippage of a filled order - the difference between the price that was planned
+/// and the average price at which the order was filled.
+///
+/// Slippage is positive when the fill was worse than planned and negative when it was better.
+/// For example, a stop at 38 of a long trade filled at 37.5 has a slippage of 0.5 per share.
+#[derive(PartialEq, Debug, Clone)]
+pub struct OrderSlippage {
+    /// The trade that the order belongs to
+    pub trade_id: Uuid,
+
+    /// The leg of the trade - entry, stop or target
+    pub leg: SlippageLeg,
+
+    /// The symbol of the trading vehicle
+    pub symbol: String,
+
+    /// The category of the order - market, limit or stop
+    pub category: OrderCategory,
+
+    /// The currency of the order
+    pub currency: Currency,
+
+    /// When the order was filled, if known
+    pub filled_at: Option<NaiveDateTime>,
+
+    /// The price at which the order was planned
+    pub planned_price: Decimal,
+
+    /// The average price at which the order was filled
+    pub filled_price: Decimal,
+
+    /// The quantity that was filled
+    pub quantity: u64,
+
+    /// Slippage of the whole order in the currency of the trade
+    pub amount: Decimal,
+
+    /// Slippage in basis points of the planned price
+    pub bps: Decimal,
+}
+
+/// The leg of a trade that an order slippage belongs to
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SlippageLeg {
+    /// The order that opens the trade
+    Entry,
+    /// The safety stop of the trade
+    Stop,
+    /// The target of the trade, or the order that closed it manually
+    Target,
+}
+
+impl Display for SlippageLeg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlippageLeg::Entry => write!(f, "entry"),
+            SlippageLeg::Stop => write!(f, "stop"),
+            SlippageLeg::Target => write!(f, "target"),
+        }
+    }
+}
+
+/// How slippage is aggregated in a report
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SlippageGrouping {
+    /// By leg of the trade - entry, stop or target
+    Leg,
+    /// By symbol of the trading vehicle
+    Symbol,
+    /// By category of the order - market, limit or stop
+    OrderType,
+    /// By hour of the day (UTC) in which the order was filled
+    HourOfDay,
+}
+
+impl SlippageGrouping {
+    /// Returns all the available groupings
+    pub fn all() -> Vec<SlippageGrouping> {
+        vec![
+            SlippageGrouping::Leg,
+            SlippageGrouping::Symbol,
+            SlippageGrouping::OrderType,
+            SlippageGrouping::HourOfDay,
+        ]
+    }
+}
+
+impl Display for SlippageGrouping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlippageGrouping::Leg => write!(f, "leg"),
+            SlippageGrouping::Symbol => write!(f, "symbol"),
+            SlippageGrouping::OrderType => write!(f, "order type"),
+            SlippageGrouping::HourOfDay => write!(f, "hour of day"),
+        }
+    }
+}
+
+/// Aggregated slippage of a group of orders
+#[derive(PartialEq, Debug, Clone)]
+pub struct SlippageSummary {
+    /// The value that identifies the group. For example: "TSLA" or "14:00"
+    pub group: String,
+
+    /// Number of orders in the group
+    pub orders: u64,
+
+    /// Total slippage of all the orders in the currency of the trades
+    pub total_amount: Decimal,
+
+    /// Average slippage in basis points
+    pub average_bps: Decimal,
+
+    /// Worst slippage in basis points
+    pub worst_bps: Decimal,
+}
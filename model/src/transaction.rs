@@ -66,6 +66,16 @@ pub enum TransactionCategory {
     /// The Uuid is the trade ID.
     CloseSafetyStopSlippage(Uuid),
 
+    /// Money transferred from the market into a trade when part of the position is closed
+    /// and the rest stays open.
+    /// The Uuid is the trade ID.
+    ClosePartial(Uuid),
+
+    /// The cost of the quantity closed by a ClosePartial, which leaves the capital in the market.
+    /// It only records the cost basis, the money of the close is the ClosePartial.
+    /// The Uuid is the trade ID.
+    ClosePartialCost(Uuid),
+
     /// Money transferred from a trade to the broker as a fee to open the trade.
     /// The Uuid is the trade ID.
     FeeOpen(Uuid),
@@ -88,6 +98,27 @@ pub enum TransactionCategory {
 
     /// Money transferred out an account to enjoy earnings.
     WithdrawalEarnings,
+
+    /// Money paid into the account as a dividend of a long position.
+    /// The Uuid is the trade ID.
+    Dividend(Uuid),
+
+    /// Money charged to the account to borrow the shares or margin of a short position.
+    /// The Uuid is the trade ID.
+    BorrowFee(Uuid),
+
+    /// Money paid into the account as interest on the cash.
+    Interest,
+
+    /// Money of the interest set aside to be paid to the tax authorities.
+    /// It is the account counterpart of PaymentTax, as the interest does not belong to a trade.
+    InterestTax,
+
+    /// Money transferred out of the account into another account.
+    TransferOut,
+
+    /// Money transferred into the account from another account.
+    TransferIn,
 }
 
 impl TransactionCategory {
@@ -102,12 +133,20 @@ impl TransactionCategory {
             TransactionCategory::CloseTarget(id) => Some(*id),
             TransactionCategory::CloseSafetyStop(id) => Some(*id),
             TransactionCategory::CloseSafetyStopSlippage(id) => Some(*id),
+            TransactionCategory::ClosePartial(id) => Some(*id),
+            TransactionCategory::ClosePartialCost(id) => Some(*id),
             TransactionCategory::FeeOpen(id) => Some(*id),
             TransactionCategory::FeeClose(id) => Some(*id),
             TransactionCategory::PaymentEarnings(id) => Some(*id),
             TransactionCategory::WithdrawalEarnings => None,
             TransactionCategory::PaymentTax(id) => Some(*id),
             TransactionCategory::WithdrawalTax => None,
+            TransactionCategory::Dividend(id) => Some(*id),
+            TransactionCategory::BorrowFee(id) => Some(*id),
+            TransactionCategory::Interest => None,
+            TransactionCategory::InterestTax => None,
+            TransactionCategory::TransferOut => None,
+            TransactionCategory::TransferIn => None,
         }
     }
 
@@ -122,12 +161,20 @@ impl TransactionCategory {
             TransactionCategory::CloseTarget(_) => "close_target",
             TransactionCategory::CloseSafetyStop(_) => "close_safety_stop",
             TransactionCategory::CloseSafetyStopSlippage(_) => "close_safety_stop_slippage",
+            TransactionCategory::ClosePartial(_) => "close_partial",
+            TransactionCategory::ClosePartialCost(_) => "close_partial_cost",
             TransactionCategory::FeeOpen(_) => "fee_open",
             TransactionCategory::FeeClose(_) => "fee_close",
             TransactionCategory::PaymentEarnings(_) => "payment_earnings",
             TransactionCategory::WithdrawalEarnings => "withdrawal_earnings",
             TransactionCategory::PaymentTax(_) => "payment_tax",
             TransactionCategory::WithdrawalTax => "withdrawal_tax",
+            TransactionCategory::Dividend(_) => "dividend",
+            TransactionCategory::BorrowFee(_) => "borrow_fee",
+            TransactionCategory::Interest => "interest",
+            TransactionCategory::InterestTax => "interest_tax",
+            TransactionCategory::TransferOut => "transfer_out",
+            TransactionCategory::TransferIn => "transfer_in",
         }
     }
 }
@@ -147,12 +194,20 @@ impl std::fmt::Display for TransactionCategory {
             TransactionCategory::CloseSafetyStopSlippage(_) => {
                 write!(f, "close_safety_stop_slippage")
             }
+            TransactionCategory::ClosePartial(_) => write!(f, "close_partial"),
+            TransactionCategory::ClosePartialCost(_) => write!(f, "close_partial_cost"),
             TransactionCategory::FeeOpen(_) => write!(f, "fee_open"),
             TransactionCategory::FeeClose(_) => write!(f, "fee_close"),
             TransactionCategory::PaymentEarnings(_) => write!(f, "payment_earnings"),
             TransactionCategory::WithdrawalEarnings => write!(f, "withdrawal_earnings"),
             TransactionCategory::PaymentTax(_) => write!(f, "payment_tax"),
             TransactionCategory::WithdrawalTax => write!(f, "withdrawal_tax"),
+            TransactionCategory::Dividend(_) => write!(f, "dividend"),
+            TransactionCategory::BorrowFee(_) => write!(f, "borrow_fee"),
+            TransactionCategory::Interest => write!(f, "interest"),
+            TransactionCategory::InterestTax => write!(f, "interest_tax"),
+            TransactionCategory::TransferOut => write!(f, "transfer_out"),
+            TransactionCategory::TransferIn => write!(f, "transfer_in"),
         }
     }
 }
@@ -188,70 +243,38 @@ impl TransactionCategory {
         match s {
             "deposit" => Ok(TransactionCategory::Deposit),
             "withdrawal" => Ok(TransactionCategory::Withdrawal),
-            "payment_tax" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::PaymentTax(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
-            "payment_from_trade" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::PaymentFromTrade(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
-            "fund_trade" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::FundTrade(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
             "withdrawal_tax" => Ok(TransactionCategory::WithdrawalTax),
-            "open_trade" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::OpenTrade(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
-            "close_target" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::CloseTarget(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
-            "close_safety_stop" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::CloseSafetyStop(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
+            "withdrawal_earnings" => Ok(TransactionCategory::WithdrawalEarnings),
+            "interest" => Ok(TransactionCategory::Interest),
+            "interest_tax" => Ok(TransactionCategory::InterestTax),
+            "transfer_out" => Ok(TransactionCategory::TransferOut),
+            "transfer_in" => Ok(TransactionCategory::TransferIn),
+            _ => {
+                // All the other categories belong to a trade
+                let trade_id = trade_id.ok_or(TransactionCategoryParseError)?;
+                TransactionCategory::parse_trade(s, trade_id)
             }
+        }
+    }
+
+    fn parse_trade(s: &str, trade_id: Uuid) -> Result<Self, TransactionCategoryParseError> {
+        match s {
+            "payment_tax" => Ok(TransactionCategory::PaymentTax(trade_id)),
+            "payment_earnings" => Ok(TransactionCategory::PaymentEarnings(trade_id)),
+            "payment_from_trade" => Ok(TransactionCategory::PaymentFromTrade(trade_id)),
+            "fund_trade" => Ok(TransactionCategory::FundTrade(trade_id)),
+            "open_trade" => Ok(TransactionCategory::OpenTrade(trade_id)),
+            "close_target" => Ok(TransactionCategory::CloseTarget(trade_id)),
+            "close_safety_stop" => Ok(TransactionCategory::CloseSafetyStop(trade_id)),
             "close_safety_stop_slippage" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::CloseSafetyStopSlippage(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
-            "fee_open" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::FeeOpen(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
-            }
-            "fee_close" => {
-                if let Some(trade_id) = trade_id {
-                    Ok(TransactionCategory::FeeClose(trade_id))
-                } else {
-                    Err(TransactionCategoryParseError)
-                }
+                Ok(TransactionCategory::CloseSafetyStopSlippage(trade_id))
             }
+            "close_partial" => Ok(TransactionCategory::ClosePartial(trade_id)),
+            "close_partial_cost" => Ok(TransactionCategory::ClosePartialCost(trade_id)),
+            "fee_open" => Ok(TransactionCategory::FeeOpen(trade_id)),
+            "fee_close" => Ok(TransactionCategory::FeeClose(trade_id)),
+            "dividend" => Ok(TransactionCategory::Dividend(trade_id)),
+            "borrow_fee" => Ok(TransactionCategory::BorrowFee(trade_id)),
             _ => Err(TransactionCategoryParseError),
         }
     }
@@ -306,6 +329,58 @@ mod tests {
         assert_eq!(result, TransactionCategory::PaymentTax(id));
     }
 
+    #[test]
+    fn test_transaction_category_from_string_payment_earnings() {
+        let id = Uuid::new_v4();
+        let result = TransactionCategory::parse("payment_earnings", Some(id))
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::PaymentEarnings(id));
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_withdrawal_earnings() {
+        let result = TransactionCategory::parse("withdrawal_earnings", None)
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::WithdrawalEarnings);
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_income() {
+        let id = Uuid::new_v4();
+        assert_eq!(
+            TransactionCategory::parse("dividend", Some(id)),
+            Ok(TransactionCategory::Dividend(id))
+        );
+        assert_eq!(
+            TransactionCategory::parse("borrow_fee", Some(id)),
+            Ok(TransactionCategory::BorrowFee(id))
+        );
+        assert_eq!(
+            TransactionCategory::parse("interest", None),
+            Ok(TransactionCategory::Interest)
+        );
+        assert_eq!(
+            TransactionCategory::parse("interest_tax", None),
+            Ok(TransactionCategory::InterestTax)
+        );
+        assert_eq!(
+            TransactionCategory::parse("dividend", None),
+            Err(TransactionCategoryParseError)
+        );
+    }
+
+    #[test]
+    fn test_transaction_category_from_string_transfer() {
+        assert_eq!(
+            TransactionCategory::parse("transfer_out", None),
+            Ok(TransactionCategory::TransferOut)
+        );
+        assert_eq!(
+            TransactionCategory::parse("transfer_in", None),
+            Ok(TransactionCategory::TransferIn)
+        );
+    }
+
     #[test]
     fn test_transaction_category_from_string_fee_open() {
         let id = Uuid::new_v4();
@@ -322,6 +397,20 @@ mod tests {
         assert_eq!(result, TransactionCategory::FeeClose(id));
     }
 
+    #[test]
+    fn test_transaction_category_from_string_close_partial() {
+        let id = Uuid::new_v4();
+        let result = TransactionCategory::parse("close_partial", Some(id))
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::ClosePartial(id));
+        assert_eq!(result.to_string(), "close_partial");
+
+        let result = TransactionCategory::parse("close_partial_cost", Some(id))
+            .expect("Failed to parse TransactionCategory from string");
+        assert_eq!(result, TransactionCategory::ClosePartialCost(id));
+        assert_eq!(result.to_string(), "close_partial_cost");
+    }
+
     #[test]
     fn test_transaction_category_from_invalid_string() {
         TransactionCategory::parse("Invalid", None)
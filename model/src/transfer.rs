@@ -0,0 +1,57 @@
+use crate::Currency;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// Transfer entity - represents money moved from one account to another.
+///
+/// A transfer is recorded as two transactions: a `TransferOut` in the account
+/// that sends the money and a `TransferIn` in the account that receives it.
+/// The transfer links both transactions so they can be traced to each other.
+///
+/// When the currencies of the accounts are different, the amount received is
+/// the amount sent multiplied by the exchange rate.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Transfer {
+    /// Unique identifier for the transfer
+    pub id: Uuid,
+
+    /// When the transfer was created
+    pub created_at: NaiveDateTime,
+    /// When the transfer was last updated
+    pub updated_at: NaiveDateTime,
+    /// When the transfer was deleted, if applicable
+    pub deleted_at: Option<NaiveDateTime>,
+
+    /// The account that sends the money
+    pub from_account_id: Uuid,
+
+    /// The `TransferOut` transaction in the account that sends the money
+    pub from_transaction_id: Uuid,
+
+    /// The amount sent
+    pub amount: Decimal,
+
+    /// The currency of the amount sent
+    pub currency: Currency,
+
+    /// The account that receives the money
+    pub to_account_id: Uuid,
+
+    /// The `TransferIn` transaction in the account that receives the money
+    pub to_transaction_id: Uuid,
+
+    /// The amount received
+    pub converted_amount: Decimal,
+
+    /// The currency of the amount received
+    pub converted_currency: Currency,
+}
+
+impl Transfer {
+    /// Returns the exchange rate from the currency sent to the currency received.
+    /// It is 1 for transfers in the same currency.
+    pub fn rate(&self) -> Option<Decimal> {
+        self.converted_amount.checked_div(self.amount)
+    }
+}
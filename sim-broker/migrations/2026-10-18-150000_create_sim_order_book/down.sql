@@ -0,0 +1,2 @@
+DROP TABLE sim_prices;
+DROP TABLE sim_orders;
@@ -0,0 +1,26 @@
+CREATE TABLE sim_orders (
+	id 			TEXT NOT NULL PRIMARY KEY,
+	created_at		DATETIME NOT NULL,
+	updated_at		DATETIME NOT NULL,
+	trade_id		TEXT NOT NULL,
+	order_id		TEXT NOT NULL,
+	symbol			TEXT NOT NULL,
+	leg			TEXT CHECK(leg IN ('entry', 'stop', 'target')) NOT NULL,
+	category		TEXT CHECK(category IN ('market', 'limit', 'stop')) NOT NULL,
+	action			TEXT CHECK(action IN ('buy', 'sell', 'short')) NOT NULL,
+	price			TEXT NOT NULL,
+	quantity		INTEGER NOT NULL,
+	status			TEXT CHECK(status IN ('held', 'new', 'filled', 'canceled')) NOT NULL,
+	filled_price		TEXT,
+	filled_at		DATETIME,
+	cancelled_at		DATETIME
+);
+
+CREATE INDEX sim_orders_trade_id ON sim_orders(trade_id);
+CREATE INDEX sim_orders_symbol_status ON sim_orders(symbol, status);
+
+CREATE TABLE sim_prices (
+	symbol			TEXT NOT NULL PRIMARY KEY,
+	price			TEXT NOT NULL,
+	updated_at		DATETIME NOT NULL
+);
@@ -0,0 +1,500 @@
+//! Trust Simulated Broker
+//!
+//! This crate provides a local broker for paper trading without network access
+//! or broker credentials. Orders rest in an order book stored in SQLite and they
+//! are filled against the price ticks that are fed into the broker.
+
+// === FINANCIAL APPLICATION SAFETY LINTS ===
+// These lint rules are critical for financial applications where precision,
+// safety, and reliability are paramount. Violations can lead to financial losses.
+
+#![deny(
+    // Error handling safety - force proper error handling
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+
+    // Financial precision safety - prevent calculation errors
+    clippy::float_arithmetic,
+    clippy::arithmetic_side_effects,
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+
+    // Code quality enforcement
+    clippy::cognitive_complexity,
+    clippy::too_many_lines,
+)]
+// Allow unwrap and expect in test code only
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
+// Standard Rust lints for code quality
+#![warn(missing_docs, rust_2018_idioms, missing_debug_implementations)]
+
+use diesel::prelude::*;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use model::{
+    Account, Broker, BrokerCapabilities, BrokerLog, Order, OrderIds, PartialClose, Status, Trade,
+};
+use order_book::OrderBook;
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+
+mod order_book;
+mod orders;
+mod schema;
+mod ticks;
+
+pub use order_book::Leg;
+pub use ticks::SimFill;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+/// Broker that keeps a local order book and fills it with price ticks
+///
+/// Submitted entries wait in the book until a tick crosses their price. The
+/// stop and the target become active once the entry is filled and they cancel
+/// each other. Clones share the same order book.
+#[derive(Clone)]
+pub struct SimBroker {
+    connection: Arc<Mutex<SqliteConnection>>,
+}
+
+impl std::fmt::Debug for SimBroker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimBroker")
+            .field("connection", &"Arc<Mutex<SqliteConnection>>")
+            .finish()
+    }
+}
+
+impl SimBroker {
+    /// Opens the order book stored in the SQLite database of the URL.
+    /// New databases get the order book tables.
+    pub fn new(url: &str) -> Result<Self, Box<dyn Error>> {
+        let mut connection = SqliteConnection::establish(url)
+            .map_err(|error| format!("Error connecting to {url}: {error}"))?;
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|error| format!("Failed to run migrations on {url}: {error}"))?;
+
+        Ok(SimBroker {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Creates an order book that only lives in memory
+    pub fn new_in_memory() -> Result<Self, Box<dyn Error>> {
+        SimBroker::new(":memory:")
+    }
+
+    /// Feeds a price of a symbol into the broker and returns the orders that it filled.
+    pub fn tick(&self, symbol: &str, price: Decimal) -> Result<Vec<SimFill>, Box<dyn Error>> {
+        ticks::tick(&mut *self.connection()?, symbol, price)
+    }
+
+    /// Feeds the ticks of a CSV with Symbol and Price columns, in the order of the rows.
+    /// Nothing is filled if any row is invalid.
+    pub fn import_ticks(&self, csv: &str) -> Result<Vec<SimFill>, Box<dyn Error>> {
+        let ticks = ticks::parse(csv)?;
+        if ticks.is_empty() {
+            return Err("No ticks found in the CSV".into());
+        }
+
+        let mut fills = Vec::new();
+        for (symbol, price) in ticks {
+            fills.extend(self.tick(&symbol, price)?);
+        }
+        Ok(fills)
+    }
+
+    /// The price of the last tick of a symbol, if any.
+    pub fn last_price(&self, symbol: &str) -> Result<Option<Decimal>, Box<dyn Error>> {
+        OrderBook::read_price(&mut *self.connection()?, symbol)
+    }
+
+    fn connection(&self) -> Result<MutexGuard<'_, SqliteConnection>, Box<dyn Error>> {
+        self.connection
+            .lock()
+            .map_err(|error| format!("Failed to acquire connection lock: {error}").into())
+    }
+}
+
+impl Broker for SimBroker {
+    fn submit_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+        orders::submit(&mut *self.connection()?, trade, account)
+    }
+
+    fn sync_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+        orders::sync(&mut *self.connection()?, trade, account)
+    }
+
+    fn close_trade(
+        &self,
+        trade: &Trade,
+        account: &Account,
+    ) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+        orders::close(&mut *self.connection()?, trade, account)
+    }
+
+    fn cancel_trade(&self, trade: &Trade, account: &Account) -> Result<BrokerLog, Box<dyn Error>> {
+        orders::cancel(&mut *self.connection()?, trade, account)
+    }
+
+    fn close_partial(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        quantity: u64,
+    ) -> Result<(PartialClose, BrokerLog), Box<dyn Error>> {
+        orders::close_partial(&mut *self.connection()?, trade, account, quantity)
+    }
+
+    fn modify_stop(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_stop_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        orders::modify(
+            &mut *self.connection()?,
+            trade,
+            account,
+            Leg::Stop,
+            new_stop_price,
+        )
+    }
+
+    fn modify_target(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_price: Decimal,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        orders::modify(
+            &mut *self.connection()?,
+            trade,
+            account,
+            Leg::Target,
+            new_price,
+        )
+    }
+
+    fn modify_entry(
+        &self,
+        trade: &Trade,
+        account: &Account,
+        new_price: Decimal,
+        new_quantity: u64,
+    ) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+        orders::modify_entry(
+            &mut *self.connection()?,
+            trade,
+            account,
+            new_price,
+            new_quantity,
+        )
+    }
+
+    /// The order book fills any symbol that gets ticks, at any time of the day.
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities {
+            extended_hours: true,
+            crypto: true,
+            partial_close: true,
+            modify_entry: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{OrderAction, OrderCategory, OrderStatus, TradeCategory, TradingVehicle};
+    use rust_decimal_macros::dec;
+
+    fn order(category: OrderCategory, action: OrderAction, price: Decimal) -> Order {
+        Order {
+            category,
+            action,
+            unit_price: price,
+            quantity: 10,
+            ..Default::default()
+        }
+    }
+
+    fn long_trade(account: &Account) -> Trade {
+        Trade {
+            account_id: account.id,
+            category: TradeCategory::Long,
+            status: Status::Submitted,
+            trading_vehicle: TradingVehicle {
+                symbol: "aapl".to_string(),
+                ..Default::default()
+            },
+            entry: order(OrderCategory::Limit, OrderAction::Buy, dec!(100)),
+            safety_stop: order(OrderCategory::Market, OrderAction::Sell, dec!(95)),
+            target: order(OrderCategory::Limit, OrderAction::Sell, dec!(110)),
+            ..Default::default()
+        }
+    }
+
+    fn short_trade(account: &Account) -> Trade {
+        Trade {
+            category: TradeCategory::Short,
+            entry: order(OrderCategory::Limit, OrderAction::Short, dec!(100)),
+            safety_stop: order(OrderCategory::Market, OrderAction::Buy, dec!(105)),
+            target: order(OrderCategory::Limit, OrderAction::Buy, dec!(90)),
+            ..long_trade(account)
+        }
+    }
+
+    fn find(orders: &[Order], id: Uuid) -> &Order {
+        orders.iter().find(|order| order.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_entry_waits_for_its_price() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        let (_, ids) = broker.submit_trade(&trade, &account).unwrap();
+
+        assert!(broker.tick("AAPL", dec!(101)).unwrap().is_empty());
+        let fills = broker.tick("AAPL", dec!(99.5)).unwrap();
+
+        let fill = fills.first().unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fill.broker_order_id, ids.entry);
+        assert_eq!(fill.leg, Leg::Entry);
+        assert_eq!(fill.price, dec!(99.5));
+
+        let (status, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(status, Status::Filled);
+        let entry = find(&orders, trade.entry.id);
+        assert_eq!(entry.broker_order_id, Some(ids.entry));
+        assert_eq!(entry.status, OrderStatus::Filled);
+        assert_eq!(entry.filled_quantity, 10);
+        assert_eq!(entry.average_filled_price, Some(dec!(99.5)));
+        assert_eq!(find(&orders, trade.safety_stop.id).status, OrderStatus::New);
+    }
+
+    #[test]
+    fn test_exits_are_held_until_the_entry_fills() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        broker.submit_trade(&trade, &account).unwrap();
+
+        // The stop is crossed, but the entry is not filled yet
+        assert!(broker.tick("AAPL", dec!(111)).unwrap().is_empty());
+        let (status, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(status, Status::Submitted);
+        assert_eq!(find(&orders, trade.target.id).status, OrderStatus::Held);
+
+        // The entry gaps below the stop, which only acts from the next tick on
+        let fills = broker.tick("AAPL", dec!(90)).unwrap();
+        assert_eq!(fills.len(), 1);
+        let fills = broker.tick("AAPL", dec!(90)).unwrap();
+        assert_eq!(fills.first().unwrap().leg, Leg::Stop);
+    }
+
+    #[test]
+    fn test_target_cancels_the_stop() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        broker.submit_trade(&trade, &account).unwrap();
+
+        broker.tick("AAPL", dec!(100)).unwrap();
+        let fills = broker.tick("AAPL", dec!(110.25)).unwrap();
+        assert_eq!(fills.first().unwrap().leg, Leg::Target);
+        assert!(broker.tick("AAPL", dec!(90)).unwrap().is_empty());
+
+        let (status, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(status, Status::ClosedTarget);
+        let target = find(&orders, trade.target.id);
+        assert_eq!(target.average_filled_price, Some(dec!(110.25)));
+        let stop = find(&orders, trade.safety_stop.id);
+        assert_eq!(stop.status, OrderStatus::Canceled);
+        assert!(stop.cancelled_at.is_some());
+    }
+
+    #[test]
+    fn test_short_trade_is_stopped() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = short_trade(&account);
+        broker.submit_trade(&trade, &account).unwrap();
+
+        assert!(broker.tick("AAPL", dec!(99)).unwrap().is_empty());
+        assert_eq!(broker.tick("AAPL", dec!(100)).unwrap().len(), 1);
+        let fills = broker.tick("AAPL", dec!(106)).unwrap();
+        assert_eq!(fills.first().unwrap().leg, Leg::Stop);
+        assert_eq!(fills.first().unwrap().action, OrderAction::Buy);
+
+        let (status, _, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(status, Status::ClosedStopLoss);
+    }
+
+    #[test]
+    fn test_modify_stop_and_target() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        let (_, ids) = broker.submit_trade(&trade, &account).unwrap();
+        broker.tick("AAPL", dec!(100)).unwrap();
+
+        let (stop, log) = broker.modify_stop(&trade, &account, dec!(99)).unwrap();
+        let (target, _) = broker.modify_target(&trade, &account, dec!(120)).unwrap();
+        assert_eq!(Some(stop), ids.stop);
+        assert_eq!(Some(target), ids.target);
+        assert_eq!(log.request, "Change the stop price to 99");
+
+        assert!(broker.tick("AAPL", dec!(115)).unwrap().is_empty());
+        let fills = broker.tick("AAPL", dec!(98.9)).unwrap();
+        assert_eq!(Some(fills.first().unwrap().broker_order_id), ids.stop);
+        assert!(broker.modify_target(&trade, &account, dec!(130)).is_err());
+    }
+
+    #[test]
+    fn test_modify_entry() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        let (_, ids) = broker.submit_trade(&trade, &account).unwrap();
+
+        let (entry, log) = broker.modify_entry(&trade, &account, dec!(98), 20).unwrap();
+        assert_eq!(entry, ids.entry);
+        assert_eq!(log.request, "Change the entry to 20 at 98");
+
+        assert!(broker.tick("AAPL", dec!(99)).unwrap().is_empty());
+        broker.tick("AAPL", dec!(98)).unwrap();
+        let (_, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(orders.first().unwrap().filled_quantity, 20);
+        assert!(broker.modify_entry(&trade, &account, dec!(97), 10).is_err());
+    }
+
+    #[test]
+    fn test_close_at_the_last_price() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        let (_, ids) = broker.submit_trade(&trade, &account).unwrap();
+        assert!(broker.close_trade(&trade, &account).is_err());
+
+        broker.tick("AAPL", dec!(100)).unwrap();
+        broker.tick("AAPL", dec!(104)).unwrap();
+        let (order, _) = broker.close_trade(&trade, &account).unwrap();
+
+        assert_eq!(order.id, trade.target.id);
+        assert_ne!(order.broker_order_id, ids.target);
+        assert_eq!(order.category, OrderCategory::Market);
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.average_filled_price, Some(dec!(104)));
+
+        let trade = Trade {
+            status: Status::Canceled,
+            ..trade
+        };
+        let (status, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(status, Status::ClosedTarget);
+        assert_eq!(
+            find(&orders, trade.target.id).broker_order_id,
+            order.broker_order_id
+        );
+        assert_eq!(
+            find(&orders, trade.safety_stop.id).status,
+            OrderStatus::Canceled
+        );
+    }
+
+    #[test]
+    fn test_close_partial_at_the_last_price() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        let (_, ids) = broker.submit_trade(&trade, &account).unwrap();
+        assert!(broker.close_partial(&trade, &account, 4).is_err());
+
+        broker.tick("AAPL", dec!(100)).unwrap();
+        broker.tick("AAPL", dec!(104)).unwrap();
+        let (closed, log) = broker.close_partial(&trade, &account, 4).unwrap();
+
+        assert_eq!(closed.quantity, 4);
+        assert_eq!(closed.price, dec!(104));
+        assert_eq!(closed.stop, ids.stop);
+        assert_eq!(closed.target, ids.target);
+        assert_eq!(
+            log.request,
+            "Close 4 at 104 and resize the stop and the target to 6"
+        );
+        assert!(broker.close_partial(&trade, &account, 6).is_err());
+
+        broker.tick("AAPL", dec!(110)).unwrap();
+        let (status, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert_eq!(status, Status::ClosedTarget);
+        assert_eq!(find(&orders, trade.target.id).filled_quantity, 6);
+    }
+
+    #[test]
+    fn test_cancel_trade() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        broker.submit_trade(&trade, &account).unwrap();
+
+        let log = broker.cancel_trade(&trade, &account).unwrap();
+
+        assert!(log.response.contains("canceled"));
+        assert!(broker.tick("AAPL", dec!(90)).unwrap().is_empty());
+        let (_, orders, _) = broker.sync_trade(&trade, &account).unwrap();
+        assert!(orders
+            .iter()
+            .all(|order| order.status == OrderStatus::Canceled));
+    }
+
+    #[test]
+    fn test_submit_is_rejected_twice_and_for_other_accounts() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+
+        assert!(broker.submit_trade(&trade, &Account::default()).is_err());
+        broker.submit_trade(&trade, &account).unwrap();
+        assert!(broker.submit_trade(&trade, &account).is_err());
+    }
+
+    #[test]
+    fn test_import_ticks() {
+        let broker = SimBroker::new_in_memory().unwrap();
+        let account = Account::default();
+        let trade = long_trade(&account);
+        broker.submit_trade(&trade, &account).unwrap();
+
+        assert!(broker
+            .import_ticks("Symbol,Price\nAAPL,100\nAAPL,x")
+            .is_err());
+        assert_eq!(broker.last_price("AAPL").unwrap(), None);
+
+        let fills = broker
+            .import_ticks("Symbol,Price\nAAPL,101\nMSFT,300\nAAPL,100\nAAPL,111")
+            .unwrap();
+
+        let legs: Vec<Leg> = fills.iter().map(|fill| fill.leg).collect();
+        assert_eq!(legs, vec![Leg::Entry, Leg::Target]);
+        assert_eq!(broker.last_price("aapl").unwrap(), Some(dec!(111)));
+        assert_eq!(broker.last_price("MSFT").unwrap(), Some(dec!(300)));
+    }
+}
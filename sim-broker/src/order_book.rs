@@ -0,0 +1,277 @@
+use crate::schema::{sim_orders, sim_prices};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use model::{OrderAction, OrderCategory, OrderStatus};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// The part of a trade that an order of the book executes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leg {
+    /// The order that opens the trade
+    Entry,
+    /// The safety stop that closes the trade at a loss
+    Stop,
+    /// The order that closes the trade at the target price
+    Target,
+}
+
+impl std::fmt::Display for Leg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Leg::Entry => write!(f, "entry"),
+            Leg::Stop => write!(f, "stop"),
+            Leg::Target => write!(f, "target"),
+        }
+    }
+}
+
+impl FromStr for Leg {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "entry" => Ok(Leg::Entry),
+            "stop" => Ok(Leg::Stop),
+            "target" => Ok(Leg::Target),
+            _ => Err(format!("Unknown order leg: {s}").into()),
+        }
+    }
+}
+
+/// An order resting in the order book of the simulated broker.
+///
+/// The stop and the target are held until the entry is filled. Orders that are
+/// waiting for a price have the status new.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimOrder {
+    pub id: Uuid,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub trade_id: Uuid,
+    pub order_id: Uuid,
+    pub symbol: String,
+    pub leg: Leg,
+    pub category: OrderCategory,
+    pub action: OrderAction,
+    pub price: Decimal,
+    pub quantity: u64,
+    pub status: OrderStatus,
+    pub filled_price: Option<Decimal>,
+    pub filled_at: Option<NaiveDateTime>,
+    pub cancelled_at: Option<NaiveDateTime>,
+}
+
+impl SimOrder {
+    /// True when the order is still waiting to be filled.
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, OrderStatus::New | OrderStatus::Held)
+    }
+
+    /// True when an order of this price and side is executed at the given price.
+    pub fn crosses(&self, price: Decimal) -> bool {
+        let buys = self.action == OrderAction::Buy;
+        // Trust saves the safety stop as a market order that is triggered at its price
+        let category = match self.leg {
+            Leg::Stop => OrderCategory::Stop,
+            _ => self.category,
+        };
+        match category {
+            OrderCategory::Market => true,
+            OrderCategory::Limit if buys => price <= self.price,
+            OrderCategory::Limit => price >= self.price,
+            OrderCategory::Stop if buys => price >= self.price,
+            OrderCategory::Stop => price <= self.price,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id.to_string(),
+            "trade_id": self.trade_id.to_string(),
+            "client_order_id": self.order_id.to_string(),
+            "symbol": self.symbol,
+            "leg": self.leg.to_string(),
+            "type": self.category.to_string(),
+            "side": self.action.to_string(),
+            "price": self.price.to_string(),
+            "quantity": self.quantity,
+            "status": self.status.to_string(),
+            "filled_price": self.filled_price.map(|price| price.to_string()),
+            "filled_at": self.filled_at.map(|date| date.to_string()),
+            "cancelled_at": self.cancelled_at.map(|date| date.to_string()),
+        })
+    }
+}
+
+/// Worker for reading and writing the order book and the last prices
+#[derive(Debug)]
+pub struct OrderBook;
+
+impl OrderBook {
+    pub fn create(
+        connection: &mut SqliteConnection,
+        order: &SimOrder,
+    ) -> Result<SimOrder, Box<dyn Error>> {
+        diesel::insert_into(sim_orders::table)
+            .values(SimOrderSQLite::try_from(order)?)
+            .get_result::<SimOrderSQLite>(connection)?
+            .try_into()
+    }
+
+    pub fn update(
+        connection: &mut SqliteConnection,
+        order: &SimOrder,
+    ) -> Result<SimOrder, Box<dyn Error>> {
+        let mut row = SimOrderSQLite::try_from(order)?;
+        row.updated_at = Utc::now().naive_utc();
+        diesel::update(sim_orders::table)
+            .filter(sim_orders::id.eq(&row.id))
+            .set(&row)
+            .get_result::<SimOrderSQLite>(connection)?
+            .try_into()
+    }
+
+    /// Orders of a trade, the oldest first.
+    pub fn read_trade(
+        connection: &mut SqliteConnection,
+        trade_id: Uuid,
+    ) -> Result<Vec<SimOrder>, Box<dyn Error>> {
+        sim_orders::table
+            .filter(sim_orders::trade_id.eq(trade_id.to_string()))
+            .order(sim_orders::created_at.asc())
+            .load::<SimOrderSQLite>(connection)?
+            .into_iter()
+            .map(SimOrder::try_from)
+            .collect()
+    }
+
+    /// Orders of a symbol that are waiting for a price, the oldest first.
+    pub fn read_waiting(
+        connection: &mut SqliteConnection,
+        symbol: &str,
+    ) -> Result<Vec<SimOrder>, Box<dyn Error>> {
+        sim_orders::table
+            .filter(sim_orders::symbol.eq(symbol.to_uppercase()))
+            .filter(sim_orders::status.eq(OrderStatus::New.to_string()))
+            .order(sim_orders::created_at.asc())
+            .load::<SimOrderSQLite>(connection)?
+            .into_iter()
+            .map(SimOrder::try_from)
+            .collect()
+    }
+
+    pub fn save_price(
+        connection: &mut SqliteConnection,
+        symbol: &str,
+        price: Decimal,
+    ) -> Result<(), Box<dyn Error>> {
+        diesel::replace_into(sim_prices::table)
+            .values((
+                sim_prices::symbol.eq(symbol.to_uppercase()),
+                sim_prices::price.eq(price.to_string()),
+                sim_prices::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .execute(connection)?;
+        Ok(())
+    }
+
+    pub fn read_price(
+        connection: &mut SqliteConnection,
+        symbol: &str,
+    ) -> Result<Option<Decimal>, Box<dyn Error>> {
+        sim_prices::table
+            .filter(sim_prices::symbol.eq(symbol.to_uppercase()))
+            .select(sim_prices::price)
+            .first::<String>(connection)
+            .optional()?
+            .map(|price| parse_decimal(&price, "price"))
+            .transpose()
+    }
+}
+
+#[derive(Queryable, Identifiable, AsChangeset, Insertable)]
+#[diesel(table_name = sim_orders)]
+#[diesel(treat_none_as_null = true)]
+struct SimOrderSQLite {
+    id: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+    trade_id: String,
+    order_id: String,
+    symbol: String,
+    leg: String,
+    category: String,
+    action: String,
+    price: String,
+    quantity: i64,
+    status: String,
+    filled_price: Option<String>,
+    filled_at: Option<NaiveDateTime>,
+    cancelled_at: Option<NaiveDateTime>,
+}
+
+impl TryFrom<&SimOrder> for SimOrderSQLite {
+    type Error = Box<dyn Error>;
+
+    fn try_from(order: &SimOrder) -> Result<Self, Self::Error> {
+        Ok(SimOrderSQLite {
+            id: order.id.to_string(),
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+            trade_id: order.trade_id.to_string(),
+            order_id: order.order_id.to_string(),
+            symbol: order.symbol.to_uppercase(),
+            leg: order.leg.to_string(),
+            category: order.category.to_string(),
+            action: order.action.to_string(),
+            price: order.price.to_string(),
+            quantity: i64::try_from(order.quantity)?,
+            status: order.status.to_string(),
+            filled_price: order.filled_price.map(|price| price.to_string()),
+            filled_at: order.filled_at,
+            cancelled_at: order.cancelled_at,
+        })
+    }
+}
+
+impl TryFrom<SimOrderSQLite> for SimOrder {
+    type Error = Box<dyn Error>;
+
+    fn try_from(value: SimOrderSQLite) -> Result<Self, Self::Error> {
+        Ok(SimOrder {
+            id: parse_uuid(&value.id, "id")?,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+            trade_id: parse_uuid(&value.trade_id, "trade_id")?,
+            order_id: parse_uuid(&value.order_id, "order_id")?,
+            symbol: value.symbol,
+            leg: Leg::from_str(&value.leg)?,
+            category: OrderCategory::from_str(&value.category)
+                .map_err(|_| format!("Unknown order category: {}", value.category))?,
+            action: OrderAction::from_str(&value.action)
+                .map_err(|_| format!("Unknown order action: {}", value.action))?,
+            price: parse_decimal(&value.price, "price")?,
+            quantity: u64::try_from(value.quantity)?,
+            status: OrderStatus::from_str(&value.status)
+                .map_err(|_| format!("Unknown order status: {}", value.status))?,
+            filled_price: value
+                .filled_price
+                .map(|price| parse_decimal(&price, "filled_price"))
+                .transpose()?,
+            filled_at: value.filled_at,
+            cancelled_at: value.cancelled_at,
+        })
+    }
+}
+
+fn parse_uuid(value: &str, field: &str) -> Result<Uuid, Box<dyn Error>> {
+    Uuid::parse_str(value).map_err(|_| format!("Failed to parse {field} of a sim order").into())
+}
+
+fn parse_decimal(value: &str, field: &str) -> Result<Decimal, Box<dyn Error>> {
+    Decimal::from_str(value).map_err(|_| format!("Failed to parse {field} of a sim order").into())
+}
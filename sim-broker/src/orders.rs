@@ -0,0 +1,395 @@
+use crate::order_book::{Leg, OrderBook, SimOrder};
+use crate::ticks;
+use chrono::Utc;
+use diesel::prelude::*;
+use model::{
+    Account, BrokerError, BrokerLog, Order, OrderCategory, OrderIds, OrderStatus, PartialClose,
+    Status, Trade,
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::error::Error;
+use uuid::Uuid;
+
+/// Places the entry in the order book. The stop and the target are held until the entry is filled.
+pub fn submit(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+) -> Result<(BrokerLog, OrderIds), Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    connection.transaction(|connection| {
+        if !OrderBook::read_trade(connection, trade.id)?.is_empty() {
+            return Err(format!(
+                "Trade {} was already submitted to the simulated broker",
+                trade.id
+            )
+            .into());
+        }
+
+        let entry = OrderBook::create(
+            connection,
+            &new_order(trade, &trade.entry, Leg::Entry, OrderStatus::New),
+        )?;
+        let stop = OrderBook::create(
+            connection,
+            &new_order(trade, &trade.safety_stop, Leg::Stop, OrderStatus::Held),
+        )?;
+        let target = OrderBook::create(
+            connection,
+            &new_order(trade, &trade.target, Leg::Target, OrderStatus::Held),
+        )?;
+
+        let log = log(
+            trade,
+            "Place the entry and hold the stop and the target",
+            &[&entry, &stop, &target],
+        )?;
+        let ids = OrderIds {
+            stop: Some(stop.id),
+            entry: entry.id,
+            target: Some(target.id),
+        };
+        Ok((log, ids))
+    })
+}
+
+pub fn sync(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+) -> Result<(Status, Vec<Order>, BrokerLog), Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    let book = OrderBook::read_trade(connection, trade.id)?;
+    if book.is_empty() {
+        return Err(format!(
+            "Trade {} was not submitted to the simulated broker",
+            trade.id
+        )
+        .into());
+    }
+
+    let orders = [&trade.entry, &trade.safety_stop, &trade.target]
+        .into_iter()
+        .filter_map(|order| current(&book, order.id).map(|sim| map(order, sim)))
+        .collect();
+    let log = log(
+        trade,
+        "Read the orders of the trade",
+        &book.iter().collect::<Vec<_>>(),
+    )?;
+
+    Ok((status(trade, &book), orders, log))
+}
+
+/// Cancels the stop and the target and closes the trade with a market order.
+/// The market order is filled at the last known price of the symbol, or by the next tick.
+pub fn close(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+) -> Result<(Order, BrokerLog), Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    connection.transaction(|connection| {
+        let book = OrderBook::read_trade(connection, trade.id)?;
+        if !book
+            .iter()
+            .any(|order| order.leg == Leg::Entry && order.status == OrderStatus::Filled)
+        {
+            return Err(format!(
+                "The entry of trade {} is not filled, it can not be closed",
+                trade.id
+            )
+            .into());
+        }
+        for order in book.into_iter().filter(SimOrder::is_open) {
+            ticks::cancel(connection, order)?;
+        }
+
+        let mut market = new_order(trade, &trade.target, Leg::Target, OrderStatus::New);
+        market.category = OrderCategory::Market;
+        if let Some(price) = OrderBook::read_price(connection, &trade.trading_vehicle.symbol)? {
+            market.status = OrderStatus::Filled;
+            market.filled_price = Some(price);
+            market.filled_at = Some(Utc::now().naive_utc());
+        }
+        let market = OrderBook::create(connection, &market)?;
+
+        let log = log(
+            trade,
+            "Cancel the open orders and close with a market order",
+            &[&market],
+        )?;
+        Ok((map(&trade.target, &market), log))
+    })
+}
+
+/// Closes part of a trade at the last known price of the symbol, and reduces the
+/// quantity of the open stop and target to the quantity that stays open.
+pub fn close_partial(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+    quantity: u64,
+) -> Result<(PartialClose, BrokerLog), Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    connection.transaction(|connection| {
+        let price =
+            OrderBook::read_price(connection, &trade.trading_vehicle.symbol)?.ok_or_else(|| {
+                format!(
+                    "There is no price of {} to close part of trade {}",
+                    trade.trading_vehicle.symbol, trade.id
+                )
+            })?;
+
+        let exits: Vec<SimOrder> = OrderBook::read_trade(connection, trade.id)?
+            .into_iter()
+            .filter(|order| order.leg != Leg::Entry && order.status == OrderStatus::New)
+            .collect();
+        let leg = |leg: Leg| {
+            exits.iter().find(|order| order.leg == leg).ok_or_else(|| {
+                format!(
+                    "The {leg} of trade {} is not open, the trade can not be closed partially",
+                    trade.id
+                )
+            })
+        };
+        let (stop, target) = (leg(Leg::Stop)?, leg(Leg::Target)?);
+        let open = stop
+            .quantity
+            .checked_sub(quantity)
+            .filter(|open| *open > 0)
+            .ok_or_else(|| format!("Trade {} has less than {quantity} open", trade.id))?;
+
+        let stop = OrderBook::update(
+            connection,
+            &SimOrder {
+                quantity: open,
+                ..stop.clone()
+            },
+        )?;
+        let target = OrderBook::update(
+            connection,
+            &SimOrder {
+                quantity: open,
+                ..target.clone()
+            },
+        )?;
+
+        let log = log(
+            trade,
+            &format!("Close {quantity} at {price} and resize the stop and the target to {open}"),
+            &[&stop, &target],
+        )?;
+        let closed = PartialClose {
+            quantity,
+            price,
+            stop: Some(stop.id),
+            target: Some(target.id),
+        };
+        Ok((closed, log))
+    })
+}
+
+pub fn cancel(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+) -> Result<BrokerLog, Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    connection.transaction(|connection| {
+        let book = OrderBook::read_trade(connection, trade.id)?;
+        if book
+            .iter()
+            .any(|order| order.leg == Leg::Entry && order.status == OrderStatus::Filled)
+        {
+            return Err(format!(
+                "The entry of trade {} is already filled, it can not be canceled",
+                trade.id
+            )
+            .into());
+        }
+        let canceled = book
+            .into_iter()
+            .filter(SimOrder::is_open)
+            .map(|order| ticks::cancel(connection, order))
+            .collect::<Result<Vec<SimOrder>, Box<dyn Error>>>()?;
+        log(
+            trade,
+            "Cancel the open orders",
+            &canceled.iter().collect::<Vec<_>>(),
+        )
+    })
+}
+
+/// Changes the price of the open stop or target of a trade and returns its ID in the broker.
+pub fn modify(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+    leg: Leg,
+    price: Decimal,
+) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    let request = format!("Change the {leg} price to {price}");
+    let order = OrderBook::read_trade(connection, trade.id)?
+        .into_iter()
+        .rev()
+        .find(|order| order.leg == leg && order.is_open())
+        .ok_or_else(|| {
+            BrokerError::sent(
+                trade.id,
+                &request,
+                format!(
+                    "The {leg} of trade {} is not open, it can not be modified",
+                    trade.id
+                ),
+            )
+        })?;
+
+    let order = OrderBook::update(connection, &SimOrder { price, ..order })?;
+    let log = log(trade, &request, &[&order])?;
+    Ok((order.id, log))
+}
+
+/// Changes the price and the quantity of the open entry of a trade, and the
+/// quantity of its held stop and target. Returns the ID of the entry in the broker.
+pub fn modify_entry(
+    connection: &mut SqliteConnection,
+    trade: &Trade,
+    account: &Account,
+    price: Decimal,
+    quantity: u64,
+) -> Result<(Uuid, BrokerLog), Box<dyn Error>> {
+    verify_account(trade, account)?;
+
+    connection.transaction(|connection| {
+        let book = OrderBook::read_trade(connection, trade.id)?;
+        if !book
+            .iter()
+            .any(|order| order.leg == Leg::Entry && order.is_open())
+        {
+            return Err(format!(
+                "The entry of trade {} is not open, it can not be modified",
+                trade.id
+            )
+            .into());
+        }
+
+        let mut entry_id = None;
+        let mut changed = Vec::new();
+        for order in book.into_iter().filter(SimOrder::is_open) {
+            let order = match order.leg {
+                Leg::Entry => SimOrder {
+                    price,
+                    quantity,
+                    ..order
+                },
+                Leg::Stop | Leg::Target => SimOrder { quantity, ..order },
+            };
+            let order = OrderBook::update(connection, &order)?;
+            if order.leg == Leg::Entry {
+                entry_id = Some(order.id);
+            }
+            changed.push(order);
+        }
+
+        let log = log(
+            trade,
+            &format!("Change the entry to {quantity} at {price}"),
+            &changed.iter().collect::<Vec<_>>(),
+        )?;
+        Ok((entry_id.ok_or("The entry of the trade is missing")?, log))
+    })
+}
+
+fn verify_account(trade: &Trade, account: &Account) -> Result<(), Box<dyn Error>> {
+    if trade.account_id != account.id {
+        return Err(format!(
+            "Trade {} does not belong to account {}",
+            trade.id, account.name
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn new_order(trade: &Trade, order: &Order, leg: Leg, status: OrderStatus) -> SimOrder {
+    let now = Utc::now().naive_utc();
+    SimOrder {
+        id: Uuid::new_v4(),
+        created_at: now,
+        updated_at: now,
+        trade_id: trade.id,
+        order_id: order.id,
+        symbol: trade.trading_vehicle.symbol.to_uppercase(),
+        leg,
+        category: order.category,
+        action: order.action,
+        price: order.unit_price,
+        quantity: order.quantity,
+        status,
+        filled_price: None,
+        filled_at: None,
+        cancelled_at: None,
+    }
+}
+
+/// The order of the book that stands for a Trust order. After a close, the
+/// target is the market order and not the canceled limit order.
+fn current(book: &[SimOrder], order_id: Uuid) -> Option<&SimOrder> {
+    let mut orders = book.iter().rev().filter(|order| order.order_id == order_id);
+    let last = orders.clone().next();
+    orders
+        .find(|order| order.status != OrderStatus::Canceled)
+        .or(last)
+}
+
+fn map(order: &Order, sim: &SimOrder) -> Order {
+    let mut order = order.clone();
+    order.broker_order_id = Some(sim.id);
+    order.category = sim.category;
+    order.status = sim.status;
+    order.submitted_at = order.submitted_at.or(Some(sim.created_at));
+    order.filled_quantity = match sim.status {
+        OrderStatus::Filled => sim.quantity,
+        _ => 0,
+    };
+    order.average_filled_price = sim.filled_price;
+    order.filled_at = sim.filled_at;
+    order.cancelled_at = sim.cancelled_at;
+    order
+}
+
+fn status(trade: &Trade, book: &[SimOrder]) -> Status {
+    let filled = |leg: Leg| {
+        book.iter()
+            .any(|order| order.leg == leg && order.status == OrderStatus::Filled)
+    };
+
+    if filled(Leg::Stop) {
+        Status::ClosedStopLoss
+    } else if filled(Leg::Target) {
+        Status::ClosedTarget
+    } else if filled(Leg::Entry) {
+        Status::Filled
+    } else {
+        trade.status
+    }
+}
+
+fn log(trade: &Trade, request: &str, orders: &[&SimOrder]) -> Result<BrokerLog, Box<dyn Error>> {
+    let orders: Vec<Value> = orders.iter().map(|order| order.to_json()).collect();
+    Ok(BrokerLog {
+        trade_id: trade.id,
+        request: request.to_string(),
+        response: serde_json::to_string(&orders)?,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,27 @@
+diesel::table! {
+    sim_orders (id) {
+        id -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        trade_id -> Text,
+        order_id -> Text,
+        symbol -> Text,
+        leg -> Text,
+        category -> Text,
+        action -> Text,
+        price -> Text,
+        quantity -> BigInt,
+        status -> Text,
+        filled_price -> Nullable<Text>,
+        filled_at -> Nullable<Timestamp>,
+        cancelled_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    sim_prices (symbol) {
+        symbol -> Text,
+        price -> Text,
+        updated_at -> Timestamp,
+    }
+}
@@ -0,0 +1,211 @@
+use crate::order_book::{Leg, OrderBook, SimOrder};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use model::{OrderAction, OrderStatus};
+use rust_decimal::Decimal;
+use std::error::Error;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// An order of the simulated broker that was filled by a price tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimFill {
+    /// ID of the trade of the order
+    pub trade_id: Uuid,
+    /// ID of the order in the simulated broker
+    pub broker_order_id: Uuid,
+    /// Symbol of the order
+    pub symbol: String,
+    /// Part of the trade that was executed
+    pub leg: Leg,
+    /// Side of the order
+    pub action: OrderAction,
+    /// Filled quantity
+    pub quantity: u64,
+    /// Price of the tick that filled the order
+    pub price: Decimal,
+    /// When the order was filled
+    pub filled_at: NaiveDateTime,
+}
+
+/// Records the last price of a symbol and fills the orders that the price crosses.
+///
+/// A filled entry releases the stop and the target of its trade, which only
+/// take part from the next tick on. A filled stop cancels the target and a
+/// filled target cancels the stop.
+pub fn tick(
+    connection: &mut SqliteConnection,
+    symbol: &str,
+    price: Decimal,
+) -> Result<Vec<SimFill>, Box<dyn Error>> {
+    if price <= Decimal::ZERO {
+        return Err(format!("The price of {symbol} must be positive, it is {price}").into());
+    }
+
+    connection.transaction(|connection| {
+        OrderBook::save_price(connection, symbol, price)?;
+
+        let mut fills: Vec<SimFill> = Vec::new();
+        for order in OrderBook::read_waiting(connection, symbol)? {
+            // A fill of this tick can have closed the trade of the order
+            let closed = fills
+                .iter()
+                .any(|fill| fill.trade_id == order.trade_id && fill.leg != Leg::Entry);
+            if closed || !order.crosses(price) {
+                continue;
+            }
+            fills.push(fill(connection, order, price)?);
+        }
+        Ok(fills)
+    })
+}
+
+fn fill(
+    connection: &mut SqliteConnection,
+    order: SimOrder,
+    price: Decimal,
+) -> Result<SimFill, Box<dyn Error>> {
+    let now = Utc::now().naive_utc();
+    let order = OrderBook::update(
+        connection,
+        &SimOrder {
+            status: OrderStatus::Filled,
+            filled_price: Some(price),
+            filled_at: Some(now),
+            ..order
+        },
+    )?;
+
+    for other in OrderBook::read_trade(connection, order.trade_id)? {
+        match order.leg {
+            Leg::Entry if other.status == OrderStatus::Held => {
+                OrderBook::update(
+                    connection,
+                    &SimOrder {
+                        status: OrderStatus::New,
+                        ..other
+                    },
+                )?;
+            }
+            Leg::Stop | Leg::Target if other.is_open() && other.id != order.id => {
+                cancel(connection, other)?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(SimFill {
+        trade_id: order.trade_id,
+        broker_order_id: order.id,
+        symbol: order.symbol,
+        leg: order.leg,
+        action: order.action,
+        quantity: order.quantity,
+        price,
+        filled_at: now,
+    })
+}
+
+pub fn cancel(
+    connection: &mut SqliteConnection,
+    order: SimOrder,
+) -> Result<SimOrder, Box<dyn Error>> {
+    OrderBook::update(
+        connection,
+        &SimOrder {
+            status: OrderStatus::Canceled,
+            cancelled_at: Some(Utc::now().naive_utc()),
+            ..order
+        },
+    )
+}
+
+/// Reads the ticks of a CSV with a header row.
+/// The columns are found by name: Symbol and Price. Other columns, like a
+/// timestamp, are ignored. The ticks keep the order of the rows.
+pub fn parse(csv: &str) -> Result<Vec<(String, Decimal)>, Box<dyn Error>> {
+    let mut lines = csv.lines().map(str::trim).filter(|line| !line.is_empty());
+    let header = lines.next().ok_or("The CSV is empty")?;
+    let names: Vec<String> = header
+        .split(',')
+        .map(|name| name.trim().trim_matches('"').to_lowercase())
+        .collect();
+    let position = |name: &str| -> Result<usize, Box<dyn Error>> {
+        names
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| format!("The CSV does not have a {name} column").into())
+    };
+    let symbol = position("symbol")?;
+    let price = position("price")?;
+
+    (2..)
+        .zip(lines)
+        .map(|(line_number, line)| {
+            parse_line(line, symbol, price)
+                .map_err(|error| format!("Line {line_number} of the CSV: {error}").into())
+        })
+        .collect()
+}
+
+fn parse_line(
+    line: &str,
+    symbol: usize,
+    price: usize,
+) -> Result<(String, Decimal), Box<dyn Error>> {
+    let values: Vec<&str> = line
+        .split(',')
+        .map(|value| value.trim().trim_matches('"'))
+        .collect();
+    let value = |column: usize| -> Result<&str, Box<dyn Error>> {
+        values
+            .get(column)
+            .copied()
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| "missing columns".into())
+    };
+
+    let price = value(price)?;
+    Ok((
+        value(symbol)?.to_uppercase(),
+        Decimal::from_str(price).map_err(|_| format!("'{price}' is not a number"))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_ticks() {
+        let csv = "Time,Symbol,Price\n\
+                   2024-01-02 09:30,aapl,185.5\n\
+                   2024-01-02 09:31,\"AAPL\",\"186\"\n";
+
+        let ticks = parse(csv).unwrap();
+
+        assert_eq!(
+            ticks,
+            vec![
+                ("AAPL".to_string(), dec!(185.5)),
+                ("AAPL".to_string(), dec!(186))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_ticks() {
+        let error = parse("Symbol,Close\nAAPL,1").err().unwrap();
+        assert_eq!(error.to_string(), "The CSV does not have a price column");
+
+        let error = parse("Symbol,Price\nAAPL,1\nAAPL,n/a").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Line 3 of the CSV: 'n/a' is not a number"
+        );
+
+        let error = parse("Symbol,Price\n,1").err().unwrap();
+        assert_eq!(error.to_string(), "Line 2 of the CSV: missing columns");
+    }
+}